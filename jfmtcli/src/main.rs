@@ -1,96 +1,1120 @@
 use std::env;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 fn print_usage(program: &str) {
-    eprintln!("Usage: {program} [--fix] <file1.java> [file2.java ...]");
+    eprintln!("Usage: {program} fmt [--check] <file.java|dir> [more ...]");
+    eprintln!("       {program} dup [--min-tokens N] <file.java|dir> [more ...]");
+    eprintln!("       {program} metrics [--json] <file.java|dir> [more ...]");
+    eprintln!("       {program} cycles <file.java|dir> [more ...]");
+    eprintln!("       {program} unused <file.java|dir> [more ...]");
+    eprintln!("       {program} javadoc [--format text|json|html] <file.java|dir> [more ...]");
+    eprintln!("       {program} package-paths [project-root]");
+    eprintln!("       {program} modules [--fix] [repo-root]");
+    eprintln!("       {program} migrate-config [--check]");
+    eprintln!("       {program} rules [file.java|dir]");
+    eprintln!("       {program} compare <old.json> <new.json>");
+    eprintln!("       {program} install-hook [--fix] [--force]");
+    eprintln!("       {program} [--fix | --check] [--unsafe] [--ci] [--warnings-as-errors] [--verify-idempotent] [--staged] [--jobs N] [--columns display|chars|utf16|bytes] [--format text|json|sonar|sarif|checkstyle] [--cache] [--timing] [--stats] <file.java|dir> [more ...]");
+    eprintln!("       {program} [--fix | --check] [--unsafe] [--warnings-as-errors] [--stdin-filename PATH] - < File.java");
 }
 
 fn main() {
+    let started = Instant::now();
     let args: Vec<String> = env::args().collect();
-    let program = args.get(0).map(String::as_str).unwrap_or("jfmtcli");
+    let program = args.first().map(String::as_str).unwrap_or("jfmtcli");
+    match args.get(1).map(String::as_str) {
+        Some("fmt") => run_fmt(program, &args[2..]),
+        Some("dup") => run_dup(program, &args[2..]),
+        Some("metrics") => run_metrics(program, &args[2..]),
+        Some("cycles") => run_cycles(program, &args[2..]),
+        Some("unused") => run_unused(program, &args[2..]),
+        Some("javadoc") => run_javadoc(program, &args[2..]),
+        Some("package-paths") => run_package_paths(program, &args[2..]),
+        Some("modules") => run_modules(program, &args[2..]),
+        Some("migrate-config") => run_migrate_config(program, &args[2..]),
+        Some("rules") => run_rules(program, &args[2..]),
+        Some("compare") => run_compare(program, &args[2..]),
+        Some("install-hook") => run_install_hook(program, &args[2..]),
+        _ => {}
+    }
 
     // Parse flags and files (simple, no external deps)
-    let mut fix = false;
-    let mut files: Vec<String> = Vec::new();
-    for arg in args.iter().skip(1) {
+    let mut options = libjfmt::LintOptions::default();
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut column_unit = None;
+    let mut show_stats = false;
+    let mut format = "text";
+    let mut ci = false;
+    let mut warnings_as_errors = false;
+    let mut stdin = false;
+    let mut stdin_filename = None;
+    let mut warnings = 0usize;
+    let mut rest = args.iter().skip(1);
+    while let Some(arg) = rest.next() {
         if arg == "--fix" {
-            fix = true;
-        } else {
-            files.push(arg.clone());
+            options.fix = true;
+        } else if arg == "--check" {
+            options.check = true;
+        } else if arg == "--verify-idempotent" {
+            options.verify_idempotent = true;
+        } else if arg == "--timing" {
+            options.timing = true;
+        } else if arg == "--ci" {
+            ci = true;
+        } else if arg == "--warnings-as-errors" {
+            warnings_as_errors = true;
+        } else if arg == "--unsafe" {
+            options.unsafe_fixes = true;
+        } else if arg == "--staged" {
+            options.staged = true;
+        } else if arg == "-" || arg == "--stdin" {
+            stdin = true;
+        } else if arg == "--stdin-filename" || arg.starts_with("--stdin-filename=") {
+            let value = match arg.strip_prefix("--stdin-filename=") {
+                Some(v) => Some(v),
+                None => rest.next().map(String::as_str),
+            };
+            let Some(name) = value else {
+                eprintln!("--stdin-filename expects a path");
+                print_usage(program);
+                std::process::exit(2);
+            };
+            stdin_filename = Some(PathBuf::from(name));
+        } else if arg == "--stats" {
+            show_stats = true;
+        } else if arg == "--cache" {
+            options.cache = Some(Arc::new(libjfmt::DiskCache::new(libjfmt::DEFAULT_CACHE_DIR)));
+        } else if arg == "--jobs" || arg == "-j" || arg.starts_with("--jobs=") {
+            let value = match arg.strip_prefix("--jobs=") {
+                Some(v) => Some(v),
+                None => rest.next().map(String::as_str),
+            };
+            match value.and_then(|v| v.parse::<usize>().ok()) {
+                Some(n) if n > 0 => options.threads = Some(n),
+                _ => {
+                    eprintln!("--jobs expects a positive number");
+                    print_usage(program);
+                    std::process::exit(2);
+                }
+            }
+        } else if arg == "--format" || arg == "--output-format" || arg.starts_with("--format=") || arg.starts_with("--output-format=") {
+            let value = match arg.split_once('=') {
+                Some((_, v)) => Some(v),
+                None => rest.next().map(String::as_str),
+            };
+            // The others are machine-readable on stdout like JSON, in their own schemas.
+            format = match value {
+                Some(f @ ("text" | "json" | "sonar" | "sarif" | "checkstyle")) => f,
+                _ => {
+                    eprintln!("--format expects one of: text, json, sonar, sarif, checkstyle");
+                    print_usage(program);
+                    std::process::exit(2);
+                }
+            };
+        } else if arg == "--columns" || arg.starts_with("--columns=") {
+            let value = match arg.strip_prefix("--columns=") {
+                Some(v) => Some(v),
+                None => rest.next().map(String::as_str),
+            };
+            column_unit = match value {
+                Some("display") => Some(libjfmt::ColumnUnit::Display),
+                Some("chars") => Some(libjfmt::ColumnUnit::Chars),
+                Some("utf16") => Some(libjfmt::ColumnUnit::Utf16),
+                Some("bytes") => Some(libjfmt::ColumnUnit::Bytes),
+                _ => {
+                    eprintln!("--columns expects one of: display, chars, utf16, bytes");
+                    print_usage(program);
+                    std::process::exit(2);
+                }
+            };
+        } else if !collect_path(arg, &mut files) {
+            warnings += 1;
         }
     }
 
-    if files.is_empty() {
+    if stdin && !files.is_empty() {
+        eprintln!("--stdin cannot be combined with file arguments");
+        std::process::exit(2);
+    }
+    if files.is_empty() && !stdin {
         print_usage(program);
         std::process::exit(2);
     }
+    if options.fix && options.check {
+        eprintln!("--fix and --check cannot be combined");
+        std::process::exit(2);
+    }
+    if options.fix && options.staged {
+        eprintln!("--fix and --staged cannot be combined: fixes apply to the working tree, not the index");
+        std::process::exit(2);
+    }
+
+    // The diff is only printed in text output, or to stdout for stdin input.
+    options.diff = options.check && (stdin || format == "text");
+
+    // The working directory's config holds run-wide settings; each file is
+    // linted with the one its own directory and `[[overrides]]` give it.
+    let mut resolver = libjfmt::ConfigResolver::new();
+    let (mut config, config_warnings) = load_config_with_warnings(&mut resolver);
+    warnings += config_warnings;
+    let warned = resolver.loaded().len();
+
+    let set_columns = |config: &mut libjfmt::Config| {
+        if let Some(unit) = column_unit {
+            config.column_unit = unit;
+        } else if format == "sonar" {
+            config.column_unit = libjfmt::ColumnUnit::Chars;
+        } else if format == "sarif" {
+            config.column_unit = libjfmt::ColumnUnit::Utf16;
+        }
+    };
+    set_columns(&mut config);
+    let json = format != "text";
+
+    if stdin {
+        let mut stdin_config = match stdin_filename.as_deref() {
+            Some(name) => config_or_exit(resolver.config_for(name)),
+            None => config.clone(),
+        };
+        for path in &resolver.loaded()[warned..] {
+            print_config_warnings(path);
+        }
+        set_columns(&mut stdin_config);
+        run_stdin(&stdin_config, &options, stdin_filename.as_deref(), warnings_as_errors);
+    }
 
-    let config = match libjfmt::load_config() {
-        Ok(c) => c,
+    let groups = config_or_exit(resolver.group(&files));
+    for path in &resolver.loaded()[warned..] {
+        warnings += print_config_warnings(path);
+    }
+    let reports = match lint_groups(groups, &options, set_columns) {
+        Ok(r) => r,
         Err(err) => {
-            eprintln!("error loading config: {err}");
+            eprintln!("error: {err}");
             std::process::exit(2);
         }
     };
 
-    let mut total_issues = 0usize;
+    if options.cache.is_some() {
+        let stats = libjfmt::CacheStats::from_reports(&reports);
+        eprintln!("cache: {} hits, {} misses", stats.hits, stats.misses);
+    }
 
-    for path in &files {
-        if !path.ends_with(".java") {
-            eprintln!("Skipping non-Java file: {path}");
-            continue;
+    let mut total_issues = 0usize;
+    let mut failing_issues = 0usize;
+    let mut errors = 0usize;
+    let mut stats = libjfmt::LintStats::default();
+    for report in &reports {
+        if let Ok(outcome) = &report.outcome {
+            if let Some(file_stats) = &outcome.stats {
+                stats.merge(file_stats);
+            }
+            warnings += usize::from(outcome.skipped.is_some());
         }
-        match lint_file(path, &config, fix) {
-            Ok(count) => total_issues += count,
+        match report_file(report, &mut stats, json, warnings_as_errors) {
+            Ok((count, failing)) => {
+                total_issues += count;
+                failing_issues += failing;
+            }
             Err(err) => {
-                eprintln!("{path}: error: {err}");
-                total_issues += 1; // count as failure
+                eprintln!("{}: error: {err}", report.path.display());
+                errors += 1;
             }
         }
     }
 
-    if total_issues > 0 {
+    if json {
+        let issues = libjfmt::ReportedIssue::from_reports(&reports);
+        let report = match format {
+            "checkstyle" => libjfmt::checkstyle_report(&issues),
+            "sonar" => serde_json::to_string_pretty(&libjfmt::sonar_report(&issues)).unwrap_or_default() + "\n",
+            "sarif" => serde_json::to_string_pretty(&libjfmt::sarif_report(&issues, config.column_unit)).unwrap_or_default() + "\n",
+            _ => serde_json::to_string_pretty(&issues).unwrap_or_default() + "\n",
+        };
+        print!("{report}");
+    }
+    if (options.fix || options.check) && !options.unsafe_fixes {
+        let skipped = reports
+            .iter()
+            .filter_map(|r| r.outcome.as_ref().ok())
+            .flat_map(|o| &o.issues)
+            .filter(|i| i.fix.as_ref().is_some_and(|f| f.applicability == libjfmt::Applicability::MaybeIncorrect))
+            .count();
+        if skipped > 0 {
+            let plural = if skipped == 1 { "" } else { "es" };
+            eprintln!("{skipped} fix{plural} skipped as possibly incorrect; rerun with --fix --unsafe to apply them");
+        }
+    }
+    if show_stats {
+        print_summary(&libjfmt::IssueSummary::from_reports(&reports));
+    }
+    if options.timing {
+        print_timing(&stats, started.elapsed());
+    }
+
+    let failed = failing_issues > 0 || errors > 0 || (ci && config.ci_warnings_as_errors && warnings > 0);
+    if ci {
+        let fixable = libjfmt::IssueSummary::from_reports(&reports).total().fixable;
+        let line = format!(
+            "jfmt-summary: files={} issues={total_issues} fixable={fixable} errors={errors} warnings={warnings} result={}",
+            reports.len(),
+            if failed { "fail" } else { "pass" }
+        );
+        // Keep stdout valid JSON in JSON mode.
+        if json { eprintln!("{line}") } else { println!("{line}") }
+    }
+    if failed {
         std::process::exit(1);
     }
 }
 
-fn lint_file(path: &str, config: &libjfmt::Config, fix: bool) -> Result<usize, String> {
-    let display_path = Path::new(path).display();
-    let src = fs::read_to_string(path).map_err(|e| format!("failed to read {display_path}: {e}"))?;
+/// Lint each group of files with its own config, after `adjust` has had a
+/// say in it; reports come back sorted by path as `lint_files` sorts them.
+fn lint_groups(
+    groups: Vec<(libjfmt::Config, Vec<PathBuf>)>,
+    options: &libjfmt::LintOptions,
+    adjust: impl Fn(&mut libjfmt::Config),
+) -> Result<Vec<libjfmt::FileReport>, libjfmt::LintError> {
+    let mut reports = Vec::new();
+    for (mut config, files) in groups {
+        adjust(&mut config);
+        reports.extend(libjfmt::lint_files(&files, &config, options)?);
+    }
+    reports.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(reports)
+}
 
-    if fix {
-        let (fixed, issues_before) = libjfmt::fix_java_source(&src, config).map_err(|e| e.to_string())?;
-        let mut issues_after = issues_before;
-        if fixed != src {
-            fs::write(path, &fixed).map_err(|e| format!("failed to write {display_path}: {e}"))?;
-            eprintln!("applied fixes: {display_path}");
-            // Re-lint the fixed content to show remaining issues only
-            issues_after = libjfmt::lint_java_source(&fixed, config).map_err(|e| e.to_string())?;
-        }
-        for issue in &issues_after {
+/// `-`/`--stdin`: lint the source on stdin, as an editor pipes a buffer.
+/// With `--fix` the fixed source (or the input, if nothing changed) goes to
+/// stdout, and with `--check` the diff `--fix` would apply; diagnostics
+/// always go to stderr, named after `--stdin-filename`.
+fn run_stdin(config: &libjfmt::Config, options: &libjfmt::LintOptions, filename: Option<&Path>, warnings_as_errors: bool) -> ! {
+    let mut source = String::new();
+    if let Err(err) = std::io::Read::read_to_string(&mut std::io::stdin(), &mut source) {
+        eprintln!("error: failed to read stdin: {err}");
+        std::process::exit(2);
+    }
+    let outcome = match libjfmt::lint_text(&source, filename, config, options) {
+        Ok(outcome) => outcome,
+        Err(err) => {
+            eprintln!("error: {err}");
+            if options.fix { print!("{source}"); }
+            std::process::exit(2);
+        }
+    };
+    let name = filename.map_or("<stdin>".into(), |p| p.display().to_string());
+    if let Some(reason) = &outcome.skipped {
+        eprintln!("{name}: {reason}");
+    }
+    if options.fix && !options.check {
+        print!("{}", outcome.fixed.as_deref().unwrap_or(&source));
+    }
+    if outcome.would_change {
+        eprintln!("{name}: would be changed by --fix");
+    }
+    if let Some(preview) = &outcome.preview {
+        print!("{}", libjfmt::unified_diff(&source, preview, &name, &name));
+    }
+    let unstable = outcome.idempotent == Some(false);
+    if unstable {
+        eprintln!("{name}: fixes are not idempotent; a second --fix pass changes the output");
+    }
+    for issue in &outcome.issues {
+        eprintln!("{}", issue_line(&name, issue));
+    }
+    let failing = outcome.issues.iter().any(|i| fails(i.severity, warnings_as_errors));
+    std::process::exit(if !failing && !outcome.would_change && !unstable { 0 } else { 1 });
+}
+
+/// `fmt` subcommand: reformat files in place. With `--check`, print the
+/// diff instead and exit 1 if any file would change.
+fn run_fmt(program: &str, args: &[String]) -> ! {
+    let mut check = false;
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut bad_paths = false;
+    for arg in args {
+        if arg == "--check" {
+            check = true;
+        } else if !collect_path(arg, &mut files) {
+            bad_paths = true;
+        }
+    }
+    if bad_paths {
+        std::process::exit(2);
+    }
+    if files.is_empty() {
+        print_usage(program);
+        std::process::exit(2);
+    }
+
+    let (_, groups) = load_file_configs(&working_dir(), &files);
+    let mut files: Vec<_> = groups.iter().flat_map(|(config, paths)| paths.iter().map(move |path| (path, config))).collect();
+    files.sort_by_key(|&(path, _)| path);
+    let (mut changed, mut errors) = (0usize, 0usize);
+    for (path, config) in files {
+        let display_path = path.display();
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(err) => {
+                eprintln!("{display_path}: error: failed to read: {err}");
+                errors += 1;
+                continue;
+            }
+        };
+        let formatted = libjfmt::format_java_source(&source, config);
+        if formatted == source { continue; }
+        changed += 1;
+        if check {
+            println!("{display_path}: would be reformatted");
+            let name = display_path.to_string();
+            print!("{}", libjfmt::unified_diff(&source, &formatted, &name, &name));
+        } else if let Err(err) = fs::write(path, formatted) {
+            eprintln!("{display_path}: error: failed to write: {err}");
+            errors += 1;
+        } else {
+            eprintln!("formatted: {display_path}");
+        }
+    }
+    if errors > 0 {
+        std::process::exit(2);
+    }
+    std::process::exit(if check && changed > 0 { 1 } else { 0 });
+}
+
+/// `dup` subcommand: report duplicated code across the given files.
+fn run_dup(program: &str, args: &[String]) -> ! {
+    let mut options = libjfmt::DupOptions::default();
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut bad_paths = false;
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        if arg == "--min-tokens" || arg.starts_with("--min-tokens=") {
+            let value = match arg.strip_prefix("--min-tokens=") {
+                Some(v) => Some(v),
+                None => rest.next().map(String::as_str),
+            };
+            match value.and_then(|v| v.parse::<usize>().ok()) {
+                Some(n) if n > 0 => options.min_tokens = n,
+                _ => {
+                    eprintln!("--min-tokens expects a positive number");
+                    print_usage(program);
+                    std::process::exit(2);
+                }
+            }
+        } else if !collect_path(arg, &mut files) {
+            bad_paths = true;
+        }
+    }
+    if bad_paths {
+        std::process::exit(2);
+    }
+    if files.is_empty() {
+        print_usage(program);
+        std::process::exit(2);
+    }
+
+    let (_, groups) = load_file_configs(&working_dir(), &files);
+
+    let report = libjfmt::find_duplicates_in_groups(&groups, &options);
+    for (path, err) in &report.errors {
+        eprintln!("{}: error: {err}", path.display());
+    }
+    for pair in &report.pairs {
+        println!(
+            "{}:{}-{}: duplicates {}:{}-{} ({} tokens)",
+            pair.first.path.display(),
+            pair.first.start_line,
+            pair.first.end_line,
+            pair.second.path.display(),
+            pair.second.start_line,
+            pair.second.end_line,
+            pair.tokens
+        );
+    }
+    std::process::exit(if report.pairs.is_empty() && report.errors.is_empty() { 0 } else { 1 });
+}
+
+/// `metrics` subcommand: print per-file and per-method metrics. Informational
+/// only; exits non-zero just when a file can't be read or parsed.
+fn run_metrics(program: &str, args: &[String]) -> ! {
+    let mut json = false;
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut bad_paths = false;
+    for arg in args {
+        if arg == "--json" {
+            json = true;
+        } else if !collect_path(arg, &mut files) {
+            bad_paths = true;
+        }
+    }
+    if bad_paths {
+        std::process::exit(2);
+    }
+    if files.is_empty() {
+        print_usage(program);
+        std::process::exit(2);
+    }
+
+    let (_, groups) = load_file_configs(&working_dir(), &files);
+
+    let mut reports: Vec<_> = groups.iter().flat_map(|(config, files)| libjfmt::collect_metrics(files, config)).collect();
+    reports.sort_by(|a, b| a.path.cmp(&b.path));
+    let mut failed = false;
+    let mut measured = Vec::new();
+    for report in &reports {
+        match &report.metrics {
+            Ok(metrics) => measured.push((report.path.display().to_string(), metrics)),
+            Err(err) => {
+                eprintln!("{}: error: {err}", report.path.display());
+                failed = true;
+            }
+        }
+    }
+
+    if json {
+        let files: Vec<_> = measured
+            .iter()
+            .map(|(path, metrics)| serde_json::json!({ "path": path, "metrics": metrics }))
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&files).unwrap_or_default());
+    } else {
+        for (path, m) in &measured {
             println!(
-                "{}:{}:{}: {}: {}",
-                display_path,
-                issue.line,
-                issue.column,
-                issue.rule_id,
-                issue.message
+                "{path}: {} lines ({} code, {} comment, {} blank), comment ratio {:.2}",
+                m.lines, m.code_lines, m.comment_lines, m.blank_lines, m.comment_ratio
             );
+            if m.methods.is_empty() { continue; }
+            println!("  {:<32} {:>6} {:>6} {:>6} {:>10} {:>7}", "method", "line", "lines", "params", "complexity", "nesting");
+            for method in &m.methods {
+                println!(
+                    "  {:<32} {:>6} {:>6} {:>6} {:>10} {:>7}",
+                    method.name, method.line, method.lines, method.parameters, method.complexity, method.max_nesting
+                );
+            }
         }
-        Ok(issues_after.len())
-    } else {
-        let issues = libjfmt::lint_java_source(&src, config).map_err(|e| e.to_string())?;
-        for issue in &issues {
+    }
+    std::process::exit(if failed { 1 } else { 0 });
+}
+
+/// `rules` subcommand: list every rule with its kind, whether the config of
+/// the given file or directory (the working directory by default) leaves it
+/// on, and what it flags.
+fn run_rules(program: &str, args: &[String]) -> ! {
+    let mut resolver = libjfmt::ConfigResolver::new();
+    let config = match args {
+        [] => resolver.config_in(&working_dir()),
+        [path] if !Path::new(path).exists() => {
+            eprintln!("{path}: error: no such file or directory");
+            std::process::exit(2);
+        }
+        [path] if Path::new(path).is_dir() => resolver.config_in(Path::new(path)),
+        [path] => resolver.config_for(Path::new(path)),
+        _ => {
+            print_usage(program);
+            std::process::exit(2);
+        }
+    };
+    let config = config_or_exit(config);
+    for path in resolver.loaded() {
+        print_config_warnings(path);
+    }
+    let enabled = libjfmt::RuleSet::all().without(config.disabled_rules());
+    for rule in libjfmt::registry() {
+        let kind = match rule.kind() {
+            libjfmt::RuleKind::Text => "text",
+            libjfmt::RuleKind::Ast => "ast",
+        };
+        let state = if enabled.contains(rule.id()) { "on" } else { "off" };
+        println!("{:<32} {kind:<4} {state:<3} {}", rule.id(), rule.description());
+    }
+    std::process::exit(0);
+}
+
+/// `cycles` subcommand: report import cycles between packages.
+fn run_cycles(program: &str, args: &[String]) -> ! {
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut bad_paths = false;
+    for arg in args {
+        bad_paths |= !collect_path(arg, &mut files);
+    }
+    if bad_paths {
+        std::process::exit(2);
+    }
+    if files.is_empty() {
+        print_usage(program);
+        std::process::exit(2);
+    }
+
+    let (config, groups) = load_file_configs(&working_dir(), &files);
+
+    let project = libjfmt::Project::load_groups(&groups, &config);
+    for (path, err) in project.errors() {
+        eprintln!("{}: error: {err}", path.display());
+    }
+    let cycles = libjfmt::package_cycles(&project);
+    for cycle in &cycles {
+        println!("package cycle: {}", cycle.packages().collect::<Vec<_>>().join(" -> "));
+        for edge in &cycle.edges {
+            println!("  {} -> {} ({}:{})", edge.from, edge.to, edge.path.display(), edge.line);
+        }
+    }
+    std::process::exit(if cycles.is_empty() && project.errors().is_empty() { 0 } else { 1 });
+}
+
+/// `unused` subcommand: report types and methods no given file refers to.
+fn run_unused(program: &str, args: &[String]) -> ! {
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut bad_paths = false;
+    for arg in args {
+        bad_paths |= !collect_path(arg, &mut files);
+    }
+    if bad_paths {
+        std::process::exit(2);
+    }
+    if files.is_empty() {
+        print_usage(program);
+        std::process::exit(2);
+    }
+
+    let (config, groups) = load_file_configs(&working_dir(), &files);
+
+    let project = libjfmt::Project::load_groups(&groups, &config);
+    for (path, err) in project.errors() {
+        eprintln!("{}: error: {err}", path.display());
+    }
+    let unused = libjfmt::unused_declarations(&project);
+    for decl in &unused {
+        println!(
+            "{}:{}:{}: unused-declaration: {} '{}' is never referenced in the analyzed sources",
+            decl.path.display(),
+            decl.line,
+            decl.column,
+            decl.kind,
+            decl.name
+        );
+    }
+    std::process::exit(if unused.is_empty() && project.errors().is_empty() { 0 } else { 1 });
+}
+
+/// `javadoc` subcommand: Javadoc coverage of public API per package.
+fn run_javadoc(program: &str, args: &[String]) -> ! {
+    let mut format = "text";
+    let mut files: Vec<PathBuf> = Vec::new();
+    let mut bad_paths = false;
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        if arg == "--format" || arg.starts_with("--format=") {
+            let value = match arg.strip_prefix("--format=") {
+                Some(v) => Some(v),
+                None => rest.next().map(String::as_str),
+            };
+            format = match value {
+                Some(f @ ("text" | "json" | "html")) => f,
+                _ => {
+                    eprintln!("--format expects one of: text, json, html");
+                    print_usage(program);
+                    std::process::exit(2);
+                }
+            };
+        } else if !collect_path(arg, &mut files) {
+            bad_paths = true;
+        }
+    }
+    if bad_paths {
+        std::process::exit(2);
+    }
+    if files.is_empty() {
+        print_usage(program);
+        std::process::exit(2);
+    }
+
+    let (config, groups) = load_file_configs(&working_dir(), &files);
+
+    let project = libjfmt::Project::load_groups(&groups, &config);
+    for (path, err) in project.errors() {
+        eprintln!("{}: error: {err}", path.display());
+    }
+    let (packages, overall) = libjfmt::javadoc_coverage(&project);
+    match format {
+        "json" => {
+            let json = serde_json::json!({ "packages": packages, "overall": overall });
+            println!("{}", serde_json::to_string_pretty(&json).unwrap_or_default());
+        }
+        "html" => {
+            println!("<table>");
+            println!("<tr><th>package</th><th>documented</th><th>total</th><th>coverage</th></tr>");
+            for p in packages.iter().chain([&overall]) {
+                println!(
+                    "<tr><td>{}</td><td>{}</td><td>{}</td><td>{:.1}%</td></tr>",
+                    html_escape(&p.package),
+                    p.documented,
+                    p.total,
+                    p.percent()
+                );
+            }
+            println!("</table>");
+        }
+        _ => {
+            println!("{:<48} {:>10} {:>6} {:>9}", "package", "documented", "total", "coverage");
+            for p in packages.iter().chain([&overall]) {
+                println!("{:<48} {:>10} {:>6} {:>8.1}%", p.package, p.documented, p.total, p.percent());
+            }
+        }
+    }
+    std::process::exit(if project.errors().is_empty() { 0 } else { 1 });
+}
+
+/// `package-paths` subcommand: detect the source roots of a Maven or Gradle
+/// project and check every file's package against its directory.
+fn run_package_paths(program: &str, args: &[String]) -> ! {
+    let root = match args {
+        [] => PathBuf::from("."),
+        [root] => PathBuf::from(root),
+        _ => {
+            print_usage(program);
+            std::process::exit(2);
+        }
+    };
+    let layout = libjfmt::SourceLayout::detect(&root).and_then(|layout| Ok((layout.java_files()?, layout)));
+    let (files, layout) = match layout {
+        Ok(found) => found,
+        Err(err) => {
+            eprintln!("{}: error: {err}", root.display());
+            std::process::exit(2);
+        }
+    };
+    for r in &layout.roots {
+        let kind = if r.kind == libjfmt::SourceKind::Test { "test" } else { "main" };
+        eprintln!("source root: {} ({kind})", r.path.display());
+    }
+
+    let (config, groups) = load_file_configs(&root, &files);
+
+    let project = libjfmt::Project::load_groups(&groups, &config).with_roots(layout.roots.iter().map(|r| r.path.clone()));
+    for (path, err) in project.errors() {
+        eprintln!("{}: error: {err}", path.display());
+    }
+    let issues = project.check(&[&libjfmt::PackagePathRule]);
+    for issue in &issues {
+        println!("{}:{}:{}: {}: {}", issue.path.display(), issue.line, issue.column, issue.rule_id, issue.message);
+    }
+    std::process::exit(if issues.is_empty() && project.errors().is_empty() { 0 } else { 1 });
+}
+
+/// `modules` subcommand: lint every Maven/Gradle module of a repository with
+/// its own config and source roots, and summarize the results per module.
+fn run_modules(program: &str, args: &[String]) -> ! {
+    let mut options = libjfmt::LintOptions::default();
+    let mut repo = None;
+    for arg in args {
+        if arg == "--fix" {
+            options.fix = true;
+        } else if repo.is_none() && !arg.starts_with('-') {
+            repo = Some(PathBuf::from(arg));
+        } else {
+            print_usage(program);
+            std::process::exit(2);
+        }
+    }
+    let repo = repo.unwrap_or_else(|| PathBuf::from("."));
+    let modules = match libjfmt::discover_modules(&repo) {
+        Ok(m) => m,
+        Err(err) => {
+            eprintln!("{}: error: {err}", repo.display());
+            std::process::exit(2);
+        }
+    };
+
+    let mut failed = false;
+    let mut summaries = Vec::new();
+    let mut resolver = libjfmt::ConfigResolver::new();
+    for module in &modules {
+        let name = module.root.strip_prefix(&repo).unwrap_or(&module.root).display().to_string();
+        let name = if name.is_empty() { ".".to_string() } else { name };
+        // Each file gets the jfmt.toml files at or above it and their
+        // `[[overrides]]`; the module root's config drives the project checks.
+        let resolved = resolver.config_in(&module.root).and_then(|c| Ok((c, resolver.group(&module.files)?)));
+        let (config, groups) = match resolved {
+            Ok(r) => r,
+            Err(err) => {
+                eprintln!("{name}: error loading config: {err}");
+                failed = true;
+                continue;
+            }
+        };
+        let reports = match lint_groups(groups, &options, |_| {}) {
+            Ok(r) => r,
+            Err(err) => {
+                eprintln!("{name}: error: {err}");
+                failed = true;
+                continue;
+            }
+        };
+        let project = libjfmt::Project::load(&module.files, &config)
+            .with_roots(module.layout.roots.iter().map(|r| r.path.clone()));
+        let project_issues = project.check(&[&libjfmt::PackagePathRule]);
+
+        let mut stats = libjfmt::LintStats::default();
+        let mut errors = 0;
+        let mut failing = 0;
+        for report in &reports {
+            match report_file(report, &mut stats, false, false) {
+                Ok((_, count)) => failing += count,
+                Err(err) => {
+                    eprintln!("{}: error: {err}", report.path.display());
+                    errors += 1;
+                }
+            }
+        }
+        for issue in &project_issues {
+            println!("{}:{}:{}: {}: {}", issue.path.display(), issue.line, issue.column, issue.rule_id, issue.message);
+        }
+        let total = libjfmt::IssueSummary::from_reports(&reports).total();
+        let issues = total.total + project_issues.len();
+        failed |= failing > 0 || !project_issues.is_empty() || errors > 0;
+        summaries.push((name, module.files.len(), issues, total.fixable, errors));
+    }
+
+    eprintln!("{:<40} {:>6} {:>8} {:>8} {:>7}", "module", "files", "issues", "fixable", "errors");
+    for (name, files, issues, fixable, errors) in &summaries {
+        eprintln!("{name:<40} {files:>6} {issues:>8} {fixable:>8} {errors:>7}");
+    }
+    std::process::exit(if failed { 1 } else { 0 });
+}
+
+/// `compare` subcommand: diff two `--format json` reports and print the
+/// issues introduced and resolved between them. Exits 1 when anything new
+/// was introduced.
+fn run_compare(program: &str, args: &[String]) -> ! {
+    let [old, new] = args else {
+        print_usage(program);
+        std::process::exit(2);
+    };
+    let read = |path: &String| -> Vec<libjfmt::ReportedIssue> {
+        let parsed = fs::read_to_string(path)
+            .map_err(|e| e.to_string())
+            .and_then(|text| serde_json::from_str(&text).map_err(|e| e.to_string()));
+        parsed.unwrap_or_else(|err| {
+            eprintln!("{path}: error: {err}");
+            std::process::exit(2);
+        })
+    };
+    let diff = libjfmt::compare_reports(&read(old), &read(new));
+    for (sign, issues) in [("+", &diff.introduced), ("-", &diff.resolved)] {
+        for issue in issues {
             println!(
-                "{}:{}:{}: {}: {}",
-                display_path,
+                "{sign} {}:{}:{}: {}: {}",
+                issue.path.display(),
                 issue.line,
                 issue.column,
-                issue.rule_id,
+                issue.rule,
                 issue.message
             );
         }
-        Ok(issues.len())
     }
+    eprintln!("{} introduced, {} resolved", diff.introduced.len(), diff.resolved.len());
+    std::process::exit(if diff.introduced.is_empty() { 0 } else { 1 });
+}
+
+/// First line after the shebang of hooks written by `install-hook`, so a
+/// re-install can tell its own hook from one it must not clobber.
+const HOOK_MARKER: &str = "# Installed by jfmtcli install-hook.";
+
+/// `install-hook` subcommand: write a git pre-commit hook that lints the
+/// staged .java files, optionally fixing and re-staging them.
+fn run_install_hook(program: &str, args: &[String]) -> ! {
+    let mut fix = false;
+    let mut force = false;
+    for arg in args {
+        match arg.as_str() {
+            "--fix" => fix = true,
+            "--force" => force = true,
+            _ => {
+                print_usage(program);
+                std::process::exit(2);
+            }
+        }
+    }
+
+    let hooks = match std::process::Command::new("git").args(["rev-parse", "--git-path", "hooks"]).output() {
+        Ok(out) if out.status.success() => PathBuf::from(String::from_utf8_lossy(&out.stdout).trim()),
+        _ => {
+            eprintln!("error: not inside a git repository");
+            std::process::exit(2);
+        }
+    };
+    let hook = hooks.join("pre-commit");
+    if let Ok(existing) = fs::read_to_string(&hook)
+        && !existing.contains(HOOK_MARKER)
+        && !force
+    {
+        eprintln!("{}: a pre-commit hook already exists; use --force to replace it", hook.display());
+        std::process::exit(1);
+    }
+
+    let exe = env::current_exe().map_or_else(|_| "jfmtcli".to_string(), |p| p.display().to_string());
+    let exe = format!("'{}'", exe.replace('\'', r"'\''"));
+    let staged = "git diff --cached --name-only -z --diff-filter=ACMR -- '*.java'";
+    let mut script = format!("#!/bin/sh\n{HOOK_MARKER}\n[ -n \"$({staged} | tr -d '\\0')\" ] || exit 0\n");
+    if fix {
+        // Re-staging a fixed file would also stage edits left out of the
+        // commit on purpose, so with any of those only check the index.
+        script += &format!(
+            "partial=$({staged} | xargs -0 git diff --name-only --)\n\
+             if [ -n \"$partial\" ]; then\n\
+             \techo 'jfmt: not fixing, these staged files also have unstaged changes:' >&2\n\
+             \techo \"$partial\" >&2\n\
+             \t{staged} | xargs -0 {exe} --staged\n\
+             \texit $?\n\
+             fi\n"
+        );
+        script += &format!("{staged} | xargs -0 {exe} --fix\nstatus=$?\n{staged} | xargs -0 git add --\nexit $status\n");
+    } else {
+        script += &format!("{staged} | xargs -0 {exe} --staged\n");
+    }
+
+    if let Err(err) = fs::create_dir_all(&hooks).and_then(|_| fs::write(&hook, script)) {
+        eprintln!("{}: error: {err}", hook.display());
+        std::process::exit(1);
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if let Err(err) = fs::set_permissions(&hook, fs::Permissions::from_mode(0o755)) {
+            eprintln!("{}: error: {err}", hook.display());
+            std::process::exit(1);
+        }
+    }
+    eprintln!("installed {}", hook.display());
+    std::process::exit(0);
+}
+
+/// Add the files `arg` names: a `.java` file, every `.java` file under a
+/// directory, or the `.java` files matching a glob the shell didn't expand.
+/// Returns false, after reporting it, when it names none or can't be read.
+fn collect_path(arg: &str, files: &mut Vec<PathBuf>) -> bool {
+    let path = Path::new(arg);
+    if arg.contains(['*', '?']) && !path.exists() {
+        match libjfmt::expand_glob(arg) {
+            Ok(found) => {
+                let before = files.len();
+                files.extend(found.into_iter().filter(|f| f.extension().is_some_and(|e| e == "java")));
+                if files.len() > before { return true; }
+                eprintln!("{arg}: error: no .java files match");
+            }
+            Err(err) => eprintln!("{arg}: error: {err}"),
+        }
+        false
+    } else if path.is_dir() {
+        match libjfmt::java_files_under(path) {
+            Ok(found) => {
+                files.extend(found);
+                true
+            }
+            Err(err) => {
+                eprintln!("{arg}: error: {err}");
+                false
+            }
+        }
+    } else if arg.ends_with(".java") {
+        files.push(path.to_path_buf());
+        true
+    } else {
+        eprintln!("{arg}: error: not a .java file or a directory");
+        false
+    }
+}
+
+fn working_dir() -> PathBuf { env::current_dir().unwrap_or_else(|_| PathBuf::from(".")) }
+
+/// Load the configuration for the working directory through `resolver`,
+/// warning about deprecated and unknown keys; returns it with how many
+/// warnings were printed. Exits on an unreadable or invalid file.
+fn load_config_with_warnings(resolver: &mut libjfmt::ConfigResolver) -> (libjfmt::Config, usize) {
+    let config = config_or_exit(resolver.config_in(&working_dir()));
+    let count = resolver.loaded().iter().map(|path| print_config_warnings(path)).sum();
+    (config, count)
+}
+
+/// The config of `dir`, for settings that concern all of `files` together,
+/// and `files` grouped by the config each gets from the `jfmt.toml` files at
+/// or above it and their `[[overrides]]`. Warns like
+/// `load_config_with_warnings`.
+fn load_file_configs(dir: &Path, files: &[PathBuf]) -> (libjfmt::Config, Vec<(libjfmt::Config, Vec<PathBuf>)>) {
+    let mut resolver = libjfmt::ConfigResolver::new();
+    let config = config_or_exit(resolver.config_in(dir));
+    let groups = config_or_exit(resolver.group(files));
+    for path in resolver.loaded() {
+        print_config_warnings(path);
+    }
+    (config, groups)
+}
+
+/// The resolved config, or exit with the error.
+fn config_or_exit<T>(config: Result<T, libjfmt::ConfigError>) -> T {
+    config.unwrap_or_else(|err| {
+        eprintln!("error loading config: {err}");
+        std::process::exit(2);
+    })
+}
+
+/// Print the warnings for the config file at `path`; returns how many.
+fn print_config_warnings(path: &Path) -> usize {
+    let Ok(text) = fs::read_to_string(path) else { return 0 };
+    let warnings = libjfmt::config_warnings(&text).unwrap_or_default();
+    for warning in &warnings {
+        eprintln!("{}: warning: {warning}", path.display());
+    }
+    warnings.len()
+}
+
+/// `migrate-config` subcommand: rewrite jfmt.toml to the current schema. With
+/// `--check`, only report what would change and exit 1 if anything would.
+fn run_migrate_config(program: &str, args: &[String]) -> ! {
+    let check = match args {
+        [] => false,
+        [flag] if flag == "--check" => true,
+        _ => {
+            print_usage(program);
+            std::process::exit(2);
+        }
+    };
+    let Some(path) = env::current_dir().ok().and_then(|dir| libjfmt::find_config_path(&dir)) else {
+        eprintln!("no jfmt.toml found");
+        std::process::exit(2);
+    };
+    let migrated = fs::read_to_string(&path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| libjfmt::migrate_config(&text).map_err(|e| e.to_string()));
+    let (text, changes) = match migrated {
+        Ok(m) => m,
+        Err(err) => {
+            eprintln!("{}: error: {err}", path.display());
+            std::process::exit(2);
+        }
+    };
+    for change in &changes {
+        println!("{}: {change}", path.display());
+    }
+    if changes.is_empty() {
+        eprintln!("{}: already up to date", path.display());
+    } else if check {
+        std::process::exit(1);
+    } else if let Err(err) = fs::write(&path, text) {
+        eprintln!("{}: error: {err}", path.display());
+        std::process::exit(2);
+    }
+    std::process::exit(0);
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// How many files `--stats` lists as top offenders.
+const TOP_OFFENDERS: usize = 10;
+
+fn print_summary(summary: &libjfmt::IssueSummary) {
+    eprintln!("{:<24} {:>8} {:>8} {:>8}", "rule", "issues", "fixable", "manual");
+    let mut rules: Vec<_> = summary.per_rule.iter().collect();
+    rules.sort_by(|a, b| b.1.total.cmp(&a.1.total).then_with(|| a.0.cmp(b.0)));
+    for (rule, count) in rules {
+        eprintln!("{:<24} {:>8} {:>8} {:>8}", rule, count.total, count.fixable, count.manual());
+    }
+    let total = summary.total();
+    eprintln!("{:<24} {:>8} {:>8} {:>8}", "total", total.total, total.fixable, total.manual());
+    eprintln!();
+    eprintln!("{:<48} {:>8}", "directory", "issues");
+    for (dir, count) in &summary.per_directory {
+        let name = dir.display().to_string();
+        eprintln!("{:<48} {:>8}", if name.is_empty() { "." } else { &name }, count);
+    }
+    eprintln!();
+    eprintln!("top offenders:");
+    for (path, count) in summary.per_file.iter().take(TOP_OFFENDERS) {
+        eprintln!("{:>8}  {}", count, path.display());
+    }
+}
+
+fn print_timing(stats: &libjfmt::LintStats, wall: Duration) {
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    eprintln!("{:<24} {:>10}", "phase", "time (ms)");
+    for (phase, time) in stats.phases() {
+        eprintln!("{:<24} {:>10.3}", phase, ms(time));
+    }
+    eprintln!();
+    eprintln!("{:<24} {:>10}", "rule", "time (ms)");
+    let mut rules: Vec<_> = stats.per_rule.iter().collect();
+    rules.sort_by(|a, b| b.1.cmp(a.1));
+    for (rule, time) in rules {
+        eprintln!("{:<24} {:>10.3}", rule, ms(*time));
+    }
+    eprintln!();
+    eprintln!("{:<24} {:>10.3}", "wall clock", ms(wall));
+    eprintln!("(phase and rule times are summed across files and worker threads)");
+}
+
+/// Whether an issue of `severity` fails the run: errors always, warnings
+/// with `--warnings-as-errors`, infos never.
+fn fails(severity: libjfmt::Severity, warnings_as_errors: bool) -> bool {
+    match severity {
+        libjfmt::Severity::Error => true,
+        libjfmt::Severity::Warning => warnings_as_errors,
+        libjfmt::Severity::Info => false,
+    }
+}
+
+/// `path:line:col: rule: message`, with the severity before the rule unless
+/// it is an error.
+fn issue_line(path: &str, issue: &libjfmt::LintIssue) -> String {
+    let severity = match issue.severity {
+        libjfmt::Severity::Error => String::new(),
+        other => format!("{}: ", other.as_str()),
+    };
+    format!("{path}:{}:{}: {severity}{}: {}", issue.line, issue.column, issue.rule_id, issue.message)
+}
+
+/// Print one file's results and count its issues, all and failing; in check
+/// mode a file `--fix` would change fails too. With `json` the issues are
+/// left to the JSON report and notices go to stderr, so stdout stays
+/// machine-readable.
+fn report_file(
+    report: &libjfmt::FileReport,
+    stats: &mut libjfmt::LintStats,
+    json: bool,
+    warnings_as_errors: bool,
+) -> Result<(usize, usize), String> {
+    let display_path = report.path.display();
+    let outcome = report.outcome.as_ref().map_err(|e| e.to_string())?;
+    if let Some(reason) = &outcome.skipped {
+        eprintln!("{display_path}: {reason}");
+        return Ok((0, 0));
+    }
+
+    if let Some(fixed) = &outcome.fixed {
+        let started = Instant::now();
+        fs::write(&report.path, fixed).map_err(|e| format!("failed to write {display_path}: {e}"))?;
+        stats.write += started.elapsed();
+        eprintln!("applied fixes: {display_path}");
+    }
+    let notice = |text: String| if json { eprintln!("{text}") } else { println!("{text}") };
+    if outcome.would_change {
+        notice(format!("{display_path}: would be changed by --fix"));
+    }
+    if let (Some(preview), Some(original)) = (&outcome.preview, &outcome.original) {
+        let name = display_path.to_string();
+        print!("{}", libjfmt::unified_diff(original, preview, &name, &name));
+    }
+    let unstable = outcome.idempotent == Some(false);
+    if unstable {
+        notice(format!("{display_path}: fixes are not idempotent; a second --fix pass changes the output"));
+    }
+    for issue in outcome.issues.iter().filter(|_| !json) {
+        println!("{}", issue_line(&display_path.to_string(), issue));
+    }
+    let failing = outcome.issues.iter().filter(|i| fails(i.severity, warnings_as_errors)).count() + usize::from(outcome.would_change);
+    Ok((outcome.issues.len() + usize::from(unstable), failing + usize::from(unstable)))
 }
@@ -0,0 +1,417 @@
+//! End-to-end runs of the binary.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// A fresh directory holding `files` (path, content).
+fn tree(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("jfmtcli-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    for (path, content) in files {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().expect("parent")).expect("create dir");
+        fs::write(path, content).expect("write");
+    }
+    root
+}
+
+fn jfmtcli() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_jfmtcli"))
+}
+
+/// Run git in `dir` with a fixed identity, returning whether it succeeded.
+fn git(dir: &Path, args: &[&str]) -> bool {
+    Command::new("git")
+        .current_dir(dir)
+        .args(["-c", "user.name=jfmt", "-c", "user.email=jfmt@example.com"])
+        .args(args)
+        .output()
+        .expect("run git")
+        .status
+        .success()
+}
+
+#[test]
+fn columns_are_reported_in_the_requested_unit() {
+    let dir = tree("columns", &[("A.java", "class A {\n\tvoid f() { String s = \"\u{1F600}\";; }\n}\n")]);
+    let column = |unit: &str| {
+        let out = jfmtcli().current_dir(&dir).args(["--columns", unit, "A.java"]).output().expect("run");
+        assert_eq!(out.status.code(), Some(1));
+        let stdout = String::from_utf8_lossy(&out.stdout).into_owned();
+        let line = stdout.lines().find(|l| l.contains("no-empty-statement")).expect("issue").to_string();
+        line.split(':').nth(2).expect("column").to_string()
+    };
+    assert_eq!(column("display"), "31");
+    assert_eq!(column("chars"), "28");
+    assert_eq!(column("utf16"), "29");
+    assert_eq!(column("bytes"), "31");
+
+    let bad = jfmtcli().current_dir(&dir).args(["--columns=points", "A.java"]).output().expect("run");
+    assert_eq!(bad.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&bad.stderr).contains("--columns expects one of"));
+}
+
+#[test]
+fn stats_tally_issues_on_stderr() {
+    let dir = tree("stats", &[("src/A.java", "import java.util.*;\n\nclass A {\n\tint x;\n}\n"), ("src/B.java", "class B {\n}\n")]);
+    let out = jfmtcli().current_dir(&dir).args(["--stats", "src/A.java", "src/B.java"]).output().expect("run");
+    assert_eq!(out.status.code(), Some(1));
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    let rows: Vec<Vec<&str>> = stderr.lines().map(|l| l.split_whitespace().collect()).collect();
+    assert!(rows.contains(&vec!["indent-style", "1", "1", "0"]));
+    assert!(rows.contains(&vec!["no-wildcard-imports", "1", "0", "1"]));
+    assert!(rows.contains(&vec!["total", "2", "1", "1"]));
+    assert!(rows.contains(&vec!["src", "2"]));
+    assert!(rows.contains(&vec!["2", "src/A.java"]));
+    assert!(!stderr.contains("B.java"));
+}
+
+#[test]
+fn compare_exits_non_zero_only_for_introduced_issues() {
+    let dir = tree("compare", &[("A.java", "class A {\n\tint x;\n}\n")]);
+    let report = |name: &str| {
+        let out = jfmtcli().current_dir(&dir).args(["--format", "json", "A.java"]).output().expect("run");
+        fs::write(dir.join(name), &out.stdout).expect("write report");
+    };
+    report("old.json");
+    fs::write(dir.join("A.java"), "import java.util.*;\n\nclass A {\n    int x;\n}\n").expect("write");
+    report("new.json");
+
+    let out = jfmtcli().current_dir(&dir).args(["compare", "old.json", "new.json"]).output().expect("run");
+    assert_eq!(out.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let signs: Vec<_> = stdout.lines().map(|l| l.split(':').next().expect("line").to_string()).collect();
+    assert_eq!(signs, ["+ A.java", "- A.java"]);
+    assert!(stdout.contains("no-wildcard-imports") && stdout.contains("indent-style"));
+
+    let out = jfmtcli().current_dir(&dir).args(["compare", "new.json", "old.json"]).output().expect("run");
+    assert_eq!(out.status.code(), Some(1));
+    let out = jfmtcli().current_dir(&dir).args(["compare", "old.json", "old.json"]).output().expect("run");
+    assert_eq!(out.status.code(), Some(0));
+    assert!(out.stdout.is_empty());
+    let out = jfmtcli().current_dir(&dir).args(["compare", "old.json", "A.java"]).output().expect("run");
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn installed_hook_blocks_commits_with_issues() {
+    let dir = tree("hook", &[("A.java", "import java.util.*;\n\nclass A {\n\tint x;\n}\n"), ("notes.txt", "\tnot java\n")]);
+    assert!(git(&dir, &["init", "-q"]));
+    let hook = dir.join(".git/hooks/pre-commit");
+    fs::write(&hook, "#!/bin/sh\nexit 0\n").expect("write hook");
+    let install = |args: &[&str]| jfmtcli().current_dir(&dir).arg("install-hook").args(args).output().expect("run").status.code();
+    assert_eq!(install(&[]), Some(1));
+    assert_eq!(fs::read_to_string(&hook).expect("read"), "#!/bin/sh\nexit 0\n");
+    assert_eq!(install(&["--force"]), Some(0));
+    assert!(fs::read_to_string(&hook).expect("read").contains("# Installed by jfmtcli install-hook."));
+    // Its own hook is replaced without --force.
+    assert_eq!(install(&["--fix"]), Some(0));
+
+    // Fixable issues are fixed and re-staged; the wildcard import still blocks.
+    assert!(git(&dir, &["add", "A.java"]));
+    assert!(!git(&dir, &["commit", "-q", "-m", "a"]));
+    let staged = Command::new("git").current_dir(&dir).args(["show", ":A.java"]).output().expect("run git");
+    assert_eq!(String::from_utf8_lossy(&staged.stdout), "import java.util.*;\n\nclass A {\n    int x;\n}\n");
+
+    fs::write(dir.join("A.java"), "class A {\n\tint x;\n}\n").expect("write");
+    assert!(git(&dir, &["add", "A.java"]));
+    assert!(git(&dir, &["commit", "-q", "-m", "a"]));
+    let committed = Command::new("git").current_dir(&dir).args(["show", "HEAD:A.java"]).output().expect("run git");
+    assert_eq!(String::from_utf8_lossy(&committed.stdout), "class A {\n    int x;\n}\n");
+
+    // Commits without .java files skip the linter.
+    assert!(git(&dir, &["add", "notes.txt"]));
+    assert!(git(&dir, &["commit", "-q", "-m", "notes"]));
+
+    let outside = tree("hook-outside", &[]);
+    fs::create_dir_all(&outside).expect("create dir");
+    let out = jfmtcli().current_dir(&outside).env("GIT_CEILING_DIRECTORIES", std::env::temp_dir()).arg("install-hook").output().expect("run");
+    assert_eq!(out.status.code(), Some(2));
+}
+
+#[test]
+fn staged_lints_the_index_not_the_working_tree() {
+    let dir = tree("staged", &[("src/A.java", "class A {\n\tint x;\n}\n"), ("src/B.java", "class B {\n}\n")]);
+    assert!(git(&dir, &["init", "-q"]));
+    assert!(git(&dir, &["add", "src/A.java"]));
+    fs::write(dir.join("src/A.java"), "class A {\n    int x;\n}\n").expect("write");
+
+    let out = jfmtcli().current_dir(&dir).args(["--staged", "src/A.java"]).output().expect("run");
+    assert_eq!(out.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&out.stdout).contains("src/A.java:2:1: indent-style"));
+    let absolute = jfmtcli().current_dir(&dir).arg("--staged").arg(dir.join("src/A.java")).output().expect("run");
+    assert_eq!(absolute.status.code(), Some(1));
+    let worktree = jfmtcli().current_dir(&dir).arg("src/A.java").output().expect("run");
+    assert_eq!(worktree.status.code(), Some(0));
+
+    let unstaged = jfmtcli().current_dir(&dir).args(["--staged", "src/B.java"]).output().expect("run");
+    assert!(String::from_utf8_lossy(&unstaged.stderr).contains("not in the git index"));
+    let fix = jfmtcli().current_dir(&dir).args(["--staged", "--fix", "src/A.java"]).output().expect("run");
+    assert_eq!(fix.status.code(), Some(2));
+}
+
+#[test]
+fn modules_are_linted_with_their_own_config() {
+    let dir = tree(
+        "modules",
+        &[
+            ("pom.xml", "<project/>\n"),
+            ("src/main/java/com/app/App.java", "package com.app;\n\nclass App {\n\tint x;\n}\n"),
+            ("tabs/build.gradle", "\n"),
+            ("tabs/jfmt.toml", "indent_style = \"tabs\"\n"),
+            ("tabs/src/main/java/com/tabs/T.java", "package com.other;\n\nclass T {\n\tint x;\n}\n"),
+        ],
+    );
+    let out = jfmtcli().current_dir(&dir).arg("modules").output().expect("run");
+    assert_eq!(out.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let rules: Vec<_> = stdout.lines().map(|l| l.split(": ").nth(1).expect("rule")).collect();
+    assert_eq!(rules, ["indent-style", "package-path"]);
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    let rows: Vec<Vec<&str>> = stderr.lines().map(|l| l.split_whitespace().collect()).collect();
+    assert!(rows.contains(&vec![".", "1", "1", "1", "0"]));
+    assert!(rows.contains(&vec!["tabs", "1", "1", "0", "0"]));
+
+    let out = jfmtcli().current_dir(&dir).args(["modules", "--fix"]).output().expect("run");
+    assert_eq!(out.status.code(), Some(1));
+    assert_eq!(fs::read_to_string(dir.join("src/main/java/com/app/App.java")).expect("read"), "package com.app;\n\nclass App {\n    int x;\n}\n");
+    assert!(fs::read_to_string(dir.join("tabs/src/main/java/com/tabs/T.java")).expect("read").contains("\tint x;"));
+}
+
+#[test]
+fn ci_mode_fails_on_warnings_and_ends_with_a_summary() {
+    let dir = tree("ci", &[("A.java", "class A {\n}\n"), ("notes.txt", "\n")]);
+    let run = |args: &[&str]| {
+        let out = jfmtcli().current_dir(&dir).args(args).output().expect("run");
+        let stdout = String::from_utf8_lossy(&out.stdout).into_owned();
+        (out.status.code(), stdout.lines().last().unwrap_or_default().to_string())
+    };
+    assert_eq!(run(&["--ci", "A.java"]), (Some(0), "jfmt-summary: files=1 issues=0 fixable=0 errors=0 warnings=0 result=pass".into()));
+    assert_eq!(run(&["A.java", "notes.txt"]).0, Some(0));
+    assert_eq!(
+        run(&["--ci", "A.java", "notes.txt"]),
+        (Some(1), "jfmt-summary: files=1 issues=0 fixable=0 errors=0 warnings=1 result=fail".into())
+    );
+
+    fs::write(dir.join("jfmt.toml"), "ci_warnings_as_errors = false
+max_line_lenght = 80
+").expect("write");
+    assert_eq!(
+        run(&["--ci", "A.java", "notes.txt"]),
+        (Some(0), "jfmt-summary: files=1 issues=0 fixable=0 errors=0 warnings=2 result=pass".into())
+    );
+
+    // JSON mode keeps stdout parseable and moves the summary to stderr.
+    fs::write(dir.join("A.java"), "class A {\n\tint x;\n}\n").expect("write");
+    let out = jfmtcli().current_dir(&dir).args(["--ci", "--format", "json", "A.java"]).output().expect("run");
+    assert_eq!(out.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&out.stdout).trim_end().ends_with(']'));
+    assert!(String::from_utf8_lossy(&out.stderr).contains("issues=1 fixable=1 errors=0 warnings=1 result=fail"));
+}
+
+#[test]
+fn sonar_output_counts_columns_in_characters() {
+    let dir = tree("sonar", &[("A.java", "class A {\n    void f() { g(\"\u{1F600}\");; }\n}\n")]);
+    let out = jfmtcli().current_dir(&dir).args(["--output-format=sonar", "A.java"]).output().expect("run");
+    assert_eq!(out.status.code(), Some(1));
+    let report: serde_json::Value = serde_json::from_slice(&out.stdout).expect("json");
+    assert_eq!(report["issues"][0]["ruleId"], "no-empty-statement");
+    assert_eq!(report["issues"][0]["primaryLocation"]["textRange"], serde_json::json!({ "startLine": 2, "startColumn": 22, "endLine": 2, "endColumn": 23 }));
+    assert_eq!(report["rules"][0]["id"], "no-empty-statement");
+}
+
+#[test]
+fn directories_are_walked_for_java_files() {
+    let dir = tree(
+        "walk",
+        &[
+            ("src/a/A.java", "class A {\n    void f() {\n        g();;\n    }\n}\n"),
+            ("src/b/B.java", "class B {\n\tint x;\n}\n"),
+            ("src/.hidden/C.java", "class C {;}\n"),
+            ("src/target/D.java", "class D {;}\n"),
+            ("src/notes.txt", "not java\n"),
+        ],
+    );
+    let out = jfmtcli().current_dir(&dir).arg("src").output().expect("run");
+    assert_eq!(out.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let files: Vec<_> = stdout.lines().map(|l| l.split(':').next().expect("path")).collect();
+    assert_eq!(files, [Path::new("src/a/A.java").to_str().unwrap(), Path::new("src/b/B.java").to_str().unwrap()]);
+
+    let metrics = jfmtcli().current_dir(&dir).args(["metrics", "src"]).output().expect("run");
+    assert_eq!(metrics.status.code(), Some(0));
+    let bad = jfmtcli().current_dir(&dir).args(["metrics", "src", "src/notes.txt"]).output().expect("run");
+    assert_eq!(bad.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&bad.stderr).contains("src/notes.txt: error: not a .java file or a directory"));
+}
+
+#[test]
+fn quoted_globs_are_expanded() {
+    let dir = tree(
+        "glob",
+        &[
+            ("src/a/A.java", "class A {\n    void f() {\n        g();;\n    }\n}\n"),
+            ("src/a/deep/B.java", "class B {\n\tint x;\n}\n"),
+            ("src/C.java", "class C {\n\tint x;\n}\n"),
+            ("src/.hidden/D.java", "class D {;}\n"),
+        ],
+    );
+    let linted = |pattern: &str| {
+        let out = jfmtcli().current_dir(&dir).arg(pattern).output().expect("run");
+        let stdout = String::from_utf8_lossy(&out.stdout).into_owned();
+        let mut files: Vec<_> = stdout.lines().map(|l| l.split(':').next().expect("path").replace('\\', "/")).collect();
+        files.dedup();
+        (out.status.code(), files)
+    };
+    assert_eq!(linted("src/**/*.java"), (Some(1), vec!["src/C.java".to_string(), "src/a/A.java".into(), "src/a/deep/B.java".into()]));
+    assert_eq!(linted("src/?/*.java"), (Some(1), vec!["src/a/A.java".to_string()]));
+
+    let none = jfmtcli().current_dir(&dir).args(["cycles", "src/*.kt"]).output().expect("run");
+    assert_eq!(none.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&none.stderr).contains("src/*.kt: error: no .java files match"));
+}
+
+#[test]
+fn stdin_is_fixed_to_stdout_with_diagnostics_on_stderr() {
+    use std::io::Write;
+    use std::process::Stdio;
+    let dir = tree("stdin", &[]);
+    fs::create_dir_all(&dir).expect("create dir");
+    let run = |args: &[&str], input: &str| {
+        let mut child = jfmtcli()
+            .current_dir(&dir)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .expect("run");
+        // A run rejecting its arguments may exit before reading stdin.
+        let _ = child.stdin.take().expect("stdin").write_all(input.as_bytes());
+        let out = child.wait_with_output().expect("wait");
+        (out.status.code(), String::from_utf8_lossy(&out.stdout).into_owned(), String::from_utf8_lossy(&out.stderr).into_owned())
+    };
+    let source = "class A {\n\tvoid f() {\n\t\tg();;\n\t}\n}\n";
+    let (code, stdout, stderr) = run(&["--fix", "--stdin-filename", "src/A.java", "-"], source);
+    assert_eq!(code, Some(0));
+    assert_eq!(stdout, "class A {\n    void f() {\n        g();\n    }\n}\n");
+    assert!(stderr.is_empty(), "{stderr}");
+
+    let (code, stdout, stderr) = run(&["-"], source);
+    assert_eq!(code, Some(1));
+    assert!(stdout.is_empty());
+    assert!(stderr.contains("<stdin>:2:1: indent-style:"), "{stderr}");
+
+    let (code, _, _) = run(&["-", "A.java"], source);
+    assert_eq!(code, Some(2));
+}
+
+#[test]
+fn check_prints_the_pending_fixes_as_a_diff() {
+    let dir = tree("check-diff", &[("A.java", "class A {\n\tint x;\n}\n"), ("B.java", "class B {\n}\n")]);
+    let out = jfmtcli().current_dir(&dir).args(["--check", "A.java", "B.java"]).output().expect("run");
+    assert_eq!(out.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("--- A.java\n+++ A.java\n@@ -1,3 +1,3 @@\n class A {\n-\tint x;\n+    int x;\n }\n"), "{stdout}");
+    assert!(!stdout.contains("B.java"));
+    assert_eq!(fs::read_to_string(dir.join("A.java")).expect("read"), "class A {\n\tint x;\n}\n");
+
+    let json = jfmtcli().current_dir(&dir).args(["--check", "--format", "json", "A.java"]).output().expect("run");
+    assert!(!String::from_utf8_lossy(&json.stdout).contains("+++"));
+}
+
+#[test]
+fn fmt_check_prints_a_diff_and_fmt_rewrites_in_place() {
+    let dir = tree("fmt", &[("A.java", "class A\n{\n  int x;\n}\n"), ("B.java", "class B {\n    int y;\n}\n")]);
+    let out = jfmtcli().current_dir(&dir).args(["fmt", "--check", "."]).output().expect("run");
+    assert_eq!(out.status.code(), Some(1));
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("A.java: would be reformatted"), "{stdout}");
+    assert!(stdout.contains("+class A {\n+    int x;\n"), "{stdout}");
+    assert!(!stdout.contains("B.java"), "{stdout}");
+    assert_eq!(fs::read_to_string(dir.join("A.java")).expect("read"), "class A\n{\n  int x;\n}\n");
+
+    let out = jfmtcli().current_dir(&dir).args(["fmt", "A.java", "B.java"]).output().expect("run");
+    assert_eq!(out.status.code(), Some(0));
+    assert_eq!(fs::read_to_string(dir.join("A.java")).expect("read"), "class A {\n    int x;\n}\n");
+    assert_eq!(jfmtcli().current_dir(&dir).args(["fmt", "--check", "."]).status().expect("run").code(), Some(0));
+    assert_eq!(jfmtcli().current_dir(&dir).args(["fmt", "missing/*.java"]).status().expect("run").code(), Some(2));
+}
+
+#[test]
+fn only_errors_fail_unless_warnings_count_as_errors() {
+    use std::io::Write;
+    use std::process::Stdio;
+    let source = "class A {\n    int x ;\n}\n";
+    let dir = tree("severity", &[("jfmt.toml", "[severity]\nspacing = \"warning\"\n"), ("A.java", source)]);
+    let out = jfmtcli().current_dir(&dir).arg("A.java").output().expect("run");
+    assert_eq!(out.status.code(), Some(0));
+    assert!(String::from_utf8_lossy(&out.stdout).contains("A.java:2:10: warning: spacing:"));
+    assert_eq!(jfmtcli().current_dir(&dir).args(["--warnings-as-errors", "A.java"]).status().expect("run").code(), Some(1));
+    // A pending fix fails --check whatever the issue's severity.
+    assert_eq!(jfmtcli().current_dir(&dir).args(["--check", "A.java"]).status().expect("run").code(), Some(1));
+    assert_eq!(fs::read_to_string(dir.join("A.java")).expect("read"), source);
+    let mut child = jfmtcli()
+        .current_dir(&dir)
+        .args(["--check", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("run");
+    let _ = child.stdin.take().expect("stdin").write_all(source.as_bytes());
+    assert_eq!(child.wait_with_output().expect("wait").status.code(), Some(1));
+}
+
+#[test]
+fn rules_lists_each_rule_with_its_kind_and_state() {
+    let dir = tree("rules", &[("jfmt.toml", "[rules]\nno-wildcard-imports = false\n")]);
+    let out = jfmtcli().current_dir(&dir).arg("rules").output().expect("run");
+    assert_eq!(out.status.code(), Some(0));
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    let line = |id: &str| stdout.lines().find(|l| l.split_whitespace().next() == Some(id)).map(|l| l.split_whitespace().skip(1).take(2).collect::<Vec<_>>());
+    assert_eq!(line("no-wildcard-imports"), Some(vec!["ast", "off"]));
+    assert_eq!(line("max-line-length"), Some(vec!["text", "on"]));
+    assert_eq!(jfmtcli().current_dir(&dir).args(["rules", "missing"]).status().expect("run").code(), Some(2));
+    assert_eq!(jfmtcli().current_dir(&dir).args(["rules", "a", "b"]).status().expect("run").code(), Some(2));
+}
+
+#[test]
+fn files_use_their_own_directory_config() {
+    let long = "class A {\n    String s = \"abcdefghijklmnopqrstuvwxyzabcdefghijkl\";\n}\n";
+    let dir = tree(
+        "cascade",
+        &[
+            ("jfmt.toml", "max_line_length = 40\n\n[[overrides]]\nfiles = \"relaxed/**\"\nmax_line_length = 100\n"),
+            ("strict/A.java", long),
+            ("relaxed/A.java", long),
+        ],
+    );
+    let out = jfmtcli().current_dir(&dir).args(["strict", "relaxed"]).output().expect("run");
+    let stdout = String::from_utf8_lossy(&out.stdout);
+    assert!(stdout.contains("strict/A.java:2:41: max-line-length"), "{stdout}");
+    assert!(!stdout.contains("relaxed/A.java"), "{stdout}");
+}
+
+#[test]
+fn subcommands_use_each_files_config() {
+    let dir = tree(
+        "nested-config",
+        &[
+            ("jfmt.toml", "[rules]\nno-wildcard-imports = false\n"),
+            ("sub/jfmt.toml", "indent_width = 2\n\n[rules]\nspacing = false\n"),
+            ("sub/A.java", "class A {\n  int x;\n}\n"),
+            ("B.java", "class B {\n    int y;\n}\n"),
+        ],
+    );
+    assert_eq!(jfmtcli().current_dir(&dir).args(["fmt", "--check", "."]).status().expect("run").code(), Some(0));
+
+    let state = |args: &[&str], id: &str| {
+        let out = jfmtcli().current_dir(&dir).args(args).output().expect("run");
+        let stdout = String::from_utf8_lossy(&out.stdout).into_owned();
+        stdout.lines().find(|l| l.split_whitespace().next() == Some(id)).and_then(|l| l.split_whitespace().nth(2)).map(str::to_string)
+    };
+    assert_eq!(state(&["rules"], "spacing").as_deref(), Some("on"));
+    assert_eq!(state(&["rules", "sub/A.java"], "spacing").as_deref(), Some("off"));
+    assert_eq!(state(&["rules", "sub"], "no-wildcard-imports").as_deref(), Some("off"));
+}
@@ -0,0 +1,63 @@
+//! Format-on-save latency guard.
+//!
+//! Run with `cargo bench -p libjfmt --bench format_on_save`. Exits non-zero when
+//! the median time to fix a typical buffer exceeds the budget, so a regression
+//! in the hot path shows up as a failing run rather than a slower editor.
+
+use std::time::{Duration, Instant};
+
+use libjfmt::{Config, Linter};
+
+const BUDGET: Duration = Duration::from_millis(10);
+const ITERATIONS: usize = 200;
+
+/// A ~800 line class with a handful of violations per method.
+fn typical_source() -> String {
+    let mut src = String::from("package com.example.bench;\n\nimport java.util.*;\nimport java.io.File;\n\npublic class Bench {\n");
+    for i in 0..60 {
+        src.push_str(&format!("    private int field{i} = {i};\n"));
+    }
+    for i in 0..80 {
+        src.push_str(&format!(
+            "\n    public int method{i}(int a, int b) {{\n        int sum = a + b + field{};\n\t\tif (sum > {i}) {{ ; }}\n        for (int j = 0; j < b; j++) {{\n            sum += j * {i};\n        }}\n        return sum;\n    }}\n",
+            i % 60
+        ));
+    }
+    src.push_str("}\n");
+    src
+}
+
+fn main() {
+    let source = typical_source();
+    let mut linter = Linter::new(Config::default()).expect("java grammar");
+
+    // Warm up caches and the allocator before measuring.
+    for _ in 0..10 {
+        linter.fix(&source).expect("fix");
+    }
+
+    let mut samples: Vec<Duration> = (0..ITERATIONS)
+        .map(|_| {
+            let start = Instant::now();
+            let fixed = linter.fix(&source).expect("fix").fixed;
+            let elapsed = start.elapsed();
+            std::hint::black_box(fixed);
+            elapsed
+        })
+        .collect();
+    samples.sort();
+
+    let median = samples[ITERATIONS / 2];
+    let p95 = samples[ITERATIONS * 95 / 100];
+    println!(
+        "format_on_save: {} lines, median {:?}, p95 {:?} (budget {:?})",
+        source.lines().count(),
+        median,
+        p95,
+        BUDGET
+    );
+    if median > BUDGET {
+        eprintln!("format_on_save: median exceeds budget");
+        std::process::exit(1);
+    }
+}
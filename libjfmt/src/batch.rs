@@ -0,0 +1,264 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+use rayon::prelude::*;
+use thiserror::Error;
+
+use crate::{apply_fixes, is_test_path, would_change, CacheKey, CacheStore, Config, Fix, FixResult, LintError, LintIssue, LintStats, Linter, RuleSet, SourceText};
+
+#[derive(Debug, Clone, Default)]
+pub struct LintOptions {
+    /// Apply safe autofixes and report only the issues left afterwards.
+    pub fix: bool,
+    /// Dry run: report whether fixing would change each file, without producing
+    /// the fixed text. Takes precedence over `fix`.
+    pub check: bool,
+    /// In check mode, also build `FileOutcome::preview` and `original` for
+    /// files that would change, so a diff can be shown.
+    pub diff: bool,
+    /// Worker threads; `None` uses one per available core.
+    pub threads: Option<usize>,
+    /// Skip files whose content and config were already linted.
+    pub cache: Option<Arc<dyn CacheStore>>,
+    /// Rules to run; text-only sets skip parsing entirely.
+    pub rules: RuleSet,
+    /// Collect per-phase and per-rule timings into `FileOutcome::stats`.
+    pub timing: bool,
+    /// Run fixing twice and record in `FileOutcome::idempotent` whether the
+    /// second pass changed anything.
+    pub verify_idempotent: bool,
+    /// Also apply fixes marked `Applicability::MaybeIncorrect`.
+    pub unsafe_fixes: bool,
+    /// Lint the content staged in the git index instead of the working tree.
+    /// Fixed output, if any, is still relative to the staged content.
+    pub staged: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum FileError {
+    #[error("failed to read: {0}")]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Lint(#[from] LintError),
+}
+
+#[derive(Debug)]
+pub struct FileReport {
+    pub path: PathBuf,
+    pub outcome: Result<FileOutcome, FileError>,
+}
+
+#[derive(Debug, Default)]
+pub struct FileOutcome {
+    pub issues: Vec<LintIssue>,
+    /// New file content when fixing changed the source; writing it is up to the caller.
+    pub fixed: Option<String>,
+    /// Whether the original content was answered from the cache; `None` when caching is off.
+    pub cache_hit: Option<bool>,
+    /// Timings for this file when `LintOptions::timing` is set.
+    pub stats: Option<LintStats>,
+    /// Set when the file was not linted because it hit a safety limit.
+    pub skipped: Option<SkipReason>,
+    /// In check mode, whether `--fix` would change the file.
+    pub would_change: bool,
+    /// In check mode with `LintOptions::diff`, the content `--fix` would
+    /// write when it differs from the file; unlike `fixed`, not meant to be
+    /// written back.
+    pub preview: Option<String>,
+    /// With `preview`, the text that was linted (the index's for `--staged`),
+    /// which a diff against the preview should start from.
+    pub original: Option<String>,
+    /// Whether a second fix pass leaves the first pass's output alone; `None`
+    /// unless `LintOptions::verify_idempotent` is set.
+    pub idempotent: Option<bool>,
+}
+
+/// Why a file was left unlinted.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum SkipReason {
+    #[error("file skipped: too large ({size} bytes, limit {limit})")]
+    TooLarge { size: u64, limit: u64 },
+    #[error("file skipped: parsing took longer than {limit_ms}ms")]
+    ParseTimeout { limit_ms: u64 },
+}
+
+/// Lint many files concurrently. Reports come back sorted by path regardless of
+/// which worker finished first, so output is stable between runs.
+///
+/// Paths naming the same file (repeated arguments, symlinks, `a/../a/B.java`)
+/// are linted once, under the first name given.
+pub fn lint_files(paths: &[PathBuf], config: &Config, options: &LintOptions) -> Result<Vec<FileReport>, LintError> {
+    // Surface grammar problems once instead of once per file.
+    Linter::new(config.clone())?;
+    let paths = &dedup_paths(paths);
+
+    let mut builder = rayon::ThreadPoolBuilder::new();
+    if let Some(n) = options.threads {
+        builder = builder.num_threads(n);
+    }
+    let pool = builder.build().map_err(|e| LintError::ThreadPool(e.to_string()))?;
+
+    let mut reports: Vec<FileReport> = pool.install(|| {
+        paths
+            .par_iter()
+            .map_init(
+                || (make_linter(config.clone(), options.rules.clone(), options), None),
+                |(linter, test_linter), path| {
+                    let linter = if is_test_path(path) {
+                        test_linter.get_or_insert_with(|| {
+                            let rules = options.rules.without(config.test_rules.disable.iter().map(String::as_str));
+                            make_linter(config.for_tests(), rules, options)
+                        })
+                    } else {
+                        linter
+                    };
+                    FileReport { path: path.clone(), outcome: lint_one(linter, path, options) }
+                },
+            )
+            .collect()
+    });
+    reports.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(reports)
+}
+
+fn make_linter(config: Config, rules: RuleSet, options: &LintOptions) -> Linter {
+    let linter = Linter::new(config)
+        .expect("java grammar checked above")
+        .with_rules(rules)
+        .with_unsafe_fixes(options.unsafe_fixes);
+    if options.timing { linter.with_timing() } else { linter }
+}
+
+/// Drop paths that resolve to a file already listed, so it isn't fixed twice.
+/// Paths that can't be resolved are kept; reading them reports the error.
+fn dedup_paths(paths: &[PathBuf]) -> Vec<PathBuf> {
+    let mut seen = HashSet::new();
+    paths
+        .iter()
+        .filter(|p| seen.insert(std::fs::canonicalize(p).unwrap_or_else(|_| p.to_path_buf())))
+        .cloned()
+        .collect()
+}
+
+fn lint_one(linter: &mut Linter, path: &Path, options: &LintOptions) -> Result<FileOutcome, FileError> {
+    let limit = linter.config().max_file_size;
+    let too_large = |size: u64| {
+        (limit > 0 && size > limit)
+            .then(|| FileOutcome { skipped: Some(SkipReason::TooLarge { size, limit }), ..Default::default() })
+    };
+    if !options.staged
+        && let Some(skipped) = too_large(std::fs::metadata(path)?.len())
+    {
+        return Ok(skipped);
+    }
+
+    let started = Instant::now();
+    let src = if options.staged {
+        SourceText::staged(path)?
+    } else {
+        SourceText::read(path, linter.config().mmap_threshold)?
+    };
+    let io = started.elapsed();
+    if options.staged
+        && let Some(skipped) = too_large(src.len() as u64)
+    {
+        return Ok(skipped);
+    }
+    linter.set_file_name(Some(path));
+    let mut outcome = lint_loaded(linter, &src, options)?;
+    if let Some(mut stats) = linter.take_stats() {
+        stats.io += io;
+        outcome.stats = Some(stats);
+    }
+    Ok(outcome)
+}
+
+/// Lint source that didn't come from a file, such as an editor buffer on
+/// stdin. `path`, if known, picks the test profile like a file path would.
+/// Safety limits on size don't apply; the parse time limit does.
+pub fn lint_text(source: &str, path: Option<&Path>, config: &Config, options: &LintOptions) -> Result<FileOutcome, FileError> {
+    Linter::new(config.clone())?;
+    let mut linter = if path.is_some_and(is_test_path) {
+        let rules = options.rules.without(config.test_rules.disable.iter().map(String::as_str));
+        make_linter(config.for_tests(), rules, options)
+    } else {
+        make_linter(config.clone(), options.rules.clone(), options)
+    };
+    linter.set_file_name(path);
+    let mut outcome = lint_loaded(&mut linter, source, options)?;
+    outcome.stats = linter.take_stats();
+    Ok(outcome)
+}
+
+/// The `LintOptions`-driven part of linting a file once its text is loaded.
+fn lint_loaded(linter: &mut Linter, src: &str, options: &LintOptions) -> Result<FileOutcome, FileError> {
+    let mut outcome = match lint_source(linter, src, options) {
+        Err(FileError::Lint(LintError::ParseTimeout { limit_ms })) => {
+            FileOutcome { skipped: Some(SkipReason::ParseTimeout { limit_ms }), ..Default::default() }
+        }
+        other => other?,
+    };
+    if options.verify_idempotent && outcome.skipped.is_none() {
+        // Bypasses the cache: the point is to exercise the rules themselves.
+        outcome.idempotent = Some(linter.verify_idempotent(src)?.is_none());
+    }
+    if options.check {
+        let fixes: Vec<Fix> = outcome.issues.iter().filter_map(|i| linter.applied_fix(i).cloned()).collect();
+        outcome.would_change = would_change(src, &fixes);
+        if outcome.would_change && options.diff {
+            outcome.preview = Some(apply_fixes(src, &fixes).map_err(LintError::from)?);
+            outcome.original = Some(src.to_string());
+        }
+    }
+    Ok(outcome)
+}
+
+fn lint_source(linter: &mut Linter, src: &str, options: &LintOptions) -> Result<FileOutcome, FileError> {
+    let fix = options.fix && !options.check;
+    let Some(cache) = options.cache.as_deref() else {
+        if !fix {
+            return Ok(FileOutcome { issues: linter.lint(src)?, fixed: None, cache_hit: None, ..Default::default() });
+        }
+        return Ok(fix_outcome(src, linter.fix(src)?, None));
+    };
+
+    let key = CacheKey::new(src, linter.file_name(), linter.config(), linter.rules());
+    let Some(entry) = cache.get(&key) else {
+        if !fix {
+            let (issues, syntax_errors) = linter.lint_counting_errors(src)?;
+            cache.put(&key, &issues, syntax_errors);
+            return Ok(FileOutcome { issues, fixed: None, cache_hit: Some(false), ..Default::default() });
+        }
+        let result = linter.fix(src)?;
+        cache.put(&key, &result.issues, result.syntax_errors);
+        cache.put(&CacheKey::new(&result.fixed, linter.file_name(), linter.config(), linter.rules()), &result.remaining, None);
+        return Ok(fix_outcome(src, result, Some(false)));
+    };
+
+    // Cache hit: cached fixes are applied without linting the original again;
+    // only the fixed text is parsed, to check it against the cached error count.
+    let issues = entry.issues;
+    let fixes: Vec<Fix> = issues.iter().filter_map(|i| linter.applied_fix(i).cloned()).collect();
+    if !fix || fixes.is_empty() {
+        return Ok(FileOutcome { issues, fixed: None, cache_hit: Some(true), ..Default::default() });
+    }
+    let fixed = apply_fixes(src, &fixes).map_err(LintError::from)?;
+    linter.verify_fixed(src, &fixed, &issues, entry.syntax_errors)?;
+    let fixed_key = CacheKey::new(&fixed, linter.file_name(), linter.config(), linter.rules());
+    let remaining = match cache.get(&fixed_key) {
+        Some(entry) => entry.issues,
+        None => {
+            let (remaining, syntax_errors) = linter.lint_counting_errors(&fixed)?;
+            cache.put(&fixed_key, &remaining, syntax_errors);
+            remaining
+        }
+    };
+    Ok(FileOutcome { issues: remaining, fixed: Some(fixed), cache_hit: Some(true), ..Default::default() })
+}
+
+fn fix_outcome(src: &str, result: FixResult, cache_hit: Option<bool>) -> FileOutcome {
+    let fixed = (result.fixed != src).then_some(result.fixed);
+    FileOutcome { issues: result.remaining, fixed, cache_hit, ..Default::default() }
+}
@@ -0,0 +1,182 @@
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use serde::{Deserialize, Serialize};
+
+use crate::rules::known_rule_id;
+use crate::{Config, FileReport, Fix, LintIssue, RuleSet, Severity};
+
+/// Default cache directory, relative to where jfmt is run.
+pub const DEFAULT_CACHE_DIR: &str = ".jfmt-cache";
+
+/// Identifies one lint result: file content and name, effective
+/// configuration, enabled rules and crate version. Any of these changing produces a different key.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CacheKey(String);
+
+impl CacheKey {
+    pub fn new(source: &str, file_name: Option<&str>, config: &Config, rules: &RuleSet) -> Self {
+        // Config is hashed through its serialized form so new fields are picked up automatically.
+        let config_text = toml::to_string(config).unwrap_or_default();
+        let mut h = Fnv128::new();
+        h.write(env!("CARGO_PKG_VERSION").as_bytes());
+        h.write(&[0]);
+        h.write(config_text.as_bytes());
+        h.write(&[0]);
+        for id in rules.ids() {
+            h.write(id.as_bytes());
+            h.write(&[0]);
+        }
+        h.write(file_name.unwrap_or_default().as_bytes());
+        h.write(&[0]);
+        h.write(source.as_bytes());
+        CacheKey(format!("{:032x}", h.finish()))
+    }
+
+    pub fn as_str(&self) -> &str { &self.0 }
+}
+
+impl fmt::Display for CacheKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result { f.write_str(&self.0) }
+}
+
+/// A cached lint result.
+#[derive(Debug, Clone)]
+pub struct CacheEntry {
+    pub issues: Vec<LintIssue>,
+    /// Syntax errors in the linted text, so fixes applied from a cache hit can
+    /// be checked without re-parsing it; `None` when unknown.
+    pub syntax_errors: Option<usize>,
+}
+
+/// Storage backend for cached lint results. Implementations must be safe to
+/// share between worker threads; failures should degrade to a miss.
+pub trait CacheStore: fmt::Debug + Send + Sync {
+    fn get(&self, key: &CacheKey) -> Option<CacheEntry>;
+    fn put(&self, key: &CacheKey, issues: &[LintIssue], syntax_errors: Option<usize>);
+}
+
+/// On-disk cache storing one JSON file per key.
+#[derive(Debug)]
+pub struct DiskCache {
+    dir: PathBuf,
+    tmp_counter: AtomicUsize,
+}
+
+impl DiskCache {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into(), tmp_counter: AtomicUsize::new(0) }
+    }
+
+    pub fn dir(&self) -> &Path { &self.dir }
+
+    fn entry_path(&self, key: &CacheKey) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+}
+
+impl CacheStore for DiskCache {
+    fn get(&self, key: &CacheKey) -> Option<CacheEntry> {
+        let text = fs::read_to_string(self.entry_path(key)).ok()?;
+        let stored: StoredEntry = serde_json::from_str(&text).ok()?;
+        // An entry naming a rule this build doesn't know is stale; treat it as a miss.
+        let issues = stored.issues.into_iter().map(StoredIssue::into_issue).collect::<Option<_>>()?;
+        Some(CacheEntry { issues, syntax_errors: stored.syntax_errors })
+    }
+
+    fn put(&self, key: &CacheKey, issues: &[LintIssue], syntax_errors: Option<usize>) {
+        let Ok(text) = serde_json::to_string(&EntryRef { issues, syntax_errors }) else { return };
+        if fs::create_dir_all(&self.dir).is_err() { return; }
+        // Write then rename so concurrent readers never see a partial entry.
+        let n = self.tmp_counter.fetch_add(1, Ordering::Relaxed);
+        let tmp = self.dir.join(format!("{key}.{}.{n}.tmp", std::process::id()));
+        if fs::write(&tmp, text).is_ok() && fs::rename(&tmp, self.entry_path(key)).is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+    }
+}
+
+/// On-disk form of a `CacheEntry`, serialized without copying the issues.
+#[derive(Serialize)]
+struct EntryRef<'a> {
+    issues: &'a [LintIssue],
+    syntax_errors: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct StoredEntry {
+    issues: Vec<StoredIssue>,
+    syntax_errors: Option<usize>,
+}
+
+/// Owned mirror of `LintIssue` for reading entries back.
+#[derive(Deserialize)]
+struct StoredIssue {
+    rule_id: String,
+    message: String,
+    line: usize,
+    column: usize,
+    offset: usize,
+    end_line: usize,
+    end_column: usize,
+    end_offset: usize,
+    severity: Severity,
+    fix: Option<Fix>,
+}
+
+impl StoredIssue {
+    fn into_issue(self) -> Option<LintIssue> {
+        let rule_id = known_rule_id(&self.rule_id)?;
+        Some(LintIssue {
+            rule_id,
+            message: self.message.into(),
+            line: self.line,
+            column: self.column,
+            offset: self.offset,
+            end_line: self.end_line,
+            end_column: self.end_column,
+            end_offset: self.end_offset,
+            severity: self.severity,
+            fix: self.fix,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl CacheStats {
+    /// Tally cache hits/misses over a batch of reports.
+    pub fn from_reports(reports: &[FileReport]) -> Self {
+        let mut stats = CacheStats::default();
+        for outcome in reports.iter().filter_map(|r| r.outcome.as_ref().ok()) {
+            match outcome.cache_hit {
+                Some(true) => stats.hits += 1,
+                Some(false) => stats.misses += 1,
+                None => {}
+            }
+        }
+        stats
+    }
+}
+
+/// 128-bit FNV-1a; stable across Rust releases, unlike `DefaultHasher`.
+struct Fnv128(u128);
+
+impl Fnv128 {
+    fn new() -> Self { Fnv128(0x6c62272e07bb014262b821756295c58d) }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.0 ^= *b as u128;
+            self.0 = self.0.wrapping_mul(0x0000000001000000000000000000013B);
+        }
+    }
+
+    fn finish(&self) -> u128 { self.0 }
+}
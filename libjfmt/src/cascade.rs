@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::layout::glob_matches_path;
+use crate::{Config, ConfigError};
+
+/// One `[[overrides]]` entry: settings for the files its globs match,
+/// relative to the directory of the `jfmt.toml` declaring it.
+#[derive(Debug, Clone)]
+struct Override {
+    dir: PathBuf,
+    files: Vec<String>,
+    settings: toml::Table,
+}
+
+impl Override {
+    fn matches(&self, file: &Path) -> bool {
+        let Ok(relative) = file.strip_prefix(&self.dir) else { return false };
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        self.files.iter().any(|glob| glob_matches_path(glob, &relative))
+    }
+}
+
+/// Built-in bases for `extends`, by name.
+const PRESETS: &[(&str, &str)] = &[
+    ("google", include_str!("../data/presets/google.toml")),
+    ("aosp", include_str!("../data/presets/aosp.toml")),
+];
+
+/// The settings of one config file, whether it sets `root = true`, and its
+/// `[[overrides]]`.
+type Layer = (toml::Table, bool, Vec<Override>);
+
+/// The merged settings of the `jfmt.toml` files that apply in a directory,
+/// and their `[[overrides]]`, outermost file's first.
+#[derive(Debug, Clone, Default)]
+struct Layers {
+    table: toml::Table,
+    overrides: Vec<Override>,
+}
+
+/// Works out each file's configuration from the `jfmt.toml` files in its
+/// directory and above. A file closer to the linted one overrides those
+/// further up key by key: tables such as `[rules]` and `[naming]` merge,
+/// other values (lists included) are replaced. One setting `root = true`
+/// stops the search there. A file may name a base with `extends`, another
+/// file or a built-in preset such as `"google"`, whose settings it is merged
+/// on top of. `[[overrides]]` entries then apply, in order, to the files
+/// their `files` globs match.
+///
+/// Each directory's config files are read once per resolver.
+#[derive(Debug, Default)]
+pub struct ConfigResolver {
+    dirs: HashMap<PathBuf, Layers>,
+    configs: HashMap<(PathBuf, Vec<usize>), Config>,
+    loaded: Vec<PathBuf>,
+}
+
+impl ConfigResolver {
+    pub fn new() -> Self { Self::default() }
+
+    /// The configuration of the file at `path`, `[[overrides]]` included.
+    pub fn config_for(&mut self, path: &Path) -> Result<Config, ConfigError> {
+        let path = resolved(path)?;
+        let dir = path.parent().map_or_else(|| path.clone(), Path::to_path_buf);
+        self.resolve(&dir, Some(&path))
+    }
+
+    /// The configuration of `dir` itself, without `[[overrides]]`.
+    pub fn config_in(&mut self, dir: &Path) -> Result<Config, ConfigError> {
+        let dir = resolved(dir)?;
+        self.resolve(&dir, None)
+    }
+
+    /// `paths` grouped by configuration, groups in order of their first
+    /// file and files in their given order, so each group can go through
+    /// `lint_files` on its own.
+    pub fn group(&mut self, paths: &[PathBuf]) -> Result<Vec<(Config, Vec<PathBuf>)>, ConfigError> {
+        let mut groups: Vec<(String, Config, Vec<PathBuf>)> = Vec::new();
+        for path in paths {
+            let config = self.config_for(path)?;
+            let key = toml::to_string(&config).unwrap_or_default();
+            match groups.iter_mut().find(|(k, _, _)| *k == key) {
+                Some((_, _, files)) => files.push(path.clone()),
+                None => groups.push((key, config, vec![path.clone()])),
+            }
+        }
+        Ok(groups.into_iter().map(|(_, config, files)| (config, files)).collect())
+    }
+
+    /// Config files read so far, in the order they were first read.
+    pub fn loaded(&self) -> &[PathBuf] { &self.loaded }
+
+    fn resolve(&mut self, dir: &Path, file: Option<&Path>) -> Result<Config, ConfigError> {
+        self.layers(dir)?;
+        let layers = &self.dirs[dir];
+        let matched: Vec<usize> = match file {
+            Some(file) => (0..layers.overrides.len()).filter(|&i| layers.overrides[i].matches(file)).collect(),
+            None => Vec::new(),
+        };
+        let key = (dir.to_path_buf(), matched);
+        if let Some(config) = self.configs.get(&key) {
+            return Ok(config.clone());
+        }
+        let mut table = layers.table.clone();
+        for &i in &key.1 {
+            merge(&mut table, layers.overrides[i].settings.clone());
+        }
+        let config: Config = toml::Value::Table(table).try_into()?;
+        self.configs.insert(key, config.clone());
+        Ok(config)
+    }
+
+    /// Fill `dirs` for `dir`, reading its `jfmt.toml` and, unless that sets
+    /// `root = true`, those of its ancestors.
+    fn layers(&mut self, dir: &Path) -> Result<(), ConfigError> {
+        if self.dirs.contains_key(dir) { return Ok(()); }
+        let candidate = dir.join("jfmt.toml");
+        let own = if candidate.is_file() { Some(self.read(&candidate, dir, &mut Vec::new())?) } else { None };
+        let mut layers = match (&own, dir.parent()) {
+            (Some((_, true, _)), _) | (_, None) => Layers::default(),
+            (_, Some(parent)) => {
+                self.layers(parent)?;
+                self.dirs[parent].clone()
+            }
+        };
+        if let Some((table, _, overrides)) = own {
+            merge(&mut layers.table, table);
+            layers.overrides.extend(overrides);
+        }
+        self.dirs.insert(dir.to_path_buf(), layers);
+        Ok(())
+    }
+
+    /// The settings of one config file with whatever it `extends` merged
+    /// underneath, whether it sets `root = true`, and its `[[overrides]]`,
+    /// its bases' first. `chain` holds the files extending this one.
+    fn read(&mut self, path: &Path, dir: &Path, chain: &mut Vec<PathBuf>) -> Result<Layer, ConfigError> {
+        let invalid = |message: String| ConfigError::File { path: path.to_path_buf(), message };
+        if chain.iter().any(|p| p == path) {
+            return Err(invalid("`extends` leads back to this file".to_string()));
+        }
+        let text = fs::read_to_string(path).map_err(|e| invalid(e.to_string()))?;
+        if !self.loaded.iter().any(|p| p == path) {
+            self.loaded.push(path.to_path_buf());
+        }
+        chain.push(path.to_path_buf());
+        let layer = self.parse(&text, path, dir, chain);
+        chain.pop();
+        layer
+    }
+
+    /// `read` for the text of `origin`, a file or preset.
+    fn parse(&mut self, text: &str, origin: &Path, dir: &Path, chain: &mut Vec<PathBuf>) -> Result<Layer, ConfigError> {
+        let invalid = |message: String| ConfigError::File { path: origin.to_path_buf(), message };
+        let mut table: toml::Table = text.parse().map_err(|e: toml::de::Error| invalid(format!("invalid toml: {e}")))?;
+        let (mut merged, _, mut overrides) = match table.remove("extends") {
+            None => Layer::default(),
+            Some(toml::Value::String(base)) => self.extend(&base, origin, dir, chain)?,
+            Some(_) => return Err(invalid("`extends` must be a path or a preset name".to_string())),
+        };
+        let root = table.get("root").and_then(toml::Value::as_bool) == Some(true);
+        table.remove("root");
+        match table.remove("overrides") {
+            None => {}
+            Some(toml::Value::Array(entries)) => {
+                for entry in entries {
+                    let toml::Value::Table(mut settings) = entry else {
+                        return Err(invalid("`overrides` must be written as [[overrides]] tables".to_string()));
+                    };
+                    let files = match settings.remove("files") {
+                        Some(toml::Value::String(glob)) => vec![glob],
+                        Some(toml::Value::Array(globs)) => globs
+                            .into_iter()
+                            .map(|g| g.as_str().map(str::to_string))
+                            .collect::<Option<Vec<_>>>()
+                            .ok_or_else(|| invalid("`files` in [[overrides]] must be a glob or a list of globs".to_string()))?,
+                        _ => return Err(invalid("each [[overrides]] entry needs `files`, a glob or a list of globs".to_string())),
+                    };
+                    overrides.push(Override { dir: dir.to_path_buf(), files, settings });
+                }
+            }
+            Some(_) => return Err(invalid("`overrides` must be written as [[overrides]] tables".to_string())),
+        }
+        merge(&mut merged, table);
+        Ok((merged, root, overrides))
+    }
+
+    /// The base named by `extends` in `origin`: a path relative to
+    /// `origin`'s directory when it holds a `/` or ends in `.toml`, else a
+    /// built-in preset. Its `root` doesn't matter, and its `[[overrides]]`
+    /// globs are relative to `dir`, like those of the file extending it.
+    fn extend(&mut self, base: &str, origin: &Path, dir: &Path, chain: &mut Vec<PathBuf>) -> Result<Layer, ConfigError> {
+        if base.contains(['/', '\\']) || base.ends_with(".toml") {
+            let path = resolved(&origin.parent().unwrap_or(Path::new(".")).join(base))?;
+            return self.read(&path, dir, chain);
+        }
+        match PRESETS.iter().find(|(name, _)| *name == base) {
+            Some((name, text)) => self.parse(text, Path::new(&format!("preset `{name}`")), dir, chain),
+            None => {
+                let names: Vec<&str> = PRESETS.iter().map(|(name, _)| *name).collect();
+                Err(ConfigError::File {
+                    path: origin.to_path_buf(),
+                    message: format!("unknown preset `{base}` in `extends` (available: {})", names.join(", ")),
+                })
+            }
+        }
+    }
+}
+
+/// `path` with links and `..` resolved when it exists, so the search walks
+/// up through the directories actually holding it; otherwise just absolute.
+fn resolved(path: &Path) -> std::io::Result<PathBuf> {
+    fs::canonicalize(path).or_else(|_| std::path::absolute(path))
+}
+
+/// Merge `over` into `base`: tables merge key by key, anything else
+/// replaces what `base` had.
+fn merge(base: &mut toml::Table, over: toml::Table) {
+    for (key, value) in over {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(inner)), toml::Value::Table(value)) => merge(inner, value),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use std::fmt::Write;
+
+use crate::ReportedIssue;
+
+/// Issues as Checkstyle XML, the format Jenkins warnings-ng, Gradle and most
+/// Java CI plugins read.
+///
+/// Issues are grouped under one `<file>` per path, in report order; files
+/// without issues are left out. Severity is the issue's configured severity
+/// and `source` is `jfmt.<rule id>`.
+pub fn checkstyle_report(issues: &[ReportedIssue]) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<checkstyle version=\"8.0\">\n");
+    let mut current = None;
+    for issue in issues {
+        if current != Some(&issue.path) {
+            if current.is_some() {
+                out.push_str("  </file>\n");
+            }
+            let _ = writeln!(out, "  <file name=\"{}\">", escape(&issue.path.to_string_lossy()));
+            current = Some(&issue.path);
+        }
+        let _ = writeln!(
+            out,
+            "    <error line=\"{}\" column=\"{}\" severity=\"{}\" message=\"{}\" source=\"jfmt.{}\"/>",
+            issue.line,
+            issue.column,
+            issue.severity.as_str(),
+            escape(&issue.message),
+            escape(&issue.rule)
+        );
+    }
+    if current.is_some() {
+        out.push_str("  </file>\n");
+    }
+    out.push_str("</checkstyle>\n");
+    out
+}
+
+/// Escape text for an attribute value. Control characters XML 1.0 can't
+/// carry at all are dropped.
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\t' => out.push_str("&#9;"),
+            '\n' => out.push_str("&#10;"),
+            '\r' => out.push_str("&#13;"),
+            c if c < ' ' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{FileReport, Severity};
+
+/// One issue as written to a JSON lint report (`--format json`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReportedIssue {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    /// End of the flagged code (exclusive column); equal to the start for
+    /// issues at a single point. Zero in reports written before ranges existed.
+    #[serde(default)]
+    pub end_line: usize,
+    #[serde(default)]
+    pub end_column: usize,
+    pub rule: String,
+    /// `error` in reports written before severities existed.
+    #[serde(default)]
+    pub severity: Severity,
+    pub message: String,
+    #[serde(default)]
+    pub fixable: bool,
+}
+
+impl ReportedIssue {
+    /// Flatten a batch of reports, in report order. Files that failed or were
+    /// skipped contribute nothing.
+    pub fn from_reports(reports: &[FileReport]) -> Vec<ReportedIssue> {
+        let mut issues = Vec::new();
+        for report in reports {
+            let Ok(outcome) = &report.outcome else { continue };
+            issues.extend(outcome.issues.iter().map(|issue| ReportedIssue {
+                path: report.path.clone(),
+                line: issue.line,
+                column: issue.column,
+                end_line: issue.end_line,
+                end_column: issue.end_column,
+                rule: issue.rule_id.to_string(),
+                severity: issue.severity,
+                message: issue.message.to_string(),
+                fixable: issue.fix.is_some(),
+            }));
+        }
+        issues
+    }
+}
+
+/// Difference between two lint reports.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReportDiff {
+    /// Issues in the new report with no counterpart in the old one.
+    pub introduced: Vec<ReportedIssue>,
+    /// Issues in the old report with no counterpart in the new one.
+    pub resolved: Vec<ReportedIssue>,
+}
+
+/// Compare an old and a new report.
+///
+/// Issues are matched by path, rule and message, not position, so edits that
+/// only shift code up or down don't show up as churn. When a key occurs a
+/// different number of times, the surplus occurrences (the last ones in the
+/// report) are the ones reported.
+pub fn compare_reports(old: &[ReportedIssue], new: &[ReportedIssue]) -> ReportDiff {
+    ReportDiff { introduced: unmatched(new, old), resolved: unmatched(old, new) }
+}
+
+/// Issues of `from` left over after pairing each with an equal-keyed one in `against`.
+fn unmatched(from: &[ReportedIssue], against: &[ReportedIssue]) -> Vec<ReportedIssue> {
+    let key = |i: &ReportedIssue| (i.path.clone(), i.rule.clone(), i.message.clone());
+    let mut available: HashMap<_, usize> = HashMap::new();
+    for issue in against {
+        *available.entry(key(issue)).or_default() += 1;
+    }
+    from.iter()
+        .filter(|issue| match available.get_mut(&key(issue)) {
+            Some(n) if *n > 0 => {
+                *n -= 1;
+                false
+            }
+            _ => true,
+        })
+        .cloned()
+        .collect()
+}
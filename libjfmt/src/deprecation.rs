@@ -0,0 +1,272 @@
+use std::collections::BTreeSet;
+
+use toml_edit::{DocumentMut, Item, Value};
+
+use crate::naming;
+use crate::rules::known_rule_id;
+use crate::{Config, ConfigError};
+
+/// A config key that was renamed or removed. Nested keys are dotted, e.g.
+/// `test-rules.disable`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeprecatedKey {
+    pub key: &'static str,
+    /// Key that replaces it with the same meaning; `None` when removed.
+    pub replacement: Option<&'static str>,
+    /// jfmt version that deprecated it.
+    pub since: &'static str,
+}
+
+/// A rule id that was renamed or removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeprecatedRule {
+    pub id: &'static str,
+    pub replacement: Option<&'static str>,
+    pub since: &'static str,
+}
+
+/// Config keys still accepted for compatibility. When renaming a key, keep
+/// the old field deserializing (e.g. with `#[serde(alias)]`) and list it here
+/// so users are warned and `migrate-config` can rewrite it.
+pub const DEPRECATED_KEYS: &[DeprecatedKey] = &[];
+
+/// Rule ids still accepted for compatibility; config and `RuleSet` lookups
+/// map them to their replacement.
+pub const DEPRECATED_RULES: &[DeprecatedRule] = &[];
+
+/// Config keys that take rule ids.
+const RULE_LISTS: &[&str] = &["test-rules.disable"];
+
+/// Config tables keyed by rule id.
+const RULE_TABLES: &[&str] = &["rules", "severity"];
+
+/// Problems with a `jfmt.toml` that still loads: deprecated keys and rule ids,
+/// keys jfmt doesn't know (usually typos, which are otherwise ignored) and
+/// `[naming]` patterns that aren't valid regexes.
+pub fn config_warnings(text: &str) -> Result<Vec<String>, ConfigError> {
+    let doc: DocumentMut = text.parse().map_err(|e: toml_edit::TomlError| ConfigError::Edit(e.to_string()))?;
+    let mut warnings = Vec::new();
+    for dep in DEPRECATED_KEYS.iter().filter(|d| get(&doc, d.key).is_some()) {
+        warnings.push(match dep.replacement {
+            Some(new) => format!("`{}` is deprecated since {}; use `{new}`", dep.key, dep.since),
+            None => format!("`{}` is deprecated since {} and has no effect", dep.key, dep.since),
+        });
+    }
+    for list in RULE_LISTS {
+        for id in rule_ids(&doc, list) {
+            if let Some(dep) = DEPRECATED_RULES.iter().find(|d| d.id == id) {
+                warnings.push(match dep.replacement {
+                    Some(new) => format!("rule `{id}` in `{list}` is deprecated since {}; use `{new}`", dep.since),
+                    None => format!("rule `{id}` in `{list}` is deprecated since {} and no longer exists", dep.since),
+                });
+            }
+        }
+    }
+    for table in RULE_TABLES {
+        for id in table_keys(&doc, table) {
+            match DEPRECATED_RULES.iter().find(|d| d.id == id) {
+                Some(dep) => warnings.push(match dep.replacement {
+                    Some(new) => format!("rule `{id}` in `[{table}]` is deprecated since {}; use `{new}`", dep.since),
+                    None => format!("rule `{id}` in `[{table}]` is deprecated since {} and no longer exists", dep.since),
+                }),
+                None if known_rule_id(&id).is_none() => warnings.push(format!("unknown rule `{id}` in `[{table}]` is ignored")),
+                None => {}
+            }
+        }
+    }
+    let known = known_keys();
+    let deprecated: BTreeSet<&str> = DEPRECATED_KEYS.iter().map(|d| d.key).collect();
+    let in_rule_table = |key: &str| RULE_TABLES.contains(&key.split_once('.').map_or(key, |(table, _)| table));
+    for key in keys(doc.as_table(), "") {
+        if !known.contains(&key) && !deprecated.contains(key.as_str()) && !in_rule_table(&key) {
+            warnings.push(format!("unknown key `{key}` is ignored"));
+        }
+    }
+    for entry in doc.get("overrides").and_then(Item::as_array_of_tables).into_iter().flat_map(|a| a.iter()) {
+        for key in keys(entry, "") {
+            if key != "files" && !known.contains(&key) && !deprecated.contains(key.as_str()) && !in_rule_table(&key) {
+                warnings.push(format!("unknown key `{key}` in [[overrides]] is ignored"));
+            }
+        }
+    }
+    if let Ok(config) = toml::from_str::<Config>(text) {
+        for (key, error) in naming::invalid_patterns(&config.naming) {
+            warnings.push(format!("`naming.{key}` is not a valid regex ({error}); its rule is skipped"));
+        }
+    }
+    Ok(warnings)
+}
+
+/// Rewrite a `jfmt.toml` to the current schema, keeping comments and layout.
+/// Returns the new text and one line per change; unknown keys are left for
+/// the user to sort out.
+pub fn migrate_config(text: &str) -> Result<(String, Vec<String>), ConfigError> {
+    let mut doc: DocumentMut = text.parse().map_err(|e: toml_edit::TomlError| ConfigError::Edit(e.to_string()))?;
+    let mut changes = Vec::new();
+    for dep in DEPRECATED_KEYS {
+        if let Some(new) = dep.replacement
+            && get(&doc, new).is_none()
+            && rename_in_place(&mut doc, dep.key, new)
+        {
+            changes.push(format!("renamed `{}` to `{new}`", dep.key));
+            continue;
+        }
+        let Some(value) = remove(&mut doc, dep.key) else { continue };
+        match dep.replacement {
+            Some(new) if get(&doc, new).is_none() => {
+                set(&mut doc, new, value);
+                changes.push(format!("renamed `{}` to `{new}`", dep.key));
+            }
+            Some(new) => changes.push(format!("removed `{}`; `{new}` is already set", dep.key)),
+            None => changes.push(format!("removed `{}`", dep.key)),
+        }
+    }
+    for list in RULE_LISTS {
+        let Some(array) = get_mut(&mut doc, list).and_then(Item::as_array_mut) else { continue };
+        let mut seen = BTreeSet::new();
+        let mut i = 0;
+        while i < array.len() {
+            let id = array.get(i).and_then(Value::as_str).unwrap_or_default().to_string();
+            let dep = DEPRECATED_RULES.iter().find(|d| d.id == id);
+            let current = dep.map_or(Some(id.as_str()), |d| d.replacement).map(str::to_string);
+            match current {
+                Some(new) if seen.insert(new.clone()) => {
+                    if new != id {
+                        array.replace(i, new.as_str());
+                        changes.push(format!("replaced rule `{id}` with `{new}` in `{list}`"));
+                    }
+                    i += 1;
+                }
+                _ => {
+                    array.remove(i);
+                    if dep.is_some() { changes.push(format!("removed rule `{id}` from `{list}`")); }
+                }
+            }
+        }
+    }
+    for table in RULE_TABLES {
+        for id in table_keys(&doc, table) {
+            let Some(dep) = DEPRECATED_RULES.iter().find(|d| d.id == id) else { continue };
+            let old = format!("{table}.{id}");
+            match dep.replacement {
+                Some(new) if !table_keys(&doc, table).iter().any(|k| k == new) => {
+                    let new_key = format!("{table}.{new}");
+                    if !rename_in_place(&mut doc, &old, &new_key)
+                        && let Some(value) = remove(&mut doc, &old)
+                    {
+                        set(&mut doc, &new_key, value);
+                    }
+                    changes.push(format!("replaced rule `{id}` with `{new}` in `[{table}]`"));
+                }
+                _ => {
+                    remove(&mut doc, &old);
+                    changes.push(format!("removed rule `{id}` from `[{table}]`"));
+                }
+            }
+        }
+    }
+    Ok((doc.to_string(), changes))
+}
+
+/// Dotted paths of every leaf key in `table`.
+fn keys(table: &toml_edit::Table, prefix: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    for (key, item) in table.iter() {
+        let path = if prefix.is_empty() { key.to_string() } else { format!("{prefix}.{key}") };
+        match item.as_table_like() {
+            Some(_) if item.is_table() => found.extend(keys(item.as_table().expect("is table"), &path)),
+            _ => found.push(path),
+        }
+    }
+    found
+}
+
+/// Every key the current `Config` understands, dotted for nested tables.
+fn known_keys() -> BTreeSet<String> {
+    fn walk(value: &serde_json::Value, prefix: &str, out: &mut BTreeSet<String>) {
+        let Some(map) = value.as_object() else { return };
+        for (key, v) in map {
+            let path = if prefix.is_empty() { key.clone() } else { format!("{prefix}.{key}") };
+            if v.is_object() { walk(v, &path, out) } else { out.insert(path); }
+        }
+    }
+    let mut known = BTreeSet::new();
+    walk(&serde_json::to_value(Config::default()).unwrap_or_default(), "", &mut known);
+    known
+}
+
+fn rule_ids(doc: &DocumentMut, key: &str) -> Vec<String> {
+    get(doc, key)
+        .and_then(Item::as_array)
+        .map(|a| a.iter().filter_map(Value::as_str).map(str::to_string).collect())
+        .unwrap_or_default()
+}
+
+/// Keys of the table (or inline table) at `key`.
+fn table_keys(doc: &DocumentMut, key: &str) -> Vec<String> {
+    get(doc, key)
+        .and_then(Item::as_table_like)
+        .map(|t| t.iter().map(|(k, _)| k.to_string()).collect())
+        .unwrap_or_default()
+}
+
+fn get<'a>(doc: &'a DocumentMut, key: &str) -> Option<&'a Item> {
+    key.split('.').try_fold(doc.as_item(), |item, part| item.get(part))
+}
+
+fn get_mut<'a>(doc: &'a mut DocumentMut, key: &str) -> Option<&'a mut Item> {
+    key.split('.').try_fold(doc.as_item_mut(), |item, part| item.get_mut(part))
+}
+
+/// Rename a key within its table, keeping its position and comments. Only
+/// possible when both keys live in the same table.
+fn rename_in_place(doc: &mut DocumentMut, old: &str, new: &str) -> bool {
+    let (parent, old_name, new_name) = match (old.rsplit_once('.'), new.rsplit_once('.')) {
+        (Some((p, o)), Some((q, n))) if p == q => (p, o, n),
+        (None, None) => ("", old, new),
+        _ => return false,
+    };
+    let table = if parent.is_empty() { Some(doc.as_table_mut()) } else { get_mut(doc, parent).and_then(Item::as_table_mut) };
+    let Some(table) = table else { return false };
+    if !table.contains_key(old_name) { return false; }
+    let entries: Vec<(toml_edit::Key, Item)> = table
+        .iter()
+        .filter_map(|(k, _)| table.get_key_value(k))
+        .map(|(key, item)| (key.clone(), item.clone()))
+        .collect();
+    table.clear();
+    for (key, item) in entries {
+        let key = if key.get() == old_name {
+            let mut renamed = toml_edit::Key::new(new_name);
+            *renamed.leaf_decor_mut() = key.leaf_decor().clone();
+            renamed
+        } else {
+            key
+        };
+        table.insert_formatted(&key, item);
+    }
+    true
+}
+
+fn remove(doc: &mut DocumentMut, key: &str) -> Option<Item> {
+    let (parent, last) = match key.rsplit_once('.') {
+        Some((parent, last)) => (get_mut(doc, parent)?, last),
+        None => (doc.as_item_mut(), key),
+    };
+    parent.as_table_like_mut()?.remove(last)
+}
+
+fn set(doc: &mut DocumentMut, key: &str, value: Item) {
+    let mut item = doc.as_item_mut();
+    let mut parts = key.split('.').peekable();
+    while let Some(part) = parts.next() {
+        if parts.peek().is_none() {
+            item[part] = value;
+            return;
+        }
+        if item.get(part).is_none() {
+            item[part] = toml_edit::table();
+        }
+        item = &mut item[part];
+    }
+}
@@ -0,0 +1,185 @@
+use std::fmt::Write;
+
+/// Lines of unchanged context around each change.
+const CONTEXT: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Keep(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Unified diff from `old` to `new`, as `diff -u` and `git diff` print it,
+/// with three lines of context. Empty when the texts are equal.
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str) -> String {
+    let a: Vec<&str> = old.split_inclusive('\n').collect();
+    let b: Vec<&str> = new.split_inclusive('\n').collect();
+    let edits = diff_lines(&a, &b);
+    if edits.iter().all(|e| matches!(e, Edit::Keep(..))) { return String::new(); }
+
+    let mut out = format!("--- {old_label}\n+++ {new_label}\n");
+    let changed: Vec<usize> = (0..edits.len()).filter(|&i| !matches!(edits[i], Edit::Keep(..))).collect();
+    let mut group_start = 0;
+    for g in 0..changed.len() {
+        let last = g + 1 == changed.len();
+        // Changes closer than twice the context share a hunk.
+        if !last && changed[g + 1] - changed[g] <= 2 * CONTEXT { continue; }
+        let from = changed[group_start].saturating_sub(CONTEXT);
+        let to = (changed[g] + CONTEXT + 1).min(edits.len());
+        write_hunk(&mut out, &edits[from..to], &a, &b);
+        group_start = g + 1;
+    }
+    out
+}
+
+fn write_hunk(out: &mut String, edits: &[Edit], a: &[&str], b: &[&str]) {
+    // Position just before the hunk in each file, for empty sides.
+    let (mut old_start, mut new_start) = (None, None);
+    let (mut old_len, mut new_len) = (0, 0);
+    for edit in edits {
+        match *edit {
+            Edit::Keep(i, j) => {
+                old_start.get_or_insert(i);
+                new_start.get_or_insert(j);
+                old_len += 1;
+                new_len += 1;
+            }
+            Edit::Delete(i) => {
+                old_start.get_or_insert(i);
+                old_len += 1;
+            }
+            Edit::Insert(j) => {
+                new_start.get_or_insert(j);
+                new_len += 1;
+            }
+        }
+    }
+    let (old_before, new_before) = position_before(edits);
+    let range = |start: Option<usize>, len: usize, before: usize| match start {
+        Some(start) if len > 0 => format!("{},{len}", start + 1),
+        _ => format!("{before},0"),
+    };
+    let _ = writeln!(out, "@@ -{} +{} @@", range(old_start, old_len, old_before), range(new_start, new_len, new_before));
+    for edit in edits {
+        let (marker, line) = match *edit {
+            Edit::Keep(i, _) => (' ', a[i]),
+            Edit::Delete(i) => ('-', a[i]),
+            Edit::Insert(j) => ('+', b[j]),
+        };
+        out.push(marker);
+        out.push_str(line);
+        if !line.ends_with('\n') {
+            out.push_str("\n\\ No newline at end of file\n");
+        }
+    }
+}
+
+/// Lines of each file before the first edit of a hunk (its 0-based start).
+fn position_before(edits: &[Edit]) -> (usize, usize) {
+    let old = edits.iter().find_map(|e| match *e {
+        Edit::Keep(i, _) | Edit::Delete(i) => Some(i),
+        Edit::Insert(_) => None,
+    });
+    let new = edits.iter().find_map(|e| match *e {
+        Edit::Keep(_, j) | Edit::Insert(j) => Some(j),
+        Edit::Delete(_) => None,
+    });
+    // A side with no lines in the hunk sits where the other side's first
+    // line does, shifted by the edits before it; zero-length ranges name
+    // the line they follow.
+    let inserted = edits.iter().take_while(|e| matches!(e, Edit::Insert(_))).count();
+    let deleted = edits.iter().take_while(|e| matches!(e, Edit::Delete(_))).count();
+    (
+        old.unwrap_or_else(|| new.unwrap_or(0).saturating_sub(deleted)),
+        new.unwrap_or_else(|| old.unwrap_or(0).saturating_sub(inserted)),
+    )
+}
+
+/// Shortest edit script turning `a` into `b` (Myers' algorithm), after
+/// trimming the common prefix and suffix. Memory grows with the square of
+/// the number of differences, not with the file size.
+fn diff_lines(a: &[&str], b: &[&str]) -> Vec<Edit> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..].iter().rev().zip(b[prefix..].iter().rev()).take_while(|(x, y)| x == y).count();
+    let (mid_a, mid_b) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut edits: Vec<Edit> = (0..prefix).map(|i| Edit::Keep(i, i)).collect();
+    edits.extend(myers(mid_a, mid_b).into_iter().map(|e| match e {
+        Edit::Keep(i, j) => Edit::Keep(i + prefix, j + prefix),
+        Edit::Delete(i) => Edit::Delete(i + prefix),
+        Edit::Insert(j) => Edit::Insert(j + prefix),
+    }));
+    let (tail_a, tail_b) = (a.len() - suffix, b.len() - suffix);
+    edits.extend((0..suffix).map(|k| Edit::Keep(tail_a + k, tail_b + k)));
+    edits
+}
+
+fn myers(a: &[&str], b: &[&str]) -> Vec<Edit> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    // trace[d][k + d] is the furthest x reached on diagonal k after d edits.
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+    let mut prev: Vec<isize> = vec![0];
+    'search: for d in 0..=(n + m) {
+        let mut v = vec![0isize; 2 * d as usize + 1];
+        let at = |v: &[isize], k: isize, d: isize| v[(k + d) as usize];
+        for k in (-d..=d).step_by(2) {
+            let mut x = if d == 0 {
+                0
+            } else if k == -d || (k != d && at(&prev, k - 1, d - 1) < at(&prev, k + 1, d - 1)) {
+                at(&prev, k + 1, d - 1)
+            } else {
+                at(&prev, k - 1, d - 1) + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[(k + d) as usize] = x;
+            if x >= n && y >= m {
+                trace.push(v);
+                break 'search;
+            }
+        }
+        trace.push(v.clone());
+        prev = v;
+    }
+
+    // Walk back from the end, one edit per step.
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for d in (0..trace.len() as isize).rev() {
+        let k = x - y;
+        if d == 0 {
+            while x > 0 && y > 0 {
+                x -= 1;
+                y -= 1;
+                edits.push(Edit::Keep(x as usize, y as usize));
+            }
+            break;
+        }
+        let prev = &trace[d as usize - 1];
+        let at = |k: isize| prev[(k + d - 1) as usize];
+        let down = k == -d || (k != d && at(k - 1) < at(k + 1));
+        let prev_k = if down { k + 1 } else { k - 1 };
+        let prev_x = at(prev_k);
+        let prev_y = prev_x - prev_k;
+        while x > prev_x && y > prev_y && (if down { y - 1 > prev_y } else { x - 1 > prev_x }) {
+            x -= 1;
+            y -= 1;
+            edits.push(Edit::Keep(x as usize, y as usize));
+        }
+        if down {
+            y -= 1;
+            edits.push(Edit::Insert(y as usize));
+        } else {
+            x -= 1;
+            edits.push(Edit::Delete(x as usize));
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+    edits.reverse();
+    edits
+}
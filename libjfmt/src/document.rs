@@ -0,0 +1,72 @@
+use tree_sitter::{InputEdit, Point, Tree};
+
+use crate::LintError;
+
+/// A byte-range replacement in a document, as sent by an editor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub start_byte: usize,
+    pub end_byte: usize,
+    pub new_text: String,
+}
+
+/// An open buffer that keeps its last syntax tree so edits can be re-parsed
+/// incrementally by `Linter::lint_document`.
+#[derive(Debug, Clone, Default)]
+pub struct Document {
+    pub(crate) source: String,
+    pub(crate) tree: Option<Tree>,
+}
+
+impl Document {
+    pub fn new(source: impl Into<String>) -> Self {
+        Self { source: source.into(), tree: None }
+    }
+
+    pub fn source(&self) -> &str { &self.source }
+
+    /// Apply an edit, keeping the previous tree in sync so the next parse only
+    /// revisits the changed region.
+    pub fn edit(&mut self, edit: &TextEdit) -> Result<(), LintError> {
+        let TextEdit { start_byte, end_byte, ref new_text } = *edit;
+        if start_byte > end_byte
+            || end_byte > self.source.len()
+            || !self.source.is_char_boundary(start_byte)
+            || !self.source.is_char_boundary(end_byte)
+        {
+            return Err(LintError::InvalidEdit { start: start_byte, end: end_byte });
+        }
+
+        let start_position = point_at(&self.source, start_byte);
+        let old_end_position = point_at(&self.source, end_byte);
+        self.source.replace_range(start_byte..end_byte, new_text);
+        let new_end_byte = start_byte + new_text.len();
+        let new_end_position = point_at(&self.source, new_end_byte);
+
+        if let Some(tree) = self.tree.as_mut() {
+            tree.edit(&InputEdit {
+                start_byte,
+                old_end_byte: end_byte,
+                new_end_byte,
+                start_position,
+                old_end_position,
+                new_end_position,
+            });
+        }
+        Ok(())
+    }
+
+    /// Replace the whole buffer; the next parse starts from scratch.
+    pub fn set_source(&mut self, source: impl Into<String>) {
+        self.source = source.into();
+        self.tree = None;
+    }
+}
+
+/// Row/column (in bytes) of a byte offset, as tree-sitter expects.
+pub(crate) fn point_at(source: &str, byte: usize) -> Point {
+    let before = &source.as_bytes()[..byte];
+    let row = before.iter().filter(|b| **b == b'\n').count();
+    let line_start = before.iter().rposition(|b| *b == b'\n').map_or(0, |i| i + 1);
+    Point { row, column: byte - line_start }
+}
@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+use tree_sitter::Node;
+
+use crate::{checkout_parser, return_parser, Config, FileError, LintError, SourceText};
+
+/// Settings for `find_duplicates`.
+#[derive(Debug, Clone)]
+pub struct DupOptions {
+    /// Smallest clone worth reporting, in normalized tokens.
+    pub min_tokens: usize,
+}
+
+impl Default for DupOptions {
+    fn default() -> Self { Self { min_tokens: 50 } }
+}
+
+/// One side of a clone pair; lines are 1-based and inclusive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CloneLocation {
+    pub path: PathBuf,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+/// Two statement sequences that are identical once identifiers and literals
+/// are abstracted away.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClonePair {
+    pub first: CloneLocation,
+    pub second: CloneLocation,
+    pub tokens: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct DupReport {
+    /// Clone pairs ordered by the first location's path and line.
+    pub pairs: Vec<ClonePair>,
+    /// Files that could not be read or parsed; they take no part in matching.
+    pub errors: Vec<(PathBuf, FileError)>,
+}
+
+/// A run of sibling statements, e.g. the body of a block.
+struct Block {
+    file: usize,
+    stmts: Vec<Stmt>,
+}
+
+struct Stmt {
+    start_byte: usize,
+    end_byte: usize,
+    start_line: usize,
+    end_line: usize,
+    tokens: Vec<&'static str>,
+}
+
+/// Statements `start..=end` of block `block`.
+#[derive(Clone, Copy)]
+struct Span {
+    block: usize,
+    start: usize,
+    end: usize,
+}
+
+/// Find duplicated statement sequences across `paths`.
+///
+/// Every statement list is cut into the shortest runs of consecutive
+/// statements reaching `min_tokens`; runs with the same normalized tokens are
+/// clones, and clones continuing statement by statement are merged into one
+/// pair covering the whole duplicated region.
+pub fn find_duplicates(paths: &[PathBuf], config: &Config, options: &DupOptions) -> DupReport {
+    find_in(paths.iter().map(|path| (path, config)).collect(), options)
+}
+
+/// `find_duplicates` with each group of files read under its own config, as
+/// `ConfigResolver::group` returns them. Files are taken in path order.
+pub fn find_duplicates_in_groups(groups: &[(Config, Vec<PathBuf>)], options: &DupOptions) -> DupReport {
+    let mut paths: Vec<_> = groups.iter().flat_map(|(config, paths)| paths.iter().map(move |path| (path, config))).collect();
+    paths.sort_by_key(|&(path, _)| path);
+    find_in(paths, options)
+}
+
+fn find_in(paths: Vec<(&PathBuf, &Config)>, options: &DupOptions) -> DupReport {
+    let parsed: Vec<_> = paths.par_iter().map(|&(path, config)| collect_blocks(path, config)).collect();
+    let mut report = DupReport::default();
+    let mut blocks = Vec::new();
+    for (file, (&(path, _), result)) in paths.iter().zip(parsed).enumerate() {
+        match result {
+            Ok(file_blocks) => blocks.extend(file_blocks.into_iter().map(|stmts| Block { file, stmts })),
+            Err(err) => report.errors.push((path.clone(), err)),
+        }
+    }
+
+    let mut by_tokens: HashMap<Vec<&'static str>, Vec<Span>> = HashMap::new();
+    for (b, block) in blocks.iter().enumerate() {
+        for start in 0..block.stmts.len() {
+            let mut key = Vec::new();
+            for (end, stmt) in block.stmts.iter().enumerate().skip(start) {
+                key.extend_from_slice(&stmt.tokens);
+                if key.len() >= options.min_tokens {
+                    by_tokens.entry(key).or_default().push(Span { block: b, start, end });
+                    break;
+                }
+            }
+        }
+    }
+
+    let mut raw = Vec::new();
+    for spans in by_tokens.values() {
+        for (i, a) in spans.iter().enumerate() {
+            for b in &spans[i + 1..] {
+                if !overlaps(&blocks, *a, *b) {
+                    raw.push((*a, *b));
+                }
+            }
+        }
+    }
+    // Runs are found starting at every statement, so one long clone shows up
+    // as a chain of matches offset by one statement on both sides.
+    let shift = |(a, b): &(Span, Span)| a.start as isize - b.start as isize;
+    raw.sort_by_key(|pair| (pair.0.block, pair.1.block, shift(pair), pair.0.start));
+    let mut merged: Vec<(Span, Span)> = Vec::new();
+    for (a, b) in raw {
+        if let Some(last) = merged.last_mut()
+            && (last.0.block, last.1.block) == (a.block, b.block)
+            && shift(last) == shift(&(a, b))
+            && a.start <= last.0.end + 1
+        {
+            let grown = (
+                Span { end: last.0.end.max(a.end), ..last.0 },
+                Span { end: last.1.end.max(b.end), ..last.1 },
+            );
+            if !overlaps(&blocks, grown.0, grown.1) {
+                *last = grown;
+                continue;
+            }
+        }
+        merged.push((a, b));
+    }
+
+    report.pairs = merged
+        .into_iter()
+        .map(|(a, b)| {
+            let tokens = blocks[a.block].stmts[a.start..=a.end].iter().map(|s| s.tokens.len()).sum();
+            ClonePair { first: location(&blocks, &paths, a), second: location(&blocks, &paths, b), tokens }
+        })
+        .collect();
+    report.pairs.sort_by(|x, y| {
+        (&x.first.path, x.first.start_line, &x.second.path, x.second.start_line)
+            .cmp(&(&y.first.path, y.first.start_line, &y.second.path, y.second.start_line))
+    });
+    report
+}
+
+fn overlaps(blocks: &[Block], a: Span, b: Span) -> bool {
+    let (ba, bb) = (&blocks[a.block], &blocks[b.block]);
+    ba.file == bb.file
+        && ba.stmts[a.start].start_byte < bb.stmts[b.end].end_byte
+        && bb.stmts[b.start].start_byte < ba.stmts[a.end].end_byte
+}
+
+fn location(blocks: &[Block], paths: &[(&PathBuf, &Config)], span: Span) -> CloneLocation {
+    let block = &blocks[span.block];
+    CloneLocation {
+        path: paths[block.file].0.clone(),
+        start_line: block.stmts[span.start].start_line,
+        end_line: block.stmts[span.end].end_line,
+    }
+}
+
+/// Parse `path` and return the statement lists of all its blocks.
+fn collect_blocks(path: &Path, config: &Config) -> Result<Vec<Vec<Stmt>>, FileError> {
+    let src = SourceText::read(path, config.mmap_threshold)?;
+    let mut parser = checkout_parser()?;
+    let tree = parser.parse(&*src, None);
+    return_parser(parser);
+    let tree = tree.ok_or(LintError::Parse)?;
+
+    let mut blocks = Vec::new();
+    let mut cursor = tree.walk();
+    loop {
+        let node = cursor.node();
+        if matches!(node.kind(), "block" | "constructor_body" | "switch_block_statement_group") {
+            let mut stmts_cursor = node.walk();
+            let stmts: Vec<Stmt> = node
+                .named_children(&mut stmts_cursor)
+                .filter(|n| !n.is_extra() && n.kind() != "switch_label")
+                .map(|n| Stmt {
+                    start_byte: n.start_byte(),
+                    end_byte: n.end_byte(),
+                    start_line: n.start_position().row + 1,
+                    end_line: n.end_position().row + 1,
+                    tokens: normalized_tokens(n),
+                })
+                .collect();
+            if !stmts.is_empty() {
+                blocks.push(stmts);
+            }
+        }
+        if cursor.goto_first_child() { continue; }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() { return Ok(blocks); }
+        }
+    }
+}
+
+/// Leaf tokens of `node` with identifiers and literals abstracted, so clones
+/// that only differ in names or constants still match. Comments are dropped.
+fn normalized_tokens(node: Node) -> Vec<&'static str> {
+    let mut tokens = Vec::new();
+    let mut cursor = node.walk();
+    loop {
+        let n = cursor.node();
+        let kind = n.kind();
+        let leaf = if n.is_extra() {
+            Some(None)
+        } else if matches!(kind, "identifier" | "type_identifier" | "field_identifier") {
+            Some(Some("$id"))
+        } else if kind.ends_with("_literal") || matches!(kind, "true" | "false") {
+            Some(Some("$lit"))
+        } else if n.child_count() == 0 {
+            Some(Some(kind))
+        } else {
+            None
+        };
+        match leaf {
+            Some(token) => tokens.extend(token),
+            None if cursor.goto_first_child() => continue,
+            None => {}
+        }
+        loop {
+            if cursor.node().id() == node.id() { return tokens; }
+            if cursor.goto_next_sibling() { break; }
+            if !cursor.goto_parent() { return tokens; }
+        }
+    }
+}
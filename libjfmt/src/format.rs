@@ -0,0 +1,509 @@
+use std::collections::HashMap;
+
+use tree_sitter::{Node, Tree};
+
+use crate::rules::{declaration_head, labeled_body, LINE_CONTAINERS};
+use crate::{checkout_parser, return_parser, Config, IndentStyle};
+
+/// Containers whose children each get a line of their own.
+const BODIES: &[&str] = &[
+    "program",
+    "block",
+    "constructor_body",
+    "class_body",
+    "interface_body",
+    "enum_body_declarations",
+    "annotation_type_body",
+    "switch_block",
+    "switch_block_statement_group",
+];
+
+/// Containers whose `{` goes at the end of the line before it.
+const BRACED: &[&str] = &[
+    "block",
+    "constructor_body",
+    "class_body",
+    "interface_body",
+    "enum_body",
+    "annotation_type_body",
+    "switch_block",
+];
+
+/// Nodes printed verbatim, as one token.
+const ATOMIC: &[&str] = &["string_literal", "text_block", "character_literal", "line_comment", "block_comment"];
+
+const INITIALIZERS: &[&str] = &["array_initializer", "element_value_array_initializer"];
+const TYPE_LISTS: &[&str] = &["type_arguments", "type_parameters"];
+
+/// Re-print `source` from its syntax tree with consistent indentation,
+/// spacing and brace placement.
+///
+/// Indentation follows `indent_style`, `indent_width`, `indent_case_labels`
+/// and `continuation_indent`; tokens on a line get single spaces where
+/// convention has them and none elsewhere; body braces open at the end of
+/// the line (`} else {` included); statements and members each start a line;
+/// blank lines are kept, at most one in a row and none just inside braces;
+/// every line ends as `line_ending` says. Where lines break within a
+/// statement is left as written.
+///
+/// Source that doesn't parse cleanly is returned unchanged, as is any result
+/// whose tokens differ from the input's, so formatting never changes what
+/// the code means.
+pub fn format_java_source(source: &str, config: &Config) -> String {
+    let Ok(mut parser) = checkout_parser() else { return source.to_string() };
+    let tree = parser.parse(source, None);
+    let formatted = tree.as_ref().filter(|t| !t.root_node().has_error()).map(|tree| Printer::new(source, config).print(tree));
+    let verified = formatted.filter(|out| {
+        let reparsed = parser.parse(out, None);
+        reparsed.is_some_and(|r| !r.root_node().has_error() && same_tokens(tree.as_ref(), source, &r, out))
+    });
+    return_parser(parser);
+    verified.unwrap_or_else(|| source.to_string())
+}
+
+/// A leaf token with the surroundings the printer asks about, gathered in
+/// one walk since `Node::parent` costs a descent from the root.
+#[derive(Clone, Copy)]
+struct Token<'t> {
+    node: Node<'t>,
+    parent: Option<Node<'t>>,
+    grandparent: Option<Node<'t>>,
+    first: bool,
+    last: bool,
+    /// The nearest line container holding the token, and its child that does.
+    container: Option<Node<'t>>,
+    stmt: Node<'t>,
+    /// The outermost node starting with the token whose parent gives each
+    /// child a line, with that parent.
+    line_child: Option<(Node<'t>, Node<'t>)>,
+}
+
+impl<'t> Token<'t> {
+    fn kind(&self) -> &'static str { self.node.kind() }
+
+    fn parent_kind(&self) -> &'static str { self.parent.map_or("", |p| p.kind()) }
+}
+
+/// An open `(` or `[`.
+struct Bracket {
+    /// Id of the line container it is in.
+    container: usize,
+    /// Whether it was opened on a continuation line of its statement.
+    continued: bool,
+    line: usize,
+    /// Indentation of that line.
+    indent: usize,
+}
+
+struct Printer<'a, 't> {
+    source: &'a str,
+    out: String,
+    /// Columns per indentation level.
+    level: usize,
+    continuation: usize,
+    tabs: bool,
+    tab_width: usize,
+    case_labels: bool,
+    /// Indentation of the line each container's `{` was printed on.
+    opened: HashMap<usize, usize>,
+    /// Parent of each line container.
+    parents: HashMap<usize, Node<'t>>,
+    /// Output line number.
+    line: usize,
+    /// Line on which the current statement of each container started.
+    statement_lines: HashMap<usize, usize>,
+    /// Open `(` and `[`.
+    brackets: Vec<Bracket>,
+    /// Line break of the output, per `line_ending`.
+    newline: &'static str,
+}
+
+impl<'a, 't> Printer<'a, 't> {
+    fn new(source: &'a str, config: &Config) -> Self {
+        let tab_width = (config.tab_width as usize).max(1);
+        let (level, tabs) = match config.indent_style {
+            IndentStyle::Spaces => ((config.indent_width as usize).max(1), false),
+            IndentStyle::Tabs => (tab_width, true),
+        };
+        let continuation = match config.continuation_indent as usize {
+            0 => 2 * level,
+            n => n,
+        };
+        Printer {
+            source,
+            out: String::with_capacity(source.len()),
+            level,
+            continuation,
+            tabs,
+            tab_width,
+            case_labels: config.indent_case_labels,
+            opened: HashMap::new(),
+            parents: HashMap::new(),
+            line: 0,
+            statement_lines: HashMap::new(),
+            brackets: Vec::new(),
+            newline: config.line_ending.resolve(source),
+        }
+    }
+
+    fn print(mut self, tree: &'t Tree) -> String {
+        let tokens = walk(tree.root_node(), &mut self.parents);
+        let mut line_indent = 0;
+        let mut prev: Option<Token> = None;
+        for token in tokens {
+            let newlines = match prev {
+                None => 1,
+                Some(prev) => self.newlines(prev, token),
+            };
+            if newlines > 0 {
+                if prev.is_some() {
+                    self.trim_line_end();
+                    self.out.push_str(&"\n".repeat(newlines));
+                    self.line += 1;
+                }
+                line_indent = self.indent_of(token);
+                if let Some(container) = token.container
+                    && starts_statement(token)
+                {
+                    self.statement_lines.insert(container.id(), self.line);
+                }
+                self.push_indent(line_indent);
+                self.push_token(token.node, Some(line_indent));
+            } else {
+                if prev.is_some_and(|prev| spaced(prev, token, self.source)) {
+                    self.out.push(' ');
+                }
+                self.push_token(token.node, None);
+            }
+            if token.kind() == "{"
+                && let Some(container) = token.parent.filter(|p| LINE_CONTAINERS.contains(&p.kind()))
+            {
+                self.opened.insert(container.id(), line_indent);
+            }
+            match token.kind() {
+                "(" | "[" => {
+                    let container = token.container.map_or(0, |c| c.id());
+                    let continued = self.statement_lines.get(&container) != Some(&self.line);
+                    self.brackets.push(Bracket { container, continued, line: self.line, indent: line_indent });
+                }
+                ")" | "]" => {
+                    self.brackets.pop();
+                }
+                _ => {}
+            }
+            if token.node.kind() == "block_comment" || token.node.kind() == "string_literal" {
+                self.line += self.source[token.node.byte_range()].matches('\n').count();
+            }
+            prev = Some(token);
+        }
+        self.trim_line_end();
+        self.out.push('\n');
+        let mut out = self.out;
+        if self.source.starts_with('\u{feff}') {
+            out.insert(0, '\u{feff}');
+        }
+        out = out.replace("\r\n", "\n");
+        if self.newline == "\r\n" {
+            out = out.replace('\n', "\r\n");
+        }
+        out
+    }
+
+    /// Line breaks to print between `prev` and `token`.
+    fn newlines(&self, prev: Token, token: Token) -> usize {
+        let written = self.source[prev.node.end_byte()..token.node.start_byte()].matches('\n').count();
+        let mut n = written.min(2);
+        let after_comment = prev.kind() == "line_comment";
+        if opens_body(token) && !after_comment {
+            n = 0;
+        }
+        if prev.kind() == "}" && !after_comment && continues_after_brace(token) {
+            n = 0;
+        }
+        if starts_body_line(token, written) || after_comment {
+            n = n.max(1);
+        }
+        // No blank lines just inside braces.
+        if prev.kind() == "{" || token.kind() == "}" {
+            n = n.min(1);
+        }
+        n
+    }
+
+    /// Columns of indentation for a line starting with `token`.
+    fn indent_of(&self, token: Token) -> usize {
+        if let Some(container) = closed_container(token) {
+            return self.opened.get(&container.id()).copied().unwrap_or(0);
+        }
+        let Some(container) = token.container else { return 0 };
+        if matches!(token.kind(), ")" | "]")
+            && let Some(open) = self.brackets.last()
+        {
+            return open.indent;
+        }
+        let base = self.content_indent(container, token.stmt);
+        if starts_statement(token) || matches!(token.kind(), "{" | "else" | "catch" | "finally") {
+            return base;
+        }
+        // One more step for each later line that left a bracket open.
+        let mut lines: Vec<usize> =
+            self.brackets.iter().filter(|b| b.container == container.id() && b.continued).map(|b| b.line).collect();
+        lines.dedup();
+        base + self.continuation * (1 + lines.len())
+    }
+
+    /// Indentation of `stmt`, a child starting a line in `container`.
+    fn content_indent(&self, container: Node, stmt: Node) -> usize {
+        let parent = self.parents.get(&container.id());
+        match container.kind() {
+            "program" => 0,
+            "enum_body_declarations" => parent.map_or(0, |&body| self.content_indent(body, stmt)),
+            "switch_block_statement_group" => {
+                let labels = parent.map_or(0, |&block| self.content_indent(block, container));
+                if stmt.kind() == "switch_label" { labels } else { labels + self.level }
+            }
+            kind => {
+                let open = self.opened.get(&container.id()).copied().unwrap_or(0);
+                if kind == "switch_block" && !self.case_labels { open } else { open + self.level }
+            }
+        }
+    }
+
+    fn push_indent(&mut self, columns: usize) {
+        if self.tabs {
+            self.out.push_str(&"\t".repeat(columns / self.tab_width));
+            self.out.push_str(&" ".repeat(columns % self.tab_width));
+        } else {
+            self.out.push_str(&" ".repeat(columns));
+        }
+    }
+
+    /// Print `token`; a block comment starting a line at `indent` has its
+    /// `*` lines re-aligned under its opening.
+    fn push_token(&mut self, token: Node, indent: Option<usize>) {
+        let text = &self.source[token.byte_range()];
+        if token.kind() != "block_comment" || !text.contains('\n') {
+            self.out.push_str(text);
+            return;
+        }
+        for (i, line) in text.lines().enumerate() {
+            if i > 0 {
+                self.trim_line_end();
+                self.out.push('\n');
+            }
+            let rest = line.trim_start();
+            match indent {
+                Some(indent) if i > 0 && rest.starts_with('*') => {
+                    self.push_indent(indent);
+                    self.out.push(' ');
+                    self.out.push_str(rest);
+                }
+                _ => self.out.push_str(line),
+            }
+        }
+    }
+
+    fn trim_line_end(&mut self) {
+        let kept = self.out.trim_end_matches([' ', '\t', '\r']).len();
+        self.out.truncate(kept);
+    }
+}
+
+/// Whether `token` starts the statement or member it belongs to, rather than
+/// continue it.
+fn starts_statement(token: Token) -> bool {
+    token.node.start_byte() <= declaration_head(token.stmt) || labeled_body(token.stmt, token.node)
+}
+
+/// Tokens of the tree in source order, recording each line container's
+/// parent in `parents`.
+fn walk<'t>(root: Node<'t>, parents: &mut HashMap<usize, Node<'t>>) -> Vec<Token<'t>> {
+    let mut found = Vec::new();
+    let mut cursor = root.walk();
+    // Ancestors of the current node, with its index among each one's children.
+    let mut ancestors: Vec<(Node, usize)> = Vec::new();
+    let mut index = 0;
+    // Positions in `ancestors` of line containers.
+    let mut containers: Vec<usize> = Vec::new();
+    loop {
+        let node = cursor.node();
+        let atomic = ATOMIC.contains(&node.kind()) || node.child_count() == 0;
+        if atomic && node.end_byte() > node.start_byte() {
+            let parent = ancestors.last().map(|&(p, _)| p);
+            let at = containers.last().copied();
+            let line_child = {
+                let mut child = node;
+                let mut found = None;
+                for &(a, _) in ancestors.iter().rev() {
+                    if BODIES.contains(&a.kind()) || has_members(a) {
+                        found = Some((child, a));
+                        break;
+                    }
+                    if a.start_byte() != node.start_byte() { break; }
+                    child = a;
+                }
+                found
+            };
+            found.push(Token {
+                node,
+                parent,
+                grandparent: ancestors.len().checked_sub(2).map(|i| ancestors[i].0),
+                first: index == 0,
+                last: parent.is_some_and(|p| index + 1 == p.child_count()),
+                container: at.map(|i| ancestors[i].0),
+                stmt: at.and_then(|i| ancestors.get(i + 1)).map_or(node, |&(s, _)| s),
+                line_child,
+            });
+        }
+        if !atomic && cursor.goto_first_child() {
+            if LINE_CONTAINERS.contains(&node.kind()) {
+                containers.push(ancestors.len());
+                if let Some(&(parent, _)) = ancestors.last() {
+                    parents.insert(node.id(), parent);
+                }
+            }
+            ancestors.push((node, index));
+            index = 0;
+            continue;
+        }
+        loop {
+            if cursor.goto_next_sibling() {
+                index += 1;
+                break;
+            }
+            if !cursor.goto_parent() { return found; }
+            let (_, i) = ancestors.pop().expect("cursor is below the root");
+            index = i;
+            if containers.last() == Some(&ancestors.len()) {
+                containers.pop();
+            }
+        }
+    }
+}
+
+/// Leaf tokens and comments in source order, with literals kept whole.
+fn leaves(root: Node) -> Vec<Node> {
+    let mut found = Vec::new();
+    let mut cursor = root.walk();
+    loop {
+        let node = cursor.node();
+        let atomic = ATOMIC.contains(&node.kind()) || node.child_count() == 0;
+        if atomic && node.end_byte() > node.start_byte() {
+            found.push(node);
+        }
+        if !atomic && cursor.goto_first_child() { continue; }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() { return found; }
+        }
+    }
+}
+
+/// Whether `a` and `b` hold the same tokens; comments may differ in whitespace.
+fn same_tokens(a: Option<&Tree>, a_src: &str, b: &Tree, b_src: &str) -> bool {
+    let Some(a) = a else { return false };
+    let (a, b) = (leaves(a.root_node()), leaves(b.root_node()));
+    a.len() == b.len()
+        && a.iter().zip(&b).all(|(x, y)| {
+            let (xs, ys) = (&a_src[x.byte_range()], &b_src[y.byte_range()]);
+            x.kind() == y.kind()
+                && match x.kind() {
+                    "block_comment" | "line_comment" => xs.split_whitespace().eq(ys.split_whitespace()),
+                    _ => xs.replace("\r\n", "\n") == ys.replace("\r\n", "\n"),
+                }
+        })
+}
+
+/// The container `token` closes, when it is a `}` ending one.
+fn closed_container<'t>(token: Token<'t>) -> Option<Node<'t>> {
+    if token.kind() != "}" { return None; }
+    token
+        .parent
+        .filter(|p| LINE_CONTAINERS.contains(&p.kind()) && p.child(0).is_some_and(|c| c.kind() == "{"))
+}
+
+/// Whether `token` is the `{` of a body that opens on the line before it.
+/// Blocks standing as statements of their own keep their line.
+fn opens_body(token: Token) -> bool {
+    token.kind() == "{"
+        && BRACED.contains(&token.parent_kind())
+        && !(token.parent_kind() == "block" && token.grandparent.is_some_and(|p| LINE_CONTAINERS.contains(&p.kind())))
+}
+
+/// `else`, `catch`, `finally` and the `while` of a `do` loop follow a `}`
+/// on its line.
+fn continues_after_brace(token: Token) -> bool {
+    match token.kind() {
+        "else" | "catch" | "finally" => true,
+        "while" => token.parent_kind() == "do_statement",
+        _ => false,
+    }
+}
+
+/// Whether `token` must start a line: it starts a statement or member of a
+/// body, or closes a non-empty one. Comments trailing code (`written` line
+/// breaks before them is zero) stay where they are.
+fn starts_body_line(token: Token, written: usize) -> bool {
+    if let Some(container) = closed_container(token) {
+        let mut cursor = container.walk();
+        return (BODIES.contains(&container.kind()) || has_members(container))
+            && container.named_children(&mut cursor).any(|c| !c.kind().ends_with("comment"));
+    }
+    if token.kind().ends_with("comment") && written == 0 { return false; }
+    let Some((node, parent)) = token.line_child else { return false };
+    // An enum with members starts its constants on a line of their own.
+    if has_members(parent) {
+        let mut cursor = parent.walk();
+        return parent.named_children(&mut cursor).find(|c| !c.kind().ends_with("comment")).is_some_and(|c| c.id() == node.id());
+    }
+    // `case 1: {` keeps its block on the label's line if written so.
+    let inline_block = parent.kind() == "switch_block_statement_group" && node.kind() == "block" && written == 0;
+    node.is_named() && !inline_block
+}
+
+/// Whether `node` is an enum body declaring more than constants.
+fn has_members(node: Node) -> bool {
+    let mut cursor = node.walk();
+    node.kind() == "enum_body" && node.named_children(&mut cursor).any(|c| c.kind() == "enum_body_declarations")
+}
+
+/// Whether a space separates `prev` and `token` on one line.
+fn spaced(prev: Token, token: Token, source: &str) -> bool {
+    let (p, t) = (prev.kind(), token.kind());
+    if p.ends_with("comment") || t.ends_with("comment") { return true; }
+    // Operators that would lex as one token if joined.
+    let p_last = source[prev.node.byte_range()].chars().last().unwrap_or(' ');
+    let t_first = source[token.node.byte_range()].chars().next().unwrap_or(' ');
+    if "+-&|<>=*/%^".contains(p_last)
+        && "+-&|<>=*/%^".contains(t_first)
+        && !TYPE_LISTS.contains(&prev.parent_kind())
+        && !TYPE_LISTS.contains(&token.parent_kind())
+    {
+        return true;
+    }
+    if matches!(t, ";" | "," | "." | ")" | "]" | "[" | "::" | "...") { return false; }
+    if matches!(p, "(" | "[" | "." | "::" | "@") { return false; }
+    if t == ":" && matches!(token.parent_kind(), "switch_block_statement_group" | "switch_label" | "labeled_statement") {
+        return false;
+    }
+    // Prefix operators hug their operand, postfix ones the operand before.
+    if matches!(prev.parent_kind(), "unary_expression" | "update_expression") && prev.first { return false; }
+    if token.parent_kind() == "update_expression" && token.last { return false; }
+    if TYPE_LISTS.contains(&token.parent_kind()) {
+        if t == "<" && (prev.node.is_named() || p == ">") { return false; }
+        if t == ">" { return false; }
+    }
+    if TYPE_LISTS.contains(&prev.parent_kind()) {
+        if p == "<" { return false; }
+        // `Collections.<T>emptyList()`
+        if p == ">" && prev.grandparent.is_some_and(|n| n.kind() == "method_invocation") { return false; }
+    }
+    if t == "(" {
+        let generic = p == ">" && TYPE_LISTS.contains(&prev.parent_kind());
+        return !(prev.node.is_named() || generic);
+    }
+    let in_initializer = |n: Token| INITIALIZERS.contains(&n.parent_kind());
+    if t == "{" && p == "{" { return false; }
+    if p == "{" && (t == "}" || in_initializer(prev)) { return false; }
+    if t == "}" && in_initializer(token) { return false; }
+    true
+}
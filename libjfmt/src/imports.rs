@@ -0,0 +1,259 @@
+use std::collections::{BTreeSet, HashSet};
+
+use tree_sitter::Node;
+
+use crate::Imports;
+
+/// One `import` declaration, reduced to what ordering cares about.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct ImportDecl {
+    pub is_static: bool,
+    /// Dotted name as imported, whitespace removed: `java.util.*`.
+    pub name: String,
+}
+
+impl ImportDecl {
+    pub fn from_node(node: Node, source: &str) -> Self {
+        let text = &source[node.byte_range()];
+        let body = text.trim_start_matches("import").trim_end_matches(';').trim();
+        let (is_static, name) = match body.strip_prefix("static") {
+            Some(rest) if rest.starts_with(char::is_whitespace) => (true, rest),
+            _ => (false, body),
+        };
+        ImportDecl { is_static, name: name.chars().filter(|c| !c.is_whitespace()).collect() }
+    }
+
+    /// The simple name the import brings into scope; `None` for on-demand
+    /// (`.*`) imports.
+    pub fn simple_name(&self) -> Option<&str> {
+        let last = self.name.rsplit('.').next()?;
+        (last != "*").then_some(last)
+    }
+
+    fn render(&self) -> String {
+        format!("import {}{};", if self.is_static { "static " } else { "" }, self.name)
+    }
+}
+
+/// The import declarations of a file when they form one contiguous block,
+/// with the byte range from the first to the last. `None` when there are no
+/// imports or anything else (a comment, a stray `;`) sits between them.
+pub(crate) fn import_block<'t>(program: Node<'t>) -> Option<(Vec<Node<'t>>, std::ops::Range<usize>)> {
+    let mut cursor = program.walk();
+    let children: Vec<Node> = program.named_children(&mut cursor).collect();
+    let first = children.iter().position(|n| n.kind() == "import_declaration")?;
+    let last = children.iter().rposition(|n| n.kind() == "import_declaration")?;
+    let block = &children[first..=last];
+    if block.iter().any(|n| n.kind() != "import_declaration") { return None; }
+    Some((block.to_vec(), block[0].start_byte()..block[block.len() - 1].end_byte()))
+}
+
+/// The import block as `organize-imports` wants it: deduplicated, split into
+/// the `[imports]` groups, sorted within each group, and joined with
+/// `newline` (plus a blank line between groups when configured).
+pub(crate) fn organize(imports: &[ImportDecl], config: &Imports, newline: &str) -> String {
+    let unique: BTreeSet<&ImportDecl> = imports.iter().collect();
+    let mut groups: Vec<Vec<&ImportDecl>> = vec![Vec::new(); config.groups.len() + 1];
+    for import in unique {
+        groups[group_of(import, config)].push(import);
+    }
+    for group in &mut groups {
+        group.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+    let separator = if config.blank_line_between_groups { format!("{newline}{newline}") } else { newline.to_string() };
+    groups
+        .iter()
+        .filter(|g| !g.is_empty())
+        .map(|g| g.iter().map(|i| i.render()).collect::<Vec<_>>().join(newline))
+        .collect::<Vec<_>>()
+        .join(&separator)
+}
+
+/// Index of the group `import` belongs to: `static` takes static imports,
+/// otherwise the longest matching package prefix wins, then `*`. Imports no
+/// group takes go last.
+fn group_of(import: &ImportDecl, config: &Imports) -> usize {
+    let mut best: Option<(usize, usize)> = None; // (prefix length, group)
+    let mut wildcard = None;
+    for (index, group) in config.groups.iter().enumerate() {
+        for prefix in group {
+            match prefix.as_str() {
+                "static" if import.is_static => return index,
+                "static" => {}
+                "*" => {
+                    wildcard.get_or_insert(index);
+                }
+                prefix if prefix_matches(&import.name, prefix) && best.is_none_or(|(len, _)| prefix.len() > len) => {
+                    best = Some((prefix.len(), index));
+                }
+                _ => {}
+            }
+        }
+    }
+    best.map(|(_, index)| index).or(wildcard).unwrap_or(config.groups.len())
+}
+
+/// Whether `name` lies in package `prefix`: `java` covers `java.util.List`
+/// but not `javafx.scene.Node`.
+fn prefix_matches(name: &str, prefix: &str) -> bool {
+    name.strip_prefix(prefix).is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+}
+
+/// Whether an import's simple name is never referenced. On-demand imports
+/// are never unused, since what they bring in isn't known.
+pub(crate) fn is_unused(import: &ImportDecl, referenced: &HashSet<&str>) -> bool {
+    import.simple_name().is_some_and(|name| !referenced.contains(name))
+}
+
+/// Simple names the code refers to outside `package` and `import`
+/// declarations, plus types named by Javadoc `{@link}`, `@see` and `@throws`
+/// tags, which need the import too.
+pub(crate) fn referenced_names<'s>(root: Node, source: &'s str) -> HashSet<&'s str> {
+    let mut names = HashSet::new();
+    let mut cursor = root.walk();
+    loop {
+        let node = cursor.node();
+        let descend = match node.kind() {
+            "import_declaration" | "package_declaration" => false,
+            "identifier" | "type_identifier" => {
+                names.insert(&source[node.byte_range()]);
+                false
+            }
+            "block_comment" => {
+                javadoc_references(&source[node.byte_range()], &mut names);
+                false
+            }
+            _ => true,
+        };
+        if descend && cursor.goto_first_child() { continue; }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() { return names; }
+        }
+    }
+}
+
+/// First name of each reference after a Javadoc tag: `Map` for
+/// `{@link Map.Entry}`, `Foo` for `@see Foo#bar()`.
+fn javadoc_references<'s>(comment: &'s str, out: &mut HashSet<&'s str>) {
+    if !comment.starts_with("/**") { return; }
+    for tag in ["@link", "@linkplain", "@see", "@throws", "@exception", "@value"] {
+        for (at, _) in comment.match_indices(tag) {
+            let rest = &comment[at + tag.len()..];
+            if !rest.starts_with(char::is_whitespace) { continue; }
+            let rest = rest.trim_start();
+            let end = rest.find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$')).unwrap_or(rest.len());
+            if end > 0 {
+                out.insert(&rest[..end]);
+            }
+        }
+    }
+}
+
+/// Byte range deleting `node`: its whole line when nothing else is on it.
+pub(crate) fn line_range(node: Node, source: &str) -> std::ops::Range<usize> {
+    let (start, end) = (node.start_byte(), node.end_byte());
+    let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[end..].find('\n').map_or(source.len(), |i| end + i + 1);
+    let alone = source[line_start..start].trim().is_empty() && source[end..line_end].trim().is_empty();
+    if alone { line_start..line_end } else { start..end }
+}
+
+/// `java.lang` types, which are in scope without an import and so never come
+/// from an on-demand import.
+const JAVA_LANG: &[&str] = &[
+    "Appendable", "ArithmeticException", "ArrayIndexOutOfBoundsException", "ArrayStoreException", "AssertionError",
+    "AutoCloseable", "Boolean", "Byte", "CharSequence", "Character", "Class", "ClassCastException", "ClassLoader",
+    "ClassNotFoundException", "CloneNotSupportedException", "Cloneable", "Comparable", "Deprecated", "Double", "Enum",
+    "Error", "Exception", "ExceptionInInitializerError", "Float", "FunctionalInterface", "IllegalAccessException",
+    "IllegalArgumentException", "IllegalMonitorStateException", "IllegalStateException", "IndexOutOfBoundsException",
+    "InstantiationException", "Integer", "InterruptedException", "Iterable", "LinkageError", "Long", "Math", "Module",
+    "NegativeArraySizeException", "NoClassDefFoundError", "NoSuchFieldException", "NoSuchMethodException",
+    "NullPointerException", "Number", "NumberFormatException", "Object", "OutOfMemoryError", "Override", "Package",
+    "Process", "ProcessBuilder", "Readable", "Record", "ReflectiveOperationException", "Runnable", "Runtime",
+    "RuntimeException", "SafeVarargs", "SecurityException", "Short", "StackOverflowError", "StackTraceElement",
+    "StrictMath", "String", "StringBuffer", "StringBuilder", "StringIndexOutOfBoundsException", "SuppressWarnings",
+    "System", "Thread", "ThreadGroup", "ThreadLocal", "Throwable", "UnsupportedOperationException", "Void",
+];
+
+/// Explicit imports to replace the file's only on-demand import with: every
+/// type name the code uses that nothing else accounts for (declared in the
+/// file, imported by name, or in `java.lang`). A guess, since types from the
+/// file's own package look the same; `None` when the file has several
+/// on-demand imports or the guess comes out empty.
+pub(crate) fn expand_wildcard(program: Node, source: &str) -> Option<Vec<String>> {
+    let mut cursor = program.walk();
+    let imports: Vec<ImportDecl> = program
+        .named_children(&mut cursor)
+        .filter(|n| n.kind() == "import_declaration")
+        .map(|n| ImportDecl::from_node(n, source))
+        .collect();
+    let [wildcard] = imports.iter().filter(|i| !i.is_static && i.simple_name().is_none()).collect::<Vec<_>>()[..] else {
+        return None;
+    };
+    let package = wildcard.name.strip_suffix(".*")?;
+    let explicit: HashSet<&str> = imports.iter().filter_map(ImportDecl::simple_name).collect();
+    let mut used = BTreeSet::new();
+    let mut declared = HashSet::new();
+    type_names(program, source, &mut used, &mut declared);
+    let names: Vec<String> = used
+        .into_iter()
+        .filter(|n| !declared.contains(n) && !explicit.contains(n) && !JAVA_LANG.contains(n))
+        .map(|n| format!("{package}.{n}"))
+        .collect();
+    (!names.is_empty()).then_some(names)
+}
+
+/// Collect names used as types (`List<String>`, `new Foo()`, `Foo.bar()`,
+/// `@Foo`) into `used`, and names the file declares into `declared`. Only
+/// the first part of a qualified name counts, and only capitalized names.
+fn type_names<'s>(root: Node, source: &'s str, used: &mut BTreeSet<&'s str>, declared: &mut HashSet<&'s str>) {
+    let text = |n: Node| &source[n.byte_range()];
+    let capitalized = |s: &str| s.starts_with(|c: char| c.is_ascii_uppercase()) && s.contains(|c: char| c.is_lowercase());
+    let mut cursor = root.walk();
+    loop {
+        let node = cursor.node();
+        let descend = match node.kind() {
+            "import_declaration" | "package_declaration" | "line_comment" | "block_comment" => false,
+            "type_identifier" => {
+                if capitalized(text(node)) { used.insert(text(node)); }
+                false
+            }
+            "scoped_type_identifier" | "scoped_identifier" => {
+                let mut first = node;
+                while let Some(child) = first.named_child(0) {
+                    first = child;
+                }
+                if capitalized(text(first)) { used.insert(text(first)); }
+                false
+            }
+            "method_invocation" | "field_access" => {
+                if let Some(object) = node.child_by_field_name("object")
+                    && object.kind() == "identifier"
+                    && capitalized(text(object))
+                {
+                    used.insert(text(object));
+                }
+                true
+            }
+            "marker_annotation" | "annotation" => {
+                if let Some(name) = node.child_by_field_name("name")
+                    && name.kind() == "identifier"
+                {
+                    used.insert(text(name));
+                }
+                true
+            }
+            "class_declaration" | "interface_declaration" | "enum_declaration" | "record_declaration"
+            | "annotation_type_declaration" | "type_parameter" => {
+                let name = node.child_by_field_name("name").or_else(|| node.named_child(0).filter(|n| n.kind() == "type_identifier"));
+                if let Some(name) = name { declared.insert(text(name)); }
+                true
+            }
+            _ => true,
+        };
+        if descend && cursor.goto_first_child() { continue; }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() { return; }
+        }
+    }
+}
@@ -0,0 +1,185 @@
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+use tree_sitter::Node;
+
+use crate::{Config, Project, Visibility};
+
+/// Javadoc coverage of the public types and methods in one package.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PackageCoverage {
+    /// Package name; the default package is reported as `(default)`.
+    pub package: String,
+    pub documented: usize,
+    pub total: usize,
+}
+
+impl PackageCoverage {
+    /// Documented share in percent; 100 when there is nothing to document.
+    pub fn percent(&self) -> f64 {
+        if self.total == 0 { 100.0 } else { self.documented as f64 * 100.0 / self.total as f64 }
+    }
+}
+
+/// Per-package Javadoc coverage of public declarations, ordered by package,
+/// plus the overall figure for the whole project.
+pub fn javadoc_coverage(project: &Project) -> (Vec<PackageCoverage>, PackageCoverage) {
+    let mut by_package: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+    for file in project.files() {
+        let package = file.package.as_deref().unwrap_or("(default)");
+        for decl in file.declarations.iter().filter(|d| d.visibility == Visibility::Public) {
+            let (documented, total) = by_package.entry(package).or_default();
+            *documented += usize::from(decl.documented);
+            *total += 1;
+        }
+    }
+    let packages: Vec<PackageCoverage> = by_package
+        .into_iter()
+        .map(|(package, (documented, total))| PackageCoverage { package: package.to_string(), documented, total })
+        .collect();
+    let overall = PackageCoverage {
+        package: "(all)".to_string(),
+        documented: packages.iter().map(|p| p.documented).sum(),
+        total: packages.iter().map(|p| p.total).sum(),
+    };
+    (packages, overall)
+}
+
+/// The Javadoc comment directly before a declaration, if there is one.
+pub(crate) fn doc_comment<'t>(node: Node<'t>, source: &str) -> Option<Node<'t>> {
+    node.prev_sibling().filter(|c| c.kind() == "block_comment" && source[c.byte_range()].starts_with("/**"))
+}
+
+/// A block tag such as `@param` starting a line of a Javadoc comment.
+pub(crate) struct BlockTag<'s> {
+    /// The tag with its `@`: `@param`.
+    pub name: &'s str,
+    /// The word after the tag, empty when there is none: the parameter name
+    /// of an `@param`.
+    pub argument: &'s str,
+    /// Byte offset of the `@` within the comment.
+    pub offset: usize,
+}
+
+/// Block tags of a Javadoc comment, in order. Lines inside `<pre>` are
+/// example code, where `@Override` is an annotation rather than a tag.
+pub(crate) fn block_tags(comment: &str) -> Vec<BlockTag<'_>> {
+    let mut tags = Vec::new();
+    let (mut offset, mut in_pre) = (0, false);
+    for line in comment.split_inclusive('\n') {
+        let start = offset;
+        offset += line.len();
+        let was_pre = in_pre;
+        if line.contains("<pre") { in_pre = true; }
+        if line.contains("</pre>") { in_pre = false; }
+        if was_pre { continue; }
+        let content = line.trim_start();
+        let content = content.strip_prefix("/**").unwrap_or(content).trim_start_matches('*').trim_start();
+        if !content.starts_with('@') { continue; }
+        let name_len = content.find(char::is_whitespace).unwrap_or(content.len());
+        let argument = content[name_len..].split_whitespace().next().filter(|a| *a != "*/").unwrap_or_default();
+        tags.push(BlockTag { name: &content[..name_len], argument, offset: start + line.len() - content.len() });
+    }
+    tags
+}
+
+/// HTML elements that start a block of their own, so a line opening with
+/// one is never joined onto the line before.
+const HTML_BLOCKS: &[&str] = &[
+    "p", "ul", "ol", "li", "dl", "dt", "dd", "table", "tr", "td", "th", "thead", "tbody", "blockquote", "div", "h1",
+    "h2", "h3", "h4", "h5", "h6", "hr", "br",
+];
+
+/// `comment` as `javadoc-format` lays it out: `/**` and `*/` on lines of
+/// their own, every line in between starting with a ` * ` aligned under
+/// the first `*`, and paragraphs with a line longer than `max_line_length`
+/// refilled to fit. `indent` is the whitespace before the comment.
+/// Paragraphs end at blank lines, block tags and HTML block elements;
+/// `<pre>` blocks are kept line for line, and so are paragraphs that already
+/// fit. One-line comments that fit are left as they are.
+pub(crate) fn reflow(comment: &str, indent: &str, config: &Config, newline: &str) -> String {
+    let (max_len, unit, tab_width) = (config.max_line_length as usize, config.line_length_unit, config.tab_width as usize);
+    let fits = |line: &str| unit.measure(indent, tab_width) + unit.measure(line, tab_width) <= max_len;
+    if !comment.contains('\n') && fits(comment) { return comment.to_string(); }
+    let Some(body) = comment.strip_prefix("/**").and_then(|b| b.strip_suffix("*/")) else { return comment.to_string() };
+
+    let mut lines: Vec<&str> = body
+        .split('\n')
+        .enumerate()
+        .map(|(i, raw)| {
+            let raw = raw.trim_end();
+            let text = if i == 0 { raw } else { raw.trim_start().strip_prefix('*').unwrap_or(raw.trim_start()) };
+            text.strip_prefix(' ').unwrap_or(text)
+        })
+        .collect();
+    while lines.last().is_some_and(|l| l.trim().is_empty()) {
+        lines.pop();
+    }
+    let first = lines.iter().position(|l| !l.trim().is_empty()).unwrap_or(lines.len());
+    if first == lines.len() { return comment.to_string(); }
+
+    let mut out: Vec<String> = Vec::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let flush = |paragraph: &mut Vec<&str>, out: &mut Vec<String>| {
+        if paragraph.iter().all(|l| fits(&format!(" * {l}"))) {
+            out.extend(paragraph.iter().map(|l| l.to_string()));
+        } else {
+            let lead = |l: &str| l[..l.len() - l.trim_start().len()].to_string();
+            let first_indent = lead(paragraph[0]);
+            let rest_indent = paragraph.get(1).map_or_else(|| first_indent.clone(), |l| lead(l));
+            let mut line = first_indent;
+            let mut empty = true;
+            for word in paragraph.iter().flat_map(|l| l.split_whitespace()) {
+                if !empty && !fits(&format!(" * {line} {word}")) {
+                    out.push(std::mem::replace(&mut line, rest_indent.clone()));
+                    empty = true;
+                }
+                if !empty { line.push(' '); }
+                line.push_str(word);
+                empty = false;
+            }
+            out.push(line);
+        }
+        paragraph.clear();
+    };
+    let mut in_pre = false;
+    for line in &lines[first..] {
+        if in_pre || line.contains("<pre") {
+            flush(&mut paragraph, &mut out);
+            out.push(line.to_string());
+            in_pre = !line.contains("</pre>");
+        } else if line.trim().is_empty() {
+            flush(&mut paragraph, &mut out);
+            out.push(String::new());
+        } else {
+            if !paragraph.is_empty() && starts_block(line) { flush(&mut paragraph, &mut out); }
+            paragraph.push(line);
+        }
+    }
+    flush(&mut paragraph, &mut out);
+
+    let mut text = String::from("/**");
+    for line in out {
+        text.push_str(newline);
+        text.push_str(indent);
+        text.push_str(" *");
+        if !line.is_empty() {
+            text.push(' ');
+            text.push_str(&line);
+        }
+    }
+    text.push_str(newline);
+    text.push_str(indent);
+    text.push_str(" */");
+    text
+}
+
+/// Whether a Javadoc line begins a new paragraph: a block tag or an HTML
+/// block element.
+fn starts_block(line: &str) -> bool {
+    let line = line.trim_start();
+    if line.starts_with('@') { return true; }
+    let Some(tag) = line.strip_prefix("</").or_else(|| line.strip_prefix('<')) else { return false };
+    let name_len = tag.find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(tag.len());
+    HTML_BLOCKS.contains(&tag[..name_len].to_ascii_lowercase().as_str())
+}
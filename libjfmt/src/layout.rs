@@ -0,0 +1,277 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Whether a source root holds production or test code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceKind {
+    Main,
+    Test,
+}
+
+/// A directory that package paths are relative to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceRoot {
+    pub path: PathBuf,
+    pub kind: SourceKind,
+}
+
+/// Source roots of a Maven or Gradle project.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceLayout {
+    /// Detected roots, in path order.
+    pub roots: Vec<SourceRoot>,
+}
+
+impl SourceLayout {
+    /// Detect the source roots under `project_root`.
+    ///
+    /// Every existing `src/<set>/java` directory is a root: `main` holds
+    /// production code, and Gradle-style sets whose name mentions "test"
+    /// (`test`, `integrationTest`, `testFixtures`) hold tests. Directories
+    /// named by Maven's `<sourceDirectory>`/`<testSourceDirectory>` and by
+    /// `srcDir`/`srcDirs` entries in `build.gradle(.kts)` are added too. A
+    /// project with none of these is treated as one main root.
+    pub fn detect(project_root: &Path) -> io::Result<Self> {
+        let mut roots = Vec::new();
+        let src = project_root.join("src");
+        if src.is_dir() {
+            for entry in fs::read_dir(&src)? {
+                let set = entry?.path();
+                let java = set.join("java");
+                if java.is_dir() {
+                    let name = set.file_name().unwrap_or_default().to_string_lossy().to_ascii_lowercase();
+                    roots.push(SourceRoot { path: java, kind: kind_for(&name) });
+                }
+            }
+        }
+        for dir in configured_dirs(project_root) {
+            let path = project_root.join(&dir);
+            if path.is_dir() && !roots.iter().any(|r| r.path == path) {
+                roots.push(SourceRoot { path, kind: kind_for(&dir.to_ascii_lowercase()) });
+            }
+        }
+        if roots.is_empty() {
+            roots.push(SourceRoot { path: project_root.to_path_buf(), kind: SourceKind::Main });
+        }
+        roots.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(SourceLayout { roots })
+    }
+
+    /// The innermost root containing `path`.
+    pub fn root_for(&self, path: &Path) -> Option<&SourceRoot> {
+        self.roots.iter().filter(|r| path.starts_with(&r.path)).max_by_key(|r| r.path.as_os_str().len())
+    }
+
+    /// Whether `path` is under a test root.
+    pub fn is_test(&self, path: &Path) -> bool {
+        self.root_for(path).is_some_and(|r| r.kind == SourceKind::Test)
+    }
+
+    /// Every `.java` file under the roots, sorted. Hidden directories and
+    /// symlinked directories are not entered.
+    pub fn java_files(&self) -> io::Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for root in &self.roots {
+            collect_java_files(&root.path, &mut files)?;
+        }
+        files.sort();
+        files.dedup();
+        Ok(files)
+    }
+}
+
+/// A Maven or Gradle module: a directory with its own build file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Module {
+    pub root: PathBuf,
+    pub layout: SourceLayout,
+    /// Java files under the module's source roots that belong to no nested
+    /// module, sorted.
+    pub files: Vec<PathBuf>,
+}
+
+/// Build files that make a directory a module.
+const BUILD_FILES: &[&str] = &["pom.xml", "build.gradle", "build.gradle.kts"];
+
+/// Build output and tool directories never searched for modules.
+const SKIPPED_DIRS: &[&str] = &["target", "build", "out", "node_modules"];
+
+/// Find every module under `repo_root`, in path order. A repository without
+/// any build file is one module rooted at `repo_root`.
+pub fn discover_modules(repo_root: &Path) -> io::Result<Vec<Module>> {
+    let mut roots = Vec::new();
+    collect_module_roots(repo_root, &mut roots)?;
+    if roots.is_empty() {
+        roots.push(repo_root.to_path_buf());
+    }
+    roots.sort();
+    let mut modules = Vec::new();
+    for root in &roots {
+        let layout = SourceLayout::detect(root)?;
+        let mut files = layout.java_files()?;
+        // A file belongs to the innermost module containing it.
+        files.retain(|f| roots.iter().filter(|r| f.starts_with(r)).max_by_key(|r| r.as_os_str().len()) == Some(root));
+        modules.push(Module { root: root.clone(), layout, files });
+    }
+    Ok(modules)
+}
+
+fn collect_module_roots(dir: &Path, out: &mut Vec<PathBuf>) -> io::Result<()> {
+    if BUILD_FILES.iter().any(|f| dir.join(f).is_file()) {
+        out.push(dir.to_path_buf());
+    }
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if entry.file_type()?.is_dir() && !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str()) {
+            collect_module_roots(&entry.path(), out)?;
+        }
+    }
+    Ok(())
+}
+
+/// Whether `path` lies in a test source set by Maven/Gradle convention: under
+/// `src/<set>/` where the set name mentions "test" (`src/test/java`,
+/// `src/integrationTest/java`, `src/testFixtures/java`).
+pub fn is_test_path(path: &Path) -> bool {
+    let parts: Vec<_> = path.components().map(|c| c.as_os_str().to_string_lossy()).collect();
+    parts.windows(2).any(|w| w[0] == "src" && kind_for(&w[1].to_ascii_lowercase()) == SourceKind::Test)
+}
+
+fn kind_for(name: &str) -> SourceKind {
+    if name.contains("test") { SourceKind::Test } else { SourceKind::Main }
+}
+
+/// Source directories named in `pom.xml` or `build.gradle(.kts)`.
+fn configured_dirs(project_root: &Path) -> Vec<String> {
+    let mut dirs = Vec::new();
+    if let Ok(pom) = fs::read_to_string(project_root.join("pom.xml")) {
+        for tag in ["sourceDirectory", "testSourceDirectory"] {
+            let (open, close) = (format!("<{tag}>"), format!("</{tag}>"));
+            let mut rest = pom.as_str();
+            while let Some(start) = rest.find(&open) {
+                rest = &rest[start + open.len()..];
+                let Some(end) = rest.find(&close) else { break };
+                // `${project.basedir}/src/it/java` is relative to the project anyway.
+                let dir = rest[..end].trim().trim_start_matches("${project.basedir}/").trim_start_matches("${basedir}/");
+                if !dir.contains("${") { dirs.push(dir.to_string()); }
+            }
+        }
+    }
+    for build in ["build.gradle", "build.gradle.kts"] {
+        let Ok(text) = fs::read_to_string(project_root.join(build)) else { continue };
+        for line in text.lines().filter(|l| l.contains("srcDir")) {
+            // Quoted strings after `srcDir`: `srcDir 'x'`, `srcDirs = ["a", "b"]`, `srcDir("x")`.
+            let after = &line[line.find("srcDir").unwrap_or(0)..];
+            let mut parts = after.split(['\'', '"']);
+            parts.next();
+            dirs.extend(parts.step_by(2).filter(|s| !s.is_empty()).map(String::from));
+        }
+    }
+    dirs
+}
+
+/// Every `.java` file under `dir`, sorted, skipping hidden directories and
+/// build output (`target`, `build`, `out`, `node_modules`).
+pub fn java_files_under(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    collect_source_files(dir, &mut files)?;
+    files.sort();
+    Ok(files)
+}
+
+fn collect_source_files(dir: &Path, out: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().into_owned();
+        if entry.file_type()?.is_dir() {
+            if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_str()) {
+                collect_source_files(&path, out)?;
+            }
+        } else if path.extension().is_some_and(|e| e == "java") && path.is_file() {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Files matching the glob `pattern`, sorted, for shells that don't expand
+/// globs themselves. Hidden directories are skipped. Backslashes are path
+/// separators on Windows.
+pub fn expand_glob(pattern: &str) -> io::Result<Vec<PathBuf>> {
+    let pattern = if cfg!(windows) { pattern.replace('\\', "/") } else { pattern.to_string() };
+    let segments: Vec<&str> = pattern.split('/').collect();
+    let literal = segments.iter().take_while(|s| !s.contains(['*', '?'])).count();
+    if literal == segments.len() {
+        let path = PathBuf::from(&pattern);
+        return Ok(if path.is_file() { vec![path] } else { Vec::new() });
+    }
+    let base = segments[..literal].join("/");
+    // Without `**`, matches are exactly as deep as the pattern.
+    let depth = if pattern.contains("**") { usize::MAX } else { segments.len() - literal };
+    let mut files = Vec::new();
+    let dir = if literal == 0 { Path::new(".") } else if base.is_empty() { Path::new("/") } else { Path::new(&base) };
+    if dir.is_dir() {
+        let prefix = if literal == 0 { String::new() } else { format!("{base}/") };
+        collect_glob_matches(dir, &prefix, &pattern, depth, &mut files)?;
+    }
+    files.sort();
+    Ok(files)
+}
+
+fn collect_glob_matches(dir: &Path, prefix: &str, pattern: &str, depth: usize, out: &mut Vec<PathBuf>) -> io::Result<()> {
+    if depth == 0 { return Ok(()); }
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let path = format!("{prefix}{name}");
+        if entry.file_type()?.is_dir() {
+            if !name.starts_with('.') {
+                collect_glob_matches(&entry.path(), &format!("{path}/"), pattern, depth - 1, out)?;
+            }
+        } else if glob_matches_path(pattern, &path) {
+            out.push(PathBuf::from(path));
+        }
+    }
+    Ok(())
+}
+
+/// Whether `path` matches the glob `pattern`: `?` matches one character and
+/// `*` any run within one path segment, `**` matches across segments, and a
+/// leading or inner `**/` also matches no directory at all.
+pub(crate) fn glob_matches_path(pattern: &str, path: &str) -> bool {
+    if let Some(rest) = pattern.strip_prefix("**/") {
+        return glob_matches_path(rest, path)
+            || path.find('/').is_some_and(|i| glob_matches_path(pattern, &path[i + 1..]));
+    }
+    if let Some(rest) = pattern.strip_prefix("**") {
+        return (0..=path.len()).filter(|&i| path.is_char_boundary(i)).any(|i| glob_matches_path(rest, &path[i..]));
+    }
+    if let Some(rest) = pattern.strip_prefix('*') {
+        let segment = path.find('/').unwrap_or(path.len());
+        return (0..=segment).filter(|&i| path.is_char_boundary(i)).any(|i| glob_matches_path(rest, &path[i..]));
+    }
+    match (pattern.chars().next(), path.chars().next()) {
+        (None, None) => true,
+        (Some(p), Some(c)) if p == c || (p == '?' && c != '/') => {
+            glob_matches_path(&pattern[p.len_utf8()..], &path[c.len_utf8()..])
+        }
+        _ => false,
+    }
+}
+
+fn collect_java_files(dir: &Path, out: &mut Vec<PathBuf>) -> io::Result<()> {
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let path = entry.path();
+        let hidden = entry.file_name().to_string_lossy().starts_with('.');
+        if entry.file_type()?.is_dir() {
+            if !hidden { collect_java_files(&path, out)?; }
+        } else if path.extension().is_some_and(|e| e == "java") && path.is_file() {
+            out.push(path);
+        }
+    }
+    Ok(())
+}
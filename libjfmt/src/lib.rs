@@ -1,9 +1,67 @@
-use std::fs;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Instant;
 
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
-use tree_sitter::{Language, Node, Parser};
+use tree_sitter::{InputEdit, Language, Parser, Tree};
+use unicode_width::UnicodeWidthChar;
+
+mod batch;
+mod cache;
+mod cascade;
+mod checkstyle;
+mod compare;
+mod deprecation;
+mod diff;
+mod document;
+mod dup;
+mod format;
+mod imports;
+mod javadoc;
+mod layout;
+mod line_index;
+mod metrics;
+mod naming;
+mod packages;
+mod project;
+mod rules;
+mod sarif;
+mod sonar;
+mod source;
+mod spellcheck;
+mod stats;
+mod summary;
+mod suppress;
+mod unused;
+mod wrap;
+
+pub use batch::{lint_files, lint_text, FileError, FileOutcome, FileReport, LintOptions, SkipReason};
+pub use cache::{CacheEntry, CacheKey, CacheStats, CacheStore, DiskCache, DEFAULT_CACHE_DIR};
+pub use cascade::ConfigResolver;
+pub use checkstyle::checkstyle_report;
+pub use compare::{compare_reports, ReportDiff, ReportedIssue};
+pub use deprecation::{config_warnings, migrate_config, DeprecatedKey, DeprecatedRule, DEPRECATED_KEYS, DEPRECATED_RULES};
+pub use diff::unified_diff;
+pub use document::{Document, TextEdit};
+pub use dup::{find_duplicates, find_duplicates_in_groups, CloneLocation, ClonePair, DupOptions, DupReport};
+pub use format::format_java_source;
+pub use javadoc::{javadoc_coverage, PackageCoverage};
+pub use layout::{discover_modules, expand_glob, is_test_path, java_files_under, Module, SourceKind, SourceLayout, SourceRoot};
+pub use line_index::{ColumnUnit, LineIndex};
+pub use metrics::{collect_metrics, file_metrics, FileMetrics, MethodMetrics, MetricsReport};
+pub use packages::{package_cycles, ImportSite, PackageCycle, PackageCycleRule, PackagePathRule};
+pub use project::{Declaration, Import, Project, ProjectFile, ProjectIssue, ProjectRule, SymbolIndex, Visibility};
+pub use rules::{all_rules, registry, Rule, RuleContext, RuleKind, RuleSet};
+pub use sarif::sarif_report;
+pub use sonar::sonar_report;
+pub use source::SourceText;
+pub use stats::LintStats;
+pub use summary::{IssueSummary, RuleCount};
+pub use unused::{unused_declarations, UnusedDeclaration, UnusedDeclarationRule};
 
 #[derive(Debug, Error)]
 pub enum LintError {
@@ -11,6 +69,16 @@ pub enum LintError {
     Language,
     #[error("failed to parse source")]
     Parse,
+    #[error("failed to start worker threads: {0}")]
+    ThreadPool(String),
+    #[error("invalid fix: {0}")]
+    Fix(#[from] FixError),
+    #[error("parsing exceeded {limit_ms}ms")]
+    ParseTimeout { limit_ms: u64 },
+    #[error("edit range {start}..{end} is outside the document or splits a character")]
+    InvalidEdit { start: usize, end: usize },
+    #[error("fixes from {} would introduce syntax errors; file left unchanged", .rules.join(", "))]
+    FixBrokeSyntax { rules: Vec<&'static str> },
 }
 
 #[derive(Debug, Error)]
@@ -19,17 +87,142 @@ pub enum ConfigError {
     Io(#[from] std::io::Error),
     #[error("invalid toml: {0}")]
     Toml(#[from] toml::de::Error),
+    #[error("invalid toml: {0}")]
+    Edit(String),
+    #[error("{}: {message}", path.display())]
+    File { path: PathBuf, message: String },
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum IndentStyle {
     Tabs,
+    #[default]
     Spaces,
 }
 
-impl Default for IndentStyle {
-    fn default() -> Self { IndentStyle::Spaces }
+/// How much an issue matters. By default only errors fail a run.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    #[default]
+    Error,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// Line break `line-ending` asks for and fixes insert.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+    /// CRLF on Windows, LF elsewhere.
+    Native,
+    /// Whichever the file uses most; ties go to its first line break.
+    #[default]
+    Auto,
+}
+
+impl LineEnding {
+    /// The line break to use in `source`.
+    pub fn resolve(self, source: &str) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+            LineEnding::Native if cfg!(windows) => "\r\n",
+            LineEnding::Native => "\n",
+            LineEnding::Auto => {
+                let total = source.matches('\n').count();
+                let crlf = source.matches("\r\n").count();
+                let first_crlf = source.find('\n').is_some_and(|i| source[..i].ends_with('\r'));
+                if crlf * 2 > total || (crlf * 2 == total && first_crlf) { "\r\n" } else { "\n" }
+            }
+        }
+    }
+}
+
+/// Where the `brace-style` rule wants opening braces.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum BraceStyle {
+    /// `if (x) {`: on the line that opens the body (K&R).
+    Kr,
+    /// On a line of its own, indented like the declaration or statement.
+    Allman,
+}
+
+/// Which form of `switch` the `switch-style` rule asks for.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SwitchStyle {
+    /// `case X -> ...` (Java 14+).
+    Arrow,
+    /// `case X: ...`.
+    Colon,
+}
+
+/// What `max-method-length` counts.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum MethodLengthUnit {
+    /// Lines from the first annotation or modifier to the closing brace.
+    #[default]
+    Lines,
+    /// Statements in the body, nested ones included.
+    Statements,
+}
+
+/// How `max-line-length` measures a line.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LineLengthUnit {
+    /// Unicode scalar values.
+    #[default]
+    Chars,
+    /// Display width: east-asian wide characters count 2, combining marks 0.
+    Width,
+    /// UTF-8 bytes.
+    Bytes,
+}
+
+impl LineLengthUnit {
+    /// Length of `line`; tabs advance to the next multiple of `tab_width`
+    /// except when counting bytes.
+    pub fn measure(self, line: &str, tab_width: usize) -> usize {
+        match self {
+            LineLengthUnit::Bytes => line.len(),
+            _ => line.chars().fold(0, |col, c| self.advance(col, c, tab_width)),
+        }
+    }
+
+    /// Position after `c` when it starts at `col`.
+    pub fn advance(self, col: usize, c: char, tab_width: usize) -> usize {
+        match self {
+            LineLengthUnit::Bytes => col + c.len_utf8(),
+            _ if c == '\t' => line_index::advance_column(col, c, tab_width),
+            LineLengthUnit::Chars => col + 1,
+            LineLengthUnit::Width => col + UnicodeWidthChar::width(c).unwrap_or(0),
+        }
+    }
+
+    fn noun(self) -> &'static str {
+        match self {
+            LineLengthUnit::Chars => "characters",
+            LineLengthUnit::Width => "columns",
+            LineLengthUnit::Bytes => "bytes",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -40,10 +233,254 @@ pub struct Config {
     pub indent_width: u16,         // used when spaces
     #[serde(default = "default_max_line_length")]
     pub max_line_length: u16,      // line length budget
+    #[serde(default)]
+    pub line_length_unit: LineLengthUnit, // chars, width or bytes
+    #[serde(default)]
+    pub max_line_length_ignore_imports: bool, // skip package/import lines
+    #[serde(default)]
+    pub max_line_length_ignore_strings: bool, // skip lines overflowing inside a string literal or URL
+    #[serde(default)]
+    pub max_line_length_ignore_links: bool,   // skip lines overflowing inside a Javadoc {@link ...}
+    #[serde(default)]
+    pub trailing_whitespace_ignore_blocks: bool, // `no-trailing-whitespace` skips block comments and text blocks
+    #[serde(default)]
+    pub eof_newline_exactly_one: bool, // `eof-newline` also flags blank lines at the end of the file
+    #[serde(default = "default_max_blank_lines")]
+    pub max_blank_lines: u16, // most blank lines allowed in a row
+    #[serde(default = "default_blank_lines_between_methods")]
+    pub blank_lines_between_methods: u16, // blank lines required around methods and constructors; 0 disables
+    #[serde(default)]
+    pub allow_static_wildcard_imports: bool, // `import static a.B.*` passes no-wildcard-imports
+    #[serde(default = "default_true")]
+    pub empty_catch_allow_comments: bool, // `empty-catch` accepts a body holding only comments
+    #[serde(default = "default_true")]
+    pub empty_catch_allow_ignored: bool, // `empty-catch` accepts catches whose variable is named `ignored`
+    #[serde(default = "default_same_line_annotations")]
+    pub same_line_annotations: Vec<String>, // annotations `annotation-placement` lets share the declaration's line
+    #[serde(default)]
+    pub allow_default_locale: bool, // `locale-sensitive` accepts calls relying on the default locale
+    #[serde(default)]
+    pub allow_console_output: bool, // `no-console-output` accepts printing to the console
+    #[serde(default)]
+    pub console_output_allow_main: bool, // `no-console-output` accepts printing from `main`
+    #[serde(default)]
+    pub license_header: Option<String>, // required file header; `${year}` matches any year
+    #[serde(default)]
+    pub spellcheck: bool,          // opt-in typo check for declared names and comments
+    #[serde(default)]
+    pub spellcheck_dictionary: Option<PathBuf>, // project words and extra typos, relative to the working directory
+    #[serde(default = "default_unused_exempt_annotations")]
+    pub unused_exempt_annotations: Vec<String>, // declarations called reflectively, skipped by `find_unused`
+    #[serde(default = "default_continuation_indent")]
+    pub continuation_indent: u16,  // extra indent for wrapped lines; 0 disables the check
+    #[serde(default = "default_true")]
+    pub indent_case_labels: bool,  // `case` labels one level inside their `switch`
+    #[serde(default = "default_tab_width")]
+    pub tab_width: u16,            // columns per tab stop when measuring and reporting
+    #[serde(default)]
+    pub line_ending: LineEnding,   // lf, crlf, native or auto (the file's majority)
+    #[serde(default)]
+    pub column_unit: ColumnUnit,   // unit of reported columns
+    #[serde(default = "default_mmap_threshold")]
+    pub mmap_threshold: u64,       // files at least this many bytes are memory-mapped
+    #[serde(default = "default_max_file_size")]
+    pub max_file_size: u64,        // larger files are skipped; 0 disables the limit
+    #[serde(default = "default_max_parse_time_ms")]
+    pub max_parse_time_ms: u64,    // give up parsing after this long; 0 disables the limit
+    #[serde(default = "default_max_method_length")]
+    pub max_method_length: u16, // longest method or constructor `max-method-length` allows; 0 disables
+    #[serde(default)]
+    pub method_length_unit: MethodLengthUnit, // whether `max_method_length` counts lines or statements
+    #[serde(default = "default_max_parameters")]
+    pub max_parameters: u16, // most parameters `max-parameters` allows; 0 disables
+    #[serde(default = "default_max_complexity")]
+    pub max_complexity: u16, // highest cyclomatic complexity `max-complexity` allows; 0 disables
+    #[serde(default = "default_max_anonymous_class_lines")]
+    pub max_anonymous_class_lines: u16, // longer anonymous classes should be named; 0 disables the length check
+    #[serde(default)]
+    pub hardcoded_path_allow: Vec<String>, // string literals `hardcoded-path` accepts; `*` matches anything
+    #[serde(default = "default_security_sensitive_paths")]
+    pub security_sensitive_paths: Vec<String>, // globs over `package/Type.java` where `insecure-random` applies
+    #[serde(default = "default_java_version")]
+    pub java_version: u16,         // language level of the sources; rules suggesting newer syntax need it
+    #[serde(default)]
+    pub switch_style: Option<SwitchStyle>, // preferred switch form; unset disables `switch-style`
+    #[serde(default)]
+    pub brace_style: Option<BraceStyle>, // opening brace placement; unset disables `brace-style`
+    #[serde(default)]
+    pub spacing: Spacing,          // checks made by the `spacing` rule
+    #[serde(default)]
+    pub imports: Imports,          // import order `organize-imports` enforces
+    #[serde(default)]
+    pub naming: Naming,            // name patterns for the naming rules
+    #[serde(default, rename = "magic-numbers")]
+    pub magic_numbers: MagicNumbers, // what `magic-number` accepts
+    #[serde(default = "default_true")]
+    pub ci_warnings_as_errors: bool, // with `--ci`, config warnings and skipped files fail the run
+    #[serde(default, rename = "test-rules")]
+    pub test_rules: TestRules,     // relaxations for test sources
+    #[serde(default)]
+    pub rules: BTreeMap<String, bool>, // per-rule switches; `false` turns a rule off everywhere
+    #[serde(default)]
+    pub severity: BTreeMap<String, Severity>, // per-rule severity; unlisted rules are errors
+    #[serde(default)]
+    pub root: bool,                // stop looking for jfmt.toml files further up; see `ConfigResolver`
+    #[serde(default)]
+    pub extends: Option<String>,   // base config file or preset merged underneath; see `ConfigResolver`
+    #[serde(default)]
+    pub overrides: Vec<toml::Table>, // `[[overrides]]`: settings for files matching `files` globs; see `ConfigResolver`
+}
+
+/// The `[spacing]` table: which conventions the `spacing` rule enforces.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct Spacing {
+    /// One space between `if`/`for`/`while`/`switch`/`catch`/`synchronized`/`try` and `(`.
+    pub after_keyword: bool,
+    /// One space between `}` and a following `else`/`catch`/`finally`.
+    pub before_continuation_keyword: bool,
+    /// One space before a `{` opening a body or block.
+    pub before_brace: bool,
+    /// No space before `;`.
+    pub before_semicolon: bool,
+    /// No space before `,`.
+    pub before_comma: bool,
+    /// One space after `,`.
+    pub after_comma: bool,
+}
+
+impl Default for Spacing {
+    fn default() -> Self {
+        Self {
+            after_keyword: true,
+            before_continuation_keyword: true,
+            before_brace: true,
+            before_semicolon: true,
+            before_comma: true,
+            after_comma: true,
+        }
+    }
+}
+
+/// The `[imports]` table: the import order `organize-imports` enforces.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct Imports {
+    /// Groups in order, each a list of package prefixes. `*` takes imports
+    /// no prefix matches and `static` every static import.
+    pub groups: Vec<Vec<String>>,
+    /// One blank line between groups.
+    pub blank_line_between_groups: bool,
+}
+
+impl Default for Imports {
+    fn default() -> Self {
+        Self {
+            groups: vec![vec!["java".into(), "javax".into()], vec!["*".into()], vec!["static".into()]],
+            blank_line_between_groups: true,
+        }
+    }
+}
+
+/// The `[naming]` table: regexes the naming rules match declared names
+/// against. A name must match the whole pattern.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct Naming {
+    /// Classes, interfaces, enums, records and annotation types.
+    pub class: String,
+    /// Methods, except those marked `@Override`.
+    pub method: String,
+    /// `static final` fields and interface fields.
+    pub constant: String,
+    /// The package declaration, dots included.
+    pub package: String,
+}
+
+impl Default for Naming {
+    fn default() -> Self {
+        Self {
+            class: "[A-Z][a-zA-Z0-9]*".into(),
+            method: "[a-z][a-zA-Z0-9]*".into(),
+            constant: "[A-Z][A-Z0-9]*(_[A-Z0-9]+)*".into(),
+            package: "[a-z][a-z0-9_]*(\\.[a-z][a-z0-9_]*)*".into(),
+        }
+    }
+}
+
+/// The `[magic-numbers]` table: numbers and places the `magic-number` rule
+/// accepts.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct MagicNumbers {
+    /// Values allowed anywhere, compared numerically (`1`, `1L` and `0x1`
+    /// are the same); a negative value covers the negated literal.
+    pub allow: Vec<f64>,
+    /// Accept literals in annotations, such as `@Size(max = 64)`.
+    pub ignore_annotations: bool,
+    /// Accept array dimensions, as in `new byte[4096]`.
+    pub ignore_array_sizes: bool,
+}
+
+impl Default for MagicNumbers {
+    fn default() -> Self { Self { allow: vec![0.0, 1.0, -1.0], ignore_annotations: true, ignore_array_sizes: false } }
+}
+
+/// The `[test-rules]` profile, applied to files under test source roots
+/// (`src/test/...`, `src/integrationTest/...`; see `is_test_path`).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TestRules {
+    /// Rule ids not run on test sources.
+    #[serde(default)]
+    pub disable: Vec<String>,
+    /// Allow `import static org.junit.Assert.*` and the like in tests.
+    #[serde(default = "default_true")]
+    pub allow_static_wildcard_imports: bool,
+    /// Allow `toUpperCase()` and friends without a `Locale` in tests.
+    #[serde(default = "default_true")]
+    pub allow_default_locale: bool,
+    /// Allow `System.out.println` and `printStackTrace()` in tests.
+    #[serde(default = "default_true")]
+    pub allow_console_output: bool,
+}
+
+impl Default for TestRules {
+    fn default() -> Self {
+        Self { disable: Vec::new(), allow_static_wildcard_imports: true, allow_default_locale: true, allow_console_output: true }
+    }
 }
 
 fn default_indent_width() -> u16 { 4 }
 fn default_max_line_length() -> u16 { 100 }
+fn default_unused_exempt_annotations() -> Vec<String> {
+    [
+        "Override", "Test", "ParameterizedTest", "RepeatedTest", "TestFactory", "BeforeEach", "AfterEach",
+        "BeforeAll", "AfterAll", "Before", "After", "BeforeClass", "AfterClass", "Bean", "Inject", "Autowired",
+        "PostConstruct", "PreDestroy", "EventListener", "Scheduled", "RequestMapping", "GetMapping", "PostMapping",
+        "PutMapping", "DeleteMapping", "PatchMapping", "Component", "Service", "Repository", "Controller",
+        "RestController", "Configuration", "SpringBootApplication", "Entity", "JsonCreator",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+fn default_same_line_annotations() -> Vec<String> { vec!["Override".to_string()] }
+fn default_continuation_indent() -> u16 { 8 }
+fn default_max_blank_lines() -> u16 { 1 }
+fn default_blank_lines_between_methods() -> u16 { 1 }
+fn default_tab_width() -> u16 { 4 }
+fn default_mmap_threshold() -> u64 { 1024 * 1024 }
+fn default_max_file_size() -> u64 { 10 * 1024 * 1024 }
+fn default_max_parse_time_ms() -> u64 { 10_000 }
+fn default_true() -> bool { true }
+fn default_java_version() -> u16 { 17 }
+fn default_max_method_length() -> u16 { 100 }
+fn default_max_parameters() -> u16 { 7 }
+fn default_max_complexity() -> u16 { 10 }
+fn default_max_anonymous_class_lines() -> u16 { 20 }
+fn default_security_sensitive_paths() -> Vec<String> {
+    ["**/auth/**", "**/security/**", "**/crypto/**"].map(String::from).to_vec()
+}
 
 impl Default for Config {
     fn default() -> Self {
@@ -51,20 +488,87 @@ impl Default for Config {
             indent_style: IndentStyle::Spaces,
             indent_width: 4,
             max_line_length: 100,
+            line_length_unit: LineLengthUnit::Chars,
+            max_line_length_ignore_imports: false,
+            max_line_length_ignore_strings: false,
+            max_line_length_ignore_links: false,
+            trailing_whitespace_ignore_blocks: false,
+            eof_newline_exactly_one: false,
+            max_blank_lines: default_max_blank_lines(),
+            blank_lines_between_methods: default_blank_lines_between_methods(),
+            allow_static_wildcard_imports: false,
+            empty_catch_allow_comments: true,
+            empty_catch_allow_ignored: true,
+            same_line_annotations: default_same_line_annotations(),
+            allow_default_locale: false,
+            allow_console_output: false,
+            console_output_allow_main: false,
+            license_header: None,
+            spellcheck: false,
+            spellcheck_dictionary: None,
+            unused_exempt_annotations: default_unused_exempt_annotations(),
+            continuation_indent: default_continuation_indent(),
+            indent_case_labels: true,
+            tab_width: default_tab_width(),
+            line_ending: LineEnding::Auto,
+            column_unit: ColumnUnit::Display,
+            mmap_threshold: default_mmap_threshold(),
+            max_file_size: default_max_file_size(),
+            max_parse_time_ms: default_max_parse_time_ms(),
+            max_method_length: default_max_method_length(),
+            method_length_unit: MethodLengthUnit::Lines,
+            max_parameters: default_max_parameters(),
+            max_complexity: default_max_complexity(),
+            max_anonymous_class_lines: default_max_anonymous_class_lines(),
+            hardcoded_path_allow: Vec::new(),
+            security_sensitive_paths: default_security_sensitive_paths(),
+            java_version: default_java_version(),
+            switch_style: None,
+            brace_style: None,
+            spacing: Spacing::default(),
+            imports: Imports::default(),
+            naming: Naming::default(),
+            magic_numbers: MagicNumbers::default(),
+            ci_warnings_as_errors: true,
+            test_rules: TestRules::default(),
+            rules: BTreeMap::new(),
+            severity: BTreeMap::new(),
+            root: false,
+            extends: None,
+            overrides: Vec::new(),
         }
     }
 }
 
-/// Find and load configuration by walking up from `start_dir` to root.
-pub fn load_config_from(start_dir: impl AsRef<Path>) -> Result<Config, ConfigError> {
-    let start = start_dir.as_ref();
-    if let Some(p) = find_config_path(start) {
-        let text = fs::read_to_string(&p)?;
-        let cfg: Config = toml::from_str(&text)?;
-        Ok(cfg)
-    } else {
-        Ok(Config::default())
+impl Config {
+    /// The config test sources are linted with: this one relaxed by `[test-rules]`.
+    pub fn for_tests(&self) -> Config {
+        let mut config = self.clone();
+        config.allow_static_wildcard_imports |= self.test_rules.allow_static_wildcard_imports;
+        config.allow_default_locale |= self.test_rules.allow_default_locale;
+        config.allow_console_output |= self.test_rules.allow_console_output;
+        config
+    }
+
+    /// Rule ids switched off in `[rules]`, as written (deprecated ids included).
+    pub fn disabled_rules(&self) -> impl Iterator<Item = &str> {
+        self.rules.iter().filter(|(_, on)| !**on).map(|(id, _)| id.as_str())
     }
+
+    /// Severity of `rule`'s issues from `[severity]`; deprecated ids there
+    /// apply to their replacement.
+    pub fn severity_of(&self, rule: &str) -> Severity {
+        match self.severity.get(rule) {
+            Some(severity) => *severity,
+            None => self.severity.iter().find(|(id, _)| rules::resolve_rule_id(id) == Some(rule)).map_or(Severity::Error, |(_, s)| *s),
+        }
+    }
+}
+
+/// Load the configuration that applies in `start_dir`: its `jfmt.toml` and
+/// those above it, merged as `ConfigResolver` does.
+pub fn load_config_from(start_dir: impl AsRef<Path>) -> Result<Config, ConfigError> {
+    ConfigResolver::new().config_in(start_dir.as_ref())
 }
 
 /// Load configuration starting at current directory.
@@ -73,7 +577,8 @@ pub fn load_config() -> Result<Config, ConfigError> {
     load_config_from(cwd)
 }
 
-fn find_config_path(start_dir: &Path) -> Option<PathBuf> {
+/// The `jfmt.toml` that applies in `start_dir`, if any.
+pub fn find_config_path(start_dir: &Path) -> Option<PathBuf> {
     let mut dir = Some(start_dir);
     while let Some(d) = dir {
         let candidate = d.join("jfmt.toml");
@@ -83,233 +588,476 @@ fn find_config_path(start_dir: &Path) -> Option<PathBuf> {
     None
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct LintIssue {
     pub rule_id: &'static str,
-    pub message: String,
+    pub message: Cow<'static, str>, // static for fixed messages; owned only when formatted
     pub line: usize,   // 1-based
-    pub column: usize, // 1-based
+    pub column: usize, // 1-based, in `Config::column_unit`
+    pub offset: usize, // byte offset of the issue start
+    pub end_line: usize,   // 1-based; where the flagged code ends
+    pub end_column: usize, // 1-based, exclusive
+    pub end_offset: usize, // byte offset of the issue end; `offset` for point issues
+    pub severity: Severity, // from `Config::severity`, filled in by the lint driver
     pub fix: Option<Fix>,
 }
 
-#[derive(Debug, Clone)]
+impl LintIssue {
+    /// An issue at `offset`; line and column are filled in by the lint driver.
+    pub(crate) fn new(rule_id: &'static str, message: impl Into<Cow<'static, str>>, offset: usize) -> Self {
+        LintIssue { rule_id, message: message.into(), line: 0, column: 0, offset, end_line: 0, end_column: 0, end_offset: offset, severity: Severity::Error, fix: None }
+    }
+
+    /// Issues within a file are always reported in this order: line, column,
+    /// then rule id, independent of rule traversal order or thread count.
+    pub fn sort_key(&self) -> (usize, usize, &'static str, usize) {
+        (self.line, self.column, self.rule_id, self.offset)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Fix {
     pub start_byte: usize,
     pub end_byte: usize,
-    pub replacement: String,
+    pub replacement: Cow<'static, str>,
+    #[serde(default)]
+    pub applicability: Applicability,
 }
 
-fn java_language() -> Result<Language, LintError> {
-    // SAFETY: Provided by tree-sitter-java crate
-    let lang = tree_sitter_java::language();
-    if lang.node_kind_count() == 0 {
-        return Err(LintError::Language);
-    }
-    Ok(lang)
+/// How confident a rule is that its fix preserves intent.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum Applicability {
+    /// Purely mechanical; applied by `--fix`.
+    #[default]
+    Safe,
+    /// Usually right but worth a look (e.g. a guessed spelling, a rewritten
+    /// header); applied only with `--fix --unsafe`.
+    MaybeIncorrect,
 }
 
-pub fn lint_java_source(source: &str, config: &Config) -> Result<Vec<LintIssue>, LintError> {
+/// The Java grammar, loaded once per process.
+pub(crate) fn java_language() -> Result<Language, LintError> {
+    static LANGUAGE: OnceLock<Option<Language>> = OnceLock::new();
+    LANGUAGE
+        .get_or_init(|| {
+            // SAFETY: Provided by tree-sitter-java crate
+            let lang = tree_sitter_java::language();
+            (lang.node_kind_count() > 0).then_some(lang)
+        })
+        .clone()
+        .ok_or(LintError::Language)
+}
+
+thread_local! {
+    /// One idle parser per thread, handed back by `Linter` on drop so repeated
+    /// `lint_java_source` calls and short-lived worker linters skip parser setup.
+    static SPARE_PARSER: RefCell<Option<Parser>> = const { RefCell::new(None) };
+}
+
+pub(crate) fn checkout_parser() -> Result<Parser, LintError> {
+    if let Some(parser) = SPARE_PARSER.with(|p| p.borrow_mut().take()) {
+        return Ok(parser);
+    }
     let mut parser = Parser::new();
     parser.set_language(&java_language()?).map_err(|_| LintError::Language)?;
+    Ok(parser)
+}
 
-    let tree = parser.parse(source, None).ok_or(LintError::Parse)?;
-    let root = tree.root_node();
+pub(crate) fn return_parser(mut parser: Parser) {
+    parser.reset();
+    // During thread teardown the slot may already be gone; then the parser is just dropped.
+    let _ = SPARE_PARSER.try_with(|p| {
+        p.borrow_mut().get_or_insert(parser);
+    });
+}
 
-    let mut issues = Vec::new();
-    // Rule: no wildcard imports (import x.y.*;)
-    collect_no_wildcard_imports(source, root, &mut issues);
-    // Rule: no stray empty statements (;)
-    collect_no_empty_statements(root, &mut issues);
-    // Config-driven rules
-    collect_line_length(source, config.max_line_length, &mut issues);
-    collect_indent_style(source, config.indent_style, config.indent_width, &mut issues);
-
-    Ok(issues)
-}
-
-fn issue_at(node: Node, rule_id: &'static str, message: impl Into<String>) -> LintIssue {
-    let start = node.start_position();
-    LintIssue {
-        rule_id,
-        message: message.into(),
-        line: start.row + 1,
-        column: start.column + 1,
-        fix: None,
-    }
-}
-
-fn collect_no_wildcard_imports(source: &str, root: Node, out: &mut Vec<LintIssue>) {
-    let mut cursor = root.walk();
-    let mut stack = vec![root];
-    while let Some(node) = stack.pop() {
-        if node.kind() == "import_declaration" {
-            // Heuristic: check the import text for ".*;"
-            if let Ok(text) = node.utf8_text(source.as_bytes()) {
-                if text.contains(".*") {
-                    out.push(issue_at(
-                        node,
-                        "no-wildcard-imports",
-                        "Avoid wildcard imports (use explicit classes)",
-                    ));
-                }
-            }
+/// Long-lived linter for hot paths such as format-on-save.
+///
+/// Holds a ready parser and an already-loaded `Config`, so each call only
+/// pays for parsing and running rules: no parser setup, no config discovery.
+pub struct Linter {
+    parser: Option<Parser>, // always Some until drop returns it to the pool
+    config: Config,
+    rules: RuleSet,
+    stats: Option<LintStats>, // collected only when timing is enabled
+    unsafe_fixes: bool,       // also apply `MaybeIncorrect` fixes
+    file_name: Option<String>, // name of the file being linted, when it came from one
+}
+
+impl Linter {
+    pub fn new(config: Config) -> Result<Self, LintError> {
+        let rules = RuleSet::all().without(config.disabled_rules());
+        Ok(Self { parser: Some(checkout_parser()?), config, rules, stats: None, unsafe_fixes: false, file_name: None })
+    }
+
+    /// Record per-phase and per-rule timings; read them with `take_stats`.
+    pub fn with_timing(mut self) -> Self {
+        self.stats = Some(LintStats::default());
+        self
+    }
+
+    /// Timings gathered since the last call, or `None` when timing is off.
+    pub fn take_stats(&mut self) -> Option<LintStats> {
+        self.stats.as_mut().map(std::mem::take)
+    }
+
+    /// Restrict linting to `rules`, less any the config's `[rules]` table
+    /// turns off. When none of them needs a syntax tree, files are never
+    /// parsed.
+    pub fn with_rules(mut self, rules: RuleSet) -> Self {
+        self.rules = rules.without(self.config.disabled_rules());
+        self
+    }
+
+    /// Let `fix` apply `MaybeIncorrect` fixes as well as safe ones.
+    pub fn with_unsafe_fixes(mut self, unsafe_fixes: bool) -> Self {
+        self.unsafe_fixes = unsafe_fixes;
+        self
+    }
+
+    /// The fix `fix` would apply for `issue`, if its applicability is allowed.
+    pub fn applied_fix<'a>(&self, issue: &'a LintIssue) -> Option<&'a Fix> {
+        issue.fix.as_ref().filter(|f| self.unsafe_fixes || f.applicability == Applicability::Safe)
+    }
+
+    /// Name the file the next sources come from, for rules that compare the
+    /// code with it (`class-file-name`). Without one they check nothing.
+    pub fn set_file_name(&mut self, path: Option<&Path>) {
+        self.file_name = path.and_then(Path::file_name).map(|n| n.to_string_lossy().into_owned());
+    }
+
+    pub fn file_name(&self) -> Option<&str> { self.file_name.as_deref() }
+
+    pub fn config(&self) -> &Config { &self.config }
+
+    pub fn rules(&self) -> &RuleSet { &self.rules }
+
+    /// Parse `source` if the enabled rules need a tree at all.
+    fn parse(&mut self, source: &str, old_tree: Option<&Tree>) -> Result<Option<Tree>, LintError> {
+        if !self.rules.needs_tree() {
+            return Ok(None);
         }
+        let started = Instant::now();
+        let parser = self.parser.as_mut().expect("parser present until drop");
+        let limit_ms = self.config.max_parse_time_ms;
+        parser.set_timeout_micros(limit_ms.saturating_mul(1000));
+        let Some(tree) = parser.parse(source, old_tree) else {
+            // A timed-out parser would resume the abandoned parse next time.
+            parser.reset();
+            return Err(if limit_ms > 0 { LintError::ParseTimeout { limit_ms } } else { LintError::Parse });
+        };
+        if let Some(stats) = self.stats.as_mut() {
+            stats.parse += started.elapsed();
+        }
+        Ok(Some(tree))
+    }
 
-        if node.child_count() > 0 {
-            cursor.reset(node);
-            for i in (0..node.child_count()).rev() {
-                if let Some(child) = node.child(i) {
-                    stack.push(child);
-                }
+    fn run_rules(&mut self, source: &str, tree: Option<&Tree>) -> Vec<LintIssue> {
+        run_rules(source, tree, &self.config, &self.rules, self.file_name.as_deref(), self.stats.as_mut())
+    }
+
+    pub fn lint(&mut self, source: &str) -> Result<Vec<LintIssue>, LintError> {
+        Ok(self.lint_counting_errors(source)?.0)
+    }
+
+    /// `lint`, also returning the number of syntax errors in `source` (`None`
+    /// when the enabled rules don't parse it) for the cache to keep.
+    pub(crate) fn lint_counting_errors(&mut self, source: &str) -> Result<(Vec<LintIssue>, Option<usize>), LintError> {
+        let tree = self.parse(source, None)?;
+        Ok((self.run_rules(source, tree.as_ref()), tree.as_ref().map(syntax_error_count)))
+    }
+
+    /// Lint an open document, re-parsing incrementally from its previous tree
+    /// when there is one and falling back to a full parse otherwise.
+    pub fn lint_document(&mut self, doc: &mut Document) -> Result<Vec<LintIssue>, LintError> {
+        let tree = self.parse(&doc.source, doc.tree.as_ref())?;
+        let issues = self.run_rules(&doc.source, tree.as_ref());
+        doc.tree = tree;
+        Ok(issues)
+    }
+
+    /// Lint an in-memory buffer and return it with safe autofixes applied.
+    ///
+    /// The issues left after fixing are computed from the fixed text by
+    /// editing the original tree and re-parsing incrementally, instead of a
+    /// second from-scratch lint.
+    ///
+    /// When the rules parse the file, the fixed text is checked to have no
+    /// more syntax errors than the original; otherwise `FixBrokeSyntax` names
+    /// the rules responsible and nothing should be written.
+    pub fn fix(&mut self, source: &str) -> Result<FixResult, LintError> {
+        let tree = self.parse(source, None)?;
+        let errors_before = tree.as_ref().map(syntax_error_count);
+        let issues = self.run_rules(source, tree.as_ref());
+        let started = Instant::now();
+        let fixes: Vec<Fix> = issues
+            .iter()
+            .filter_map(|i| self.applied_fix(i).cloned())
+            .collect();
+        let fixed = apply_fixes(source, &fixes)?;
+        if let Some(stats) = self.stats.as_mut() {
+            stats.fix += started.elapsed();
+        }
+        if fixed == source {
+            return Ok(FixResult { fixed, remaining: issues.clone(), issues, syntax_errors: errors_before });
+        }
+
+        let new_tree = match tree {
+            Some(mut tree) => {
+                // All changes fall inside [first fix start, last fix end); describe them as one edit.
+                let start_byte = fixes.iter().map(|f| f.start_byte).min().unwrap_or(0);
+                let old_end_byte = fixes.iter().map(|f| f.end_byte).max().unwrap_or(0).min(source.len());
+                let new_end_byte = fixed.len() - (source.len() - old_end_byte);
+                tree.edit(&InputEdit {
+                    start_byte,
+                    old_end_byte,
+                    new_end_byte,
+                    start_position: document::point_at(source, start_byte),
+                    old_end_position: document::point_at(source, old_end_byte),
+                    new_end_position: document::point_at(&fixed, new_end_byte),
+                });
+                self.parse(&fixed, Some(&tree))?
             }
+            None => None,
+        };
+        if let (Some(before), Some(new_tree)) = (errors_before, new_tree.as_ref()) {
+            self.check_syntax(source, &issues, before, new_tree)?;
         }
+        let remaining = self.run_rules(&fixed, new_tree.as_ref());
+        Ok(FixResult { fixed, issues, remaining, syntax_errors: errors_before })
     }
-}
 
-fn collect_no_empty_statements(root: Node, out: &mut Vec<LintIssue>) {
-    let mut cursor = root.walk();
-    let mut stack = vec![root];
-    while let Some(node) = stack.pop() {
-        // Java grammar exposes empty statements as "empty_statement".
-        // Do not flag generic ';' tokens inside valid statements (e.g., package/import).
-        let kind = node.kind();
-        if kind == "empty_statement" {
-            let mut issue = issue_at(
-                node,
-                "no-empty-statement",
-                "Remove unnecessary empty statement",
-            );
-            issue.fix = Some(Fix {
-                start_byte: node.start_byte(),
-                end_byte: node.end_byte(),
-                replacement: String::new(),
-            });
-            out.push(issue);
+    /// Check fixes that were applied without going through `fix` (e.g. from
+    /// cached issues) the same way `fix` does. Only `fixed` is parsed when the
+    /// original's error count is already known. A no-op for text-only rule sets.
+    pub(crate) fn verify_fixed(
+        &mut self,
+        source: &str,
+        fixed: &str,
+        issues: &[LintIssue],
+        errors_before: Option<usize>,
+    ) -> Result<(), LintError> {
+        let Some(after) = self.parse(fixed, None)? else {
+            return Ok(());
+        };
+        let before = match errors_before {
+            Some(before) => before,
+            None => self.parse(source, None)?.as_ref().map_or(0, syntax_error_count),
+        };
+        self.check_syntax(source, issues, before, &after)
+    }
+
+    /// Fail when `after` has more syntax errors than the original's `before`,
+    /// blaming each rule whose fixes alone break the parse.
+    fn check_syntax(&mut self, source: &str, issues: &[LintIssue], before: usize, after: &Tree) -> Result<(), LintError> {
+        if syntax_error_count(after) <= before {
+            return Ok(());
         }
-        if node.child_count() > 0 {
-            cursor.reset(node);
-            for i in (0..node.child_count()).rev() {
-                if let Some(child) = node.child(i) {
-                    stack.push(child);
-                }
+        let mut fixing: Vec<&'static str> = issues.iter().filter(|i| self.applied_fix(i).is_some()).map(|i| i.rule_id).collect();
+        fixing.sort_unstable();
+        fixing.dedup();
+        let mut blamed = Vec::new();
+        for &rule in &fixing {
+            let fixes: Vec<Fix> =
+                issues.iter().filter(|i| i.rule_id == rule).filter_map(|i| self.applied_fix(i).cloned()).collect();
+            let partial = apply_fixes(source, &fixes)?;
+            if self.parse(&partial, None)?.is_some_and(|tree| syntax_error_count(&tree) > before) {
+                blamed.push(rule);
             }
         }
+        // No single rule is at fault: the combination is.
+        Err(LintError::FixBrokeSyntax { rules: if blamed.is_empty() { fixing } else { blamed } })
+    }
+
+    /// Fix `source`, then fix the result again. Returns the second pass's
+    /// output when it still differs from the first, i.e. when fixes oscillate
+    /// or only converge over several runs; `None` means fixing is idempotent.
+    pub fn verify_idempotent(&mut self, source: &str) -> Result<Option<String>, LintError> {
+        let once = self.fix(source)?.fixed;
+        let twice = self.fix(&once)?.fixed;
+        Ok((twice != once).then_some(twice))
     }
 }
 
-fn collect_line_length(source: &str, max_len: u16, out: &mut Vec<LintIssue>) {
-    let max_len = max_len as usize;
-    for (idx, line) in source.lines().enumerate() {
-        let visual_len = line.chars().count();
-        if visual_len > max_len {
-            out.push(LintIssue {
-                rule_id: "max-line-length",
-                message: format!("Line exceeds {} characters (was {})", max_len, visual_len),
-                line: idx + 1,
-                column: max_len + 1,
-                fix: None,
-            });
+impl Drop for Linter {
+    fn drop(&mut self) {
+        if let Some(parser) = self.parser.take() {
+            return_parser(parser);
         }
     }
 }
 
-fn collect_indent_style(source: &str, style: IndentStyle, indent_width: u16, out: &mut Vec<LintIssue>) {
-    let mut byte_pos = 0usize;
-    for (idx, line_inc) in source.split_inclusive('\n').enumerate() {
-        let line = line_inc.strip_suffix('\n').unwrap_or(line_inc);
-        let start_byte = byte_pos;
-        byte_pos += line_inc.as_bytes().len();
-
-        let trimmed_end = line.trim_end();
-        if trimmed_end.is_empty() { continue; }
-        let leading_ws_len = trimmed_end
-            .chars()
-            .take_while(|c| *c == ' ' || *c == '\t')
-            .count();
-        if leading_ws_len == 0 { continue; }
-        let leading: String = trimmed_end.chars().take(leading_ws_len).collect();
-        let has_space = leading.chars().any(|c| c == ' ');
-        let has_tab = leading.chars().any(|c| c == '\t');
-
-        match style {
-            IndentStyle::Tabs => {
-                if has_space {
-                    // Suggest fix only if spaces count aligns to indent width and no tabs mixed.
-                    let spaces_count = leading.chars().take_while(|c| *c == ' ').count();
-                    let only_spaces = !has_tab && spaces_count == leading_ws_len;
-                    let fix = if only_spaces && spaces_count % indent_width as usize == 0 {
-                        let tabs = spaces_count / indent_width as usize;
-                        Some(Fix {
-                            start_byte,
-                            end_byte: start_byte + leading_ws_len,
-                            replacement: "\t".repeat(tabs),
-                        })
-                    } else {
-                        None
-                    };
-
-                    let issue = LintIssue {
-                        rule_id: "indent-style",
-                        message: "Use tabs for indentation".to_string(),
-                        line: idx + 1,
-                        column: 1,
-                        fix,
-                    };
-                    out.push(issue);
-                }
+/// Result of fixing a buffer.
+#[derive(Debug, Clone)]
+pub struct FixResult {
+    /// Source with the allowed autofixes applied (unchanged when nothing was fixable).
+    pub fixed: String,
+    /// Issues found in the original source.
+    pub issues: Vec<LintIssue>,
+    /// Issues still present in `fixed`.
+    pub remaining: Vec<LintIssue>,
+    /// Syntax errors in the original source; `None` when the rules didn't parse it.
+    pub syntax_errors: Option<usize>,
+}
+
+/// Number of ERROR and MISSING nodes in `tree`.
+fn syntax_error_count(tree: &Tree) -> usize {
+    if !tree.root_node().has_error() {
+        return 0;
+    }
+    let mut count = 0;
+    let mut cursor = tree.walk();
+    'walk: loop {
+        let node = cursor.node();
+        if node.is_error() || node.is_missing() {
+            count += 1;
+        }
+        // Subtrees without errors can be skipped wholesale.
+        if node.has_error() && cursor.goto_first_child() {
+            continue;
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                break 'walk;
             }
-            IndentStyle::Spaces => {
-                if has_tab {
-                    // Replace each leading tab with indent_width spaces; keep spaces as-is.
-                    let replacement: String = leading
-                        .chars()
-                        .map(|c| if c == '\t' { " ".repeat(indent_width as usize) } else { c.to_string() })
-                        .collect();
-                    let issue = LintIssue {
-                        rule_id: "indent-style",
-                        message: "Use spaces for indentation".to_string(),
-                        line: idx + 1,
-                        column: 1,
-                        fix: Some(Fix {
-                            start_byte,
-                            end_byte: start_byte + leading_ws_len,
-                            replacement,
-                        }),
-                    };
-                    out.push(issue);
-                }
+        }
+    }
+    count
+}
+
+pub fn lint_java_source(source: &str, config: &Config) -> Result<Vec<LintIssue>, LintError> {
+    Linter::new(config.clone())?.lint(source)
+}
+
+fn run_rules(
+    source: &str,
+    tree: Option<&Tree>,
+    config: &Config,
+    rules: &RuleSet,
+    file_name: Option<&str>,
+    mut stats: Option<&mut LintStats>,
+) -> Vec<LintIssue> {
+    let started = Instant::now();
+    let mut issues = Vec::new();
+    let spelling = (config.spellcheck && rules.contains("spellcheck"))
+        .then(|| spellcheck::dictionary(config.spellcheck_dictionary.as_deref()));
+    let ctx = rules::RuleContext {
+        source,
+        config,
+        rules,
+        file_name,
+        spelling: spelling.as_deref(),
+        referenced: Default::default(),
+        enums: Default::default(),
+    };
+    // AST rules share a single walk of the tree, which also collects
+    // annotations for `@SuppressWarnings` when the file might have one
+    let mut annotations = Vec::new();
+    if let Some(tree) = tree {
+        let collect = suppress::wants_annotations(source).then_some(&mut annotations);
+        rules::run_node_rules(tree.root_node(), &ctx, &mut issues, collect, stats.as_deref_mut());
+    }
+    // Line-based rules
+    rules::run_text_rules(&ctx, &mut issues, stats.as_deref_mut());
+    suppress::apply(source, tree, &annotations, rules, &mut issues);
+    if let Some(tree) = tree {
+        wrap::add_fixes(tree.root_node(), &ctx, &mut issues);
+    }
+
+    if !issues.is_empty() {
+        let index = LineIndex::new(source);
+        let tab_width = config.tab_width as usize;
+        for issue in &mut issues {
+            issue.line = index.line(issue.offset) + 1;
+            issue.column = index.column(source, issue.offset, config.column_unit, tab_width) + 1;
+            // A point issue whose fix starts there spans the text it replaces.
+            if issue.end_offset <= issue.offset {
+                issue.end_offset = issue.fix.as_ref().filter(|f| f.start_byte == issue.offset).map_or(issue.offset, |f| f.end_byte);
             }
+            issue.end_line = index.line(issue.end_offset) + 1;
+            issue.end_column = index.column(source, issue.end_offset, config.column_unit, tab_width) + 1;
+            issue.severity = config.severity_of(issue.rule_id);
         }
+        issues.sort_by(|a, b| a.sort_key().cmp(&b.sort_key()));
+    }
+
+    if let Some(stats) = stats {
+        stats.rules += started.elapsed();
+    }
+    issues
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum FixError {
+    #[error("fix range {start}..{end} is outside the source ({len} bytes)")]
+    OutOfBounds { start: usize, end: usize, len: usize },
+    #[error("fix range {start}..{end} splits a UTF-8 character")]
+    NotCharBoundary { start: usize, end: usize },
+}
+
+impl Fix {
+    /// Check that the range is ordered, inside `source` and on char boundaries.
+    pub fn validate(&self, source: &str) -> Result<(), FixError> {
+        let (start, end) = (self.start_byte, self.end_byte);
+        if start > end || end > source.len() {
+            return Err(FixError::OutOfBounds { start, end, len: source.len() });
+        }
+        if !source.is_char_boundary(start) || !source.is_char_boundary(end) {
+            return Err(FixError::NotCharBoundary { start, end });
+        }
+        Ok(())
     }
 }
 
-/// Apply a set of non-overlapping fixes to the source. If fixes overlap, later ones win by sorting by range.
-pub fn apply_fixes(source: &str, fixes: &[Fix]) -> String {
-    if fixes.is_empty() { return source.to_string(); }
+/// Apply fixes to the source in order of position. A fix overlapping one that
+/// starts earlier is skipped, so two rules rewriting the same text never
+/// interleave; one rewriting exactly the same text as the fix before it
+/// replaces that one.
+///
+/// Every fix is validated first, so a bad range from a rule is reported as an
+/// error instead of panicking on a slice.
+pub fn apply_fixes(source: &str, fixes: &[Fix]) -> Result<String, FixError> {
+    if fixes.is_empty() { return Ok(source.to_string()); }
+    for f in fixes {
+        f.validate(source)?;
+    }
     let mut fixes = fixes.to_vec();
     fixes.sort_by_key(|f| f.start_byte);
     let mut out = String::with_capacity(source.len());
     let mut cursor = 0usize;
+    let mut last: Option<(usize, usize, usize)> = None; // (start, end, where its replacement begins in `out`)
     for f in fixes {
-        if f.start_byte > cursor {
+        if let Some((start, end, at)) = last
+            && (start, end) == (f.start_byte, f.end_byte)
+            && end > start
+        {
+            out.truncate(at);
+        } else if f.start_byte < cursor {
+            // Its issue stays in `remaining` for the next run.
+            continue;
+        } else if f.start_byte > cursor {
             out.push_str(&source[cursor..f.start_byte]);
         }
+        last = Some((f.start_byte, f.end_byte, out.len()));
         out.push_str(&f.replacement);
-        cursor = f.end_byte.min(source.len());
+        cursor = f.end_byte;
     }
     if cursor < source.len() {
         out.push_str(&source[cursor..]);
     }
-    out
+    Ok(out)
+}
+
+/// Whether applying `fixes` would change `source`, decided by comparing each
+/// fix with the text it replaces instead of building the fixed string.
+pub fn would_change<'a>(source: &str, fixes: impl IntoIterator<Item = &'a Fix>) -> bool {
+    fixes
+        .into_iter()
+        .any(|f| source.get(f.start_byte..f.end_byte) != Some(&*f.replacement))
 }
 
 /// Lint and return a fixed version of the source, applying safe autofixes.
-pub fn fix_java_source(source: &str, config: &Config) -> Result<(String, Vec<LintIssue>), LintError> {
-    let issues = lint_java_source(source, config)?;
-    let fixes: Vec<Fix> = issues
-        .iter()
-        .filter_map(|i| i.fix.clone())
-        .collect();
-    let fixed = apply_fixes(source, &fixes);
-    Ok((fixed, issues))
+pub fn fix_java_source(source: &str, config: &Config) -> Result<FixResult, LintError> {
+    Linter::new(config.clone())?.fix(source)
 }
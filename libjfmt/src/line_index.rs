@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+
+/// Unit for reported columns.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ColumnUnit {
+    /// Characters, with tabs advancing to the next `tab_width` stop.
+    #[default]
+    Display,
+    /// Unicode scalar values; a tab counts as one.
+    Chars,
+    /// UTF-16 code units, as LSP clients expect.
+    Utf16,
+    /// UTF-8 bytes.
+    Bytes,
+}
+
+/// Maps byte offsets in a source file to lines and columns.
+///
+/// Built once per file so every rule and output format agrees on positions.
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(source: &str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(source.bytes().enumerate().filter(|(_, b)| *b == b'\n').map(|(i, _)| i + 1));
+        Self { line_starts }
+    }
+
+    /// 0-based line containing `offset`.
+    pub fn line(&self, offset: usize) -> usize {
+        self.line_starts.partition_point(|start| *start <= offset) - 1
+    }
+
+    /// Byte offset where `line` (0-based) starts.
+    pub fn line_start(&self, line: usize) -> usize {
+        self.line_starts.get(line).copied().unwrap_or(usize::MAX)
+    }
+
+    /// 0-based column of `offset` as an editor displays it: one per character,
+    /// with tabs advancing to the next multiple of `tab_width`.
+    pub fn display_column(&self, source: &str, offset: usize, tab_width: usize) -> usize {
+        let start = self.line_start(self.line(offset));
+        let prefix = source.get(start..offset).unwrap_or_default();
+        prefix.chars().fold(0, |col, c| advance_column(col, c, tab_width))
+    }
+
+    /// 0-based column of `offset` counted in `unit`.
+    pub fn column(&self, source: &str, offset: usize, unit: ColumnUnit, tab_width: usize) -> usize {
+        let start = self.line_start(self.line(offset));
+        let prefix = source.get(start..offset).unwrap_or_default();
+        match unit {
+            ColumnUnit::Display => self.display_column(source, offset, tab_width),
+            ColumnUnit::Chars => prefix.chars().count(),
+            ColumnUnit::Utf16 => prefix.chars().map(char::len_utf16).sum(),
+            ColumnUnit::Bytes => prefix.len(),
+        }
+    }
+}
+
+/// Column after `c` when it starts at `col`; tabs jump to the next tab stop.
+pub fn advance_column(col: usize, c: char, tab_width: usize) -> usize {
+    if c == '\t' && tab_width > 0 {
+        (col / tab_width + 1) * tab_width
+    } else {
+        col + 1
+    }
+}
@@ -0,0 +1,184 @@
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+use serde::Serialize;
+use tree_sitter::Node;
+
+use crate::{checkout_parser, return_parser, Config, FileError, LintError, SourceText};
+
+/// Size and comment statistics for a file, plus one entry per method.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct FileMetrics {
+    pub lines: usize,
+    pub code_lines: usize,
+    pub comment_lines: usize,
+    pub blank_lines: usize,
+    /// Comment lines over code plus comment lines; 0 for an empty file.
+    pub comment_ratio: f64,
+    pub methods: Vec<MethodMetrics>,
+}
+
+/// Metrics for a method or constructor.
+#[derive(Debug, Clone, Serialize)]
+pub struct MethodMetrics {
+    pub name: String,
+    /// 1-based line of the declaration.
+    pub line: usize,
+    pub lines: usize,
+    pub parameters: usize,
+    /// McCabe complexity: 1 plus one per branch, loop, catch, case and `&&`/`||`.
+    pub complexity: usize,
+    /// Deepest nesting of control-flow statements in the body.
+    pub max_nesting: usize,
+}
+
+#[derive(Debug)]
+pub struct MetricsReport {
+    pub path: PathBuf,
+    pub metrics: Result<FileMetrics, FileError>,
+}
+
+/// Compute metrics for many files concurrently; reports keep the order of `paths`.
+pub fn collect_metrics(paths: &[PathBuf], config: &Config) -> Vec<MetricsReport> {
+    paths
+        .par_iter()
+        .map(|path| MetricsReport { path: path.clone(), metrics: metrics_for_path(path, config) })
+        .collect()
+}
+
+fn metrics_for_path(path: &Path, config: &Config) -> Result<FileMetrics, FileError> {
+    let src = SourceText::read(path, config.mmap_threshold)?;
+    Ok(file_metrics(&src)?)
+}
+
+/// Metrics for one in-memory source file.
+pub fn file_metrics(source: &str) -> Result<FileMetrics, LintError> {
+    let mut parser = checkout_parser()?;
+    let tree = parser.parse(source, None);
+    return_parser(parser);
+    let tree = tree.ok_or(LintError::Parse)?;
+
+    let lines = source.lines().count();
+    let mut has_code = vec![false; lines];
+    let mut has_comment = vec![false; lines];
+    let mut methods = Vec::new();
+    let mut cursor = tree.walk();
+    loop {
+        let node = cursor.node();
+        if matches!(node.kind(), "method_declaration" | "constructor_declaration") {
+            methods.push(method_metrics(node, source));
+        }
+        let flags = match node.kind() {
+            "line_comment" | "block_comment" => Some(&mut has_comment),
+            _ if node.child_count() == 0 => Some(&mut has_code),
+            _ => None,
+        };
+        if let Some(flags) = flags {
+            let (first, last) = (node.start_position().row, node.end_position().row);
+            for flag in flags.iter_mut().take(last + 1).skip(first) {
+                *flag = true;
+            }
+        } else if cursor.goto_first_child() {
+            continue;
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                let code_lines = has_code.iter().filter(|c| **c).count();
+                let comment_lines = has_comment.iter().zip(&has_code).filter(|(m, c)| **m && !**c).count();
+                let counted = code_lines + comment_lines;
+                return Ok(FileMetrics {
+                    lines,
+                    code_lines,
+                    comment_lines,
+                    blank_lines: lines - counted,
+                    comment_ratio: if counted == 0 { 0.0 } else { comment_lines as f64 / counted as f64 },
+                    methods,
+                });
+            }
+        }
+    }
+}
+
+pub(crate) fn method_metrics(node: Node, source: &str) -> MethodMetrics {
+    let name = node
+        .child_by_field_name("name")
+        .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+        .unwrap_or_default()
+        .to_string();
+    let parameters = node.child_by_field_name("parameters").map_or(0, |params| {
+        let mut cursor = params.walk();
+        params.named_children(&mut cursor).filter(|p| !p.is_extra()).count()
+    });
+    let (complexity, max_nesting) = match node.child_by_field_name("body") {
+        Some(body) => (1 + decision_points(body), nesting(body, 0)),
+        None => (1, 0),
+    };
+    MethodMetrics {
+        name,
+        line: node.start_position().row + 1,
+        lines: node.end_position().row - node.start_position().row + 1,
+        parameters,
+        complexity,
+        max_nesting,
+    }
+}
+
+/// Nested classes are measured on their own, so don't descend into them.
+fn is_nested_scope(node: Node) -> bool {
+    matches!(node.kind(), "class_body" | "interface_body" | "enum_body")
+}
+
+fn decision_points(node: Node) -> usize {
+    let own = match node.kind() {
+        "if_statement" | "for_statement" | "enhanced_for_statement" | "while_statement" | "do_statement"
+        | "catch_clause" | "ternary_expression" => 1,
+        "switch_label" => usize::from(node.child(0).is_some_and(|k| k.kind() == "case")),
+        "binary_expression" => usize::from(
+            node.child_by_field_name("operator").is_some_and(|op| matches!(op.kind(), "&&" | "||")),
+        ),
+        _ => 0,
+    };
+    let mut cursor = node.walk();
+    own + node
+        .children(&mut cursor)
+        .filter(|c| !is_nested_scope(*c))
+        .map(decision_points)
+        .sum::<usize>()
+}
+
+/// Statements under `node`, counting those nested in blocks, loops and
+/// lambdas but not those of nested classes.
+pub(crate) fn statement_count(node: Node) -> usize {
+    let own = match node.kind() {
+        "local_variable_declaration" | "explicit_constructor_invocation" => 1,
+        // A switch is a statement only where statements go.
+        "switch_expression" => usize::from(node.parent().is_some_and(|p| matches!(p.kind(), "block" | "switch_block_statement_group"))),
+        kind => usize::from(kind.ends_with("_statement")),
+    };
+    let mut cursor = node.walk();
+    own + node
+        .children(&mut cursor)
+        .filter(|c| !is_nested_scope(*c))
+        .map(statement_count)
+        .sum::<usize>()
+}
+
+fn nesting(node: Node, depth: usize) -> usize {
+    let nests = matches!(
+        node.kind(),
+        "if_statement" | "for_statement" | "enhanced_for_statement" | "while_statement" | "do_statement"
+            | "try_statement" | "try_with_resources_statement" | "switch_expression" | "synchronized_statement"
+    );
+    // `else if` continues a chain rather than nesting inside it.
+    let else_if = node.kind() == "if_statement"
+        && node
+            .parent()
+            .and_then(|p| p.child_by_field_name("alternative"))
+            .is_some_and(|alt| alt.id() == node.id());
+    let depth = if nests && !else_if { depth + 1 } else { depth };
+    let mut cursor = node.walk();
+    node.children(&mut cursor)
+        .filter(|c| !is_nested_scope(*c))
+        .map(|c| nesting(c, depth))
+        .fold(depth, usize::max)
+}
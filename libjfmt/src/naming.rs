@@ -0,0 +1,34 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use regex::Regex;
+
+use crate::Naming;
+
+/// `pattern` compiled to match whole names, once per process. `None` when it
+/// isn't a valid regex; `config_warnings` reports those.
+pub(crate) fn pattern(pattern: &str) -> Option<Arc<Regex>> {
+    static COMPILED: OnceLock<Mutex<HashMap<String, Option<Arc<Regex>>>>> = OnceLock::new();
+    let mut compiled = COMPILED.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+    compiled
+        .entry(pattern.to_string())
+        .or_insert_with(|| anchored(pattern).ok().map(Arc::new))
+        .clone()
+}
+
+/// `[naming]` keys whose pattern doesn't compile, with the reason (the last
+/// line of the regex error, which is the one without the caret diagram).
+pub(crate) fn invalid_patterns(naming: &Naming) -> Vec<(&'static str, String)> {
+    [("class", &naming.class), ("method", &naming.method), ("constant", &naming.constant), ("package", &naming.package)]
+        .into_iter()
+        .filter_map(|(key, pattern)| anchored(pattern).err().map(|e| (key, last_line(&e.to_string()))))
+        .collect()
+}
+
+fn last_line(text: &str) -> String {
+    text.lines().last().unwrap_or_default().trim_start_matches("error: ").to_string()
+}
+
+fn anchored(pattern: &str) -> Result<Regex, regex::Error> {
+    Regex::new(&format!("^(?:{pattern})$"))
+}
@@ -0,0 +1,201 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::path::PathBuf;
+
+use crate::{Project, ProjectIssue, ProjectRule};
+
+/// Where a package depends on another: the first import creating the edge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportSite {
+    pub from: String,
+    pub to: String,
+    pub path: PathBuf,
+    pub line: usize,
+}
+
+/// Packages that depend on each other through imports, as one concrete loop:
+/// `edges[i].to == edges[i + 1].from`, and the last edge returns to the first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageCycle {
+    pub edges: Vec<ImportSite>,
+}
+
+impl PackageCycle {
+    /// Packages along the loop, starting and ending with the same one.
+    pub fn packages(&self) -> impl Iterator<Item = &str> {
+        self.edges.iter().map(|e| e.from.as_str()).chain(self.edges.first().map(|e| e.from.as_str()))
+    }
+}
+
+/// Reports every import that closes a package dependency cycle.
+pub struct PackageCycleRule;
+
+impl ProjectRule for PackageCycleRule {
+    fn id(&self) -> &'static str { "package-cycle" }
+
+    fn check(&self, project: &Project, out: &mut Vec<ProjectIssue>) {
+        for cycle in package_cycles(project) {
+            let packages = cycle.packages().collect::<Vec<_>>().join(" -> ");
+            for edge in &cycle.edges {
+                out.push(ProjectIssue {
+                    rule_id: self.id(),
+                    path: edge.path.clone(),
+                    line: edge.line,
+                    column: 1,
+                    message: format!("Import is part of package cycle {packages}").into(),
+                });
+            }
+        }
+    }
+}
+
+/// Reports files whose `package` declaration doesn't match their directory
+/// under the project's source roots. Files outside every root are skipped.
+pub struct PackagePathRule;
+
+impl ProjectRule for PackagePathRule {
+    fn id(&self) -> &'static str { "package-path" }
+
+    fn check(&self, project: &Project, out: &mut Vec<ProjectIssue>) {
+        for file in project.files() {
+            let Some(expected) = project.expected_package(&file.path) else { continue };
+            let declared = file.package.as_deref().unwrap_or_default();
+            if declared == expected { continue; }
+            let describe = |p: &str| if p.is_empty() { "the default package".to_string() } else { format!("package '{p}'") };
+            out.push(ProjectIssue {
+                rule_id: self.id(),
+                path: file.path.clone(),
+                line: file.package_line.unwrap_or(1),
+                column: 1,
+                message: format!("File declares {} but its directory implies {}", describe(declared), describe(&expected)).into(),
+            });
+        }
+    }
+}
+
+/// Find dependency cycles in the package import graph of `project`.
+///
+/// Only packages declared by project files take part, so imports of
+/// libraries never form edges. Packages are grouped into strongly connected
+/// components; each group is reported once, as its shortest loop through the
+/// alphabetically first package, ordered by that package.
+pub fn package_cycles(project: &Project) -> Vec<PackageCycle> {
+    let symbols = project.symbols();
+    let mut edges: BTreeMap<(&str, &str), ImportSite> = BTreeMap::new();
+    for file in project.files() {
+        let Some(from) = file.package.as_deref() else { continue };
+        for import in &file.imports {
+            let to = package_of(&import.name);
+            if to == from || !symbols.has_package(to) { continue; }
+            edges.entry((from, to)).or_insert_with(|| ImportSite {
+                from: from.to_string(),
+                to: to.to_string(),
+                path: file.path.clone(),
+                line: import.line,
+            });
+        }
+    }
+
+    let mut graph: BTreeMap<&str, Vec<&str>> = symbols.packages().map(|p| (p, Vec::new())).collect();
+    for (from, to) in edges.keys() {
+        graph.entry(from).or_default().push(to);
+    }
+    let mut cycles = Vec::new();
+    for component in strongly_connected(&graph).into_iter().filter(|c| c.len() > 1) {
+        let start = component.iter().min().copied().expect("component is not empty");
+        if let Some(path) = shortest_loop(&graph, &component, start) {
+            let cycle = path.windows(2).map(|w| edges[&(w[0], w[1])].clone()).collect();
+            cycles.push(PackageCycle { edges: cycle });
+        }
+    }
+    cycles.sort_by(|a, b| a.edges[0].from.cmp(&b.edges[0].from));
+    cycles
+}
+
+/// Package part of an imported name: the segments before the first one that
+/// starts with an uppercase letter (the type). Wildcard imports of a package
+/// keep every segment.
+fn package_of(name: &str) -> &str {
+    let name = name.trim_end_matches(".*");
+    let mut end = 0;
+    for segment in name.split('.') {
+        if segment.starts_with(|c: char| c.is_uppercase()) { break; }
+        end += segment.len() + 1;
+    }
+    &name[..end.saturating_sub(1).min(name.len())]
+}
+
+/// Tarjan's algorithm, iterative so deep graphs can't overflow the stack.
+fn strongly_connected<'a>(graph: &BTreeMap<&'a str, Vec<&'a str>>) -> Vec<Vec<&'a str>> {
+    let mut index: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut low: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut on_stack = BTreeSet::new();
+    let mut stack = Vec::new();
+    let mut components = Vec::new();
+    for &root in graph.keys() {
+        if index.contains_key(root) { continue; }
+        let mut work = vec![(root, 0usize)];
+        while let Some(&mut (node, ref mut next)) = work.last_mut() {
+            if *next == 0 {
+                let n = index.len();
+                index.insert(node, n);
+                low.insert(node, n);
+                stack.push(node);
+                on_stack.insert(node);
+            }
+            let succ = &graph[node];
+            if let Some(&child) = succ.get(*next) {
+                *next += 1;
+                if !index.contains_key(child) {
+                    work.push((child, 0));
+                } else if on_stack.contains(child) {
+                    let l = low[node].min(index[child]);
+                    low.insert(node, l);
+                }
+                continue;
+            }
+            work.pop();
+            if let Some(&(parent, _)) = work.last() {
+                let l = low[parent].min(low[node]);
+                low.insert(parent, l);
+            }
+            if low[node] == index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = stack.pop() {
+                    on_stack.remove(member);
+                    component.push(member);
+                    if member == node { break; }
+                }
+                components.push(component);
+            }
+        }
+    }
+    components
+}
+
+/// Shortest path from `start` back to itself within `component`, as the
+/// packages visited (first and last are `start`).
+fn shortest_loop<'a>(graph: &BTreeMap<&'a str, Vec<&'a str>>, component: &[&'a str], start: &'a str) -> Option<Vec<&'a str>> {
+    let members: BTreeSet<&str> = component.iter().copied().collect();
+    let mut came_from: BTreeMap<&str, &str> = BTreeMap::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        for &next in &graph[node] {
+            if !members.contains(next) { continue; }
+            if next == start {
+                let mut path = vec![start, node];
+                let mut at = node;
+                while at != start {
+                    at = came_from[at];
+                    path.push(at);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            if next != start && !came_from.contains_key(next) {
+                came_from.insert(next, node);
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
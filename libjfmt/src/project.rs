@@ -0,0 +1,337 @@
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use rayon::prelude::*;
+use tree_sitter::Node;
+
+use crate::javadoc;
+use crate::{checkout_parser, return_parser, Config, FileError, LineIndex, LintError, SourceText};
+
+/// A set of Java files analyzed together, for checks that need to see more
+/// than one file at a time (package dependencies, cross-file references, ...).
+///
+/// Every file is parsed once, in parallel, into a `ProjectFile` summary; the
+/// trees themselves are not kept. `ProjectRule`s then run over the summaries
+/// and the shared `SymbolIndex`.
+#[derive(Debug)]
+pub struct Project {
+    config: Config,
+    roots: Vec<PathBuf>,
+    files: Vec<ProjectFile>,
+    errors: Vec<(PathBuf, FileError)>,
+    symbols: SymbolIndex,
+}
+
+/// What the project index knows about one file.
+#[derive(Debug, Clone, Default)]
+pub struct ProjectFile {
+    pub path: PathBuf,
+    pub package: Option<String>,
+    /// 1-based line of the `package` declaration.
+    pub package_line: Option<usize>,
+    pub imports: Vec<Import>,
+    /// Types and methods declared in the file, outermost first.
+    pub declarations: Vec<Declaration>,
+    /// Every identifier the file uses, other than the names it declares.
+    pub references: HashSet<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Import {
+    /// Imported name as written, with `.*` for on-demand imports.
+    pub name: String,
+    pub is_static: bool,
+    pub line: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+    Public,
+    Protected,
+    PackagePrivate,
+    Private,
+}
+
+/// A type or method declaration.
+#[derive(Debug, Clone)]
+pub struct Declaration {
+    /// `class`, `interface`, `enum`, `record`, `annotation type` or `method`.
+    pub kind: &'static str,
+    pub name: String,
+    pub line: usize,
+    pub column: usize,
+    pub visibility: Visibility,
+    /// Simple names of the annotations on the declaration.
+    pub annotations: Vec<String>,
+    /// For types, simple names of the annotations on their direct members.
+    pub member_annotations: Vec<String>,
+    /// A `static main` method, or a type declaring one.
+    pub entry_point: bool,
+    /// Preceded by a `/** ... */` comment.
+    pub documented: bool,
+}
+
+/// Project-wide lookups shared by all project rules.
+#[derive(Debug, Default)]
+pub struct SymbolIndex {
+    /// Declared package → indices into `Project::files`.
+    packages: BTreeMap<String, Vec<usize>>,
+    /// Declared simple name → (file index, declaration index).
+    declarations: HashMap<String, Vec<(usize, usize)>>,
+    references: HashSet<String>,
+}
+
+impl SymbolIndex {
+    /// Packages declared by project files, in name order.
+    pub fn packages(&self) -> impl Iterator<Item = &str> { self.packages.keys().map(String::as_str) }
+
+    pub fn has_package(&self, package: &str) -> bool { self.packages.contains_key(package) }
+
+    /// Files declaring `package`, as indices into `Project::files`.
+    pub fn files_in_package(&self, package: &str) -> &[usize] {
+        self.packages.get(package).map_or(&[], Vec::as_slice)
+    }
+
+    /// Declarations with simple name `name`, as (file, declaration) indices.
+    pub fn declarations_named(&self, name: &str) -> &[(usize, usize)] {
+        self.declarations.get(name).map_or(&[], Vec::as_slice)
+    }
+
+    /// Whether any project file uses `name` other than to declare it.
+    pub fn is_referenced(&self, name: &str) -> bool { self.references.contains(name) }
+}
+
+/// A finding from a project rule.
+#[derive(Debug, Clone)]
+pub struct ProjectIssue {
+    pub rule_id: &'static str,
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    pub message: Cow<'static, str>,
+}
+
+/// A check that runs over a whole `Project` rather than one file.
+pub trait ProjectRule: Send + Sync {
+    fn id(&self) -> &'static str;
+    fn check(&self, project: &Project, out: &mut Vec<ProjectIssue>);
+}
+
+impl Project {
+    /// Index `paths`. Files that can't be read or parsed are recorded in
+    /// `errors` and left out of the index.
+    pub fn load(paths: &[PathBuf], config: &Config) -> Self {
+        Self::index(paths.iter().map(|path| (path, config)).collect(), config)
+    }
+
+    /// Like `load`, with each group of files read under its own config as
+    /// `ConfigResolver::group` returns them; `config` holds the settings of
+    /// the project as a whole. Files are indexed in path order.
+    pub fn load_groups(groups: &[(Config, Vec<PathBuf>)], config: &Config) -> Self {
+        let mut paths: Vec<_> = groups.iter().flat_map(|(c, paths)| paths.iter().map(move |path| (path, c))).collect();
+        paths.sort_by_key(|&(path, _)| path);
+        Self::index(paths, config)
+    }
+
+    fn index(paths: Vec<(&PathBuf, &Config)>, config: &Config) -> Self {
+        let indexed: Vec<_> = paths.par_iter().map(|&(path, file_config)| index_file(path, file_config)).collect();
+        let mut files = Vec::new();
+        let mut errors = Vec::new();
+        for (&(path, _), result) in paths.iter().zip(indexed) {
+            match result {
+                Ok(file) => files.push(file),
+                Err(err) => errors.push((path.clone(), err)),
+            }
+        }
+
+        let mut symbols = SymbolIndex::default();
+        for (f, file) in files.iter().enumerate() {
+            if let Some(package) = &file.package {
+                symbols.packages.entry(package.clone()).or_default().push(f);
+            }
+            for (d, decl) in file.declarations.iter().enumerate() {
+                symbols.declarations.entry(decl.name.clone()).or_default().push((f, d));
+            }
+            symbols.references.extend(file.references.iter().cloned());
+        }
+        Project { config: config.clone(), roots: Vec::new(), files, errors, symbols }
+    }
+
+    /// Directories that package paths are relative to, e.g. `src/main/java`.
+    pub fn with_roots(mut self, roots: impl IntoIterator<Item = PathBuf>) -> Self {
+        self.roots = roots.into_iter().collect();
+        self
+    }
+
+    pub fn config(&self) -> &Config { &self.config }
+
+    pub fn roots(&self) -> &[PathBuf] { &self.roots }
+
+    pub fn files(&self) -> &[ProjectFile] { &self.files }
+
+    pub fn errors(&self) -> &[(PathBuf, FileError)] { &self.errors }
+
+    pub fn symbols(&self) -> &SymbolIndex { &self.symbols }
+
+    /// The source root containing `path`, if any.
+    pub fn source_root(&self, path: &Path) -> Option<&Path> {
+        self.roots.iter().map(PathBuf::as_path).filter(|root| path.starts_with(root)).max_by_key(|r| r.as_os_str().len())
+    }
+
+    /// The package a file at `path` should declare given its directory under
+    /// its source root; `None` outside every root.
+    pub fn expected_package(&self, path: &Path) -> Option<String> {
+        let dir = path.parent()?.strip_prefix(self.source_root(path)?).ok()?;
+        let segments: Vec<_> = dir.components().map(|c| c.as_os_str().to_string_lossy()).collect();
+        Some(segments.join("."))
+    }
+
+    /// Run `rules` and return their issues ordered by path, line and column.
+    pub fn check(&self, rules: &[&dyn ProjectRule]) -> Vec<ProjectIssue> {
+        let mut issues = Vec::new();
+        for rule in rules {
+            rule.check(self, &mut issues);
+        }
+        issues.sort_by(|a, b| (&a.path, a.line, a.column, a.rule_id).cmp(&(&b.path, b.line, b.column, b.rule_id)));
+        issues
+    }
+}
+
+/// Declarations the index records and what they are called in reports.
+const INDEXED: &[(&str, &str)] = &[
+    ("class_declaration", "class"),
+    ("interface_declaration", "interface"),
+    ("enum_declaration", "enum"),
+    ("record_declaration", "record"),
+    ("annotation_type_declaration", "annotation type"),
+    ("method_declaration", "method"),
+];
+
+fn index_file(path: &Path, config: &Config) -> Result<ProjectFile, FileError> {
+    let src = SourceText::read(path, config.mmap_threshold)?;
+    let mut parser = checkout_parser()?;
+    let tree = parser.parse(&*src, None);
+    return_parser(parser);
+    let tree = tree.ok_or(LintError::Parse)?;
+
+    let source: &str = &src;
+    let line_index = LineIndex::new(source);
+    let mut file = ProjectFile { path: path.to_path_buf(), ..Default::default() };
+    let mut cursor = tree.walk();
+    loop {
+        let node = cursor.node();
+        let kind = node.kind();
+        match kind {
+            "package_declaration" => {
+                file.package = declared_name(node, source);
+                file.package_line = Some(node.start_position().row + 1);
+            }
+            "import_declaration" => file.imports.extend(declared_name(node, source).map(|name| Import {
+                name,
+                is_static: source[node.byte_range()].split_whitespace().nth(1) == Some("static"),
+                line: node.start_position().row + 1,
+            })),
+            "identifier" | "type_identifier" if !is_declared_name(node) => {
+                file.references.insert(source[node.byte_range()].to_string());
+            }
+            _ => {}
+        }
+        if let Some(&(_, noun)) = INDEXED.iter().find(|(k, _)| *k == kind)
+            && let Some(name) = node.child_by_field_name("name")
+        {
+            let offset = name.start_byte();
+            let body = if noun == "method" { Vec::new() } else { members(node) };
+            file.declarations.push(Declaration {
+                kind: noun,
+                name: source[name.byte_range()].to_string(),
+                line: line_index.line(offset) + 1,
+                column: line_index.column(source, offset, config.column_unit, config.tab_width as usize) + 1,
+                visibility: visibility(node),
+                annotations: annotations(node, source),
+                member_annotations: body.iter().flat_map(|m| annotations(*m, source)).collect(),
+                entry_point: is_main(node, source) || body.iter().any(|m| is_main(*m, source)),
+                documented: javadoc::doc_comment(node, source).is_some(),
+            });
+        }
+        if cursor.goto_first_child() { continue; }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() { return Ok(file); }
+        }
+    }
+}
+
+/// Name in a `package` or `import` declaration, with `.*` for wildcards.
+fn declared_name(node: Node, source: &str) -> Option<String> {
+    let mut cursor = node.walk();
+    let name = node
+        .named_children(&mut cursor)
+        .find(|n| matches!(n.kind(), "scoped_identifier" | "identifier"))
+        .map(|n| source[n.byte_range()].to_string());
+    // `import a.b.*;` keeps the asterisk outside the name node.
+    let wildcard = node.named_children(&mut cursor).any(|n| n.kind() == "asterisk");
+    name.map(|n| if wildcard { n + ".*" } else { n })
+}
+
+/// Whether `node` is the name a type, method or constructor declares, which
+/// is not a use of that name.
+fn is_declared_name(node: Node) -> bool {
+    node.parent().is_some_and(|p| {
+        (p.kind() == "constructor_declaration" || INDEXED.iter().any(|(k, _)| *k == p.kind()))
+            && p.child_by_field_name("name").is_some_and(|n| n.id() == node.id())
+    })
+}
+
+fn modifiers(node: Node) -> Option<Node> {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor).find(|c| c.kind() == "modifiers")
+}
+
+fn visibility(node: Node) -> Visibility {
+    let explicit = modifiers(node).and_then(|m| {
+        let mut cursor = m.walk();
+        m.children(&mut cursor).find_map(|c| match c.kind() {
+            "public" => Some(Visibility::Public),
+            "protected" => Some(Visibility::Protected),
+            "private" => Some(Visibility::Private),
+            _ => None,
+        })
+    });
+    // Interface and annotation members are implicitly public.
+    let in_interface = node.parent().is_some_and(|p| matches!(p.kind(), "interface_body" | "annotation_type_body"));
+    explicit.unwrap_or(if in_interface { Visibility::Public } else { Visibility::PackagePrivate })
+}
+
+/// Simple names of the annotations on a declaration (`@org.junit.Test` → `Test`).
+fn annotations(node: Node, source: &str) -> Vec<String> {
+    let Some(m) = modifiers(node) else { return Vec::new() };
+    let mut cursor = m.walk();
+    m.named_children(&mut cursor)
+        .filter(|c| matches!(c.kind(), "annotation" | "marker_annotation"))
+        .filter_map(|a| a.child_by_field_name("name"))
+        .map(|n| source[n.byte_range()].rsplit('.').next().unwrap_or_default().to_string())
+        .collect()
+}
+
+fn is_main(node: Node, source: &str) -> bool {
+    node.kind() == "method_declaration"
+        && node.child_by_field_name("name").is_some_and(|n| &source[n.byte_range()] == "main")
+        && modifiers(node).is_some_and(|m| source[m.byte_range()].split_whitespace().any(|w| w == "static"))
+}
+
+/// Members declared directly in a type's body.
+fn members(node: Node) -> Vec<Node> {
+    let mut found = Vec::new();
+    if let Some(body) = node.child_by_field_name("body") {
+        let mut cursor = body.walk();
+        for child in body.named_children(&mut cursor) {
+            if child.kind() == "enum_body_declarations" {
+                let mut inner = child.walk();
+                found.extend(child.named_children(&mut inner));
+            } else {
+                found.push(child);
+            }
+        }
+    }
+    found
+}
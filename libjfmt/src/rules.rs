@@ -0,0 +1,3401 @@
+use std::borrow::Cow;
+use std::cell::OnceCell;
+use std::collections::{BTreeSet, HashSet};
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use tree_sitter::Node;
+
+use crate::imports::{self, ImportDecl};
+use crate::javadoc;
+use crate::layout::glob_matches_path;
+use crate::line_index::advance_column;
+use crate::metrics;
+use crate::naming;
+use crate::spellcheck;
+use crate::{java_language, Applicability, BraceStyle, Config, DEPRECATED_RULES, Fix, IndentStyle, LintIssue, LintStats, MethodLengthUnit, SwitchStyle};
+
+/// Shared inputs for rules run during the tree traversal.
+pub struct RuleContext<'a> {
+    pub source: &'a str,
+    pub config: &'a Config,
+    /// Rules enabled for this file; some rules consult each other's state.
+    pub rules: &'a RuleSet,
+    /// Name of the file the source came from (`Foo.java`), when known.
+    pub file_name: Option<&'a str>,
+    /// Spellcheck dictionary, when that rule is on; looked up once per file.
+    pub(crate) spelling: Option<&'a spellcheck::Dictionary>,
+    /// Simple names the file refers to, gathered on first use.
+    pub(crate) referenced: OnceCell<HashSet<&'a str>>,
+    /// Constant names of each enum the file declares, gathered on first use.
+    pub(crate) enums: OnceCell<Vec<BTreeSet<&'a str>>>,
+}
+
+impl<'a> RuleContext<'a> {
+    /// Names `root`'s file refers to outside its imports; see
+    /// `imports::referenced_names`.
+    fn referenced_names(&self, root: Node) -> &HashSet<&'a str> {
+        self.referenced.get_or_init(|| imports::referenced_names(root, self.source))
+    }
+
+    /// Constants of the enums declared in `root`'s file; see `enum_constants`.
+    fn enum_constants(&self, root: Node) -> &[BTreeSet<&'a str>] {
+        self.enums.get_or_init(|| enum_constants(root, self.source))
+    }
+}
+
+/// How a rule inspects a file, which decides the cheapest pipeline to run it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleKind {
+    /// Works on raw lines; no syntax tree needed.
+    Text,
+    /// Needs the parsed syntax tree.
+    Ast,
+}
+
+/// A lint rule. The driver walks the tree once, handing each node to the
+/// enabled rules whose `node_kinds` include its kind, then gives every
+/// enabled text rule the whole source. To add a rule, implement this trait
+/// (or add a `NodeRule`/`TextRule` entry) and list it in `RULES`.
+pub trait Rule: Sync {
+    /// Stable id used in config, suppressions and reports.
+    fn id(&self) -> &'static str;
+
+    /// One line saying what the rule flags, for listings and docs.
+    fn description(&self) -> &'static str;
+
+    /// Node kinds handed to `check_node`; empty for rules that only read text.
+    fn node_kinds(&self) -> &'static [&'static str] { &[] }
+
+    fn check_node(&self, _node: Node, _ctx: &RuleContext, _out: &mut Vec<LintIssue>) {}
+
+    fn check_text(&self, _ctx: &RuleContext, _out: &mut Vec<LintIssue>) {}
+
+    fn kind(&self) -> RuleKind {
+        if self.node_kinds().is_empty() { RuleKind::Text } else { RuleKind::Ast }
+    }
+}
+
+/// A rule that only cares about specific node kinds, checked by a function.
+struct NodeRule {
+    id: &'static str,
+    description: &'static str,
+    kinds: &'static [&'static str],
+    check: fn(Node, &RuleContext, &mut Vec<LintIssue>),
+}
+
+impl Rule for NodeRule {
+    fn id(&self) -> &'static str { self.id }
+    fn description(&self) -> &'static str { self.description }
+    fn node_kinds(&self) -> &'static [&'static str] { self.kinds }
+    fn check_node(&self, node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) { (self.check)(node, ctx, out) }
+}
+
+/// A rule that scans the source text line by line.
+struct TextRule {
+    id: &'static str,
+    description: &'static str,
+    check: fn(&RuleContext, &mut Vec<LintIssue>),
+}
+
+impl Rule for TextRule {
+    fn id(&self) -> &'static str { self.id }
+    fn description(&self) -> &'static str { self.description }
+    fn check_text(&self, ctx: &RuleContext, out: &mut Vec<LintIssue>) { (self.check)(ctx, out) }
+}
+
+/// Every built-in rule, in the order `all_rules` lists them.
+static RULES: &[&dyn Rule] = &[
+    &NodeRule {
+        id: "no-wildcard-imports",
+        description: "Wildcard imports such as `import java.util.*;`",
+        kinds: &["import_declaration"],
+        check: check_wildcard_import,
+    },
+    &NodeRule {
+        id: "organize-imports",
+        description: "Imports not sorted, grouped per `[imports]` and free of duplicates",
+        kinds: &["program"],
+        check: check_import_order,
+    },
+    &NodeRule {
+        id: "unused-import",
+        description: "Imports whose name the file never uses",
+        kinds: &["import_declaration"],
+        check: check_unused_imports,
+    },
+    &NodeRule {
+        id: "no-empty-statement",
+        description: "Stray `;` statements",
+        kinds: &[";"],
+        check: check_empty_statement,
+    },
+    &NodeRule {
+        id: "continuation-indent",
+        description: "Wrapped lines not indented by `continuation_indent` or aligned with an open parenthesis",
+        kinds: LINE_CONTAINERS,
+        check: check_continuation_indent,
+    },
+    &NodeRule {
+        id: "indentation",
+        description: "Statements, declarations, `case` labels and closing braces at the wrong depth",
+        kinds: LINE_CONTAINERS,
+        check: check_indentation,
+    },
+    &NodeRule {
+        id: "operator-whitespace",
+        description: "Binary operators without a space on each side, unary operators apart from their operand",
+        kinds: &["binary_expression", "assignment_expression", "variable_declarator", "unary_expression", "update_expression"],
+        check: check_operator_whitespace,
+    },
+    &NodeRule {
+        id: "spacing",
+        description: "Spacing around keywords, braces, `;` and `,` per `[spacing]`",
+        kinds: SPACING_TOKENS,
+        check: check_spacing,
+    },
+    &NodeRule {
+        id: "class-naming",
+        description: "Class, interface, enum and record names not matching `naming.class`",
+        kinds: &["class_declaration", "interface_declaration", "enum_declaration", "record_declaration", "annotation_type_declaration"],
+        check: check_class_naming,
+    },
+    &NodeRule {
+        id: "method-naming",
+        description: "Method names not matching `naming.method`",
+        kinds: &["method_declaration"],
+        check: check_method_naming,
+    },
+    &NodeRule {
+        id: "constant-naming",
+        description: "`static final` field names not matching `naming.constant`",
+        kinds: &["field_declaration", "constant_declaration"],
+        check: check_constant_naming,
+    },
+    &NodeRule {
+        id: "package-naming",
+        description: "Package names not matching `naming.package`",
+        kinds: &["package_declaration"],
+        check: check_package_naming,
+    },
+    &NodeRule {
+        id: "one-top-level-class",
+        description: "Files declaring more than one top-level type",
+        kinds: TYPE_DECLARATIONS,
+        check: check_top_level_count,
+    },
+    &NodeRule {
+        id: "class-file-name",
+        description: "Public top-level types not named after their file",
+        kinds: TYPE_DECLARATIONS,
+        check: check_class_file_name,
+    },
+    &NodeRule {
+        id: "missing-javadoc",
+        description: "Public types, methods and constructors without a Javadoc comment",
+        kinds: &[
+            "class_declaration", "interface_declaration", "enum_declaration", "record_declaration", "annotation_type_declaration",
+            "method_declaration", "constructor_declaration",
+        ],
+        check: check_missing_javadoc,
+    },
+    &NodeRule {
+        id: "javadoc-tags",
+        description: "Javadoc `@param` and `@return` tags not matching the method they document",
+        kinds: &["method_declaration", "constructor_declaration"],
+        check: check_javadoc_tags,
+    },
+    &NodeRule {
+        id: "javadoc-format",
+        description: "Javadoc comments not aligned under `/**` or not wrapped to `max_line_length`",
+        kinds: &["block_comment"],
+        check: check_javadoc_format,
+    },
+    &NodeRule {
+        id: "modifier-order",
+        description: "Modifiers out of the order the JLS recommends (`public static final`)",
+        kinds: &["modifiers"],
+        check: check_modifier_order,
+    },
+    &NodeRule {
+        id: "annotation-placement",
+        description: "Declaration annotations sharing a line with what follows them",
+        kinds: &["modifiers"],
+        check: check_annotation_placement,
+    },
+    &NodeRule {
+        id: "require-braces",
+        description: "`if`, `for`, `while` and `do` bodies that are a single statement without braces",
+        kinds: &["if_statement", "for_statement", "enhanced_for_statement", "while_statement", "do_statement"],
+        check: check_require_braces,
+    },
+    &NodeRule {
+        id: "brace-style",
+        description: "Opening braces not placed as `brace_style` says",
+        kinds: &[
+            "class_declaration", "interface_declaration", "enum_declaration", "record_declaration",
+            "annotation_type_declaration", "method_declaration", "constructor_declaration", "if_statement",
+            "for_statement", "enhanced_for_statement", "while_statement", "do_statement", "try_statement",
+            "try_with_resources_statement", "catch_clause", "finally_clause", "synchronized_statement",
+            "switch_expression", "static_initializer",
+        ],
+        check: check_brace_style,
+    },
+    &NodeRule {
+        id: "switch-style",
+        description: "Switches not in the form `switch_style` prefers",
+        kinds: &["switch_block"],
+        check: check_switch_style,
+    },
+    &NodeRule {
+        id: "switch-fallthrough",
+        description: "Switch cases that fall through to the next without a `// fallthrough` comment",
+        kinds: &["switch_block"],
+        check: check_switch_fallthrough,
+    },
+    &NodeRule {
+        id: "missing-default",
+        description: "Switch statements without a `default` branch",
+        kinds: &["switch_expression"],
+        check: check_missing_default,
+    },
+    &NodeRule {
+        id: "switch-expression",
+        description: "Switch statements that read better as a switch expression",
+        kinds: &["switch_expression"],
+        check: check_switch_expression,
+    },
+    &NodeRule {
+        id: "max-method-length",
+        description: "Methods and constructors longer than `max_method_length`",
+        kinds: &["method_declaration", "constructor_declaration"],
+        check: check_method_length,
+    },
+    &NodeRule {
+        id: "max-parameters",
+        description: "Methods and constructors with more than `max_parameters` parameters",
+        kinds: &["method_declaration", "constructor_declaration"],
+        check: check_parameter_count,
+    },
+    &NodeRule {
+        id: "max-complexity",
+        description: "Methods and constructors with cyclomatic complexity above `max_complexity`",
+        kinds: &["method_declaration", "constructor_declaration"],
+        check: check_complexity,
+    },
+    &NodeRule {
+        id: "anonymous-class-size",
+        description: "Anonymous classes too long or with several methods",
+        kinds: &["object_creation_expression"],
+        check: check_anonymous_class,
+    },
+    &NodeRule {
+        id: "hardcoded-path",
+        description: "String literals holding absolute, machine-specific paths",
+        kinds: &["string_literal"],
+        check: check_hardcoded_path,
+    },
+    &NodeRule {
+        id: "locale-sensitive",
+        description: "Case conversion and formatting relying on the default locale",
+        kinds: &["method_invocation"],
+        check: check_locale_sensitive,
+    },
+    &NodeRule {
+        id: "no-console-output",
+        description: "`System.out`/`System.err` prints and `printStackTrace()` calls",
+        kinds: &["method_invocation"],
+        check: check_console_output,
+    },
+    &NodeRule {
+        id: "magic-number",
+        description: "Numeric literals outside constant declarations other than `[magic-numbers]` allows",
+        kinds: &[
+            "decimal_integer_literal", "hex_integer_literal", "octal_integer_literal", "binary_integer_literal",
+            "decimal_floating_point_literal", "hex_floating_point_literal",
+        ],
+        check: check_magic_number,
+    },
+    &NodeRule {
+        id: "null-collection-return",
+        description: "`return null;` from methods returning an array, collection or `Optional`",
+        kinds: &["return_statement"],
+        check: check_null_collection_return,
+    },
+    &NodeRule {
+        id: "bigdecimal-double",
+        description: "`new BigDecimal(double)` capturing a binary approximation",
+        kinds: &["object_creation_expression"],
+        check: check_bigdecimal_double,
+    },
+    &NodeRule {
+        id: "string-equality",
+        description: "`==` and `!=` on strings",
+        kinds: &["binary_expression"],
+        check: check_string_equality,
+    },
+    &NodeRule {
+        id: "float-equality",
+        description: "`==` and `!=` on floating-point values",
+        kinds: &["binary_expression"],
+        check: check_float_equality,
+    },
+    &NodeRule {
+        id: "array-to-string",
+        description: "Arrays printed or concatenated without `Arrays.toString`",
+        kinds: &["identifier"],
+        check: check_array_to_string,
+    },
+    &NodeRule {
+        id: "legacy-date-time",
+        description: "`Date`, `Calendar` and `SimpleDateFormat` where `java.time` applies",
+        kinds: &["import_declaration", "object_creation_expression"],
+        check: check_legacy_date_time,
+    },
+    &NodeRule {
+        id: "insecure-random",
+        description: "`Random` and `Math.random()` in security-sensitive code",
+        kinds: &["object_creation_expression", "method_invocation"],
+        check: check_insecure_random,
+    },
+    &NodeRule {
+        id: "missing-charset",
+        description: "Byte/char conversions relying on the platform default charset",
+        kinds: &["object_creation_expression", "method_invocation"],
+        check: check_missing_charset,
+    },
+    &NodeRule {
+        id: "unclosed-resource",
+        description: "Streams, readers and connections opened outside try-with-resources and never closed",
+        kinds: &["local_variable_declaration"],
+        check: check_unclosed_resource,
+    },
+    &NodeRule {
+        id: "swallowed-exception",
+        description: "Catch blocks that neither rethrow, log nor use the exception",
+        kinds: &["catch_clause"],
+        check: check_swallowed_exception,
+    },
+    &NodeRule {
+        id: "equals-hashcode",
+        description: "Classes overriding `equals(Object)` without `hashCode()`, or the reverse",
+        kinds: &["class_body", "enum_body_declarations"],
+        check: check_equals_hashcode,
+    },
+    &NodeRule {
+        id: "empty-catch",
+        description: "Catch blocks with an empty body",
+        kinds: &["catch_clause"],
+        check: check_empty_catch,
+    },
+    &NodeRule {
+        id: "unused-variable",
+        description: "Local variables and parameters that are never read",
+        kinds: VARIABLE_OWNERS,
+        check: check_unused_variables,
+    },
+    &NodeRule {
+        id: "instanceof-pattern",
+        description: "Casts right after an `instanceof` check a pattern would make redundant",
+        kinds: &["if_statement"],
+        check: check_instanceof_pattern,
+    },
+    &NodeRule {
+        id: "no-blank-line-at-block-boundary",
+        description: "Blank lines right after `{` or right before `}`",
+        kinds: &["class_body", "interface_body", "enum_body", "annotation_type_body", "constructor_body", "block"],
+        check: check_block_boundary_blank_lines,
+    },
+    &NodeRule {
+        id: "blank-line-between-methods",
+        description: "Methods and constructors not set apart from other members by `blank_lines_between_methods` blank lines",
+        kinds: &["class_body", "interface_body", "enum_body_declarations"],
+        check: check_blank_lines_between_methods,
+    },
+    &NodeRule {
+        id: "spellcheck",
+        description: "Known misspellings in declared names and comments",
+        kinds: &["program", "line_comment", "block_comment", "identifier", "type_identifier"],
+        check: check_spelling,
+    },
+    &TextRule { id: "max-line-length", description: "Lines longer than `max_line_length`", check: collect_line_length },
+    &TextRule { id: "indent-style", description: "Indentation not matching `indent_style`", check: collect_indent_style },
+    &TextRule { id: "no-trailing-whitespace", description: "Spaces and tabs at the end of a line", check: collect_trailing_whitespace },
+    &TextRule { id: "max-blank-lines", description: "More blank lines in a row than `max_blank_lines`", check: collect_blank_line_runs },
+    &TextRule { id: "line-ending", description: "Line breaks other than `line_ending` asks for", check: collect_line_endings },
+    &TextRule { id: "eof-newline", description: "Files not ending in a newline, or in more than one", check: collect_eof_newline },
+    &TextRule {
+        id: "license-header",
+        description: "Files not starting with the configured `license_header`",
+        check: collect_license_header,
+    },
+    // Reported by `suppress::apply` once every other rule has run.
+    &TextRule {
+        id: "unused-suppression",
+        description: "Suppressions that suppressed nothing or name an unknown rule",
+        check: |_, _| {},
+    },
+];
+
+/// Every built-in rule, for listing and documentation.
+pub fn registry() -> &'static [&'static dyn Rule] { RULES }
+
+/// Every rule id this crate can emit, with how it inspects files.
+pub fn all_rules() -> impl Iterator<Item = (&'static str, RuleKind)> {
+    RULES.iter().map(|r| (r.id(), r.kind()))
+}
+
+/// Map a rule id onto the static id of a known rule.
+pub(crate) fn known_rule_id(id: &str) -> Option<&'static str> {
+    all_rules().map(|(r, _)| r).find(|r| *r == id)
+}
+
+/// Like `known_rule_id`, but a deprecated id maps to its replacement.
+pub(crate) fn resolve_rule_id(id: &str) -> Option<&'static str> {
+    match DEPRECATED_RULES.iter().find(|d| d.id == id) {
+        Some(dep) => dep.replacement.and_then(known_rule_id),
+        None => known_rule_id(id),
+    }
+}
+
+/// The effective set of enabled rules.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleSet {
+    enabled: BTreeSet<&'static str>,
+}
+
+impl Default for RuleSet {
+    fn default() -> Self { Self::all() }
+}
+
+impl RuleSet {
+    pub fn all() -> Self {
+        Self { enabled: all_rules().map(|(id, _)| id).collect() }
+    }
+
+    /// Only the given rules; unknown ids are ignored and deprecated ones
+    /// stand for their replacement.
+    pub fn only<'a>(ids: impl IntoIterator<Item = &'a str>) -> Self {
+        Self { enabled: ids.into_iter().filter_map(resolve_rule_id).collect() }
+    }
+
+    pub fn disable(&mut self, id: &str) {
+        if let Some(id) = resolve_rule_id(id) {
+            self.enabled.remove(id);
+        }
+    }
+
+    /// This set minus `ids`; unknown ids are ignored.
+    pub fn without<'a>(&self, ids: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut rules = self.clone();
+        for id in ids {
+            rules.disable(id);
+        }
+        rules
+    }
+
+    pub fn contains(&self, id: &str) -> bool { self.enabled.contains(id) }
+
+    pub fn ids(&self) -> impl Iterator<Item = &'static str> + '_ { self.enabled.iter().copied() }
+
+    /// Whether any enabled rule needs a syntax tree; if not, parsing is skipped.
+    pub fn needs_tree(&self) -> bool {
+        RULES.iter().any(|r| r.kind() == RuleKind::Ast && self.contains(r.id()))
+    }
+}
+
+/// Rules indexed by node kind id. Built once per process; a kind name can map
+/// to several ids (aliases), so every id is matched by name.
+fn rules_by_kind() -> &'static [Vec<&'static dyn Rule>] {
+    static INDEX: OnceLock<Vec<Vec<&'static dyn Rule>>> = OnceLock::new();
+    INDEX.get_or_init(|| {
+        let Ok(lang) = java_language() else { return Vec::new() };
+        (0..lang.node_kind_count() as u16)
+            .map(|id| {
+                let name = lang.node_kind_for_id(id).unwrap_or_default();
+                RULES.iter().copied().filter(|r| r.node_kinds().contains(&name)).collect()
+            })
+            .collect()
+    })
+}
+
+/// Walk the tree once in document order, dispatching nodes to node rules.
+/// With `stats`, each dispatch is timed and attributed to its rule. With
+/// `annotations`, every `annotation` node is collected for suppressions.
+pub(crate) fn run_node_rules<'t>(
+    root: Node<'t>,
+    ctx: &RuleContext,
+    out: &mut Vec<LintIssue>,
+    mut annotations: Option<&mut Vec<Node<'t>>>,
+    mut stats: Option<&mut LintStats>,
+) {
+    let index = rules_by_kind();
+    let mut cursor = root.walk();
+    loop {
+        let node = cursor.node();
+        if let Some(annotations) = annotations.as_deref_mut()
+            && node.kind() == "annotation"
+        {
+            annotations.push(node);
+        }
+        if let Some(rules) = index.get(node.kind_id() as usize) {
+            for rule in rules.iter().filter(|r| ctx.rules.contains(r.id())) {
+                match stats.as_deref_mut() {
+                    Some(stats) => {
+                        let started = Instant::now();
+                        rule.check_node(node, ctx, out);
+                        stats.add_rule_time(rule.id(), started.elapsed());
+                    }
+                    None => rule.check_node(node, ctx, out),
+                }
+            }
+        }
+        if cursor.goto_first_child() { continue; }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() { return; }
+        }
+    }
+}
+
+pub(crate) fn run_text_rules(ctx: &RuleContext, out: &mut Vec<LintIssue>, mut stats: Option<&mut LintStats>) {
+    for rule in RULES.iter().filter(|r| r.kind() == RuleKind::Text && ctx.rules.contains(r.id())) {
+        let started = Instant::now();
+        rule.check_text(ctx, out);
+        if let Some(stats) = stats.as_deref_mut() {
+            stats.add_rule_time(rule.id(), started.elapsed());
+        }
+    }
+}
+
+fn issue_at(node: Node, rule_id: &'static str, message: impl Into<Cow<'static, str>>) -> LintIssue {
+    LintIssue { end_offset: node.end_byte(), ..LintIssue::new(rule_id, message, node.start_byte()) }
+}
+
+fn check_wildcard_import(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    // Heuristic: check the import text for ".*;"
+    if let Ok(text) = node.utf8_text(ctx.source.as_bytes())
+        && text.contains(".*")
+        && !(ctx.config.allow_static_wildcard_imports && text.split_whitespace().nth(1) == Some("static"))
+    {
+        let mut issue = issue_at(node, "no-wildcard-imports", "Avoid wildcard imports (use explicit classes)");
+        // Expanded from the names the file uses; a type from the file's own
+        // package would be imported from the wildcard's, hence only a guess.
+        if let Some(program) = node.parent()
+            && let Some(names) = imports::expand_wildcard(program, ctx.source)
+            && ImportDecl::from_node(node, ctx.source).simple_name().is_none()
+            && !text.split_whitespace().any(|w| w == "static")
+        {
+            let line = &ctx.source[ctx.source[..node.start_byte()].rfind('\n').map_or(0, |i| i + 1)..node.start_byte()];
+            let newline = ctx.config.line_ending.resolve(ctx.source);
+            let separator = format!("{newline}{}", if line.trim().is_empty() { line } else { "" });
+            let replacement = names.iter().map(|n| format!("import {n};")).collect::<Vec<_>>().join(&separator);
+            issue.fix = Some(Fix {
+                start_byte: node.start_byte(),
+                end_byte: node.end_byte(),
+                replacement: Cow::Owned(replacement),
+                applicability: Applicability::MaybeIncorrect,
+            });
+        }
+        out.push(issue);
+    }
+}
+
+/// Compare the import block with its organized form and, if they differ,
+/// replace the whole block in one fix.
+fn check_import_order(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let Some((nodes, range)) = imports::import_block(node) else { return };
+    let decls: Vec<ImportDecl> = nodes.iter().map(|n| ImportDecl::from_node(*n, ctx.source)).collect();
+    let newline = ctx.config.line_ending.resolve(ctx.source);
+    let organized = imports::organize(&decls, &ctx.config.imports, newline);
+    if ctx.source[range.clone()] == organized { return; }
+    // Drop what `unused-import` would delete, since this fix overlaps its fixes.
+    let organized = if ctx.rules.contains("unused-import") {
+        let referenced = ctx.referenced_names(node);
+        let used: Vec<ImportDecl> = decls.into_iter().filter(|d| !imports::is_unused(d, referenced)).collect();
+        imports::organize(&used, &ctx.config.imports, newline)
+    } else {
+        organized
+    };
+    let mut issue = LintIssue::new("organize-imports", "Imports are not sorted, grouped and deduplicated", range.start);
+    issue.end_offset = range.end;
+    issue.fix = Some(Fix {
+        start_byte: range.start,
+        end_byte: range.end,
+        replacement: Cow::Owned(organized),
+        applicability: Applicability::Safe,
+    });
+    out.push(issue);
+}
+
+fn check_unused_imports(import: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let Some(root) = import.parent() else { return };
+    let decl = ImportDecl::from_node(import, ctx.source);
+    if !imports::is_unused(&decl, ctx.referenced_names(root)) { return; }
+    let range = imports::line_range(import, ctx.source);
+    let mut issue = issue_at(import, "unused-import", format!("Unused import `{}`", decl.name));
+    issue.fix = Some(Fix { start_byte: range.start, end_byte: range.end, replacement: Cow::Borrowed(""), applicability: Applicability::Safe });
+    out.push(issue);
+}
+
+fn check_empty_statement(node: Node, _ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    // The grammar has no empty statement node: a `;` standing alone as a
+    // statement sits directly in the block. Other `;` tokens end a statement
+    // or declaration, and one that is an `if`/loop body can't be dropped.
+    if !node.parent().is_some_and(|p| matches!(p.kind(), "block" | "constructor_body" | "switch_block_statement_group")) {
+        return;
+    }
+    let mut issue = issue_at(
+        node,
+        "no-empty-statement",
+        "Remove unnecessary empty statement",
+    );
+    issue.fix = Some(Fix {
+        start_byte: node.start_byte(),
+        end_byte: node.end_byte(),
+        replacement: Cow::Borrowed(""),
+        applicability: Applicability::Safe,
+    });
+    out.push(issue);
+}
+
+fn collect_line_length(ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let max_len = ctx.config.max_line_length as usize;
+    let (unit, tab_width) = (ctx.config.line_length_unit, ctx.config.tab_width as usize);
+    let mut line_start = 0usize;
+    for line_inc in ctx.source.split_inclusive('\n') {
+        let start = line_start;
+        line_start += line_inc.len();
+        let line = line_inc.strip_suffix('\n').unwrap_or(line_inc);
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        // Cheap pre-check: only tabs can make a line measure longer than its byte length.
+        if line.len() <= max_len && !line.contains('\t') { continue; }
+        let visual_len = unit.measure(line, tab_width);
+        if visual_len > max_len {
+            // Point at the first character that no longer fits.
+            let mut used = 0;
+            let overflow = line
+                .char_indices()
+                .find(|(_, c)| {
+                    used = unit.advance(used, *c, tab_width);
+                    used > max_len
+                })
+                .map_or(0, |(i, _)| i);
+            if line_length_exempt(ctx.config, line, overflow) { continue; }
+            out.push(LintIssue::new(
+                "max-line-length",
+                format!("Line exceeds {} {} (was {})", max_len, unit.noun(), visual_len),
+                start + overflow,
+            ));
+        }
+    }
+}
+
+/// Whether a long line is exempt because it cannot be meaningfully wrapped:
+/// package/imports, or an overflow that falls inside one unbreakable token.
+fn line_length_exempt(config: &Config, line: &str, overflow: usize) -> bool {
+    let trimmed = line.trim_start();
+    if config.max_line_length_ignore_imports
+        && (trimmed.starts_with("import ") || trimmed.starts_with("package "))
+    {
+        return true;
+    }
+    let inside = |(start, end): (usize, usize)| start <= overflow && overflow < end;
+    if config.max_line_length_ignore_strings
+        && (string_literal_spans(line).any(inside) || url_spans(line).any(inside))
+    {
+        return true;
+    }
+    config.max_line_length_ignore_links && link_spans(line).any(inside)
+}
+
+/// Byte ranges of `"..."` literals on a line, honouring backslash escapes.
+fn string_literal_spans(line: &str) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let bytes = line.as_bytes();
+    let mut i = 0;
+    std::iter::from_fn(move || {
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\'' => i += if bytes.get(i + 1) == Some(&b'\\') { 4 } else { 3 }, // skip char literals like '"'
+                b'"' => {
+                    let start = i;
+                    i += 1;
+                    while i < bytes.len() && bytes[i] != b'"' {
+                        i += if bytes[i] == b'\\' { 2 } else { 1 };
+                    }
+                    i += 1;
+                    return Some((start, i.min(bytes.len())));
+                }
+                _ => i += 1,
+            }
+        }
+        None
+    })
+}
+
+/// Byte ranges of URLs (anything around a `://`, up to whitespace or quotes).
+fn url_spans(line: &str) -> impl Iterator<Item = (usize, usize)> + '_ {
+    let is_delim = |c: char| c.is_whitespace() || matches!(c, '"' | '(' | ')' | '<' | '>');
+    line.match_indices("://").map(move |(at, _)| {
+        let start = line[..at].rfind(is_delim).map_or(0, |i| i + 1);
+        let end = line[at..].find(is_delim).map_or(line.len(), |i| at + i);
+        (start, end)
+    })
+}
+
+/// Byte ranges of Javadoc `{@link ...}` / `{@linkplain ...}` tags.
+fn link_spans(line: &str) -> impl Iterator<Item = (usize, usize)> + '_ {
+    line.match_indices("{@link").map(move |(at, _)| {
+        (at, line[at..].find('}').map_or(line.len(), |i| at + i + 1))
+    })
+}
+
+/// Nodes whose children each start a fresh line rather than continue one.
+pub(crate) const LINE_CONTAINERS: &[&str] = &[
+    "program",
+    "block",
+    "constructor_body",
+    "class_body",
+    "interface_body",
+    "enum_body",
+    "enum_body_declarations",
+    "annotation_type_body",
+    "switch_block",
+    "switch_block_statement_group",
+    "array_initializer",
+    "element_value_array_initializer",
+];
+
+/// Tokens that may open a line at the statement's own indentation.
+const LINE_OPENERS: &[&str] = &["{", "}", ")", "]", "else", "catch", "finally", "line_comment", "block_comment"];
+
+/// Lines that continue a statement or declaration started on an earlier line
+/// must be indented by a multiple of `continuation_indent` past the line the
+/// statement starts on, or aligned just inside an open parenthesis.
+///
+/// Runs on each line container and checks the lines of its direct children;
+/// lines owned by a nested container are left to that container's visit.
+fn check_continuation_indent(container: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let step = ctx.config.continuation_indent as usize;
+    if step == 0 { return; }
+    let (source, tab_width) = (ctx.source, ctx.config.tab_width as usize);
+    let mut cursor = container.walk();
+    for stmt in container.named_children(&mut cursor) {
+        let head = declaration_head(stmt);
+        let end = stmt.end_byte();
+        // Continuation lines start after the line holding the head.
+        let Some(mut line_start) = source[head..end].find('\n').map(|i| head + i + 1) else { continue };
+        while line_start < end {
+            let line_inc = source[line_start..].split_inclusive('\n').next().unwrap_or_default();
+            let start = line_start;
+            line_start += line_inc.len();
+            let Some(ws) = line_inc.find(|c: char| c != ' ' && c != '\t') else { continue };
+            if matches!(line_inc.as_bytes()[ws], b'\n' | b'\r') { continue; }
+            let first = start + ws;
+            if first >= end { break; }
+            let Some(token) = stmt.descendant_for_byte_range(first, first) else { continue };
+            // Lines inside multi-line comments or text blocks don't start a token.
+            if token.start_byte() != first || LINE_OPENERS.contains(&token.kind()) { continue; }
+            if token.parent().is_some_and(|p| matches!(p.kind(), "string_literal" | "text_block")) { continue; }
+            if !owned_by(token, stmt) || labeled_body(stmt, token) { continue; }
+
+            let base = display_column(source, line_indent(source, head), tab_width);
+            let indent = display_column(source, first, tab_width);
+            if indent > base && (indent - base).is_multiple_of(step) { continue; }
+            if paren_alignments(token, stmt).any(|at| display_column(source, at, tab_width) == indent) {
+                continue;
+            }
+            let mut issue = LintIssue::new(
+                "continuation-indent",
+                format!("Continuation line should be indented by a multiple of {step} past its statement or aligned with an open parenthesis"),
+                first,
+            );
+            // Round down to a whole number of steps, keeping at least one.
+            let expected = base + step * (indent.saturating_sub(base) / step).max(1);
+            issue.fix = Some(Fix {
+                start_byte: start,
+                end_byte: first,
+                replacement: indent_whitespace(ctx.config, expected),
+                applicability: Applicability::Safe,
+            });
+            out.push(issue);
+        }
+    }
+}
+
+/// Whether `stmt` is the outermost statement holding `token`, i.e. no line
+/// container sits between them.
+fn owned_by(token: Node, stmt: Node) -> bool {
+    let mut node = token;
+    while node.id() != stmt.id() {
+        match node.parent() {
+            Some(parent) if !LINE_CONTAINERS.contains(&parent.kind()) => node = parent,
+            _ => return false,
+        }
+    }
+    true
+}
+
+/// Every line that starts a statement, declaration or closing brace must be
+/// indented one level per enclosing body, block, switch case or array
+/// initializer. A nested body's level is counted from the line its `{` is
+/// on, so a lambda or anonymous class passed on a continuation line is
+/// judged from there. Columns are measured with tabs expanded, so a line using
+/// the wrong indent character only fires when its depth is off too, and then
+/// its fix replaces the one `indent-style` would make. Continuation lines are
+/// left to `continuation-indent`.
+///
+/// Runs on each line container and checks the lines its direct children
+/// start, like `continuation-indent`.
+fn check_indentation(container: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let (source, tab_width) = (ctx.source, ctx.config.tab_width as usize);
+    let level = match ctx.config.indent_style {
+        IndentStyle::Spaces => ctx.config.indent_width as usize,
+        IndentStyle::Tabs => tab_width,
+    };
+    if level == 0 { return; }
+    let root = std::iter::successors(Some(container), |n| n.parent()).last().unwrap_or(container);
+    let nesting = Nesting { source, root, level, tab_width, case_labels: ctx.config.indent_case_labels };
+    let mut cursor = container.walk();
+    for stmt in container.children(&mut cursor) {
+        let end = stmt.end_byte();
+        let mut line_start = line_start(source, stmt.start_byte());
+        while line_start < end {
+            let start = line_start;
+            line_start += source[start..].find('\n').map_or(source.len() - start, |i| i + 1);
+            let first = line_indent(source, start);
+            if first < stmt.start_byte() { continue; }
+            if first >= end { break; }
+            let Some(token) = nesting.first_token(start) else { continue };
+            if !owned_by(token, stmt) { continue; }
+            let Some(expected) = nesting.expected(start) else { continue };
+            let actual = display_column(source, first, tab_width);
+            if actual == expected { continue; }
+            let mut issue = LintIssue::new(
+                "indentation",
+                format!("Expected indentation of {expected} columns, found {actual}"),
+                first,
+            );
+            let replacement = indent_whitespace(ctx.config, expected);
+            issue.fix = Some(Fix { start_byte: start, end_byte: first, replacement, applicability: Applicability::Safe });
+            out.push(issue);
+        }
+    }
+}
+
+/// Expected indentation of lines, derived from the syntax tree. Lines are
+/// named by the byte offset they start at.
+struct Nesting<'a, 't> {
+    source: &'a str,
+    root: Node<'t>,
+    /// Columns per indentation level.
+    level: usize,
+    tab_width: usize,
+    /// Whether `case` labels are indented inside their `switch`.
+    case_labels: bool,
+}
+
+impl<'t> Nesting<'_, 't> {
+    /// Expected column of the first token on the line starting at `line`, or
+    /// `None` when the line continues a statement, sits inside a comment or
+    /// text block, or its indentation isn't decided by nesting (`{` or `)`
+    /// opening a line).
+    fn expected(&self, line: usize) -> Option<usize> {
+        let token = self.first_token(line)?;
+        if token.kind() == "}" {
+            let container = token.parent().filter(|p| LINE_CONTAINERS.contains(&p.kind()))?;
+            let open = container.child(0).filter(|c| c.kind() == "{")?;
+            return Some(self.line_column(open.start_byte()));
+        }
+        if LINE_OPENERS.contains(&token.kind()) && !matches!(token.kind(), "line_comment" | "block_comment") {
+            return None;
+        }
+        let mut stmt = token;
+        while let Some(parent) = stmt.parent() {
+            if LINE_CONTAINERS.contains(&parent.kind()) { break; }
+            stmt = parent;
+        }
+        let container = stmt.parent()?;
+        let head = declaration_head(stmt);
+        if token.start_byte() > head && self.source[head..token.start_byte()].contains('\n') && !labeled_body(stmt, token) {
+            return None;
+        }
+        // `{a, b,\n c}` aligns its elements rather than indenting them.
+        if let Some(open) = container.child(0).filter(|c| c.kind() == "{")
+            && open.next_sibling().is_some_and(|n| n.id() != stmt.id() && n.start_position().row == open.start_position().row)
+        {
+            return None;
+        }
+        Some(self.inside(container, stmt))
+    }
+
+    /// Expected column of `stmt`, a child starting a line in `container`.
+    fn inside(&self, container: Node<'t>, stmt: Node<'t>) -> usize {
+        match container.kind() {
+            "program" => 0,
+            "enum_body_declarations" => container.parent().map_or(0, |body| self.inside(body, stmt)),
+            "switch_block_statement_group" => {
+                let labels = container.parent().map_or(0, |block| self.inside(block, container));
+                if stmt.kind() == "switch_label" { labels } else { labels + self.level }
+            }
+            _ => {
+                let base = container.child(0).map_or(0, |open| self.line_column(open.start_byte()));
+                if container.kind() == "switch_block" && !self.case_labels { base } else { base + self.level }
+            }
+        }
+    }
+
+    /// Expected column of the line containing `offset`, or its actual one
+    /// where nesting doesn't decide it.
+    fn line_column(&self, offset: usize) -> usize {
+        self.expected(line_start(self.source, offset))
+            .unwrap_or_else(|| display_column(self.source, line_indent(self.source, offset), self.tab_width))
+    }
+
+    /// The token starting the line at `line` after its indentation, if any.
+    fn first_token(&self, line: usize) -> Option<Node<'t>> {
+        let first = line_indent(self.source, line);
+        if matches!(self.source.as_bytes().get(first), None | Some(b'\n' | b'\r')) { return None; }
+        let token = self.root.descendant_for_byte_range(first, first)?;
+        if token.start_byte() != first { return None; }
+        if token.parent().is_some_and(|p| matches!(p.kind(), "string_literal" | "text_block")) { return None; }
+        Some(token)
+    }
+}
+
+/// Whether `token` starts the statement of `stmt`, a label on its own line,
+/// which is indented like the label rather than as a continuation.
+pub(crate) fn labeled_body(stmt: Node, token: Node) -> bool {
+    stmt.kind() == "labeled_statement"
+        && stmt.named_child(stmt.named_child_count().saturating_sub(1)).is_some_and(|body| body.start_byte() == token.start_byte())
+}
+
+/// Where a statement or declaration really starts: after any annotations on
+/// their own lines, so `@Override` doesn't make the signature a continuation.
+pub(crate) fn declaration_head(stmt: Node) -> usize {
+    let Some(modifiers) = stmt.child(0).filter(|c| c.kind() == "modifiers") else { return stmt.start_byte() };
+    let mut cursor = modifiers.walk();
+    let first_keyword = modifiers
+        .children(&mut cursor)
+        .find(|c| !matches!(c.kind(), "annotation" | "marker_annotation" | "line_comment" | "block_comment"));
+    first_keyword
+        .or_else(|| modifiers.next_sibling())
+        .map_or(stmt.start_byte(), |n| n.start_byte())
+}
+
+/// Byte offset where the line containing `offset` starts.
+fn line_start(source: &str, offset: usize) -> usize {
+    source[..offset].rfind('\n').map_or(0, |i| i + 1)
+}
+
+/// Byte offset of the first non-blank character on the line containing `offset`.
+fn line_indent(source: &str, offset: usize) -> usize {
+    let start = line_start(source, offset);
+    start + source[start..].len() - source[start..].trim_start_matches([' ', '\t']).len()
+}
+
+/// 0-based display column of `offset`, without building a `LineIndex` for
+/// rules that only look at a few lines.
+fn display_column(source: &str, offset: usize, tab_width: usize) -> usize {
+    source[line_start(source, offset)..offset].chars().fold(0, |col, c| advance_column(col, c, tab_width))
+}
+
+/// Offsets just inside each `(` enclosing `token` (up to `stmt`) that is
+/// followed by more code on its own line; those are valid alignment points.
+fn paren_alignments<'t>(token: Node<'t>, stmt: Node<'t>) -> impl Iterator<Item = usize> + 't {
+    let first = token.start_byte();
+    std::iter::successors(token.parent(), move |n| (n.id() != stmt.id()).then(|| n.parent()).flatten())
+        .filter_map(move |node| {
+            let mut cursor = node.walk();
+            let open = node
+                .children(&mut cursor)
+                .take_while(|c| c.start_byte() < first)
+                .filter(|c| c.kind() == "(")
+                .last()?;
+            let next = open.next_sibling().filter(|n| n.start_position().row == open.start_position().row)?;
+            (next.start_byte() < first).then_some(next.start_byte())
+        })
+}
+
+/// Nodes whose `name` field declares an identifier.
+const DECLARATIONS: &[&str] = &[
+    "class_declaration",
+    "interface_declaration",
+    "enum_declaration",
+    "record_declaration",
+    "annotation_type_declaration",
+    "method_declaration",
+    "constructor_declaration",
+    "variable_declarator",
+    "formal_parameter",
+    "catch_formal_parameter",
+    "enum_constant",
+];
+
+/// Opt-in: flag known misspellings in declared names and comments. Typos in
+/// comments get a fix; renaming an identifier is left to the author.
+fn check_spelling(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let Some(dict) = ctx.spelling else { return };
+    match node.kind() {
+        "program" => {
+            if let Some(err) = &dict.error {
+                out.push(issue_at(node, "spellcheck", err.clone()));
+            }
+        }
+        "line_comment" | "block_comment" => {
+            let text = &ctx.source[node.byte_range()];
+            for (at, word) in spellcheck::words(text) {
+                let Some(correction) = dict.correction(word) else { continue };
+                let start = node.start_byte() + at;
+                let mut issue = LintIssue::new("spellcheck", typo_message(word, correction), start);
+                issue.fix = Some(Fix {
+                    start_byte: start,
+                    end_byte: start + word.len(),
+                    replacement: spellcheck::match_case(word, correction).into(),
+                    applicability: Applicability::MaybeIncorrect,
+                });
+                out.push(issue);
+            }
+        }
+        _ => {
+            // Only declarations; uses of a name would repeat the same report.
+            let declared = node.parent().is_some_and(|p| {
+                DECLARATIONS.contains(&p.kind()) && p.child_by_field_name("name").is_some_and(|n| n.id() == node.id())
+            });
+            if !declared { return; }
+            let text = &ctx.source[node.byte_range()];
+            for (at, word) in spellcheck::words(text) {
+                if let Some(correction) = dict.correction(word) {
+                    out.push(LintIssue::new("spellcheck", typo_message(word, correction), node.start_byte() + at));
+                }
+            }
+        }
+    }
+}
+
+fn typo_message(word: &str, correction: &str) -> String {
+    format!("Possible typo '{word}' (did you mean '{correction}'?)")
+}
+
+/// Binary and assignment operators take a space on each side; unary and
+/// increment operators none between them and their operand. A side that
+/// breaks the line is left to the indentation rules.
+fn check_operator_whitespace(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let source = ctx.source;
+    if matches!(node.kind(), "unary_expression" | "update_expression") {
+        let (Some(first), Some(second)) = (node.child(0), node.child(1)) else { return };
+        let gap = &source[first.end_byte()..second.start_byte()];
+        if gap.is_empty() || !gap.bytes().all(|b| b == b' ' || b == b'\t') { return; }
+        let (op, side) = if first.is_named() { (second, "before") } else { (first, "after") };
+        let op_text = &source[op.byte_range()];
+        // `- -x` and `+ +x` need the space to stay two operators.
+        if side == "after" && source[second.byte_range()].starts_with(['+', '-']) { return; }
+        let mut issue = LintIssue::new("operator-whitespace", format!("Unexpected space {side} '{op_text}'"), first.end_byte());
+        issue.fix = Some(Fix {
+            start_byte: first.end_byte(),
+            end_byte: second.start_byte(),
+            replacement: Cow::Borrowed(""),
+            applicability: Applicability::Safe,
+        });
+        out.push(issue);
+        return;
+    }
+
+    let op = match node.kind() {
+        "variable_declarator" => {
+            let mut cursor = node.walk();
+            node.children(&mut cursor).find(|c| c.kind() == "=")
+        }
+        _ => node.child_by_field_name("operator"),
+    };
+    let Some(op) = op else { return };
+    let (Some(left), Some(right)) = (op.prev_sibling(), op.next_sibling()) else { return };
+    let op_text = &source[op.byte_range()];
+    for (side, gap, at) in [("before", left.end_byte()..op.start_byte(), op.start_byte()), ("after", op.end_byte()..right.start_byte(), op.end_byte())] {
+        if !gap.is_empty() { continue; }
+        let mut issue = LintIssue::new("operator-whitespace", format!("Missing space {side} '{op_text}'"), at);
+        issue.fix = Some(Fix { start_byte: at, end_byte: at, replacement: Cow::Borrowed(" "), applicability: Applicability::Safe });
+        out.push(issue);
+    }
+}
+
+/// Blank lines right after a body's `{` or right before its `}`. An empty
+/// body with only blank lines is reported once.
+fn check_block_boundary_blank_lines(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let source = ctx.source;
+    let (Some(open), Some(close)) = (node.child(0), node.child(node.child_count().saturating_sub(1))) else { return };
+    if open.kind() != "{" || close.kind() != "}" { return; }
+    let is_blank = |line: &str| line.trim().is_empty();
+    let mut push = |start: usize, end: usize, message: &'static str| {
+        let mut issue = LintIssue::new("no-blank-line-at-block-boundary", message, start);
+        issue.fix = Some(Fix { start_byte: start, end_byte: end, replacement: Cow::Borrowed(""), applicability: Applicability::Safe });
+        out.push(issue);
+    };
+
+    // After `{`: only when nothing else follows it on its line.
+    let mut after_open = None;
+    if let Some(eol) = source[open.end_byte()..close.start_byte()].find('\n').map(|i| open.end_byte() + i)
+        && is_blank(&source[open.end_byte()..eol])
+    {
+        let start = eol + 1;
+        let mut end = start;
+        while let Some(len) = source[end..close.start_byte()].find('\n') {
+            if !is_blank(&source[end..end + len]) { break; }
+            end += len + 1;
+        }
+        if end > start {
+            push(start, end, "Remove blank lines after '{'");
+            after_open = Some(start);
+        }
+    }
+
+    // Before `}`: only when it starts its line.
+    let line_start = source[..close.start_byte()].rfind('\n').map_or(0, |i| i + 1);
+    if line_start <= open.end_byte() || !is_blank(&source[line_start..close.start_byte()]) { return; }
+    let mut start = line_start;
+    while start > open.end_byte() {
+        let prev = source[..start - 1].rfind('\n').map_or(0, |i| i + 1);
+        if prev <= open.end_byte() || !is_blank(&source[prev..start]) { break; }
+        start = prev;
+    }
+    if start < line_start && after_open != Some(start) {
+        push(start, line_start, "Remove blank lines before '}'");
+    }
+}
+
+/// Runs of more than `max_blank_lines` blank lines, outside block comments
+/// and text blocks. Blank lines ending the file are left to `eof-newline`.
+fn collect_blank_line_runs(ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let max = ctx.config.max_blank_lines as usize;
+    let blocks = multiline_blocks(ctx.source);
+    let mut run: Vec<usize> = Vec::new(); // starts of the blank lines in the current run
+    let mut line_start = 0;
+    for line in ctx.source.split_inclusive('\n') {
+        let start = line_start;
+        line_start += line.len();
+        if line.ends_with('\n') && line.trim().is_empty() {
+            run.push(start);
+            continue;
+        }
+        if run.len() > max && !blocks.iter().any(|b| b.contains(&run[0])) {
+            let mut issue = LintIssue::new(
+                "max-blank-lines",
+                format!("{} blank lines in a row; at most {max} allowed", run.len()),
+                run[max],
+            );
+            issue.fix = Some(Fix { start_byte: run[max], end_byte: start, replacement: Cow::Borrowed(""), applicability: Applicability::Safe });
+            out.push(issue);
+        }
+        run.clear();
+    }
+}
+
+/// Members next to a method or constructor in a class, interface or enum
+/// body with fewer than `blank_lines_between_methods` blank lines between
+/// them. Comments before a member belong to it; a comment on the line a
+/// member ends on belongs to that member.
+fn check_blank_lines_between_methods(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let required = ctx.config.blank_lines_between_methods as usize;
+    if required == 0 { return; }
+    let source = ctx.source;
+    let mut prev: Option<(usize, bool)> = None; // where the previous member ends, and whether it is a method
+    let mut lead = None; // start of the comments before the current member
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        let is_method = matches!(child.kind(), "method_declaration" | "constructor_declaration");
+        if matches!(child.kind(), "line_comment" | "block_comment") {
+            match prev {
+                Some((end, was_method)) if lead.is_none() && !source[end..child.start_byte()].contains('\n') => {
+                    prev = Some((child.end_byte(), was_method));
+                }
+                _ => {
+                    lead.get_or_insert(child.start_byte());
+                }
+            }
+            continue;
+        }
+        let start = lead.take().unwrap_or(child.start_byte());
+        if let Some((end, was_method)) = prev
+            && (is_method || was_method)
+        {
+            let gap = &source[end..start];
+            let blank = gap.matches('\n').count().saturating_sub(1);
+            if gap.trim().is_empty() && gap.contains('\n') && blank < required {
+                let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+                let mut issue = LintIssue::new(
+                    "blank-line-between-methods",
+                    format!("Expected {required} blank line(s) between methods and other members, found {blank}"),
+                    start,
+                );
+                issue.fix = Some(Fix {
+                    start_byte: line_start,
+                    end_byte: line_start,
+                    replacement: Cow::Owned(ctx.config.line_ending.resolve(source).repeat(required - blank)),
+                    applicability: Applicability::Safe,
+                });
+                out.push(issue);
+            }
+        }
+        prev = Some((child.end_byte(), is_method));
+    }
+}
+
+/// Spaces and tabs before each line break or the end of the file, except
+/// inside block comments and text blocks when configured.
+fn collect_trailing_whitespace(ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let blocks = if ctx.config.trailing_whitespace_ignore_blocks { multiline_blocks(ctx.source) } else { Vec::new() };
+    let mut line_start = 0;
+    for line in ctx.source.split_inclusive('\n') {
+        let start = line_start;
+        line_start += line.len();
+        let content = line.trim_end_matches(['\n', '\r']);
+        let kept = content.trim_end_matches([' ', '\t']).len();
+        if kept == content.len() { continue; }
+        let (from, to) = (start + kept, start + content.len());
+        if blocks.iter().any(|r| r.contains(&from)) { continue; }
+        let mut issue = LintIssue::new("no-trailing-whitespace", "Trailing whitespace", from);
+        issue.end_offset = to;
+        issue.fix = Some(Fix { start_byte: from, end_byte: to, replacement: Cow::Borrowed(""), applicability: Applicability::Safe });
+        out.push(issue);
+    }
+}
+
+/// Byte ranges of block comments and text blocks, found from the text alone
+/// so line rules don't need the tree. String and char literals and line
+/// comments are skipped, so a `"/*"` or `// */` neither opens nor closes one.
+fn multiline_blocks(source: &str) -> Vec<std::ops::Range<usize>> {
+    let bytes = source.as_bytes();
+    let find = |from: usize, what: &[u8]| bytes[from..].windows(what.len()).position(|w| w == what).map(|i| from + i);
+    let mut blocks = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        let rest = &bytes[i..];
+        if rest.starts_with(b"/*") {
+            let end = find(i + 2, b"*/").map_or(bytes.len(), |e| e + 2);
+            blocks.push(i..end);
+            i = end;
+        } else if rest.starts_with(b"//") {
+            i = find(i, b"\n").unwrap_or(bytes.len());
+        } else if rest.starts_with(b"\"\"\"") {
+            let mut end = i + 3;
+            while end < bytes.len() && !bytes[end..].starts_with(b"\"\"\"") {
+                end += if bytes[end] == b'\\' { 2 } else { 1 };
+            }
+            let end = (end + 3).min(bytes.len());
+            blocks.push(i..end);
+            i = end;
+        } else if rest[0] == b'"' || rest[0] == b'\'' {
+            let mut end = i + 1;
+            while end < bytes.len() && bytes[end] != rest[0] && bytes[end] != b'\n' {
+                end += if bytes[end] == b'\\' { 2 } else { 1 };
+            }
+            i = end + 1;
+        } else {
+            i += 1;
+        }
+    }
+    blocks
+}
+
+/// Report `name` when it doesn't match `pattern`. An invalid pattern checks
+/// nothing; `config_warnings` reports it.
+fn check_name(name: Node, ctx: &RuleContext, rule_id: &'static str, what: &str, pattern: &str, out: &mut Vec<LintIssue>) {
+    let Some(regex) = naming::pattern(pattern) else { return };
+    let text: String = ctx.source[name.byte_range()].split_whitespace().collect();
+    if !regex.is_match(&text) {
+        out.push(issue_at(name, rule_id, format!("{what} name '{text}' doesn't match '{pattern}'")));
+    }
+}
+
+fn check_class_naming(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let Some(name) = node.child_by_field_name("name") else { return };
+    check_name(name, ctx, "class-naming", "Type", &ctx.config.naming.class, out);
+}
+
+/// Method names, except on `@Override` methods, whose name the supertype
+/// decides.
+fn check_method_naming(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let Some(name) = node.child_by_field_name("name") else { return };
+    if has_override(node, ctx.source) { return; }
+    check_name(name, ctx, "method-naming", "Method", &ctx.config.naming.method, out);
+}
+
+/// Fields that are `static final`, or declared in an interface (implicitly
+/// so). `serialVersionUID` and `serialPersistentFields` are spelled as
+/// serialization requires.
+fn check_constant_naming(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    if node.kind() == "field_declaration" {
+        let Some(modifiers) = node.child(0).filter(|c| c.kind() == "modifiers") else { return };
+        let mut cursor = modifiers.walk();
+        let keywords: Vec<&str> = modifiers.children(&mut cursor).map(|c| c.kind()).collect();
+        if !keywords.contains(&"static") || !keywords.contains(&"final") { return; }
+    }
+    let mut cursor = node.walk();
+    for declarator in node.children_by_field_name("declarator", &mut cursor) {
+        let Some(name) = declarator.child_by_field_name("name") else { continue };
+        if matches!(&ctx.source[name.byte_range()], "serialVersionUID" | "serialPersistentFields") { continue; }
+        check_name(name, ctx, "constant-naming", "Constant", &ctx.config.naming.constant, out);
+    }
+}
+
+fn check_package_naming(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let mut cursor = node.walk();
+    let Some(name) = node.named_children(&mut cursor).find(|c| matches!(c.kind(), "scoped_identifier" | "identifier")) else { return };
+    check_name(name, ctx, "package-naming", "Package", &ctx.config.naming.package, out);
+}
+
+/// Class, interface, enum, record and annotation type declarations.
+const TYPE_DECLARATIONS: &[&str] =
+    &["class_declaration", "interface_declaration", "enum_declaration", "record_declaration", "annotation_type_declaration"];
+
+/// Top-level type declarations of `root`'s file.
+fn top_level_types(root: Node) -> Vec<Node> {
+    let mut cursor = root.walk();
+    root.named_children(&mut cursor).filter(|c| TYPE_DECLARATIONS.contains(&c.kind())).collect()
+}
+
+/// Every top-level type after the first, each of which belongs in a file of
+/// its own.
+fn check_top_level_count(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let Some(root) = node.parent().filter(|p| p.kind() == "program") else { return };
+    let types = top_level_types(root);
+    if types.first() == Some(&node) { return; }
+    let Some(name) = node.child_by_field_name("name") else { return };
+    out.push(issue_at(
+        name,
+        "one-top-level-class",
+        format!("File declares {} top-level types; move '{}' to its own file", types.len(), &ctx.source[name.byte_range()]),
+    ));
+}
+
+/// Public top-level types whose name isn't the file's, which javac rejects.
+/// Needs the file name, so sources linted without one pass.
+fn check_class_file_name(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let Some(stem) = ctx.file_name.and_then(|f| f.strip_suffix(".java")) else { return };
+    if node.parent().is_none_or(|p| p.kind() != "program") { return; }
+    let Some(modifiers) = node.child(0).filter(|c| c.kind() == "modifiers") else { return };
+    let mut cursor = modifiers.walk();
+    if !modifiers.children(&mut cursor).any(|c| c.kind() == "public") { return; }
+    let Some(name) = node.child_by_field_name("name") else { return };
+    let name_text = &ctx.source[name.byte_range()];
+    if name_text != stem {
+        out.push(issue_at(name, "class-file-name", format!("Public type '{name_text}' must be declared in {name_text}.java, not {stem}.java")));
+    }
+}
+
+/// Whether a method declaration carries `@Override`.
+fn has_override(node: Node, source: &str) -> bool {
+    let Some(modifiers) = node.child(0).filter(|c| c.kind() == "modifiers") else { return false };
+    let mut cursor = modifiers.walk();
+    modifiers.named_children(&mut cursor).any(|a| {
+        a.kind() == "marker_annotation" && a.child_by_field_name("name").is_some_and(|n| &source[n.byte_range()] == "Override")
+    })
+}
+
+/// Whether a declaration is part of the public API: `public`, or a member of
+/// an interface and so implicitly public, inside types that are all public
+/// themselves. Members of anonymous and local classes never are.
+fn is_public_api(node: Node) -> bool {
+    let mut node = node;
+    loop {
+        let Some(parent) = node.parent() else { return true };
+        let mut keywords = Vec::new();
+        if let Some(modifiers) = node.child(0).filter(|c| c.kind() == "modifiers") {
+            let mut cursor = modifiers.walk();
+            keywords.extend(modifiers.children(&mut cursor).map(|c| c.kind()));
+        }
+        let implicit = matches!(parent.kind(), "interface_body" | "annotation_type_body") && !keywords.contains(&"private");
+        if !keywords.contains(&"public") && !implicit { return false; }
+        match parent.kind() {
+            "program" => return true,
+            "class_body" | "interface_body" | "annotation_type_body" | "enum_body" | "enum_body_declarations" => {
+                let mut owner = parent.parent();
+                if parent.kind() == "enum_body_declarations" {
+                    owner = owner.and_then(|body| body.parent());
+                }
+                let Some(decl) = owner.filter(|o| o.kind().ends_with("_declaration")) else { return false };
+                node = decl;
+            }
+            _ => return false,
+        }
+    }
+}
+
+/// Public API declarations with no `/**` comment right before them.
+/// `@Override` methods inherit the overridden method's documentation.
+fn check_missing_javadoc(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let source = ctx.source;
+    if javadoc::doc_comment(node, source).is_some() || has_override(node, source) || !is_public_api(node) { return; }
+    let Some(name) = node.child_by_field_name("name") else { return };
+    let what = match node.kind() {
+        "class_declaration" => "class",
+        "interface_declaration" => "interface",
+        "enum_declaration" => "enum",
+        "record_declaration" => "record",
+        "annotation_type_declaration" => "annotation type",
+        "constructor_declaration" => "constructor",
+        _ => "method",
+    };
+    out.push(issue_at(name, "missing-javadoc", format!("Public {what} '{}' has no Javadoc", &source[name.byte_range()])));
+}
+
+/// `@param` tags naming no parameter (or type parameter, as `<T>`) and
+/// `@return` tags on methods that return nothing. Once a comment has block
+/// tags at all, every parameter needs its `@param` and a value-returning
+/// method its `@return`; comments with only a description, or deferring to
+/// `{@inheritDoc}`, are left alone.
+fn check_javadoc_tags(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let source = ctx.source;
+    let Some(comment) = javadoc::doc_comment(node, source) else { return };
+    let text = &source[comment.byte_range()];
+    if text.contains("{@inheritDoc}") { return; }
+    let tags = javadoc::block_tags(text);
+
+    let mut params = Vec::new();
+    if let Some(list) = node.child_by_field_name("parameters") {
+        let mut cursor = list.walk();
+        for param in list.named_children(&mut cursor) {
+            let name = match param.kind() {
+                "formal_parameter" => param.child_by_field_name("name"),
+                "spread_parameter" => {
+                    let mut walk = param.walk();
+                    let declarator = param.named_children(&mut walk).find(|c| c.kind() == "variable_declarator");
+                    declarator.and_then(|d| d.child_by_field_name("name"))
+                }
+                _ => None,
+            };
+            params.extend(name);
+        }
+    }
+    let mut type_params = Vec::new();
+    if let Some(list) = node.child_by_field_name("type_parameters") {
+        let mut cursor = list.walk();
+        for param in list.named_children(&mut cursor) {
+            let mut walk = param.walk();
+            let name = param.named_children(&mut walk).find(|c| c.kind() == "type_identifier");
+            type_params.extend(name.map(|n| format!("<{}>", &source[n.byte_range()])));
+        }
+    }
+    let returns_value = node.child_by_field_name("type").is_some_and(|t| t.kind() != "void_type");
+
+    for tag in &tags {
+        let offset = comment.start_byte() + tag.offset;
+        match tag.name {
+            "@param" if !params.iter().any(|p| &source[p.byte_range()] == tag.argument) && !type_params.iter().any(|t| t == tag.argument) => {
+                out.push(LintIssue::new("javadoc-tags", format!("@param '{}' doesn't name a parameter", tag.argument), offset));
+            }
+            "@return" if !returns_value => {
+                out.push(LintIssue::new("javadoc-tags", "@return on a declaration that returns nothing", offset));
+            }
+            _ => {}
+        }
+    }
+    if !tags.iter().any(|t| matches!(t.name, "@param" | "@return" | "@throws" | "@exception")) { return; }
+    for param in params {
+        let name = &source[param.byte_range()];
+        if !tags.iter().any(|t| t.name == "@param" && t.argument == name) {
+            out.push(issue_at(param, "javadoc-tags", format!("Parameter '{name}' has no @param tag")));
+        }
+    }
+    let has_return = tags.iter().any(|t| t.name == "@return") || text.contains("{@return");
+    if returns_value && !has_return && let Some(name) = node.child_by_field_name("name") {
+        out.push(issue_at(name, "javadoc-tags", "Javadoc has no @return tag"));
+    }
+}
+
+/// Javadoc comments not in the layout `javadoc::reflow` gives them. Banner
+/// comments (`/*****`) and comments sharing their line with code before
+/// them are left alone.
+fn check_javadoc_format(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let source = ctx.source;
+    let text = &source[node.byte_range()];
+    if !text.starts_with("/**") || text.starts_with("/***") { return; }
+    let line_start = source[..node.start_byte()].rfind('\n').map_or(0, |i| i + 1);
+    let indent = &source[line_start..node.start_byte()];
+    if !indent.bytes().all(|b| b == b' ' || b == b'\t') { return; }
+    let formatted = javadoc::reflow(text, indent, ctx.config, ctx.config.line_ending.resolve(source));
+    if formatted == text { return; }
+    let mut issue = issue_at(node, "javadoc-format", "Javadoc isn't aligned under `/**` or runs past max_line_length");
+    issue.fix = Some(Fix {
+        start_byte: node.start_byte(),
+        end_byte: node.end_byte(),
+        replacement: Cow::Owned(formatted),
+        applicability: Applicability::Safe,
+    });
+    out.push(issue);
+}
+
+/// Modifier keywords in the order the JLS recommends.
+const MODIFIER_ORDER: &[&str] = &[
+    "public", "protected", "private", "abstract", "default", "static", "sealed", "non-sealed", "final", "transient",
+    "volatile", "synchronized", "native", "strictfp",
+];
+
+/// Modifier keywords out of `MODIFIER_ORDER`, put back in order in the
+/// places they take up. Annotations stay where they are, since one after the
+/// keywords may annotate the type.
+fn check_modifier_order(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let source = ctx.source;
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node.children(&mut cursor).collect();
+    if children.iter().any(|c| c.kind().ends_with("comment")) { return; }
+    let rank = |n: &Node| MODIFIER_ORDER.iter().position(|m| *m == n.kind());
+    let keywords: Vec<Node> = children.into_iter().filter(|c| rank(c).is_some()).collect();
+    let mut sorted = keywords.clone();
+    sorted.sort_by_key(rank);
+    let Some(wrong) = keywords.iter().zip(&sorted).position(|(a, b)| a.id() != b.id()) else { return };
+    let (first, last) = (keywords[wrong], keywords[keywords.len() - 1]);
+    let mut replacement = String::new();
+    for (i, slot) in keywords.iter().enumerate().skip(wrong) {
+        if i > wrong {
+            replacement.push_str(&source[keywords[i - 1].end_byte()..slot.start_byte()]);
+        }
+        replacement.push_str(&source[sorted[i].byte_range()]);
+    }
+    let expected: Vec<&str> = sorted.iter().map(|n| &source[n.byte_range()]).collect();
+    let mut issue = issue_at(first, "modifier-order", format!("Modifiers should be ordered '{}'", expected.join(" ")));
+    issue.fix = Some(Fix {
+        start_byte: first.start_byte(),
+        end_byte: last.end_byte(),
+        replacement: Cow::Owned(replacement),
+        applicability: Applicability::Safe,
+    });
+    out.push(issue);
+}
+
+/// Declarations whose annotations `annotation-placement` wants on lines of
+/// their own; parameters and local variables keep theirs inline.
+const ANNOTATED_DECLARATIONS: &[&str] = &[
+    "class_declaration", "interface_declaration", "enum_declaration", "record_declaration", "annotation_type_declaration",
+    "method_declaration", "constructor_declaration", "field_declaration", "constant_declaration",
+    "annotation_type_element_declaration",
+];
+
+/// Annotations ahead of a declaration's keywords with something other than
+/// a comment after them on their line, unless `same_line_annotations` names
+/// them. Annotations after a keyword are left alone, since they may annotate
+/// the type.
+fn check_annotation_placement(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let source = ctx.source;
+    let Some(declaration) = node.parent().filter(|p| ANNOTATED_DECLARATIONS.contains(&p.kind())) else { return };
+    let line_start = source[..declaration.start_byte()].rfind('\n').map_or(0, |i| i + 1);
+    let indent: String = source[line_start..].chars().take_while(|c| *c == ' ' || *c == '\t').collect();
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        match child.kind() {
+            "line_comment" | "block_comment" => continue,
+            "marker_annotation" | "annotation" => {}
+            _ => break,
+        }
+        let Some(name) = child.child_by_field_name("name") else { continue };
+        let name = source[name.byte_range()].rsplit('.').next().unwrap_or_default();
+        if ctx.config.same_line_annotations.iter().any(|a| a == name) { continue; }
+        let rest = &source[child.end_byte()..];
+        let gap = rest.len() - rest.trim_start_matches([' ', '\t']).len();
+        let next = &rest[gap..];
+        if next.is_empty() || next.starts_with(['\n', '\r']) || next.starts_with("//") || next.starts_with("/*") { continue; }
+        let mut issue = issue_at(child, "annotation-placement", format!("Annotation '@{name}' should be on a line of its own"));
+        issue.fix = Some(Fix {
+            start_byte: child.end_byte(),
+            end_byte: child.end_byte() + gap,
+            replacement: Cow::Owned(format!("{}{indent}", ctx.config.line_ending.resolve(source))),
+            applicability: Applicability::Safe,
+        });
+        out.push(issue);
+    }
+}
+
+/// Control statement bodies that aren't blocks, wrapped in braces. A body on
+/// the header's line gets `{ ... }` there; one on the next line gets the `{`
+/// after the header and the `}` on a line of its own, indented like the
+/// statement. `else if` and empty bodies (`while (x);`) are left alone.
+fn check_require_braces(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let source = ctx.source;
+    let fields: &[&str] = if node.kind() == "if_statement" { &["consequence", "alternative"] } else { &["body"] };
+    for body in fields.iter().filter_map(|f| node.child_by_field_name(f)) {
+        if matches!(body.kind(), "block" | "if_statement" | "empty_statement" | ";") { continue; }
+        let Some(prev) = body.prev_sibling().filter(|p| !p.kind().ends_with("comment")) else { continue };
+        let gap = &source[prev.end_byte()..body.start_byte()];
+        if !gap.trim().is_empty() { continue; }
+        let keyword = match node.kind() {
+            "if_statement" if prev.kind() == "else" => "else",
+            "if_statement" => "if",
+            "while_statement" => "while",
+            "do_statement" => "do",
+            _ => "for",
+        };
+        let (end, close) = if gap.contains('\n') {
+            // After a trailing `//` comment, so the comment stays with its line.
+            let line_end = source[body.end_byte()..].find('\n').map_or(source.len(), |i| body.end_byte() + i);
+            let line_end = if source[..line_end].ends_with('\r') { line_end - 1 } else { line_end };
+            let rest = source[body.end_byte()..line_end].trim_start();
+            let end = if rest.starts_with("//") { line_end } else { body.end_byte() };
+            let line_start = source[..node.start_byte()].rfind('\n').map_or(0, |i| i + 1);
+            let indent: String = source[line_start..].chars().take_while(|c| *c == ' ' || *c == '\t').collect();
+            let close = format!("{}{indent}}}", ctx.config.line_ending.resolve(source));
+            // An `else` on the next line joins the `}`: `} else`.
+            let mut next = body.next_sibling();
+            while let Some(comment) = next.filter(|n| n.kind().ends_with("comment") && n.end_byte() <= end) {
+                next = comment.next_sibling();
+            }
+            match next.filter(|n| n.kind() == "else" && source[end..n.start_byte()].trim().is_empty()) {
+                Some(other) => (other.start_byte(), close + " "),
+                None => (end, close),
+            }
+        } else {
+            (body.end_byte(), " }".to_string())
+        };
+        let mut issue = issue_at(body, "require-braces", format!("Put braces around the '{keyword}' body"));
+        issue.fix = Some(Fix {
+            start_byte: prev.end_byte(),
+            end_byte: end,
+            replacement: Cow::Owned(format!(" {{{}{close}", source[prev.end_byte()..end].trim_end())),
+            applicability: Applicability::Safe,
+        });
+        out.push(issue);
+    }
+}
+
+/// Body fields of the declarations and statements `brace-style` checks.
+/// Lambdas, array initializers and anonymous classes are left alone.
+const BRACED_BODIES: &[(&str, &[&str])] = &[
+    ("class_declaration", &["body"]),
+    ("interface_declaration", &["body"]),
+    ("enum_declaration", &["body"]),
+    ("record_declaration", &["body"]),
+    ("annotation_type_declaration", &["body"]),
+    ("method_declaration", &["body"]),
+    ("constructor_declaration", &["body"]),
+    ("if_statement", &["consequence", "alternative"]),
+    ("for_statement", &["body"]),
+    ("enhanced_for_statement", &["body"]),
+    ("while_statement", &["body"]),
+    ("do_statement", &["body"]),
+    ("try_statement", &["body"]),
+    ("try_with_resources_statement", &["body"]),
+    ("catch_clause", &["body"]),
+    ("synchronized_statement", &["body"]),
+    ("switch_expression", &["body"]),
+];
+
+/// Opening braces of bodies on the wrong line for `brace_style`, moved with
+/// the whitespace before them.
+fn check_brace_style(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let Some(style) = ctx.config.brace_style else { return };
+    let source = ctx.source;
+    let mut bodies: Vec<Node> = BRACED_BODIES
+        .iter()
+        .filter(|(kind, _)| *kind == node.kind())
+        .flat_map(|(_, fields)| fields.iter().filter_map(|f| node.child_by_field_name(f)))
+        .collect();
+    // `finally` and static initializers have an unnamed block.
+    if matches!(node.kind(), "finally_clause" | "static_initializer") {
+        let mut cursor = node.walk();
+        bodies.extend(node.named_children(&mut cursor).filter(|c| c.kind() == "block"));
+    }
+    for body in bodies {
+        let Some(open) = body.child(0).filter(|c| c.kind() == "{") else { continue };
+        let Some(prev) = body.prev_sibling() else { continue };
+        if prev.kind().ends_with("comment") { continue; }
+        let gap = &source[prev.end_byte()..open.start_byte()];
+        if !gap.trim().is_empty() { continue; }
+        let (message, replacement) = match style {
+            BraceStyle::Kr if gap.contains('\n') => ("Put the opening brace on the same line", " ".to_string()),
+            BraceStyle::Allman if !gap.contains('\n') => {
+                let line_start = source[..node.start_byte()].rfind('\n').map_or(0, |i| i + 1);
+                let indent: String = source[line_start..].chars().take_while(|c| *c == ' ' || *c == '\t').collect();
+                ("Put the opening brace on its own line", format!("{}{indent}", ctx.config.line_ending.resolve(source)))
+            }
+            _ => continue,
+        };
+        let mut issue = issue_at(open, "brace-style", message);
+        issue.fix = Some(Fix {
+            start_byte: prev.end_byte(),
+            end_byte: open.start_byte(),
+            replacement: replacement.into(),
+            applicability: Applicability::Safe,
+        });
+        out.push(issue);
+    }
+}
+
+/// Switches written in the form `Config::switch_style` doesn't prefer.
+/// Colon-form switches whose every case runs one statement and breaks are
+/// rewritten to arrow form; anything else is only reported.
+fn check_switch_style(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let Some(style) = ctx.config.switch_style else { return };
+    if ctx.config.java_version < 14 { return; }
+    let mut cursor = node.walk();
+    let cases: Vec<Node> = node.named_children(&mut cursor).filter(|c| c.kind() != "line_comment" && c.kind() != "block_comment").collect();
+    let (unwanted, message) = match style {
+        SwitchStyle::Arrow => ("switch_block_statement_group", "Use arrow-form 'case ... ->' labels in this switch"),
+        SwitchStyle::Colon => ("switch_rule", "Use colon-form 'case ...:' labels in this switch"),
+    };
+    if !cases.iter().any(|c| c.kind() == unwanted) { return; }
+    let mut issue = issue_at(node, "switch-style", message);
+    // Comments between cases would be lost by the rewrite, and switches
+    // `switch-expression` rewrites are left to it so the fixes don't overlap.
+    let mut rewritten = Vec::new();
+    if ctx.rules.contains("switch-expression")
+        && let Some(switch) = node.parent()
+    {
+        check_switch_expression(switch, ctx, &mut rewritten);
+    }
+    if style == SwitchStyle::Arrow && cases.len() == node.named_child_count() && rewritten.iter().all(|i| i.fix.is_none()) {
+        let newline = ctx.config.line_ending.resolve(ctx.source);
+        issue.fix = arrow_cases(&cases, ctx.source, newline).map(|replacement| Fix {
+            start_byte: cases[0].start_byte(),
+            end_byte: cases[cases.len() - 1].end_byte(),
+            replacement: replacement.into(),
+            applicability: Applicability::Safe,
+        });
+    }
+    out.push(issue);
+}
+
+/// Colon-form cases whose statements can run on into the next case. Cases
+/// with no statements share the next one's and are fine, and a comment
+/// saying "fall through" (or "fallthrough", "falls through") between the
+/// last statement and the next label marks it as intended.
+fn check_switch_fallthrough(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let source = ctx.source;
+    let mut cursor = node.walk();
+    let groups: Vec<Node> = node.named_children(&mut cursor).filter(|c| c.kind() == "switch_block_statement_group").collect();
+    for pair in groups.windows(2) {
+        let mut walk = pair[0].walk();
+        let statements: Vec<Node> = pair[0]
+            .named_children(&mut walk)
+            .filter(|c| !matches!(c.kind(), "switch_label" | "line_comment" | "block_comment"))
+            .collect();
+        let Some(last) = statements.last() else { continue };
+        if !completes_normally(*last) { continue; }
+        let between = source[last.end_byte()..pair[1].start_byte()].to_ascii_lowercase().replace([' ', '-', '_'], "");
+        if between.contains("fallthrough") || between.contains("fallsthrough") || between.contains("fallthru") { continue; }
+        let label = pair[1].named_child(0).unwrap_or(pair[1]);
+        out.push(issue_at(label, "switch-fallthrough", "Previous case falls through; end it with break or add a // fallthrough comment"));
+    }
+}
+
+/// Whether control can run past the end of `statement`: it isn't a jump,
+/// a block ending in one, or an `if`/`else` whose branches both jump.
+fn completes_normally(statement: Node) -> bool {
+    match statement.kind() {
+        "break_statement" | "continue_statement" | "return_statement" | "throw_statement" | "yield_statement" => false,
+        "block" => {
+            let mut cursor = statement.walk();
+            let last = statement
+                .named_children(&mut cursor)
+                .filter(|c| !matches!(c.kind(), "line_comment" | "block_comment"))
+                .last();
+            last.is_none_or(completes_normally)
+        }
+        "if_statement" => match (statement.child_by_field_name("consequence"), statement.child_by_field_name("alternative")) {
+            (Some(then), Some(otherwise)) => completes_normally(then) || completes_normally(otherwise),
+            _ => true,
+        },
+        _ => true,
+    }
+}
+
+/// Switch statements with no `default` label. Switch expressions and
+/// pattern switches are left to the compiler, which requires them to be
+/// exhaustive, and so are switches whose cases name every constant of an
+/// enum declared in the same file.
+fn check_missing_default(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let source = ctx.source;
+    let statement = node.parent().is_some_and(|p| {
+        matches!(
+            p.kind(),
+            "block" | "switch_block_statement_group" | "constructor_body" | "labeled_statement" | "if_statement"
+                | "while_statement" | "for_statement" | "enhanced_for_statement" | "do_statement"
+        )
+    });
+    let Some(body) = node.child_by_field_name("body").filter(|_| statement) else { return };
+    let mut labels = Vec::new();
+    let mut cursor = body.walk();
+    for case in body.named_children(&mut cursor) {
+        let mut walk = case.walk();
+        labels.extend(case.named_children(&mut walk).filter(|c| c.kind() == "switch_label"));
+    }
+    let mut constants = BTreeSet::new();
+    let mut only_constants = true;
+    for label in &labels {
+        let mut walk = label.walk();
+        let parts: Vec<Node> = label.children(&mut walk).collect();
+        if parts.iter().any(|p| p.kind() == "default" || p.kind().ends_with("pattern") || p.kind() == "guard") { return; }
+        for value in parts.iter().filter(|p| p.is_named()) {
+            match value.kind() {
+                "identifier" => { constants.insert(&source[value.byte_range()]); }
+                _ => only_constants = false,
+            }
+        }
+    }
+    if only_constants && !constants.is_empty() && covers_local_enum(node, &constants, ctx) { return; }
+    out.push(issue_at(node, "missing-default", "Switch has no default branch"));
+}
+
+/// Whether `constants` are exactly the constants of an enum declared in the
+/// file containing `node`.
+fn covers_local_enum(node: Node, constants: &BTreeSet<&str>, ctx: &RuleContext) -> bool {
+    let root = std::iter::successors(Some(node), |n| n.parent()).last().unwrap_or(node);
+    ctx.enum_constants(root).contains(constants)
+}
+
+/// Constant names of each enum declared under `root`.
+fn enum_constants<'s>(root: Node, source: &'s str) -> Vec<BTreeSet<&'s str>> {
+    let mut enums = Vec::new();
+    let mut cursor = root.walk();
+    loop {
+        let n = cursor.node();
+        if n.kind() == "enum_body" {
+            let mut walk = n.walk();
+            enums.push(
+                n.named_children(&mut walk)
+                    .filter(|c| c.kind() == "enum_constant")
+                    .filter_map(|c| c.child_by_field_name("name"))
+                    .map(|name| &source[name.byte_range()])
+                    .collect(),
+            );
+        }
+        if cursor.goto_first_child() { continue; }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() { return enums; }
+        }
+    }
+}
+
+/// Arrow-form text for colon-form `groups`, one case per line, if each
+/// group is labels, one expression or `throw` statement and a `break` (the
+/// last may omit it, and a lone `break` becomes `{}`). Groups with only
+/// labels fall through, so their labels join the next case. Lines are
+/// separated by `newline`.
+fn arrow_cases(groups: &[Node], source: &str, newline: &str) -> Option<String> {
+    let line_start = source[..groups[0].start_byte()].rfind('\n').map_or(0, |i| i + 1);
+    let line = &source[line_start..groups[0].start_byte()];
+    let indent = &line[..line.len() - line.trim_start_matches([' ', '\t']).len()];
+    let is_break = |n: &Node| n.kind() == "break_statement" && n.named_child_count() == 0;
+    let mut cases = Vec::new();
+    let mut values = Vec::new();
+    for (i, group) in groups.iter().enumerate() {
+        if group.kind() != "switch_block_statement_group" { return None; }
+        let mut cursor = group.walk();
+        let children: Vec<Node> = group.named_children(&mut cursor).collect();
+        let split = children.iter().position(|c| c.kind() != "switch_label").unwrap_or(children.len());
+        let (labels, body) = children.split_at(split);
+        for label in labels {
+            label_values(*label, source, &mut values)?;
+        }
+        let last = i + 1 == groups.len();
+        let statement = match body {
+            [] if !last => continue,
+            [only] if is_break(only) => "{}",
+            [stmt, brk] if is_break(brk) && matches!(stmt.kind(), "expression_statement" | "throw_statement") => {
+                &source[stmt.byte_range()]
+            }
+            [stmt] if stmt.kind() == "throw_statement" || (last && stmt.kind() == "expression_statement") => {
+                &source[stmt.byte_range()]
+            }
+            _ => return None,
+        };
+        cases.push(format!("{} -> {statement}", arrow_label(&values)?));
+        values.clear();
+    }
+    Some(cases.join(&format!("{newline}{indent}")))
+}
+
+/// Add what `label` matches to `values`: each constant, or `None` for
+/// `default`. Fails on patterns, guards and comments, which aren't rewritten.
+fn label_values<'s>(label: Node, source: &'s str, values: &mut Vec<Option<&'s str>>) -> Option<()> {
+    let mut cursor = label.walk();
+    let exprs: Vec<Node> = label.named_children(&mut cursor).collect();
+    if exprs.is_empty() {
+        values.push(None);
+    }
+    for expr in exprs {
+        if matches!(expr.kind(), "pattern" | "guard" | "line_comment" | "block_comment") { return None; }
+        values.push(Some(&source[expr.byte_range()]));
+    }
+    Some(())
+}
+
+/// `case A, B` or `default` for label values; `None` when `default` is
+/// mixed with constants.
+fn arrow_label(values: &[Option<&str>]) -> Option<String> {
+    match values {
+        [None] => Some("default".to_string()),
+        _ if values.contains(&None) => None,
+        _ => Some(format!("case {}", values.iter().flatten().copied().collect::<Vec<_>>().join(", "))),
+    }
+}
+
+/// What a switch computes when every case ends in the same kind of statement.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SwitchResult<'s> {
+    /// `return value;`
+    Return,
+    /// `name = value;`
+    Assign(&'s str),
+}
+
+/// Switch statements on Java 14+ whose every case returns a value or assigns
+/// the same variable, which read better as a switch expression. Switches
+/// with a `default`, one value per case and nothing else are rewritten.
+fn check_switch_expression(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    if ctx.config.java_version < 14 { return; }
+    let Some(parent) = node.parent() else { return };
+    if !LINE_CONTAINERS.contains(&parent.kind()) { return; }
+    let Some(body) = node.child_by_field_name("body") else { return };
+    let source = ctx.source;
+    let mut cursor = body.walk();
+    let cases: Vec<Node> = body.named_children(&mut cursor).collect();
+    let mut result = None;
+    let mut arms = Vec::new();
+    let mut values = Vec::new();
+    let mut simple = true;
+    for (i, case) in cases.iter().enumerate() {
+        let mut cursor = case.walk();
+        let children: Vec<Node> = case.named_children(&mut cursor).collect();
+        let split = children.iter().position(|c| c.kind() != "switch_label").unwrap_or(children.len());
+        let (labels, stmts) = children.split_at(split);
+        for label in labels {
+            simple &= label_values(*label, source, &mut values).is_some();
+        }
+        let last = i + 1 == cases.len();
+        let stmts = match (case.kind(), stmts) {
+            ("switch_block_statement_group", []) if !last => continue,
+            ("switch_rule", [block]) if block.kind() == "block" => {
+                let mut cursor = block.walk();
+                block.named_children(&mut cursor).collect()
+            }
+            ("switch_rule" | "switch_block_statement_group", _) => stmts.to_vec(),
+            _ => return,
+        };
+        let Some((this, value)) = switch_arm(&stmts, source, case.kind() == "switch_rule" || last) else { return };
+        if result.is_some_and(|r| r != this) { return; }
+        result = Some(this);
+        match arrow_label(&values) {
+            Some(label) => arms.push(format!("{label} -> {value};")),
+            None => simple = false,
+        }
+        values.clear();
+    }
+    let Some(result) = result else { return };
+    let what = match result {
+        SwitchResult::Return => "returns a value".to_string(),
+        SwitchResult::Assign(name) => format!("assigns '{name}'"),
+    };
+    let mut issue = issue_at(node, "switch-expression", format!("Every case {what}; use a switch expression"));
+    let exhaustive = arms.iter().any(|a| a.starts_with("default "));
+    if simple && exhaustive && cases.len() == body.named_child_count() && !has_comment(body) {
+        let line = |at: usize| {
+            let rest = &source[source[..at].rfind('\n').map_or(0, |i| i + 1)..];
+            &rest[..rest.len() - rest.trim_start_matches([' ', '\t']).len()]
+        };
+        let (indent, case_indent) = (line(node.start_byte()), line(cases[0].start_byte()));
+        let target = match result {
+            SwitchResult::Return => "return ".to_string(),
+            SwitchResult::Assign(name) => format!("{name} = "),
+        };
+        let head = &source[node.start_byte()..body.start_byte()];
+        let newline = ctx.config.line_ending.resolve(source);
+        let arms = arms.join(&format!("{newline}{case_indent}"));
+        issue.fix = Some(Fix {
+            start_byte: node.start_byte(),
+            end_byte: node.end_byte(),
+            replacement: format!("{target}{head}{{{newline}{case_indent}{arms}{newline}{indent}}};").into(),
+            applicability: Applicability::Safe,
+        });
+    }
+    out.push(issue);
+}
+
+/// The result and value of one case's statements: `return v;`, or `x = v;`
+/// followed by `break;` unless `may_end` (the case can't fall through).
+fn switch_arm<'s>(stmts: &[Node], source: &'s str, may_end: bool) -> Option<(SwitchResult<'s>, &'s str)> {
+    let text = |n: Node| &source[n.byte_range()];
+    match stmts {
+        [ret] if ret.kind() == "return_statement" => Some((SwitchResult::Return, text(ret.named_child(0)?))),
+        [assign, brk] if brk.kind() == "break_statement" && brk.named_child_count() == 0 => assignment(*assign, source),
+        [assign] if may_end => assignment(*assign, source),
+        _ => None,
+    }
+}
+
+/// `name = value;` as an expression statement.
+fn assignment<'s>(stmt: Node, source: &'s str) -> Option<(SwitchResult<'s>, &'s str)> {
+    let expr = stmt.named_child(0).filter(|e| stmt.kind() == "expression_statement" && e.kind() == "assignment_expression")?;
+    let (left, right) = (expr.child_by_field_name("left")?, expr.child_by_field_name("right")?);
+    if left.kind() != "identifier" || expr.child_by_field_name("operator").is_some_and(|op| op.kind() != "=") { return None; }
+    Some((SwitchResult::Assign(&source[left.byte_range()]), &source[right.byte_range()]))
+}
+
+fn has_comment(node: Node) -> bool {
+    let mut cursor = node.walk();
+    node.children(&mut cursor).any(|c| matches!(c.kind(), "line_comment" | "block_comment") || has_comment(c))
+}
+
+/// `if (x instanceof Foo) { Foo f = (Foo) x; ... }` on Java 16+, where the
+/// pattern `x instanceof Foo f` declares `f` without the cast. The fix moves
+/// the name into the condition and deletes the declaration.
+fn check_instanceof_pattern(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    if ctx.config.java_version < 16 { return; }
+    let source = ctx.source;
+    let text = |n: Node| &source[n.byte_range()];
+    let Some(test) = node
+        .child_by_field_name("condition")
+        .and_then(|c| c.named_child(0))
+        .filter(|t| t.kind() == "instanceof_expression")
+    else {
+        return;
+    };
+    let (Some(subject), Some(ty)) = (test.child_by_field_name("left"), test.child_by_field_name("right")) else { return };
+    if test.child_by_field_name("name").is_some() || subject.kind() != "identifier" { return; }
+    let Some(decl) = node
+        .child_by_field_name("consequence")
+        .filter(|b| b.kind() == "block")
+        .and_then(|b| b.named_child(0))
+        .filter(|d| d.kind() == "local_variable_declaration" && d.named_child_count() == 2)
+    else {
+        return;
+    };
+    let (Some(decl_type), Some(declarator)) = (decl.child_by_field_name("type"), decl.child_by_field_name("declarator")) else {
+        return;
+    };
+    let (Some(name), Some(cast)) = (declarator.child_by_field_name("name"), declarator.child_by_field_name("value")) else {
+        return;
+    };
+    let same = |a: Option<Node>, b: Node| a.is_some_and(|a| text(a) == text(b));
+    if cast.kind() != "cast_expression"
+        || !same(Some(decl_type), ty)
+        || !same(cast.child_by_field_name("type"), ty)
+        || !same(cast.child_by_field_name("value"), subject)
+    {
+        return;
+    }
+
+    // Delete the declaration's line when it has nothing else on it.
+    let line_start = source[..decl.start_byte()].rfind('\n').map_or(0, |i| i + 1);
+    let rest = &source[decl.end_byte()..];
+    let line_end = rest.find('\n').map_or(source.len(), |i| decl.end_byte() + i + 1);
+    let (start, end) = if source[line_start..decl.start_byte()].trim().is_empty() && source[decl.end_byte()..line_end].trim().is_empty() {
+        (line_start, line_end)
+    } else {
+        (decl.start_byte(), decl.end_byte() + rest.len() - rest.trim_start_matches([' ', '\t']).len())
+    };
+    let name = text(name);
+    let mut issue = issue_at(
+        test,
+        "instanceof-pattern",
+        format!("Use '{} instanceof {} {name}' instead of casting in the block", text(subject), text(ty)),
+    );
+    issue.fix = Some(Fix {
+        start_byte: ty.end_byte(),
+        end_byte: end,
+        replacement: format!(" {name}{}", &source[ty.end_byte()..start]).into(),
+        applicability: Applicability::Safe,
+    });
+    out.push(issue);
+}
+
+/// Anonymous classes longer than `Config::max_anonymous_class_lines` or with
+/// more than one method, which are easier to follow as named classes.
+fn check_anonymous_class(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let Some(body) = node.named_children(&mut node.walk()).find(|c| c.kind() == "class_body") else { return };
+    let max_lines = ctx.config.max_anonymous_class_lines as usize;
+    let lines = body.end_position().row - body.start_position().row + 1;
+    let methods = body.named_children(&mut body.walk()).filter(|c| c.kind() == "method_declaration").count();
+    let reason = if max_lines > 0 && lines > max_lines {
+        format!("spans {lines} lines (max {max_lines})")
+    } else if methods > 1 {
+        format!("declares {methods} methods")
+    } else {
+        return;
+    };
+    out.push(issue_at(
+        node,
+        "anonymous-class-size",
+        format!("Anonymous class {reason}; extract it to a named nested or local class"),
+    ));
+}
+
+/// Methods and constructors over `max_method_length`, in lines of the
+/// declaration or statements of the body per `method_length_unit`.
+fn check_method_length(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let max = ctx.config.max_method_length as usize;
+    if max == 0 { return; }
+    let (length, unit) = match ctx.config.method_length_unit {
+        MethodLengthUnit::Lines => (metrics::method_metrics(node, ctx.source).lines, "lines"),
+        MethodLengthUnit::Statements => (node.child_by_field_name("body").map_or(0, metrics::statement_count), "statements"),
+    };
+    if length <= max { return; }
+    let name = node.child_by_field_name("name").unwrap_or(node);
+    let what = if node.kind() == "constructor_declaration" { "Constructor" } else { "Method" };
+    out.push(issue_at(name, "max-method-length", format!("{what} '{}' has {length} {unit} (max {max})", &ctx.source[name.byte_range()])));
+}
+
+/// Methods and constructors taking more than `max_parameters` parameters.
+/// `@Override` methods are skipped, since the supertype fixes their
+/// signature.
+fn check_parameter_count(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let max = ctx.config.max_parameters as usize;
+    if max == 0 || has_override(node, ctx.source) { return; }
+    let count = metrics::method_metrics(node, ctx.source).parameters;
+    if count <= max { return; }
+    let name = node.child_by_field_name("name").unwrap_or(node);
+    let what = if node.kind() == "constructor_declaration" { "Constructor" } else { "Method" };
+    out.push(issue_at(name, "max-parameters", format!("{what} '{}' has {count} parameters (max {max})", &ctx.source[name.byte_range()])));
+}
+
+/// Methods and constructors whose McCabe complexity, as `jfmtcli metrics`
+/// reports it, is above `max_complexity`.
+fn check_complexity(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let max = ctx.config.max_complexity as usize;
+    if max == 0 { return; }
+    let complexity = metrics::method_metrics(node, ctx.source).complexity;
+    if complexity <= max { return; }
+    let name = node.child_by_field_name("name").unwrap_or(node);
+    let what = if node.kind() == "constructor_declaration" { "Constructor" } else { "Method" };
+    out.push(issue_at(
+        name,
+        "max-complexity",
+        format!("{what} '{}' has cyclomatic complexity {complexity} (max {max})", &ctx.source[name.byte_range()]),
+    ));
+}
+
+/// Top-level directories whose absolute paths only exist on some machines.
+const UNIX_ROOTS: &[&str] = &["/home/", "/Users/", "/tmp/", "/var/", "/etc/", "/opt/", "/usr/", "/mnt/", "/root/", "/private/"];
+
+/// Calls and constructors whose arguments are file paths.
+const PATH_BUILDERS: &[&str] = &["File", "FileInputStream", "FileOutputStream", "FileReader", "FileWriter", "get", "of", "resolve"];
+
+/// String literals holding an absolute path (`C:\\...`, `\\\\server`, `/home/...`)
+/// or a `\\` separator used to build a path. Literals matching one of
+/// `Config::hardcoded_path_allow` are skipped.
+fn check_hardcoded_path(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let text = &ctx.source[node.byte_range()];
+    let Some(content) = text.strip_prefix('"').and_then(|t| t.strip_suffix('"')).filter(|t| !t.starts_with("\"\"")) else {
+        return;
+    };
+    if ctx.config.hardcoded_path_allow.iter().any(|p| glob_match(p, content)) { return; }
+    let bytes = content.as_bytes();
+    let drive = bytes.len() > 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && matches!(bytes[2], b'\\' | b'/');
+    let message = if drive || content.starts_with(r"\\\\") || UNIX_ROOTS.iter().any(|r| content.starts_with(r) || content == &r[..r.len() - 1]) {
+        format!("Absolute path \"{content}\" is machine-specific; take it from configuration or build it from a base directory")
+    } else if content.contains(r"\\") && in_path_builder(node, ctx.source) {
+        "Hardcoded '\\' separator; use Path.resolve or File.separator".to_string()
+    } else {
+        return;
+    };
+    out.push(issue_at(node, "hardcoded-path", message));
+}
+
+/// Whether `node` is an argument of a path-building call, possibly inside a
+/// string concatenation.
+fn in_path_builder(node: Node, source: &str) -> bool {
+    let mut at = node;
+    while let Some(parent) = at.parent().filter(|p| matches!(p.kind(), "binary_expression" | "parenthesized_expression")) {
+        at = parent;
+    }
+    let Some(call) = at.parent().filter(|p| p.kind() == "argument_list").and_then(|a| a.parent()) else { return false };
+    let name = match call.kind() {
+        "object_creation_expression" => call.child_by_field_name("type"),
+        "method_invocation" => call.child_by_field_name("name"),
+        _ => None,
+    };
+    name.is_some_and(|n| PATH_BUILDERS.contains(&&source[n.byte_range()]))
+}
+
+/// Whether `text` matches `pattern`, where `*` matches any run of characters.
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let Some((head, rest)) = pattern.split_once('*') else { return pattern == text };
+    let Some(mut text) = text.strip_prefix(head) else { return false };
+    let mut parts: Vec<&str> = rest.split('*').collect();
+    let tail = parts.pop().unwrap_or_default();
+    for part in parts {
+        match text.find(part) {
+            Some(i) => text = &text[i + part.len()..],
+            None => return false,
+        }
+    }
+    text.len() >= tail.len() && text.ends_with(tail)
+}
+
+/// `toUpperCase()`, `toLowerCase()` and `String.format(...)` without a
+/// `Locale`, which change behavior with the default locale (in Turkish,
+/// `"TITLE".toLowerCase()` yields a dotless `ı`).
+fn check_locale_sensitive(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    if ctx.config.allow_default_locale { return; }
+    let source = ctx.source;
+    let (Some(name), Some(args)) = (node.child_by_field_name("name"), node.child_by_field_name("arguments")) else { return };
+    let name = &source[name.byte_range()];
+    let message = match name {
+        "toUpperCase" | "toLowerCase" if args.named_child_count() == 0 => {
+            format!("'{name}()' depends on the default locale; use '{name}(Locale.ROOT)' or pass the intended Locale")
+        }
+        "format"
+            if node.child_by_field_name("object").is_some_and(|o| &source[o.byte_range()] == "String")
+                && args.named_child(0).is_some_and(|a| matches!(a.kind(), "string_literal" | "text_block")) =>
+        {
+            "'String.format' depends on the default locale; use 'String.format(Locale.ROOT, ...)' or pass the intended Locale"
+                .to_string()
+        }
+        _ => return,
+    };
+    out.push(issue_at(node, "locale-sensitive", message));
+}
+
+/// `System.out.print*`, `System.err.print*` and `printStackTrace()` (bare or
+/// onto `System.out`/`System.err`), which bypass logging. Calls inside a
+/// `static main` pass when `console_output_allow_main` is set.
+fn check_console_output(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    if ctx.config.allow_console_output { return; }
+    let source = ctx.source;
+    let (Some(name), Some(args)) = (node.child_by_field_name("name"), node.child_by_field_name("arguments")) else { return };
+    let name = &source[name.byte_range()];
+    let is_stream = |n: Node| {
+        let text: String = source[n.byte_range()].split_whitespace().collect();
+        matches!(text.trim_start_matches("java.lang."), "System.out" | "System.err")
+    };
+    let message = if name.starts_with("print") && name != "printStackTrace" {
+        let Some(stream) = node.child_by_field_name("object").filter(|o| is_stream(*o)) else { return };
+        let stream: String = source[stream.byte_range()].split_whitespace().collect();
+        format!("'{stream}.{name}' writes to the console; use a logger")
+    } else if name == "printStackTrace" && (args.named_child_count() == 0 || args.named_child(0).is_some_and(is_stream)) {
+        "'printStackTrace()' writes to the console; log the exception instead".to_string()
+    } else {
+        return;
+    };
+    if ctx.config.console_output_allow_main {
+        let mut at = node;
+        while let Some(parent) = at.parent() {
+            if parent.kind() == "method_declaration" {
+                let is_main = parent.child_by_field_name("name").is_some_and(|n| &source[n.byte_range()] == "main");
+                let is_static = parent.child(0).filter(|m| m.kind() == "modifiers").is_some_and(|m| {
+                    let mut cursor = m.walk();
+                    m.children(&mut cursor).any(|c| c.kind() == "static")
+                });
+                if is_main && is_static { return; }
+                break;
+            }
+            at = parent;
+        }
+    }
+    out.push(issue_at(node, "no-console-output", message));
+}
+
+/// Numeric literals not in `magic_numbers.allow`, outside the declarations
+/// that name them: `static final` fields, interface fields and enum constant
+/// arguments. Annotations and array dimensions pass when configured.
+fn check_magic_number(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let source = ctx.source;
+    let settings = &ctx.config.magic_numbers;
+    let negated = node.parent().filter(|p| {
+        p.kind() == "unary_expression" && p.child_by_field_name("operator").is_some_and(|o| o.kind() == "-")
+    });
+    let literal = negated.unwrap_or(node);
+    let value = literal_value(node.kind(), &source[node.byte_range()]).map(|v| if negated.is_some() { -v } else { v });
+    if value.is_some_and(|v| settings.allow.contains(&v)) { return; }
+    if settings.ignore_array_sizes && literal.parent().is_some_and(|p| p.kind() == "dimensions_expr") { return; }
+    let mut at = literal;
+    while let Some(parent) = at.parent() {
+        match parent.kind() {
+            "constant_declaration" | "enum_constant" => return,
+            "annotation" | "annotation_type_element_declaration" if settings.ignore_annotations => return,
+            "field_declaration" => {
+                let Some(modifiers) = parent.child(0).filter(|c| c.kind() == "modifiers") else { break };
+                let mut cursor = modifiers.walk();
+                let keywords: Vec<&str> = modifiers.children(&mut cursor).map(|c| c.kind()).collect();
+                if keywords.contains(&"static") && keywords.contains(&"final") { return; }
+                break;
+            }
+            "class_body" | "block" | "constructor_body" => break,
+            _ => at = parent,
+        }
+    }
+    let text: String = source[literal.byte_range()].split_whitespace().collect();
+    out.push(issue_at(literal, "magic-number", format!("Magic number {text}; extract it to a named constant")));
+}
+
+/// The value of a numeric literal of tree-sitter kind `kind`, ignoring
+/// underscores and type suffixes. `None` for hex floating point literals and
+/// integers too large for an `i128`.
+fn literal_value(kind: &str, text: &str) -> Option<f64> {
+    let text = text.replace('_', "");
+    let integer = text.trim_end_matches(['l', 'L']);
+    let parsed = match kind {
+        "hex_integer_literal" => i128::from_str_radix(&integer[2..], 16).ok()? as f64,
+        "binary_integer_literal" => i128::from_str_radix(&integer[2..], 2).ok()? as f64,
+        "octal_integer_literal" => i128::from_str_radix(integer.trim_start_matches('0'), 8).unwrap_or(0) as f64,
+        "decimal_integer_literal" => integer.parse::<i128>().ok()? as f64,
+        "decimal_floating_point_literal" => text.trim_end_matches(['f', 'F', 'd', 'D']).parse().ok()?,
+        _ => return None,
+    };
+    Some(parsed)
+}
+
+/// Collection-like return types, with what to return instead of `null`.
+const EMPTY_VALUES: &[(&str, &str)] = &[
+    ("Collection", "List.of()"),
+    ("List", "List.of()"),
+    ("Set", "Set.of()"),
+    ("Map", "Map.of()"),
+    ("Optional", "Optional.empty()"),
+];
+
+/// `return null;` from a method declared to return an array, collection or
+/// `Optional`, which callers then have to null-check.
+fn check_null_collection_return(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    if node.named_child(0).is_none_or(|v| v.kind() != "null_literal") { return; }
+    let mut at = node;
+    let method = loop {
+        let Some(parent) = at.parent() else { return };
+        match parent.kind() {
+            "method_declaration" => break parent,
+            "lambda_expression" | "class_body" => return,
+            _ => at = parent,
+        }
+    };
+    let Some(ty) = method.child_by_field_name("type") else { return };
+    let empty = if ty.kind() == "array_type" {
+        let element = ty.child_by_field_name("element").map_or("", |e| &ctx.source[e.byte_range()]);
+        format!("new {element}[0]")
+    } else {
+        let base = if ty.kind() == "generic_type" { ty.named_child(0) } else { Some(ty) };
+        let Some(name) = base.map(|b| &ctx.source[b.byte_range()]) else { return };
+        let name = name.rsplit('.').next().unwrap_or(name);
+        let Some((_, empty)) = EMPTY_VALUES.iter().find(|(t, _)| *t == name) else { return };
+        empty.to_string()
+    };
+    out.push(issue_at(
+        node,
+        "null-collection-return",
+        format!("Return an empty value such as '{empty}' instead of null"),
+    ));
+}
+
+/// `new BigDecimal(0.1)`, which captures the binary approximation of the
+/// double (`0.1000000000000000055511151231257827...`) rather than the value
+/// written. The fix passes the literal as a string, which changes the value
+/// to the intended one, so it is maybe-incorrect.
+fn check_bigdecimal_double(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let source = ctx.source;
+    let Some(ty) = node.child_by_field_name("type") else { return };
+    if !matches!(&source[ty.byte_range()], "BigDecimal" | "java.math.BigDecimal") { return; }
+    let Some(args) = node.child_by_field_name("arguments").filter(|a| a.named_child_count() == 1) else { return };
+    let Some(arg) = args.named_child(0) else { return };
+    let literal = match arg.kind() {
+        "unary_expression" => arg.child_by_field_name("operand"),
+        _ => Some(arg),
+    };
+    if literal.is_none_or(|l| l.kind() != "decimal_floating_point_literal") { return; }
+    let text = &source[arg.byte_range()];
+    let mut issue = issue_at(
+        node,
+        "bigdecimal-double",
+        format!("new BigDecimal({text}) holds the double's binary approximation; use BigDecimal.valueOf({text}) or new BigDecimal(\"...\")"),
+    );
+    let digits: String = text.chars().filter(|c| !matches!(c, '_' | ' ' | 'd' | 'D' | 'f' | 'F')).collect();
+    issue.fix = Some(Fix {
+        start_byte: arg.start_byte(),
+        end_byte: arg.end_byte(),
+        replacement: format!("\"{digits}\"").into(),
+        applicability: Applicability::MaybeIncorrect,
+    });
+    out.push(issue);
+}
+
+/// `==` and `!=` with a float or double literal or local on either side,
+/// which rounding makes unreliable.
+fn check_float_equality(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let Some(op) = node.child_by_field_name("operator").filter(|op| matches!(op.kind(), "==" | "!=")) else { return };
+    let (Some(left), Some(right)) = (node.child_by_field_name("left"), node.child_by_field_name("right")) else { return };
+    if left.kind() == "null_literal" || right.kind() == "null_literal" { return; }
+    if !is_floating(left, ctx.source) && !is_floating(right, ctx.source) { return; }
+    out.push(issue_at(
+        node,
+        "float-equality",
+        format!(
+            "Floating-point '{}' comparison is unreliable; compare with a tolerance (Math.abs(a - b) < epsilon) or use Double.compare",
+            op.kind()
+        ),
+    ));
+}
+
+/// Whether `expr` is a float or double literal, or a local or parameter
+/// declared `float`, `double`, `Float` or `Double` in the enclosing method.
+fn is_floating(expr: Node, source: &str) -> bool {
+    match expr.kind() {
+        "decimal_floating_point_literal" | "hex_floating_point_literal" => true,
+        "unary_expression" | "parenthesized_expression" => {
+            expr.named_child(expr.named_child_count().saturating_sub(1)).is_some_and(|e| is_floating(e, source))
+        }
+        "identifier" => declared_type(expr, source)
+            .is_some_and(|(ty, dims)| !dims && (ty.kind() == "floating_point_type" || matches!(&source[ty.byte_range()], "Double" | "Float"))),
+        _ => false,
+    }
+}
+
+/// `==` and `!=` between strings, which compares identity. With a literal on
+/// either side, offers `"lit".equals(other)` (negated for `!=`), which is
+/// also null-safe; a maybe-incorrect fix, since identity may be intended.
+fn check_string_equality(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let source = ctx.source;
+    let Some(op) = node.child_by_field_name("operator").filter(|op| matches!(op.kind(), "==" | "!=")) else { return };
+    let (Some(left), Some(right)) = (node.child_by_field_name("left"), node.child_by_field_name("right")) else { return };
+    if left.kind() == "null_literal" || right.kind() == "null_literal" { return; }
+    if !is_string(left, source) && !is_string(right, source) { return; }
+    let mut issue = issue_at(node, "string-equality", format!("Strings compared with '{}'; use equals()", op.kind()));
+    let (literal, other) = if left.kind() == "string_literal" { (left, right) } else { (right, left) };
+    if literal.kind() == "string_literal" {
+        let negate = if op.kind() == "!=" { "!" } else { "" };
+        issue.fix = Some(Fix {
+            start_byte: node.start_byte(),
+            end_byte: node.end_byte(),
+            replacement: format!("{negate}{}.equals({})", &source[literal.byte_range()], &source[other.byte_range()]).into(),
+            applicability: Applicability::MaybeIncorrect,
+        });
+    }
+    out.push(issue);
+}
+
+/// Whether `expr` is a string: a literal, a concatenation with one, a local,
+/// parameter or field declared `String`, or a call to a common method
+/// returning one (`toString()`, `trim()`, `substring(..)` and the like).
+fn is_string(expr: Node, source: &str) -> bool {
+    match expr.kind() {
+        "string_literal" => true,
+        "binary_expression" => concatenates_string(expr),
+        "parenthesized_expression" => expr.named_child(0).is_some_and(|e| is_string(e, source)),
+        "identifier" => declared_type(expr, source)
+            .is_some_and(|(ty, dims)| !dims && matches!(&source[ty.byte_range()], "String" | "java.lang.String")),
+        "method_invocation" => expr.child_by_field_name("name").is_some_and(|name| {
+            matches!(
+                &source[name.byte_range()],
+                "toString" | "trim" | "strip" | "substring" | "toUpperCase" | "toLowerCase" | "concat" | "intern" | "name"
+            )
+        }),
+        _ => false,
+    }
+}
+
+/// Declared type of the variable `ident` refers to, looked up by name among
+/// the locals and parameters of the enclosing method, then the fields of the
+/// enclosing class, with whether the declarator adds array dimensions
+/// (`int a[]`). Shadowing is not modeled; the first declaration wins.
+fn declared_type<'t>(ident: Node<'t>, source: &str) -> Option<(Node<'t>, bool)> {
+    let name = &source[ident.byte_range()];
+    let mut ancestors = std::iter::successors(ident.parent(), |n| n.parent());
+    if let Some(method) = ancestors
+        .clone()
+        .find(|n| matches!(n.kind(), "method_declaration" | "constructor_declaration" | "lambda_expression"))
+        && let Some(found) = find_declaration(method, name, source, true)
+    {
+        return Some(found);
+    }
+    let class = ancestors.find(|n| matches!(n.kind(), "class_body" | "enum_body" | "enum_body_declarations"))?;
+    find_declaration(class, name, source, false)
+}
+
+/// A declaration of `name` in `scope`: anywhere within it when `deep`,
+/// otherwise only fields directly in it.
+fn find_declaration<'t>(scope: Node<'t>, name: &str, source: &str, deep: bool) -> Option<(Node<'t>, bool)> {
+    let is_name = |n: Option<Node>| n.is_some_and(|n| &source[n.byte_range()] == name);
+    let mut cursor = scope.walk();
+    if !cursor.goto_first_child() { return None; }
+    loop {
+        let node = cursor.node();
+        if let Some(ty) = node.child_by_field_name("type") {
+            match node.kind() {
+                "local_variable_declaration" | "field_declaration" => {
+                    let mut names = node.walk();
+                    let found = node.children_by_field_name("declarator", &mut names).find(|d| is_name(d.child_by_field_name("name")));
+                    if let Some(d) = found {
+                        return Some((ty, d.child_by_field_name("dimensions").is_some()));
+                    }
+                }
+                "formal_parameter" | "enhanced_for_statement" | "catch_formal_parameter" if is_name(node.child_by_field_name("name")) => {
+                    return Some((ty, node.child_by_field_name("dimensions").is_some()));
+                }
+                _ => {}
+            }
+        }
+        if deep && cursor.goto_first_child() { continue; }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() || cursor.node().id() == scope.id() { return None; }
+        }
+    }
+}
+
+/// Arrays printed with `System.out.println(arr)`, `String.valueOf(arr)` or
+/// `"..." + arr`, which show `[I@1b6d3586` instead of the elements. A
+/// `char[]` passed to `println`/`print`/`valueOf` prints its characters and
+/// is fine.
+fn check_array_to_string(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let source = ctx.source;
+    let Some(parent) = node.parent() else { return };
+    // Check the cheap syntactic context before looking up the declaration.
+    let (printed, concatenated) = match parent.kind() {
+        "argument_list" if parent.named_child_count() == 1 => (
+            parent.parent().is_some_and(|call| {
+                let text = |field| call.child_by_field_name(field).map_or("", |n| &source[n.byte_range()]);
+                matches!((text("object"), text("name")), ("System.out" | "System.err", "println" | "print") | ("String", "valueOf"))
+            }),
+            false,
+        ),
+        "binary_expression" => (false, concatenates_string(parent)),
+        _ => return,
+    };
+    if !printed && !concatenated { return; }
+    let Some((ty, dims)) = declared_type(node, source) else { return };
+    if !dims && ty.kind() != "array_type" { return; }
+    let char_array = if dims {
+        &source[ty.byte_range()] == "char"
+    } else {
+        ty.child_by_field_name("element").is_some_and(|e| &source[e.byte_range()] == "char")
+            && ty.child_by_field_name("dimensions").is_some_and(|d| &source[d.byte_range()] == "[]")
+    };
+    if printed && char_array { return; }
+    let name = &source[node.byte_range()];
+    out.push(issue_at(
+        node,
+        "array-to-string",
+        format!("Array '{name}' prints as a type and hash code; use Arrays.toString({name})"),
+    ));
+}
+
+/// Whether `node` is a `+` in a chain of `+` whose operands include a string
+/// literal, making it a string concatenation.
+fn concatenates_string(node: Node) -> bool {
+    if !is_plus(node) { return false; }
+    let mut top = node;
+    while let Some(parent) = top.parent().filter(|p| is_plus(*p)) {
+        top = parent;
+    }
+    has_string_operand(top)
+}
+
+/// Whether a `+`, through nested `+` operands, adds a string literal.
+fn has_string_operand(node: Node) -> bool {
+    is_plus(node)
+        && ["left", "right"].into_iter().filter_map(|f| node.child_by_field_name(f)).any(|operand| {
+            matches!(operand.kind(), "string_literal" | "text_block") || has_string_operand(operand)
+        })
+}
+
+fn is_plus(node: Node) -> bool {
+    node.kind() == "binary_expression" && node.child_by_field_name("operator").is_some_and(|op| op.kind() == "+")
+}
+
+/// Pre-Java 8 date and time classes with their `java.time` replacements.
+const LEGACY_DATE_TYPES: &[(&str, &str, &str)] = &[
+    ("java.util", "Date", "Instant or LocalDateTime"),
+    ("java.util", "Calendar", "ZonedDateTime or LocalDate"),
+    ("java.util", "GregorianCalendar", "ZonedDateTime or LocalDate"),
+    ("java.text", "SimpleDateFormat", "DateTimeFormatter"),
+];
+
+/// Imports and constructor calls of `LEGACY_DATE_TYPES`, on Java 8+.
+fn check_legacy_date_time(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    if ctx.config.java_version < 8 { return; }
+    let source = ctx.source;
+    let name = match node.kind() {
+        "import_declaration" => node.named_children(&mut node.walk()).find(|c| c.kind() == "scoped_identifier"),
+        _ => node.child_by_field_name("type"),
+    };
+    let Some(name) = name.map(|n| &source[n.byte_range()]) else { return };
+    let Some((_, ty, instead)) = LEGACY_DATE_TYPES.iter().find(|(package, ty, _)| {
+        let qualified = name.strip_prefix(package).and_then(|n| n.strip_prefix('.'));
+        // Imports are always qualified; `new` may use the simple name.
+        qualified.or((node.kind() != "import_declaration").then_some(name)) == Some(*ty)
+    }) else {
+        return;
+    };
+    out.push(issue_at(node, "legacy-date-time", format!("'{ty}' is a legacy date-time class; use java.time's {instead}")));
+}
+
+/// `new Random()` and `Math.random()` in files whose implied path matches
+/// `Config::security_sensitive_paths`, where predictable values can leak
+/// tokens or keys.
+fn check_insecure_random(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let source = ctx.source;
+    let text = |field| node.child_by_field_name(field).map_or("", |n| &source[n.byte_range()]);
+    let call = match node.kind() {
+        "object_creation_expression" if matches!(text("type"), "Random" | "java.util.Random") => "new Random()",
+        "method_invocation" if text("object") == "Math" && text("name") == "random" => "Math.random()",
+        _ => return,
+    };
+    let patterns = &ctx.config.security_sensitive_paths;
+    if patterns.is_empty() { return; }
+    let Some(root) = std::iter::successors(Some(node), |n| n.parent()).last() else { return };
+    let path = implied_path(root, source);
+    if !patterns.iter().any(|p| glob_matches_path(p, &path)) { return; }
+    out.push(issue_at(
+        node,
+        "insecure-random",
+        format!("{call} is predictable; use java.security.SecureRandom in security-sensitive code"),
+    ));
+}
+
+/// The path a file's `package` and first top-level type imply, relative to
+/// its source root: `com/acme/auth/Login.java`.
+fn implied_path(root: Node, source: &str) -> String {
+    let mut cursor = root.walk();
+    let mut path = String::new();
+    let mut type_name = None;
+    for child in root.named_children(&mut cursor) {
+        match child.kind() {
+            "package_declaration" => {
+                if let Some(name) = child.named_children(&mut child.walk()).find(|n| matches!(n.kind(), "scoped_identifier" | "identifier")) {
+                    path = source[name.byte_range()].replace('.', "/") + "/";
+                }
+            }
+            _ if type_name.is_none() && DECLARATIONS.contains(&child.kind()) => {
+                type_name = child.child_by_field_name("name").map(|n| &source[n.byte_range()]);
+            }
+            _ => {}
+        }
+    }
+    path + type_name.unwrap_or_default() + ".java"
+}
+
+/// Reader and writer constructors that take a charset after their first
+/// argument, with the Java version that added the overload.
+const CHARSET_CONSTRUCTORS: &[(&str, u16)] =
+    &[("InputStreamReader", 1), ("OutputStreamWriter", 1), ("FileReader", 11), ("FileWriter", 11)];
+
+/// `new String(bytes)`, `getBytes()` and reader/writer constructors relying
+/// on the platform charset. The fix passes `StandardCharsets.UTF_8`, which
+/// keeps behavior only where UTF-8 is already the default (Java 18+), so it
+/// is maybe-incorrect below that.
+fn check_missing_charset(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let source = ctx.source;
+    let text = |n: Option<Node>| n.map_or("", |n| &source[n.byte_range()]);
+    let Some(args) = node.child_by_field_name("arguments") else { return };
+    let count = args.named_child_count();
+    let first = args.named_child(0);
+    let (call, fixable) = match node.kind() {
+        "method_invocation" if text(node.child_by_field_name("name")) == "getBytes" && count == 0 => ("getBytes()".to_string(), true),
+        "object_creation_expression" => {
+            let ty = text(node.child_by_field_name("type"));
+            let ty = ty.rsplit('.').next().unwrap_or(ty);
+            if ty == "String" && matches!(count, 1 | 3) && first.is_some_and(|a| is_byte_array(a, source)) {
+                ("new String(byte[])".to_string(), count == 1)
+            } else if let Some((_, since)) = CHARSET_CONSTRUCTORS.iter().find(|(c, _)| *c == ty)
+                && count == 1
+            {
+                (format!("new {ty}(...)"), ctx.config.java_version >= *since)
+            } else {
+                return;
+            }
+        }
+        _ => return,
+    };
+    let mut issue = issue_at(
+        node,
+        "missing-charset",
+        format!("{call} uses the platform default charset; pass one explicitly, e.g. StandardCharsets.UTF_8"),
+    );
+    if fixable {
+        let imported = source.contains("import java.nio.charset.StandardCharsets;") || source.contains("import java.nio.charset.*;");
+        let charset = if imported { "StandardCharsets.UTF_8" } else { "java.nio.charset.StandardCharsets.UTF_8" };
+        let (at, replacement) = match first {
+            Some(first) => (first.end_byte(), format!(", {charset}")),
+            None => (args.end_byte() - 1, charset.to_string()),
+        };
+        issue.fix = Some(Fix {
+            start_byte: at,
+            end_byte: at,
+            replacement: replacement.into(),
+            applicability: if ctx.config.java_version >= 18 { Applicability::Safe } else { Applicability::MaybeIncorrect },
+        });
+    }
+    out.push(issue);
+}
+
+/// Whether `expr` is known to be a `byte[]`: a variable declared so, or a
+/// `getBytes(...)`/`readAllBytes()` call.
+fn is_byte_array(expr: Node, source: &str) -> bool {
+    match expr.kind() {
+        "identifier" => declared_type(expr, source).is_some_and(|(ty, dims)| {
+            if dims { &source[ty.byte_range()] == "byte" } else { &source[ty.byte_range()] == "byte[]" }
+        }),
+        "method_invocation" => expr
+            .child_by_field_name("name")
+            .is_some_and(|n| matches!(&source[n.byte_range()], "getBytes" | "readAllBytes" | "toByteArray")),
+        _ => false,
+    }
+}
+
+/// Types whose instances hold an OS or database handle until closed.
+const CLOSEABLE_TYPES: &[&str] = &[
+    "FileInputStream",
+    "FileOutputStream",
+    "FileReader",
+    "FileWriter",
+    "BufferedReader",
+    "BufferedWriter",
+    "InputStreamReader",
+    "OutputStreamWriter",
+    "PrintWriter",
+    "Scanner",
+    "RandomAccessFile",
+    "ZipFile",
+    "JarFile",
+    "Socket",
+    "ServerSocket",
+];
+
+/// Calls returning a resource the caller must close.
+const CLOSEABLE_FACTORIES: &[&str] = &[
+    "getConnection",
+    "createStatement",
+    "prepareStatement",
+    "prepareCall",
+    "executeQuery",
+    "newInputStream",
+    "newOutputStream",
+    "newBufferedReader",
+    "newBufferedWriter",
+];
+
+/// Locals initialized with a new closeable resource, outside a
+/// try-with-resources header, that the method neither closes, returns,
+/// passes to another call nor stores elsewhere. A heuristic: closing on one
+/// path only still counts as closed.
+fn check_unclosed_resource(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let source = ctx.source;
+    let mut cursor = node.walk();
+    for declarator in node.children_by_field_name("declarator", &mut cursor) {
+        let (Some(name), Some(value)) = (declarator.child_by_field_name("name"), declarator.child_by_field_name("value")) else {
+            continue;
+        };
+        let text = |n: Option<Node>| n.map_or("", |n| &source[n.byte_range()]);
+        let created = match value.kind() {
+            "object_creation_expression" => {
+                let ty = text(value.child_by_field_name("type"));
+                CLOSEABLE_TYPES.contains(&ty.rsplit('.').next().unwrap_or(ty))
+            }
+            "method_invocation" => CLOSEABLE_FACTORIES.contains(&text(value.child_by_field_name("name"))),
+            _ => false,
+        };
+        if !created { continue; }
+        let Some(body) = std::iter::successors(node.parent(), |n| n.parent())
+            .find(|n| matches!(n.kind(), "method_declaration" | "constructor_declaration" | "lambda_expression"))
+            .and_then(|m| m.child_by_field_name("body"))
+        else {
+            continue;
+        };
+        let name = &source[name.byte_range()];
+        if !resource_escapes(body, name, source) {
+            out.push(issue_at(
+                declarator,
+                "unclosed-resource",
+                format!("'{name}' is never closed; declare it in a try-with-resources statement"),
+            ));
+        }
+    }
+}
+
+/// Whether some use of `name` in `body` closes it, hands it on (argument,
+/// `return`, assignment, try-with-resources) or otherwise lets it escape.
+fn resource_escapes(body: Node, name: &str, source: &str) -> bool {
+    let mut cursor = body.walk();
+    loop {
+        let node = cursor.node();
+        if node.kind() == "identifier"
+            && &source[node.byte_range()] == name
+            && let Some(parent) = node.parent()
+        {
+            let escapes = match parent.kind() {
+                "argument_list" | "return_statement" | "resource" | "array_initializer" | "lambda_expression" => true,
+                "assignment_expression" => parent.child_by_field_name("right").is_some_and(|r| r.id() == node.id()),
+                "variable_declarator" => parent.child_by_field_name("value").is_some_and(|v| v.id() == node.id()),
+                "method_invocation" => {
+                    parent.child_by_field_name("object").is_some_and(|o| o.id() == node.id())
+                        && parent.child_by_field_name("name").is_some_and(|n| &source[n.byte_range()] == "close")
+                }
+                _ => false,
+            };
+            if escapes { return true; }
+        }
+        if cursor.goto_first_child() { continue; }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() || cursor.node().id() == body.id() { return false; }
+        }
+    }
+}
+
+/// Nodes that open a scope for local variables and parameters.
+const LOCAL_SCOPES: &[&str] = &[
+    "method_declaration", "constructor_declaration", "block", "constructor_body", "switch_block", "for_statement",
+    "enhanced_for_statement", "catch_clause", "try_with_resources_statement", "lambda_expression",
+];
+
+/// Code that can declare local variables. `block` stands for instance
+/// initializers; blocks elsewhere sit inside one of the others.
+const VARIABLE_OWNERS: &[&str] = &[
+    "method_declaration", "constructor_declaration", "compact_constructor_declaration", "static_initializer", "block",
+    "lambda_expression",
+];
+
+/// Whether `node` owns local variables on its own, rather than inside
+/// another owner whose check covers it.
+fn outermost_variable_owner(node: Node) -> bool {
+    let owner = |n: Node| match n.kind() {
+        "block" => n.parent().is_some_and(|p| matches!(p.kind(), "class_body" | "enum_body_declarations")),
+        kind => VARIABLE_OWNERS.contains(&kind),
+    };
+    owner(node) && std::iter::successors(node.parent(), |n| n.parent()).all(|n| !owner(n))
+}
+
+/// A local variable or parameter in scope during `check_unused_variables`.
+struct Local<'s, 't> {
+    name: &'s str,
+    node: Node<'t>,
+    what: &'static str,
+    read: bool,
+}
+
+/// Local variables, and parameters of methods that can't be overridden
+/// (private, static or final ones and constructors), that nothing reads.
+/// Names resolve to the innermost declaration in scope, so a local of an
+/// anonymous class method shadows the enclosing method's, and a use inside a
+/// lambda or anonymous class counts for the local it captures. Assigning
+/// with `=` isn't a read. Names `ignored` and `unused` mark a deliberate
+/// choice; catch, lambda and resource variables aren't checked.
+///
+/// Runs on the outermost method, constructor, initializer or lambda, whose
+/// walk covers the classes and lambdas nested in it.
+fn check_unused_variables(root: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    if !outermost_variable_owner(root) { return; }
+    let source = ctx.source;
+    let text = |n: Node| &source[n.byte_range()];
+    let mut scopes: Vec<Vec<Local>> = Vec::new();
+    let mut not_reads: HashSet<usize> = HashSet::new(); // identifiers naming a declaration or assigned to
+    let mut cursor = root.walk();
+    loop {
+        let node = cursor.node();
+        if LOCAL_SCOPES.contains(&node.kind()) {
+            scopes.push(Vec::new());
+        }
+        let mut declared: Vec<(Node, &'static str)> = Vec::new();
+        match node.kind() {
+            "method_declaration" | "constructor_declaration" => {
+                not_reads.extend(node.child_by_field_name("name").map(|n| n.id()));
+                let params_checked = parameters_checked(node, ctx);
+                let params: Vec<Node> =
+                    node.child_by_field_name("parameters").map(|p| p.named_children(&mut p.walk()).collect()).unwrap_or_default();
+                for param in params {
+                    let name = match param.kind() {
+                        "formal_parameter" => param.child_by_field_name("name"),
+                        "spread_parameter" => param
+                            .named_children(&mut param.walk())
+                            .find(|c| c.kind() == "variable_declarator")
+                            .and_then(|d| d.child_by_field_name("name")),
+                        _ => None,
+                    };
+                    let Some(name) = name else { continue };
+                    not_reads.insert(name.id());
+                    if params_checked { declared.push((name, "Parameter")); }
+                }
+            }
+            "local_variable_declaration" | "field_declaration" | "constant_declaration" => {
+                let mut walk = node.walk();
+                for name in node.children_by_field_name("declarator", &mut walk).filter_map(|d| d.child_by_field_name("name")) {
+                    not_reads.insert(name.id());
+                    if node.kind() == "local_variable_declaration" { declared.push((name, "Local variable")); }
+                }
+            }
+            "enhanced_for_statement" => {
+                if let Some(name) = node.child_by_field_name("name") {
+                    not_reads.insert(name.id());
+                    declared.push((name, "Local variable"));
+                }
+            }
+            "lambda_expression" => {
+                if let Some(params) = node.child_by_field_name("parameters") {
+                    let mut walk = params.walk();
+                    let names: Vec<Node> = match params.kind() {
+                        "identifier" => vec![params],
+                        "formal_parameters" => params.named_children(&mut walk).filter_map(|p| p.child_by_field_name("name")).collect(),
+                        _ => params.named_children(&mut walk).collect(),
+                    };
+                    not_reads.extend(names.iter().map(|n| n.id()));
+                }
+            }
+            "assignment_expression" => {
+                if let (Some(left), Some("=")) = (node.child_by_field_name("left"), node.child_by_field_name("operator").map(|o| o.kind()))
+                    && left.kind() == "identifier"
+                {
+                    not_reads.insert(left.id());
+                }
+            }
+            "identifier" if !not_reads.contains(&node.id()) => {
+                let name = text(node);
+                if let Some(local) = scopes.iter_mut().rev().find_map(|s| s.iter_mut().rev().find(|l| l.name == name)) {
+                    local.read = true;
+                }
+            }
+            kind => {
+                // Names of declarations and members, which aren't variable reads.
+                let field = match kind {
+                    "method_invocation" | "class_declaration" | "interface_declaration" | "enum_declaration" | "record_declaration"
+                    | "enum_constant" | "catch_formal_parameter" | "resource" | "method_reference" => "name",
+                    "field_access" => "field",
+                    _ => "",
+                };
+                if let Some(name) = node.child_by_field_name(field) {
+                    not_reads.insert(name.id());
+                }
+            }
+        }
+        if let Some(scope) = scopes.last_mut() {
+            scope.extend(declared.into_iter().map(|(name, what)| Local { name: text(name), node: name, what, read: false }));
+        }
+        if cursor.goto_first_child() { continue; }
+        loop {
+            let left = cursor.node();
+            if LOCAL_SCOPES.contains(&left.kind()) {
+                for local in scopes.pop().unwrap_or_default() {
+                    if local.read || matches!(local.name, "ignored" | "unused") { continue; }
+                    out.push(issue_at(local.node, "unused-variable", format!("{} '{}' is never read", local.what, local.name)));
+                }
+            }
+            if cursor.goto_next_sibling() { break; }
+            if !cursor.goto_parent() { return; }
+        }
+    }
+}
+
+/// Whether a method's parameters are checked: it has a body, can't be
+/// overridden (a constructor, or private, static or final), isn't `main`,
+/// and carries none of `unused_exempt_annotations`.
+fn parameters_checked(node: Node, ctx: &RuleContext) -> bool {
+    if node.child_by_field_name("body").is_none() { return false; }
+    if node.child_by_field_name("name").is_some_and(|n| &ctx.source[n.byte_range()] == "main") { return false; }
+    let Some(modifiers) = node.child(0).filter(|c| c.kind() == "modifiers") else {
+        return node.kind() == "constructor_declaration";
+    };
+    let mut cursor = modifiers.walk();
+    let mut fixed = node.kind() == "constructor_declaration";
+    for modifier in modifiers.children(&mut cursor) {
+        match modifier.kind() {
+            "private" | "static" | "final" => fixed = true,
+            "marker_annotation" | "annotation" => {
+                let name = modifier.child_by_field_name("name").map_or("", |n| &ctx.source[n.byte_range()]);
+                let simple = name.rsplit('.').next().unwrap_or(name);
+                if ctx.config.unused_exempt_annotations.iter().any(|a| a == simple) { return false; }
+            }
+            _ => {}
+        }
+    }
+    fixed
+}
+
+/// Logging methods recognized on an object whose name mentions `log`.
+const LOG_METHODS: &[&str] = &["trace", "debug", "info", "warn", "warning", "error", "fatal", "severe", "log"];
+
+/// Catch variable names that mark ignoring the exception as deliberate.
+const IGNORED_CATCH_NAMES: &[&str] = &["_", "ignored", "ignore", "expected"];
+
+/// Whether a catch body holds no statements, only comments if anything.
+fn catch_body_is_empty(body: Node) -> bool {
+    let mut cursor = body.walk();
+    body.named_children(&mut cursor).all(|c| matches!(c.kind(), "line_comment" | "block_comment"))
+}
+
+/// Catch blocks that neither throw, log, nor use the caught exception, so
+/// the failure disappears, e.g. `catch (Exception e) { return null; }`.
+/// A name in `IGNORED_CATCH_NAMES` marks a deliberate choice, and empty
+/// bodies are left to `empty-catch`.
+fn check_swallowed_exception(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let source = ctx.source;
+    let Some(param) = node.named_children(&mut node.walk()).find(|c| c.kind() == "catch_formal_parameter") else { return };
+    let Some(body) = node.child_by_field_name("body") else { return };
+    if catch_body_is_empty(body) { return; }
+    let name = param.child_by_field_name("name").map_or("", |n| &source[n.byte_range()]);
+    if name.is_empty() || IGNORED_CATCH_NAMES.contains(&name) { return; }
+    let mut cursor = body.walk();
+    loop {
+        let n = cursor.node();
+        let handled = match n.kind() {
+            "throw_statement" => true,
+            "identifier" => &source[n.byte_range()] == name,
+            "method_invocation" => {
+                let text = |field| n.child_by_field_name(field).map_or("", |f| &source[f.byte_range()]);
+                text("object").to_ascii_lowercase().contains("log") && LOG_METHODS.contains(&text("name"))
+            }
+            _ => false,
+        };
+        if handled { return; }
+        if cursor.goto_first_child() { continue; }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() || cursor.node().id() == body.id() {
+                out.push(issue_at(
+                    param,
+                    "swallowed-exception",
+                    format!("Caught '{name}' is neither rethrown, logged nor used; handle it or name it 'ignored'"),
+                ));
+                return;
+            }
+        }
+    }
+}
+
+/// A class body declaring `equals(Object)` or `hashCode()` but not both, so
+/// equal objects could hash differently. Reported at the one it declares.
+fn check_equals_hashcode(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let source = ctx.source;
+    let (mut equals, mut hash_code) = (None, None);
+    let mut cursor = node.walk();
+    for method in node.named_children(&mut cursor).filter(|c| c.kind() == "method_declaration") {
+        let (Some(name), Some(params)) = (method.child_by_field_name("name"), method.child_by_field_name("parameters")) else { continue };
+        let mut walk = params.walk();
+        let types: Vec<&str> = params
+            .named_children(&mut walk)
+            .filter_map(|p| p.child_by_field_name("type"))
+            .map(|t| &source[t.byte_range()])
+            .collect();
+        match (&source[name.byte_range()], &types[..]) {
+            ("equals", ["Object" | "java.lang.Object"]) => equals = Some(name),
+            ("hashCode", []) => hash_code = Some(name),
+            _ => {}
+        }
+    }
+    let (name, message) = match (equals, hash_code) {
+        (Some(name), None) => (name, "Class overrides equals(Object) but not hashCode()"),
+        (None, Some(name)) => (name, "Class overrides hashCode() but not equals(Object)"),
+        _ => return,
+    };
+    out.push(issue_at(name, "equals-hashcode", message));
+}
+
+/// Catch blocks with nothing in them; comments alone count as something
+/// with `empty_catch_allow_comments`, and a variable named as in
+/// `IGNORED_CATCH_NAMES` marks a deliberate choice with
+/// `empty_catch_allow_ignored`.
+fn check_empty_catch(node: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let Some(body) = node.child_by_field_name("body") else { return };
+    if !catch_body_is_empty(body) { return; }
+    if body.named_child_count() > 0 && ctx.config.empty_catch_allow_comments { return; }
+    let param = node.named_children(&mut node.walk()).find(|c| c.kind() == "catch_formal_parameter");
+    let name = param.and_then(|p| p.child_by_field_name("name")).map_or("", |n| &ctx.source[n.byte_range()]);
+    if IGNORED_CATCH_NAMES.contains(&name) && ctx.config.empty_catch_allow_ignored { return; }
+    out.push(issue_at(node, "empty-catch", "Empty catch block; handle the exception or explain why it can be ignored"));
+}
+
+/// Keywords written `keyword (`.
+const PAREN_KEYWORDS: &[&str] = &["if", "for", "while", "switch", "catch", "synchronized", "try"];
+
+/// Keywords written `} keyword`.
+const CONTINUATION_KEYWORDS: &[&str] = &["else", "catch", "finally"];
+
+/// Tokens `check_spacing` looks at; the token before or after each decides
+/// whether its gap is checked.
+const SPACING_TOKENS: &[&str] = &["(", "{", ";", ",", "else", "catch", "finally"];
+
+/// Token spacing conventions from `Config::spacing`, checked between `token`
+/// and the token next to it on the same line. Gaps holding a line break or a
+/// comment are never touched.
+fn check_spacing(token: Node, ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let spacing = &ctx.config.spacing;
+    let source = ctx.source;
+    // `want` is the required gap: one space, or none.
+    let mut expect = |at: usize, end: usize, want: &'static str, message: String| {
+        let gap = &source[at..end];
+        if gap == want || !gap.bytes().all(|b| b == b' ' || b == b'\t') { return; }
+        let mut issue = LintIssue::new("spacing", message, at);
+        issue.fix = Some(Fix { start_byte: at, end_byte: end, replacement: Cow::Borrowed(want), applicability: Applicability::Safe });
+        out.push(issue);
+    };
+    let text = &source[token.byte_range()];
+    if spacing.after_comma
+        && text == ","
+        && let Some(next) = next_token(token)
+    {
+        expect(token.end_byte(), next.start_byte(), " ", "Expected one space after ','".to_string());
+    }
+    let Some(prev) = prev_token(token) else { return };
+    let prev_text = &source[prev.byte_range()];
+    let gap = (prev.end_byte(), token.start_byte());
+    if spacing.after_keyword && text == "(" && PAREN_KEYWORDS.contains(&prev_text) && !prev.is_named() {
+        expect(gap.0, gap.1, " ", format!("Expected one space between '{prev_text}' and '('"));
+    }
+    if spacing.before_continuation_keyword && prev_text == "}" && CONTINUATION_KEYWORDS.contains(&text) && !token.is_named() {
+        expect(gap.0, gap.1, " ", format!("Expected one space between '}}' and '{text}'"));
+    }
+    if spacing.before_brace
+        && text == "{"
+        && !matches!(prev_text, "(" | "{" | "[")
+        && token.parent().is_some_and(|p| p.kind().ends_with("body") || matches!(p.kind(), "block" | "switch_block"))
+    {
+        expect(gap.0, gap.1, " ", "Expected one space before '{'".to_string());
+    }
+    // `for (;;)` has nothing before its semicolons.
+    if spacing.before_semicolon && text == ";" && !matches!(prev_text, "(" | ";") && gap.0 < gap.1 {
+        expect(gap.0, gap.1, "", "Unexpected space before ';'".to_string());
+    }
+    if spacing.before_comma && text == "," && gap.0 < gap.1 {
+        expect(gap.0, gap.1, "", "Unexpected space before ','".to_string());
+    }
+}
+
+/// The token before `node`: the last leaf before it, with string literals
+/// counted as one token and comments included.
+fn prev_token(node: Node) -> Option<Node> {
+    let mut at = node;
+    let mut token = loop {
+        match at.prev_sibling() {
+            Some(prev) => break prev,
+            None => at = at.parent()?,
+        }
+    };
+    while token.kind() != "string_literal" && token.child_count() > 0 {
+        token = token.child(token.child_count() - 1)?;
+    }
+    Some(token)
+}
+
+/// The token after `node`, as `prev_token` counts them.
+fn next_token(node: Node) -> Option<Node> {
+    let mut at = node;
+    let mut token = loop {
+        match at.next_sibling() {
+            Some(next) => break next,
+            None => at = at.parent()?,
+        }
+    };
+    while token.kind() != "string_literal" && token.child_count() > 0 {
+        token = token.child(0)?;
+    }
+    Some(token)
+}
+
+/// Static whitespace runs so indentation fixes can borrow instead of allocating per line.
+const SPACES: &str = "                                                                ";
+const TABS: &str = "\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t\t";
+
+/// `n` copies of the whitespace character in `run`, borrowed when short enough.
+fn whitespace(run: &'static str, n: usize) -> Cow<'static, str> {
+    if n <= run.len() { Cow::Borrowed(&run[..n]) } else { Cow::Owned(run[..1].repeat(n)) }
+}
+
+/// Leading whitespace reaching display column `columns` in `indent_style`:
+/// tabs padded with spaces when the column isn't a whole number of tabs.
+pub(crate) fn indent_whitespace(config: &Config, columns: usize) -> Cow<'static, str> {
+    let tab_width = (config.tab_width as usize).max(1);
+    match config.indent_style {
+        IndentStyle::Spaces => whitespace(SPACES, columns),
+        IndentStyle::Tabs if columns.is_multiple_of(tab_width) => whitespace(TABS, columns / tab_width),
+        IndentStyle::Tabs => Cow::Owned(format!("{}{}", TABS[..1].repeat(columns / tab_width), " ".repeat(columns % tab_width))),
+    }
+}
+
+fn collect_indent_style(ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let (style, indent_width) = (ctx.config.indent_style, ctx.config.indent_width as usize);
+    let mut byte_pos = 0usize;
+    for line_inc in ctx.source.split_inclusive('\n') {
+        let line = line_inc.strip_suffix('\n').unwrap_or(line_inc);
+        let start_byte = byte_pos;
+        byte_pos += line_inc.len();
+
+        let trimmed_end = line.trim_end().as_bytes();
+        if trimmed_end.is_empty() { continue; }
+        // Leading whitespace is ASCII, so byte counts equal char counts here.
+        let leading_ws_len = trimmed_end
+            .iter()
+            .take_while(|b| **b == b' ' || **b == b'\t')
+            .count();
+        if leading_ws_len == 0 { continue; }
+        let leading = &trimmed_end[..leading_ws_len];
+        let tabs = leading.iter().filter(|b| **b == b'\t').count();
+        let spaces = leading_ws_len - tabs;
+
+        match style {
+            IndentStyle::Tabs => {
+                if spaces > 0 {
+                    // Suggest fix only if spaces count aligns to indent width and no tabs mixed.
+                    let fix = if tabs == 0 && spaces % indent_width == 0 {
+                        Some(Fix {
+                            start_byte,
+                            end_byte: start_byte + leading_ws_len,
+                            replacement: whitespace(TABS, spaces / indent_width),
+                            applicability: Applicability::Safe,
+                        })
+                    } else {
+                        None
+                    };
+
+                    let mut issue = LintIssue::new("indent-style", "Use tabs for indentation", start_byte);
+                    issue.fix = fix;
+                    out.push(issue);
+                }
+            }
+            IndentStyle::Spaces => {
+                if tabs > 0 {
+                    // Replace the run with as many spaces as it spans, so the line keeps its column.
+                    let width = leading.iter().fold(0, |col, &b| advance_column(col, b as char, ctx.config.tab_width as usize));
+                    let mut issue = LintIssue::new("indent-style", "Use spaces for indentation", start_byte);
+                    issue.fix = Some(Fix {
+                        start_byte,
+                        end_byte: start_byte + leading_ws_len,
+                        replacement: whitespace(SPACES, width),
+                        applicability: Applicability::Safe,
+                    });
+                    out.push(issue);
+                }
+            }
+        }
+    }
+}
+
+/// A non-empty file must end with a line break, and with `eof_newline_exactly_one`
+/// with no blank lines after the last one.
+fn collect_eof_newline(ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let source = ctx.source;
+    let content_end = source.trim_end().len();
+    if content_end == 0 { return; }
+    let newline = ctx.config.line_ending.resolve(source);
+    let (message, start) = if !source.ends_with('\n') {
+        ("File does not end with a newline", source.len())
+    } else if ctx.config.eof_newline_exactly_one && source[content_end..].matches('\n').count() > 1 {
+        ("File ends with more than one newline", content_end)
+    } else {
+        return;
+    };
+    let end = if start == content_end { source.len() } else { start };
+    let mut issue = LintIssue::new("eof-newline", message, start);
+    issue.end_offset = end;
+    issue.fix = Some(Fix {
+        start_byte: start,
+        end_byte: end,
+        replacement: Cow::Borrowed(newline),
+        applicability: Applicability::Safe,
+    });
+    out.push(issue);
+}
+
+/// Line breaks other than the one `line_ending` resolves to for the file.
+fn collect_line_endings(ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let crlf = ctx.config.line_ending.resolve(ctx.source) == "\r\n";
+    let (name, found) = if crlf { ("CRLF", "LF") } else { ("LF", "CRLF") };
+    for (at, _) in ctx.source.match_indices('\n') {
+        let has_cr = ctx.source[..at].ends_with('\r');
+        if has_cr == crlf { continue; }
+        let (start, end) = if has_cr { (at - 1, at) } else { (at, at) };
+        let mut issue = LintIssue::new("line-ending", format!("Expected {name} line ending, found {found}"), start);
+        issue.fix = Some(Fix {
+            start_byte: start,
+            end_byte: end,
+            replacement: Cow::Borrowed(if has_cr { "" } else { "\r" }),
+            applicability: Applicability::Safe,
+        });
+        out.push(issue);
+    }
+}
+
+/// Flags files whose header doesn't match `Config::license_header`. A leading
+/// comment mentioning a copyright or license is treated as an outdated header
+/// and replaced; otherwise the header is inserted at the top of the file.
+fn collect_license_header(ctx: &RuleContext, out: &mut Vec<LintIssue>) {
+    let Some(template) = ctx.config.license_header.as_deref() else { return };
+    // Written with the file's line breaks, so CRLF files match and stay CRLF.
+    let newline = ctx.config.line_ending.resolve(ctx.source);
+    let template = template.trim_end().replace("\r\n", "\n").replace('\n', newline);
+    let template = template.as_str();
+    let start = ctx.source.len() - ctx.source.trim_start().len();
+    if match_header(template, &ctx.source[start..]).is_some() { return; }
+
+    let existing = leading_comment(&ctx.source[start..])
+        .filter(|c| {
+            let lower = c.to_ascii_lowercase();
+            lower.contains("copyright") || lower.contains("license")
+        });
+    let issue = match existing {
+        Some(old) => {
+            let year = first_year(old).map_or_else(current_year, str::to_string);
+            let mut issue = LintIssue::new("license-header", "License header does not match the configured template", start);
+            issue.fix = Some(Fix {
+                start_byte: start,
+                end_byte: start + old.len(),
+                replacement: template.replace("${year}", &year).into(),
+                applicability: Applicability::MaybeIncorrect,
+            });
+            issue
+        }
+        None => {
+            let mut issue = LintIssue::new("license-header", "Missing license header", 0);
+            issue.fix = Some(Fix {
+                start_byte: 0,
+                end_byte: start,
+                replacement: format!("{}{newline}{newline}", template.replace("${year}", &current_year())).into(),
+                applicability: Applicability::Safe,
+            });
+            issue
+        }
+    };
+    out.push(issue);
+}
+
+/// Match `template` against the start of `text`, where `${year}` accepts a
+/// year or a range like `2019-2024`. Returns the matched length.
+fn match_header(template: &str, text: &str) -> Option<usize> {
+    let mut pos = 0;
+    for (i, part) in template.split("${year}").enumerate() {
+        if i > 0 {
+            pos += year_len(&text[pos..])?;
+        }
+        if !text[pos..].starts_with(part) { return None; }
+        pos += part.len();
+    }
+    Some(pos)
+}
+
+/// Length of a `YYYY` or `YYYY-YYYY` prefix of `text`.
+fn year_len(text: &str) -> Option<usize> {
+    let digits = |s: &str| s.len() >= 4 && s.as_bytes()[..4].iter().all(u8::is_ascii_digit);
+    if !digits(text) { return None; }
+    match text[4..].strip_prefix('-') {
+        Some(rest) if digits(rest) => Some(9),
+        _ => Some(4),
+    }
+}
+
+/// The first year or year range in `text`, kept when an outdated header is replaced.
+fn first_year(text: &str) -> Option<&str> {
+    (0..text.len()).find_map(|i| {
+        let rest = text.get(i..)?;
+        let boundary = i == 0 || !text.as_bytes()[i - 1].is_ascii_digit();
+        let len = year_len(rest).filter(|_| boundary)?;
+        Some(&rest[..len])
+    })
+}
+
+/// The comment `text` starts with: one `/* ... */` block or a run of `//` lines.
+fn leading_comment(text: &str) -> Option<&str> {
+    if text.starts_with("/*") {
+        return text.find("*/").map(|end| &text[..end + 2]);
+    }
+    if !text.starts_with("//") { return None; }
+    let mut end = 0;
+    for line in text.split_inclusive('\n') {
+        if !line.trim_start().starts_with("//") { break; }
+        end += line.len();
+    }
+    Some(text[..end].trim_end())
+}
+
+/// The current calendar year (UTC), for filling `${year}` in new headers.
+fn current_year() -> String {
+    let secs = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs());
+    // Civil-from-days (Howard Hinnant), reduced to the year.
+    let z = (secs / 86_400) as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let year = yoe + era * 400 + i64::from(mp >= 10);
+    year.to_string()
+}
@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use crate::sonar::rule_info;
+use crate::{ColumnUnit, ReportedIssue, Severity};
+
+/// Issues as a SARIF 2.1.0 log with a single run, for GitHub Code Scanning
+/// and other SARIF consumers.
+///
+/// Every rule that fired gets a `reportingDescriptor` with its name and a
+/// default level derived from its impact (high: `error`, medium: `warning`,
+/// low: `note`); each result's level is the issue's configured severity.
+/// Relative paths are given against `%SRCROOT%`, so run jfmt from the
+/// repository root. `unit` is how the issue columns were counted; SARIF
+/// only knows UTF-16 code units and code points, and anything else is
+/// declared as UTF-16.
+pub fn sarif_report(issues: &[ReportedIssue], unit: ColumnUnit) -> Value {
+    let mut index: BTreeMap<&str, usize> = issues.iter().map(|i| (i.rule.as_str(), 0)).collect();
+    for (n, slot) in index.values_mut().enumerate() {
+        *slot = n;
+    }
+    let rules: Vec<Value> = index
+        .keys()
+        .map(|&id| {
+            let rule = rule_info(id);
+            let name = if rule.name.is_empty() { id } else { rule.name };
+            json!({
+                "id": id,
+                "shortDescription": { "text": name },
+                "fullDescription": { "text": format!("{name} (jfmt rule `{id}`)") },
+                "defaultConfiguration": { "level": level(rule.impact) },
+                "properties": { "tags": [rule.attribute.to_lowercase()] },
+            })
+        })
+        .collect();
+    let results: Vec<Value> = issues
+        .iter()
+        .map(|issue| {
+            let mut region = json!({ "startLine": issue.line, "startColumn": issue.column });
+            if (issue.end_line, issue.end_column) > (issue.line, issue.column) {
+                region["endLine"] = json!(issue.end_line);
+                region["endColumn"] = json!(issue.end_column);
+            }
+            let mut result = json!({
+                "ruleId": issue.rule,
+                "ruleIndex": index[issue.rule.as_str()],
+                "level": match issue.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                    Severity::Info => "note",
+                },
+                "message": { "text": issue.message },
+                "locations": [{
+                    "physicalLocation": { "artifactLocation": artifact(&issue.path), "region": region },
+                }],
+            });
+            if issue.fixable {
+                result["properties"] = json!({ "fixable": true });
+            }
+            result
+        })
+        .collect();
+    let column_kind = match unit {
+        ColumnUnit::Chars => "unicodeCodePoints",
+        _ => "utf16CodeUnits",
+    };
+    json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "jfmt",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                },
+            },
+            "columnKind": column_kind,
+            "results": results,
+        }],
+    })
+}
+
+fn level(impact: &str) -> &'static str {
+    match impact {
+        "HIGH" => "error",
+        "MEDIUM" => "warning",
+        _ => "note",
+    }
+}
+
+/// `artifactLocation` for `path`: a forward-slash URI, relative to the
+/// source root unless the path is absolute.
+fn artifact(path: &Path) -> Value {
+    let text = path.to_string_lossy().replace('\\', "/");
+    if path.is_absolute() {
+        let text = if text.starts_with('/') { text } else { format!("/{text}") };
+        return json!({ "uri": format!("file://{}", percent_encode(&text)) });
+    }
+    let text = text.trim_start_matches("./");
+    json!({ "uri": percent_encode(text), "uriBaseId": "%SRCROOT%" })
+}
+
+/// Escape the characters that can't appear raw in a URI path.
+fn percent_encode(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for b in path.bytes() {
+        if b.is_ascii_alphanumeric() || b"/-._~!$&'()*+,;=:@".contains(&b) {
+            out.push(b as char);
+        } else {
+            out.push_str(&format!("%{b:02X}"));
+        }
+    }
+    out
+}
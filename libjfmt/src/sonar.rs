@@ -0,0 +1,143 @@
+use std::collections::BTreeSet;
+
+use serde_json::{json, Value};
+
+use crate::ReportedIssue;
+
+/// How a rule is presented in SonarQube (and, reusing the name and impact,
+/// in SARIF).
+pub(crate) struct SonarRule {
+    pub(crate) id: &'static str,
+    pub(crate) name: &'static str,
+    /// Sonar clean code attribute.
+    pub(crate) attribute: &'static str,
+    /// Impact severity on maintainability: `LOW`, `MEDIUM` or `HIGH`.
+    pub(crate) impact: &'static str,
+    /// Minutes to resolve one issue by hand.
+    effort: u32,
+}
+
+const RULES: &[SonarRule] = &[
+    SonarRule { id: "no-wildcard-imports", name: "Wildcard imports", attribute: "CLEAR", impact: "LOW", effort: 2 },
+    SonarRule { id: "organize-imports", name: "Import order", attribute: "FORMATTED", impact: "LOW", effort: 1 },
+    SonarRule { id: "unused-import", name: "Unused import", attribute: "CLEAR", impact: "LOW", effort: 1 },
+    SonarRule { id: "no-empty-statement", name: "Empty statements", attribute: "CLEAR", impact: "LOW", effort: 1 },
+    SonarRule { id: "continuation-indent", name: "Continuation indent", attribute: "FORMATTED", impact: "LOW", effort: 1 },
+    SonarRule { id: "indentation", name: "Indentation depth", attribute: "FORMATTED", impact: "LOW", effort: 1 },
+    SonarRule { id: "operator-whitespace", name: "Whitespace around operators", attribute: "FORMATTED", impact: "LOW", effort: 1 },
+    SonarRule { id: "spacing", name: "Keyword and punctuation spacing", attribute: "FORMATTED", impact: "LOW", effort: 1 },
+    SonarRule { id: "max-blank-lines", name: "Too many blank lines in a row", attribute: "FORMATTED", impact: "LOW", effort: 1 },
+    SonarRule { id: "blank-line-between-methods", name: "Methods not separated by blank lines", attribute: "FORMATTED", impact: "LOW", effort: 1 },
+    SonarRule { id: "class-naming", name: "Type name convention", attribute: "IDENTIFIABLE", impact: "LOW", effort: 5 },
+    SonarRule { id: "method-naming", name: "Method name convention", attribute: "IDENTIFIABLE", impact: "LOW", effort: 5 },
+    SonarRule { id: "constant-naming", name: "Constant name convention", attribute: "IDENTIFIABLE", impact: "LOW", effort: 5 },
+    SonarRule { id: "package-naming", name: "Package name convention", attribute: "IDENTIFIABLE", impact: "LOW", effort: 5 },
+    SonarRule { id: "one-top-level-class", name: "Several top-level types in one file", attribute: "MODULAR", impact: "LOW", effort: 10 },
+    SonarRule { id: "class-file-name", name: "Public type not named after its file", attribute: "CONVENTIONAL", impact: "HIGH", effort: 5 },
+    SonarRule { id: "missing-javadoc", name: "Undocumented public API", attribute: "CLEAR", impact: "LOW", effort: 5 },
+    SonarRule { id: "javadoc-tags", name: "Javadoc tags out of step with signature", attribute: "CLEAR", impact: "LOW", effort: 2 },
+    SonarRule { id: "javadoc-format", name: "Javadoc layout", attribute: "FORMATTED", impact: "LOW", effort: 1 },
+    SonarRule { id: "annotation-placement", name: "Annotation on the declaration's line", attribute: "FORMATTED", impact: "LOW", effort: 1 },
+    SonarRule { id: "modifier-order", name: "Modifiers out of order", attribute: "CONVENTIONAL", impact: "LOW", effort: 1 },
+    SonarRule { id: "require-braces", name: "Control statement without braces", attribute: "CLEAR", impact: "LOW", effort: 1 },
+    SonarRule { id: "brace-style", name: "Brace placement", attribute: "FORMATTED", impact: "LOW", effort: 1 },
+    SonarRule { id: "switch-style", name: "Switch label form", attribute: "CONVENTIONAL", impact: "LOW", effort: 2 },
+    SonarRule { id: "switch-fallthrough", name: "Switch case fall-through", attribute: "LOGICAL", impact: "MEDIUM", effort: 5 },
+    SonarRule { id: "missing-default", name: "Switch without default", attribute: "COMPLETE", impact: "LOW", effort: 5 },
+    SonarRule { id: "switch-expression", name: "Switch statement computing a value", attribute: "CLEAR", impact: "LOW", effort: 3 },
+    SonarRule { id: "max-method-length", name: "Method too long", attribute: "FOCUSED", impact: "MEDIUM", effort: 20 },
+    SonarRule { id: "max-parameters", name: "Too many parameters", attribute: "FOCUSED", impact: "MEDIUM", effort: 20 },
+    SonarRule { id: "max-complexity", name: "Method too complex", attribute: "FOCUSED", impact: "MEDIUM", effort: 30 },
+    SonarRule { id: "anonymous-class-size", name: "Oversized anonymous class", attribute: "FOCUSED", impact: "MEDIUM", effort: 10 },
+    SonarRule { id: "hardcoded-path", name: "Hardcoded filesystem path", attribute: "CONVENTIONAL", impact: "MEDIUM", effort: 5 },
+    SonarRule { id: "locale-sensitive", name: "Default locale dependence", attribute: "CONVENTIONAL", impact: "MEDIUM", effort: 2 },
+    SonarRule { id: "no-console-output", name: "Console output instead of logging", attribute: "CONVENTIONAL", impact: "MEDIUM", effort: 5 },
+    SonarRule { id: "magic-number", name: "Magic number", attribute: "CLEAR", impact: "LOW", effort: 5 },
+    SonarRule { id: "null-collection-return", name: "Null returned for a collection", attribute: "LOGICAL", impact: "MEDIUM", effort: 5 },
+    SonarRule { id: "bigdecimal-double", name: "BigDecimal from double", attribute: "LOGICAL", impact: "HIGH", effort: 2 },
+    SonarRule { id: "string-equality", name: "String compared with ==", attribute: "LOGICAL", impact: "HIGH", effort: 2 },
+    SonarRule { id: "float-equality", name: "Floating-point equality", attribute: "LOGICAL", impact: "MEDIUM", effort: 5 },
+    SonarRule { id: "array-to-string", name: "Array printed with toString", attribute: "LOGICAL", impact: "MEDIUM", effort: 2 },
+    SonarRule { id: "legacy-date-time", name: "Legacy date-time API", attribute: "CONVENTIONAL", impact: "LOW", effort: 10 },
+    SonarRule { id: "insecure-random", name: "Predictable random in security code", attribute: "TRUSTWORTHY", impact: "HIGH", effort: 5 },
+    SonarRule { id: "missing-charset", name: "Platform default charset", attribute: "CONVENTIONAL", impact: "MEDIUM", effort: 2 },
+    SonarRule { id: "unclosed-resource", name: "Resource not closed", attribute: "COMPLETE", impact: "HIGH", effort: 5 },
+    SonarRule { id: "swallowed-exception", name: "Swallowed exception", attribute: "COMPLETE", impact: "HIGH", effort: 5 },
+    SonarRule { id: "equals-hashcode", name: "equals without hashCode", attribute: "LOGICAL", impact: "HIGH", effort: 5 },
+    SonarRule { id: "empty-catch", name: "Empty catch block", attribute: "COMPLETE", impact: "MEDIUM", effort: 5 },
+    SonarRule { id: "unused-variable", name: "Unused local variable or parameter", attribute: "CLEAR", impact: "MEDIUM", effort: 2 },
+    SonarRule { id: "instanceof-pattern", name: "Cast after instanceof", attribute: "CLEAR", impact: "LOW", effort: 2 },
+    SonarRule { id: "no-blank-line-at-block-boundary", name: "Blank line at block boundary", attribute: "FORMATTED", impact: "LOW", effort: 1 },
+    SonarRule { id: "no-trailing-whitespace", name: "Trailing whitespace", attribute: "FORMATTED", impact: "LOW", effort: 1 },
+    SonarRule { id: "spellcheck", name: "Misspelled words", attribute: "CLEAR", impact: "LOW", effort: 1 },
+    SonarRule { id: "max-line-length", name: "Line too long", attribute: "FORMATTED", impact: "LOW", effort: 2 },
+    SonarRule { id: "line-ending", name: "Line ending", attribute: "FORMATTED", impact: "LOW", effort: 1 },
+    SonarRule { id: "eof-newline", name: "Newline at end of file", attribute: "FORMATTED", impact: "LOW", effort: 1 },
+    SonarRule { id: "indent-style", name: "Indentation style", attribute: "FORMATTED", impact: "LOW", effort: 1 },
+    SonarRule { id: "license-header", name: "License header", attribute: "LAWFUL", impact: "MEDIUM", effort: 2 },
+    SonarRule { id: "unused-suppression", name: "Unused suppression", attribute: "CLEAR", impact: "LOW", effort: 1 },
+];
+
+/// Used for rules without an entry above.
+const DEFAULT_RULE: SonarRule = SonarRule { id: "", name: "", attribute: "CONVENTIONAL", impact: "LOW", effort: 5 };
+
+/// Presentation of rule `id`, or defaults for rules without an entry.
+pub(crate) fn rule_info(id: &str) -> &'static SonarRule {
+    RULES.iter().find(|r| r.id == id).unwrap_or(&DEFAULT_RULE)
+}
+
+/// Issues in SonarQube's Generic Issue Import format (`rules` plus `issues`),
+/// for `sonar.externalIssuesReportPaths`.
+///
+/// Columns are passed through as 0-based `startColumn`s, so they should be
+/// counted in characters. Issues with an autofix take one minute to resolve;
+/// others take the rule's estimate.
+pub fn sonar_report(issues: &[ReportedIssue]) -> Value {
+    let used: BTreeSet<&str> = issues.iter().map(|i| i.rule.as_str()).collect();
+    let rules: Vec<Value> = used
+        .into_iter()
+        .map(|id| {
+            let rule = rule_info(id);
+            json!({
+                "id": id,
+                "name": if rule.name.is_empty() { id } else { rule.name },
+                "description": format!("jfmt rule `{id}`"),
+                "engineId": "jfmt",
+                "cleanCodeAttribute": rule.attribute,
+                "impacts": [{ "softwareQuality": "MAINTAINABILITY", "severity": rule.impact }],
+                // Pre-10.3 servers read these instead of the impacts.
+                "type": "CODE_SMELL",
+                "severity": legacy_severity(rule.impact),
+            })
+        })
+        .collect();
+    let issues: Vec<Value> = issues
+        .iter()
+        .map(|issue| {
+            let mut range = json!({ "startLine": issue.line, "startColumn": issue.column.saturating_sub(1) });
+            // Sonar rejects empty ranges, so point issues give only the start.
+            if (issue.end_line, issue.end_column) > (issue.line, issue.column) {
+                range["endLine"] = json!(issue.end_line);
+                range["endColumn"] = json!(issue.end_column.saturating_sub(1));
+            }
+            json!({
+                "ruleId": issue.rule,
+                "effortMinutes": if issue.fixable { 1 } else { rule_info(&issue.rule).effort },
+                "primaryLocation": {
+                    "message": issue.message,
+                    "filePath": issue.path,
+                    "textRange": range,
+                },
+            })
+        })
+        .collect();
+    json!({ "rules": rules, "issues": issues })
+}
+
+fn legacy_severity(impact: &str) -> &'static str {
+    match impact {
+        "HIGH" => "CRITICAL",
+        "MEDIUM" => "MAJOR",
+        _ => "MINOR",
+    }
+}
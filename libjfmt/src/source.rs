@@ -0,0 +1,68 @@
+use std::fs::{self, File};
+use std::io;
+use std::ops::Deref;
+use std::path::Path;
+use std::process::Command;
+
+use memmap2::Mmap;
+
+/// Contents of a source file, either read into memory or memory-mapped.
+///
+/// Large generated files are mapped instead of copied into a `String`; the
+/// parser and rules read straight from the mapping.
+pub enum SourceText {
+    Owned(String),
+    Mapped(Mmap),
+}
+
+impl SourceText {
+    /// Read `path`, memory-mapping it when it is at least `mmap_threshold`
+    /// bytes. Falls back to a plain read if mapping is not possible.
+    pub fn read(path: &Path, mmap_threshold: u64) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let len = file.metadata()?.len();
+        if len > 0 && len >= mmap_threshold {
+            // SAFETY: the mapping is read-only and dropped after linting. If another
+            // process truncates the file meanwhile, reads may fault; that is the
+            // accepted trade-off for avoiding the copy on multi-megabyte inputs.
+            if let Ok(map) = unsafe { Mmap::map(&file) } {
+                std::str::from_utf8(&map)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                return Ok(SourceText::Mapped(map));
+            }
+        }
+        Ok(SourceText::Owned(fs::read_to_string(path)?))
+    }
+
+    /// The content of `path` as staged in the git index, which may differ from
+    /// the working tree. Relative paths are taken from the current directory.
+    pub fn staged(path: &Path) -> io::Result<Self> {
+        // `:./name` from the file's own directory: git reads `:/...` as a
+        // commit message search, so absolute paths can't be passed as is.
+        let dir = path.parent().filter(|d| !d.as_os_str().is_empty()).unwrap_or(Path::new("."));
+        let mut spec = std::ffi::OsString::from(":./");
+        spec.push(path.file_name().unwrap_or(path.as_os_str()));
+        let out = Command::new("git").arg("-C").arg(dir).arg("show").arg(spec).output()?;
+        if !out.status.success() {
+            let stderr = String::from_utf8_lossy(&out.stderr);
+            let msg = stderr.lines().next().unwrap_or_default().trim_start_matches("fatal: ");
+            return Err(io::Error::other(format!("not in the git index: {msg}")));
+        }
+        let text = String::from_utf8(out.stdout).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        Ok(SourceText::Owned(text))
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            SourceText::Owned(s) => s,
+            // SAFETY: validated as UTF-8 in `read` and the mapping is read-only.
+            SourceText::Mapped(map) => unsafe { std::str::from_utf8_unchecked(map) },
+        }
+    }
+}
+
+impl Deref for SourceText {
+    type Target = str;
+
+    fn deref(&self) -> &str { self.as_str() }
+}
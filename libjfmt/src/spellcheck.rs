@@ -0,0 +1,103 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+
+/// Misspellings shipped with jfmt, one `typo->correction` per line. This is a
+/// list of common typos, not a full wordlist: words it doesn't name are
+/// never flagged.
+const BUNDLED: &str = include_str!("../data/misspellings.txt");
+
+/// Known typos plus the project's own additions and exceptions.
+#[derive(Debug, Default)]
+pub(crate) struct Dictionary {
+    typos: HashMap<String, String>,
+    allowed: HashSet<String>,
+    /// Set when the project dictionary could not be read.
+    pub error: Option<String>,
+}
+
+impl Dictionary {
+    /// Build from the bundled list and an optional project dictionary. In the
+    /// project file, a bare word is accepted even if listed as a typo, and a
+    /// `typo->correction` line adds a misspelling.
+    fn load(project: Option<&Path>) -> Self {
+        let mut dict = Dictionary::default();
+        dict.add(BUNDLED);
+        if let Some(path) = project {
+            match std::fs::read_to_string(path) {
+                Ok(text) => dict.add(&text),
+                Err(err) => dict.error = Some(format!("cannot read spellcheck dictionary {}: {err}", path.display())),
+            }
+        }
+        dict
+    }
+
+    fn add(&mut self, text: &str) {
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            match line.split_once("->") {
+                Some((typo, fix)) => {
+                    self.typos.insert(typo.trim().to_ascii_lowercase(), fix.trim().to_string());
+                }
+                None => {
+                    self.allowed.insert(line.to_ascii_lowercase());
+                }
+            }
+        }
+    }
+
+    /// The correction for `word` if it is a known typo.
+    pub fn correction(&self, word: &str) -> Option<&str> {
+        let lower = word.to_ascii_lowercase();
+        if self.allowed.contains(&lower) { return None; }
+        self.typos.get(&lower).map(String::as_str)
+    }
+}
+
+/// The dictionary for a project file, loaded once per process. Rules get it
+/// through the rule context, which looks it up once per file.
+pub(crate) fn dictionary(project: Option<&Path>) -> Arc<Dictionary> {
+    static LOADED: OnceLock<Mutex<HashMap<Option<PathBuf>, Arc<Dictionary>>>> = OnceLock::new();
+    let mut loaded = LOADED.get_or_init(Default::default).lock().unwrap_or_else(|e| e.into_inner());
+    loaded
+        .entry(project.map(Path::to_path_buf))
+        .or_insert_with(|| Arc::new(Dictionary::load(project)))
+        .clone()
+}
+
+/// Words in `text` with their byte offsets. Runs of letters are split at
+/// camelCase boundaries, so `recieveHTTPResponse` yields `recieve`, `HTTP`
+/// and `Response`; digits and punctuation separate words.
+pub(crate) fn words(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    let bytes = text.as_bytes();
+    let mut i = 0;
+    std::iter::from_fn(move || {
+        while i < bytes.len() && !bytes[i].is_ascii_alphabetic() {
+            i += 1;
+        }
+        if i >= bytes.len() { return None; }
+        let start = i;
+        i += 1;
+        while i < bytes.len() && bytes[i].is_ascii_alphabetic() {
+            let (prev, cur) = (bytes[i - 1], bytes[i]);
+            let next_lower = bytes.get(i + 1).is_some_and(u8::is_ascii_lowercase);
+            if cur.is_ascii_uppercase() && (prev.is_ascii_lowercase() || (prev.is_ascii_uppercase() && next_lower)) {
+                break;
+            }
+            i += 1;
+        }
+        Some((start, &text[start..i]))
+    })
+}
+
+/// `correction` with the capitalization of `word`. Corrections from a project
+/// dictionary may be non-ASCII (`ecole->école`), so case is changed per char.
+pub(crate) fn match_case(word: &str, correction: &str) -> String {
+    if word.len() > 1 && word.bytes().all(|b| b.is_ascii_uppercase()) {
+        return correction.to_uppercase();
+    }
+    let mut chars = correction.chars();
+    match chars.next() {
+        Some(first) if word.starts_with(|c: char| c.is_ascii_uppercase()) => first.to_uppercase().chain(chars).collect(),
+        _ => correction.to_string(),
+    }
+}
@@ -0,0 +1,49 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// Wall-clock timings collected while linting, per phase and per rule.
+///
+/// Timings from several files (or worker threads) are summed with `merge`, so
+/// phase totals can exceed the elapsed time of a parallel run.
+#[derive(Debug, Clone, Default)]
+pub struct LintStats {
+    /// Reading source files.
+    pub io: Duration,
+    /// Parsing, including incremental re-parses after fixes.
+    pub parse: Duration,
+    /// Running rules; the sum of `per_rule` plus dispatch overhead.
+    pub rules: Duration,
+    /// Applying fixes to the source text.
+    pub fix: Duration,
+    /// Writing fixed files back to disk.
+    pub write: Duration,
+    pub per_rule: BTreeMap<&'static str, Duration>,
+}
+
+impl LintStats {
+    pub fn merge(&mut self, other: &LintStats) {
+        self.io += other.io;
+        self.parse += other.parse;
+        self.rules += other.rules;
+        self.fix += other.fix;
+        self.write += other.write;
+        for (rule, time) in &other.per_rule {
+            *self.per_rule.entry(rule).or_default() += *time;
+        }
+    }
+
+    pub(crate) fn add_rule_time(&mut self, rule: &'static str, time: Duration) {
+        *self.per_rule.entry(rule).or_default() += time;
+    }
+
+    /// Phases in pipeline order, for display.
+    pub fn phases(&self) -> [(&'static str, Duration); 5] {
+        [
+            ("io", self.io),
+            ("parse", self.parse),
+            ("rules", self.rules),
+            ("fix", self.fix),
+            ("write", self.write),
+        ]
+    }
+}
@@ -0,0 +1,56 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::FileReport;
+
+/// Issue counts for one rule.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RuleCount {
+    pub total: usize,
+    /// Issues `--fix` can resolve; the rest need manual work.
+    pub fixable: usize,
+}
+
+impl RuleCount {
+    pub fn manual(&self) -> usize { self.total - self.fixable }
+}
+
+/// Aggregate view of a run's issues, for planning cleanups rather than fixing
+/// individual sites.
+#[derive(Debug, Clone, Default)]
+pub struct IssueSummary {
+    pub per_rule: BTreeMap<&'static str, RuleCount>,
+    /// Issue count per parent directory of the linted files.
+    pub per_directory: BTreeMap<PathBuf, usize>,
+    /// Files with at least one issue, most issues first, ties by path.
+    pub per_file: Vec<(PathBuf, usize)>,
+}
+
+impl IssueSummary {
+    /// Tally issues over a batch of reports. Files that failed or were skipped
+    /// contribute nothing.
+    pub fn from_reports(reports: &[FileReport]) -> Self {
+        let mut summary = IssueSummary::default();
+        for report in reports {
+            let Ok(outcome) = &report.outcome else { continue };
+            if outcome.issues.is_empty() { continue; }
+            for issue in &outcome.issues {
+                let count = summary.per_rule.entry(issue.rule_id).or_default();
+                count.total += 1;
+                count.fixable += usize::from(issue.fix.is_some());
+            }
+            let dir = report.path.parent().unwrap_or(Path::new("")).to_path_buf();
+            *summary.per_directory.entry(dir).or_default() += outcome.issues.len();
+            summary.per_file.push((report.path.clone(), outcome.issues.len()));
+        }
+        summary.per_file.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        summary
+    }
+
+    pub fn total(&self) -> RuleCount {
+        self.per_rule.values().fold(RuleCount::default(), |acc, c| RuleCount {
+            total: acc.total + c.total,
+            fixable: acc.fixable + c.fixable,
+        })
+    }
+}
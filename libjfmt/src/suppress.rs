@@ -0,0 +1,227 @@
+use std::borrow::Cow;
+
+use tree_sitter::{Node, Tree};
+
+use crate::rules::known_rule_id;
+use crate::{Applicability, Fix, LineIndex, LintIssue, RuleSet};
+
+/// Rule reporting suppressions that no longer suppress anything.
+const UNUSED: &str = "unused-suppression";
+
+/// What a suppression covers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Scope {
+    /// One line (0-based).
+    Line(usize),
+    File,
+    /// Byte range of a declaration annotated with `@SuppressWarnings`.
+    Range(usize, usize),
+}
+
+#[derive(Debug)]
+struct Suppression {
+    scope: Scope,
+    /// Rule ids it names, each with whether it suppressed anything. Empty
+    /// means every rule.
+    rules: Vec<(String, bool)>,
+    /// For the every-rule form, whether it suppressed anything.
+    used: bool,
+    /// Where it is written: the comment or the annotation string.
+    offset: usize,
+    /// Text to delete to remove it; `None` for annotations.
+    removal: Option<(usize, usize)>,
+    /// Whether the syntax tree confirmed it is a comment. Without a tree a
+    /// marker inside a string or block comment looks the same, so removing
+    /// it is only maybe correct.
+    confirmed: bool,
+}
+
+/// Drop issues covered by a suppression in `source`, then, if enabled, add
+/// `unused-suppression` issues for suppressions that covered nothing.
+///
+/// Suppressions are `// jfmt:ignore [rule, ...]` after code on the same line
+/// or alone on the line before, `// jfmt:ignore-file [rule, ...]` anywhere,
+/// and `@SuppressWarnings("jfmt:rule")` (or plain `"jfmt"`) on a declaration.
+/// Naming no rule suppresses all of them.
+///
+/// `annotations` are the `annotation` nodes the tree walk collected when
+/// `wants_annotations` said so; with a tree, comments only count when they
+/// are `line_comment` nodes, so a marker inside a string or block comment is
+/// ignored.
+pub(crate) fn apply(source: &str, tree: Option<&Tree>, annotations: &[Node], rules: &RuleSet, issues: &mut Vec<LintIssue>) {
+    // Every form contains this marker; most files have none.
+    if !source.contains("jfmt") { return; }
+    let mut found = comments(source, tree);
+    for &annotation in annotations {
+        suppress_warnings(annotation, source, &mut found);
+    }
+    if found.is_empty() { return; }
+
+    let index = LineIndex::new(source);
+    issues.retain(|issue| !suppress(&mut found, issue, &index));
+    if !rules.contains(UNUSED) { return; }
+
+    let mut unused = Vec::new();
+    for s in &found {
+        if s.rules.is_empty() {
+            if !s.used {
+                unused.push(stale(s, "Suppression does not suppress anything".into()));
+            }
+            continue;
+        }
+        let unknown: Vec<&str> = s.rules.iter().map(|(id, _)| id.as_str()).filter(|id| known_rule_id(id).is_none()).collect();
+        for id in &unknown {
+            unused.push(LintIssue::new(UNUSED, format!("Suppression names unknown rule '{id}'"), s.offset));
+        }
+        // Rules not run this time can't be judged.
+        let idle: Vec<&str> = s
+            .rules
+            .iter()
+            .filter(|(id, used)| !used && known_rule_id(id).is_some_and(|id| rules.contains(id)))
+            .map(|(id, _)| id.as_str())
+            .collect();
+        if idle.is_empty() { continue; }
+        let message = format!("Suppression of {} is unused", quoted(&idle));
+        if idle.len() + unknown.len() == s.rules.len() {
+            unused.push(stale(s, message.into()));
+        } else {
+            unused.push(LintIssue::new(UNUSED, message, s.offset));
+        }
+    }
+    unused.retain(|issue| !suppress(&mut found, issue, &index));
+    issues.extend(unused);
+}
+
+/// Whether the tree walk should collect `annotation` nodes for `apply`.
+pub(crate) fn wants_annotations(source: &str) -> bool {
+    source.contains("jfmt") && source.contains("SuppressWarnings")
+}
+
+/// Whether a suppression covers `issue`; marks the one that does as used.
+fn suppress(found: &mut [Suppression], issue: &LintIssue, index: &LineIndex) -> bool {
+    let line = index.line(issue.offset);
+    for s in found.iter_mut() {
+        let covers = match s.scope {
+            Scope::Line(l) => l == line,
+            Scope::File => true,
+            Scope::Range(start, end) => (start..end).contains(&issue.offset),
+        };
+        if !covers { continue; }
+        if s.rules.is_empty() {
+            s.used = true;
+            return true;
+        }
+        if let Some((_, used)) = s.rules.iter_mut().find(|(id, _)| id == issue.rule_id) {
+            *used = true;
+            return true;
+        }
+    }
+    false
+}
+
+/// An `unused-suppression` issue that removes the whole suppression.
+fn stale(s: &Suppression, message: Cow<'static, str>) -> LintIssue {
+    let mut issue = LintIssue::new(UNUSED, message, s.offset);
+    issue.fix = s.removal.map(|(start_byte, end_byte)| Fix {
+        start_byte,
+        end_byte,
+        replacement: Cow::Borrowed(""),
+        applicability: if s.confirmed { Applicability::Safe } else { Applicability::MaybeIncorrect },
+    });
+    issue
+}
+
+fn quoted(ids: &[&str]) -> String {
+    ids.iter().map(|id| format!("'{id}'")).collect::<Vec<_>>().join(", ")
+}
+
+/// `// jfmt:ignore` and `// jfmt:ignore-file` comments, found by scanning
+/// lines; with a tree, only those that are `line_comment` nodes.
+fn comments(source: &str, tree: Option<&Tree>) -> Vec<Suppression> {
+    let mut found = Vec::new();
+    let mut line_start = 0;
+    for (line_no, line) in source.split_inclusive('\n').enumerate() {
+        let start = line_start;
+        line_start += line.len();
+        let Some(at) = line.find("jfmt:ignore") else { continue };
+        let Some(slashes) = line[..at].trim_end().strip_suffix("//") else { continue };
+        let code = slashes.trim_end();
+        let rest = &line[at + "jfmt:ignore".len()..];
+        let (file, rest) = match rest.strip_prefix("-file") {
+            Some(rest) => (true, rest),
+            None => (false, rest),
+        };
+        if !rest.is_empty() && !rest.starts_with(char::is_whitespace) { continue; }
+        let offset = start + slashes.len();
+        let is_comment = |tree: &Tree| {
+            let node = tree.root_node().descendant_for_byte_range(offset, offset + 2);
+            node.is_some_and(|n| n.kind() == "line_comment" && n.start_byte() == offset)
+        };
+        if tree.is_some_and(|t| !is_comment(t)) { continue; }
+        let rules = rest
+            .split([',', ' ', '\t'])
+            .map(str::trim)
+            .filter(|t| !t.is_empty())
+            .take_while(|t| t.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-'))
+            .map(|t| (t.to_string(), false))
+            .collect();
+        let alone = code.trim().is_empty();
+        let scope = match (file, alone) {
+            (true, _) => Scope::File,
+            (false, true) => Scope::Line(line_no + 1),
+            (false, false) => Scope::Line(line_no),
+        };
+        let comment_end = start + line.trim_end().len();
+        // A comment alone on its line goes with the line; a trailing one
+        // takes the whitespace before it.
+        let removal = if alone { (start, start + line.len()) } else { (start + code.len(), comment_end) };
+        found.push(Suppression {
+            scope,
+            rules,
+            used: false,
+            offset,
+            removal: Some(removal),
+            confirmed: tree.is_some(),
+        });
+    }
+    found
+}
+
+/// The strings starting with `jfmt` in `annotation` if it is a
+/// `@SuppressWarnings` on a declaration, scoped to that declaration.
+fn suppress_warnings(annotation: Node, source: &str, found: &mut Vec<Suppression>) {
+    if !annotation.child_by_field_name("name").is_some_and(|n| source[n.byte_range()].ends_with("SuppressWarnings")) { return; }
+    let Some(decl) = annotation.parent().filter(|m| m.kind() == "modifiers").and_then(|m| m.parent()) else { return };
+    for string in strings(annotation) {
+        let text = source[string.byte_range()].trim_matches('"');
+        let rules = match text.strip_prefix("jfmt") {
+            Some("") => Vec::new(),
+            Some(rest) => match rest.strip_prefix(':') {
+                Some(id) => vec![(id.to_string(), false)],
+                None => continue,
+            },
+            None => continue,
+        };
+        found.push(Suppression {
+            scope: Scope::Range(decl.start_byte(), decl.end_byte()),
+            rules,
+            used: false,
+            offset: string.start_byte(),
+            removal: None,
+            confirmed: true,
+        });
+    }
+}
+
+fn strings(node: Node) -> Vec<Node> {
+    let mut found = Vec::new();
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        if child.kind() == "string_literal" {
+            found.push(child);
+        } else {
+            found.extend(strings(child));
+        }
+    }
+    found
+}
@@ -0,0 +1,67 @@
+use std::path::PathBuf;
+
+use crate::{Project, ProjectIssue, ProjectRule, Visibility};
+
+/// A public or package-private type or method nothing else refers to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnusedDeclaration {
+    pub path: PathBuf,
+    pub line: usize,
+    pub column: usize,
+    /// `class`, `interface`, `enum`, `record`, `annotation type` or `method`.
+    pub kind: &'static str,
+    pub name: String,
+}
+
+/// Reports declarations found by `unused_declarations`.
+pub struct UnusedDeclarationRule;
+
+impl ProjectRule for UnusedDeclarationRule {
+    fn id(&self) -> &'static str { "unused-declaration" }
+
+    fn check(&self, project: &Project, out: &mut Vec<ProjectIssue>) {
+        out.extend(unused_declarations(project).into_iter().map(|d| ProjectIssue {
+            rule_id: self.id(),
+            message: format!("{} '{}' is never referenced in the analyzed sources", d.kind, d.name).into(),
+            path: d.path,
+            line: d.line,
+            column: d.column,
+        }));
+    }
+}
+
+/// Non-private types and methods of `project` that no project file refers to
+/// by name, ordered by path and position.
+///
+/// Matching is by simple name, so overloads and same-named members share
+/// their uses: some dead code goes unreported, but nothing used is flagged.
+/// Declarations annotated with one of `Config::unused_exempt_annotations`,
+/// `main` methods, and types whose members carry such annotations or a `main`
+/// method are exempt, since frameworks or the JVM call them reflectively.
+pub fn unused_declarations(project: &Project) -> Vec<UnusedDeclaration> {
+    let exempt_names = &project.config().unused_exempt_annotations;
+    let exempt = |names: &[String]| names.iter().any(|a| exempt_names.contains(a));
+    let symbols = project.symbols();
+    let mut unused = Vec::new();
+    for file in project.files() {
+        for decl in &file.declarations {
+            if matches!(decl.visibility, Visibility::Private | Visibility::Protected)
+                || decl.entry_point
+                || exempt(&decl.annotations)
+                || exempt(&decl.member_annotations)
+                || symbols.is_referenced(&decl.name)
+            {
+                continue;
+            }
+            unused.push(UnusedDeclaration {
+                path: file.path.clone(),
+                line: decl.line,
+                column: decl.column,
+                kind: decl.kind,
+                name: decl.name.clone(),
+            });
+        }
+    }
+    unused.sort_by(|a, b| (&a.path, a.line, a.column).cmp(&(&b.path, b.line, b.column)));
+    unused
+}
@@ -0,0 +1,132 @@
+use std::borrow::Cow;
+
+use tree_sitter::Node;
+
+use crate::line_index::advance_column;
+use crate::rules::{indent_whitespace, RuleContext};
+use crate::{Applicability, Fix, LintIssue};
+
+/// A place a long line may be broken: the whitespace between two tokens,
+/// and how deeply the construct offering it is nested.
+struct Break {
+    start: usize,
+    end: usize,
+    depth: u32,
+}
+
+/// Attach a wrapping fix to every `max-line-length` issue whose line can be
+/// broken so that each piece fits. Needs the tree, so text-only runs report
+/// long lines without one.
+pub(crate) fn add_fixes(root: Node, ctx: &RuleContext, issues: &mut [LintIssue]) {
+    for issue in issues.iter_mut().filter(|i| i.rule_id == "max-line-length" && i.fix.is_none()) {
+        issue.fix = wrap_line(root, ctx, issue.offset);
+    }
+}
+
+/// Break the line holding `offset` after commas in argument and parameter
+/// lists, before binary operators and before the dots of a call chain.
+/// Outer constructs are broken first; within one, the last break that still
+/// fits. Continuation lines are indented one `continuation_indent` (or
+/// `indent_width` when that is 0) past the original line. Lines opening a
+/// body are left alone.
+fn wrap_line(root: Node, ctx: &RuleContext, offset: usize) -> Option<Fix> {
+    let source = ctx.source;
+    let config = ctx.config;
+    let (max_len, unit, tab_width) = (config.max_line_length as usize, config.line_length_unit, config.tab_width as usize);
+    let line_start = source[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = source[offset..].find('\n').map_or(source.len(), |i| offset + i);
+    let line_end = if source[..line_end].ends_with('\r') { line_end - 1 } else { line_end };
+    let line = &source[line_start..line_end];
+    let leading = line.len() - line.trim_start_matches([' ', '\t']).len();
+    // A body opened on a continuation line is indented from that line, so
+    // wrapping would shift the whole body.
+    if line.trim_end().ends_with('{') { return None; }
+
+    let breaks = breaks_in(root, source, line_start + leading, line_end);
+    let step = if config.continuation_indent > 0 { config.continuation_indent } else { config.indent_width } as usize;
+    let column = line[..leading].chars().fold(0, |col, c| advance_column(col, c, tab_width));
+    let indent = indent_whitespace(config, column + step);
+    let fits = |prefix: &str, text: &str| unit.measure(prefix, tab_width) + unit.measure(text, tab_width) <= max_len;
+
+    let mut chosen: Vec<&Break> = Vec::new();
+    let (mut from, mut prefix) = (line_start, "");
+    while !fits(prefix, &source[from..line_end]) {
+        let fitting: Vec<&Break> = breaks
+            .iter()
+            .filter(|b| b.start > from && !source[from..b.start].trim().is_empty())
+            .filter(|b| fits(prefix, &source[from..b.start]))
+            .collect();
+        let next = match fitting.iter().map(|b| b.depth).min() {
+            Some(depth) => fitting.into_iter().filter(|b| b.depth == depth).max_by_key(|b| b.start)?,
+            // Nothing fits, so this piece can't be made short enough.
+            None => return None,
+        };
+        chosen.push(next);
+        (from, prefix) = (next.end, &*indent);
+    }
+    let (first, last) = (chosen.first()?, chosen.last()?);
+    let newline = config.line_ending.resolve(source);
+    let mut replacement = String::new();
+    let mut at = first.start;
+    for b in &chosen {
+        replacement.push_str(&source[at..b.start]);
+        replacement.push_str(newline);
+        replacement.push_str(&indent);
+        at = b.end;
+    }
+    Some(Fix {
+        start_byte: first.start,
+        end_byte: last.end,
+        replacement: Cow::Owned(replacement),
+        applicability: Applicability::Safe,
+    })
+}
+
+/// Break points between `start` and `end`, in order. Only gaps holding
+/// nothing but spaces qualify, so comments stay where they are.
+fn breaks_in(root: Node, source: &str, start: usize, end: usize) -> Vec<Break> {
+    let mut breaks = Vec::new();
+    let mut add = |before: Node, after: Node, depth: u32| {
+        let (from, to) = (before.end_byte(), after.start_byte());
+        if start < from && to < end && source[from..to].bytes().all(|b| b == b' ' || b == b'\t') {
+            breaks.push(Break { start: from, end: to, depth });
+        }
+    };
+    let mut cursor = root.walk();
+    loop {
+        let node = cursor.node();
+        let overlaps = node.start_byte() < end && node.end_byte() > start;
+        if overlaps {
+            let depth = cursor.depth();
+            match node.kind() {
+                "argument_list" | "formal_parameters" => {
+                    let mut walk = node.walk();
+                    let children: Vec<Node> = node.children(&mut walk).collect();
+                    for pair in children.windows(2).filter(|p| p[0].kind() == ",") {
+                        add(pair[0], pair[1], depth);
+                    }
+                }
+                "binary_expression" => {
+                    if let (Some(left), Some(operator)) = (node.child_by_field_name("left"), node.child_by_field_name("operator")) {
+                        add(left, operator, depth);
+                    }
+                }
+                "method_invocation" => {
+                    if let Some(object) = node.child_by_field_name("object").filter(|o| o.kind() == "method_invocation")
+                        && let Some(dot) = object.next_sibling().filter(|d| d.kind() == ".")
+                    {
+                        add(object, dot, depth);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if overlaps && cursor.goto_first_child() { continue; }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                breaks.sort_by_key(|b| b.start);
+                return breaks;
+            }
+        }
+    }
+}
@@ -0,0 +1,293 @@
+//! Reports that look across files: duplicates, metrics, cycles and coverage.
+
+use std::fs;
+use std::path::PathBuf;
+
+use libjfmt::{
+    collect_metrics, discover_modules, file_metrics, find_duplicates, find_duplicates_in_groups, javadoc_coverage, package_cycles, unused_declarations, Config, DupOptions, PackageCycleRule, PackagePathRule,
+    Project, ProjectIssue, ProjectRule, SourceKind, SourceLayout, UnusedDeclarationRule,
+};
+
+/// A fresh directory holding `files` (path, content).
+fn tree(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("jfmt-analysis-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    for (path, content) in files {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().expect("parent")).expect("create dir");
+        fs::write(path, content).expect("write");
+    }
+    root
+}
+
+#[test]
+fn renamed_copies_of_statement_runs_are_duplicates() {
+    let a = "class A {\n    void f() {\n        int total = 0;\n        for (int i = 0; i < 10; i++) {\n            total += i * 2;\n        }\n        System.out.println(total);\n    }\n}\n";
+    let b = "class B {\n    void g() {\n        log();\n        int sum = 1;\n        for (int j = 0; j < 20; j++) {\n            sum += j * 3;\n        }\n        System.out.println(sum);\n    }\n}\n";
+    let c = "class C {\n    void h() {\n        int x = 0;\n    }\n}\n";
+    let root = tree("dup", &[("A.java", a), ("B.java", b), ("C.java", c)]);
+    let paths = vec![root.join("A.java"), root.join("B.java"), root.join("C.java"), root.join("Missing.java")];
+
+    let report = find_duplicates(&paths, &Config::default(), &DupOptions { min_tokens: 20 });
+    assert_eq!(report.pairs.len(), 1);
+    let pair = &report.pairs[0];
+    assert_eq!((pair.first.path.file_name(), pair.first.start_line, pair.first.end_line), (Some("A.java".as_ref()), 3, 6));
+    assert_eq!((pair.second.path.file_name(), pair.second.start_line, pair.second.end_line), (Some("B.java".as_ref()), 4, 7));
+    assert!(pair.tokens >= 20);
+    assert_eq!(report.errors.len(), 1);
+
+    let strict = find_duplicates(&paths, &Config::default(), &DupOptions { min_tokens: 100 });
+    assert!(strict.pairs.is_empty());
+
+    // Files read under different configs are still compared with each other.
+    let mapped = Config { mmap_threshold: 1, ..Config::default() };
+    let groups = vec![(Config::default(), vec![paths[1].clone(), paths[3].clone()]), (mapped, vec![paths[2].clone(), paths[0].clone()])];
+    let grouped = find_duplicates_in_groups(&groups, &DupOptions { min_tokens: 20 });
+    assert_eq!((grouped.pairs, grouped.errors.len()), (report.pairs, 1));
+}
+
+#[test]
+fn metrics_count_lines_and_method_complexity() {
+    let source = "// header\nclass A {\n\n    /** Doc. */\n    int f(int a, int b) {\n        if (a > 0 && b > 0) {\n            for (int i = 0; i < a; i++) {\n                b--;\n            }\n        }\n        return a > b ? a : b;\n    }\n\n    void g() {}\n}\n";
+    let m = file_metrics(source).expect("metrics");
+    assert_eq!((m.lines, m.code_lines, m.comment_lines, m.blank_lines), (15, 11, 2, 2));
+    let methods: Vec<_> = m.methods.iter().map(|m| (m.name.as_str(), m.line, m.lines, m.parameters, m.complexity, m.max_nesting)).collect();
+    assert_eq!(methods, [("f", 5, 8, 2, 5, 2), ("g", 14, 1, 0, 1, 0)]);
+
+    let root = tree("metrics", &[("A.java", source)]);
+    let reports = collect_metrics(&[root.join("A.java"), root.join("Missing.java")], &Config::default());
+    assert_eq!(reports[0].metrics.as_ref().expect("metrics").methods.len(), 2);
+    assert!(reports[1].metrics.is_err());
+}
+
+#[test]
+fn package_import_loops_are_reported_once_each() {
+    let root = tree(
+        "cycles",
+        &[
+            ("dao/Repo.java", "package com.app.dao;\n\nimport com.app.service.Service;\nimport java.util.List;\n\nclass Repo {\n}\n"),
+            ("service/Service.java", "package com.app.service;\n\nimport com.app.dao.Repo;\nimport com.app.dao.*;\n\nclass Service {\n}\n"),
+            ("web/Web.java", "package com.app.web;\n\nimport com.app.service.Service;\n\nclass Web {\n}\n"),
+        ],
+    );
+    let paths: Vec<_> = ["dao/Repo.java", "service/Service.java", "web/Web.java"].iter().map(|p| root.join(p)).collect();
+    let project = Project::load(&paths, &Config::default());
+    assert!(project.errors().is_empty());
+    let cycles = package_cycles(&project);
+    assert_eq!(cycles.len(), 1);
+    let cycle = &cycles[0];
+    assert_eq!(cycle.packages().collect::<Vec<_>>(), ["com.app.dao", "com.app.service", "com.app.dao"]);
+    let edges: Vec<_> = cycle.edges.iter().map(|e| (e.path.file_name().expect("file").to_owned(), e.line)).collect();
+    assert_eq!(edges, [("Repo.java".into(), 3), ("Service.java".into(), 3)]);
+
+    let acyclic = Project::load(&[root.join("web/Web.java"), root.join("service/Service.java")], &Config::default());
+    assert!(package_cycles(&acyclic).is_empty());
+
+    // Grouped by config, files are indexed in path order.
+    let groups = vec![(Config::default(), vec![paths[2].clone(), paths[0].clone()]), (Config { tab_width: 8, ..Config::default() }, vec![paths[1].clone()])];
+    let grouped = Project::load_groups(&groups, &Config::default());
+    assert_eq!(grouped.files().iter().map(|f| f.path.clone()).collect::<Vec<_>>(), paths);
+    assert_eq!(package_cycles(&grouped).len(), 1);
+}
+
+#[test]
+fn declarations_nothing_refers_to_are_unused() {
+    let root = tree(
+        "unused",
+        &[
+            ("App.java", "class App {\n    public static void main(String[] args) {\n        new Helper().help();\n    }\n}\n"),
+            (
+                "Helper.java",
+                "class Helper {\n    void help() {\n    }\n\n    void orphan() {\n    }\n\n    private void hidden() {\n    }\n\n    @Test\n    void checked() {\n    }\n}\n",
+            ),
+            ("Dead.java", "class Dead {\n}\n"),
+        ],
+    );
+    let paths: Vec<_> = ["App.java", "Helper.java", "Dead.java"].iter().map(|p| root.join(p)).collect();
+    let unused = unused_declarations(&Project::load(&paths, &Config::default()));
+    let found: Vec<_> = unused.iter().map(|u| (u.kind, u.name.as_str(), u.line, u.column)).collect();
+    assert_eq!(found, [("class", "Dead", 1, 7), ("method", "orphan", 5, 10)]);
+
+    // With no exempt annotations, `@Test` no longer protects `checked`.
+    let strict = Config { unused_exempt_annotations: Vec::new(), ..Config::default() };
+    let names: Vec<_> = unused_declarations(&Project::load(&paths, &strict)).into_iter().map(|u| u.name).collect();
+    assert_eq!(names, ["Dead", "orphan", "checked"]);
+}
+
+/// Flags files whose package doesn't match their directory.
+struct PackagePath;
+
+impl ProjectRule for PackagePath {
+    fn id(&self) -> &'static str { "package-path" }
+
+    fn check(&self, project: &Project, out: &mut Vec<ProjectIssue>) {
+        for file in project.files() {
+            let expected = project.expected_package(&file.path);
+            if expected.is_some() && expected != file.package {
+                out.push(ProjectIssue { rule_id: self.id(), path: file.path.clone(), line: 1, column: 1, message: "misplaced".into() });
+            }
+        }
+    }
+}
+
+#[test]
+fn project_rules_share_one_index() {
+    let root = tree(
+        "project",
+        &[
+            ("src/com/app/a/A.java", "package com.app.a;
+
+import com.app.b.B;
+
+class A {
+    B b;
+}
+"),
+            ("src/com/app/b/B.java", "package com.app.a;
+
+import com.app.a.A;
+
+class B {
+    void idle() {
+    }
+}
+"),
+            ("src/com/app/c/C.java", "package com.app.c;
+
+class C {
+    A a;
+}
+"),
+        ],
+    );
+    let paths: Vec<_> =
+        ["src/com/app/a/A.java", "src/com/app/b/B.java", "src/com/app/c/C.java", "Missing.java"].iter().map(|p| root.join(p)).collect();
+    let project = Project::load(&paths, &Config::default()).with_roots([root.join("src")]);
+    assert_eq!(project.files().len(), 3);
+    assert_eq!(project.errors().len(), 1);
+    assert_eq!(project.symbols().files_in_package("com.app.a"), [0, 1]);
+    assert_eq!(project.expected_package(&paths[2]).as_deref(), Some("com.app.c"));
+    assert_eq!(project.expected_package(&root.join("Other.java")), None);
+    let symbols = project.symbols();
+    assert!(symbols.has_package("com.app.c") && !symbols.has_package("com.app.b"));
+    assert_eq!(symbols.declarations_named("B"), [(1, 0)]);
+    assert!(symbols.is_referenced("A") && !symbols.is_referenced("idle"));
+
+    let issues = project.check(&[&PackagePath, &UnusedDeclarationRule, &PackageCycleRule]);
+    let found: Vec<_> =
+        issues.iter().map(|i| (i.rule_id, i.path.strip_prefix(&root).expect("under root").to_string_lossy().into_owned(), i.line)).collect();
+    assert_eq!(
+        found,
+        [
+            ("package-path", "src/com/app/b/B.java".to_owned(), 1),
+            ("unused-declaration", "src/com/app/b/B.java".to_owned(), 6),
+            ("unused-declaration", "src/com/app/c/C.java".to_owned(), 3),
+        ]
+    );
+}
+
+#[test]
+fn javadoc_coverage_counts_public_declarations_per_package() {
+    let root = tree(
+        "javadoc",
+        &[
+            (
+                "a/A.java",
+                "package com.a;\n\n/** A. */\npublic class A {\n    /** Documented. */\n    public void f() {\n    }\n\n    // Not Javadoc.\n    public void g() {\n    }\n\n    void hidden() {\n    }\n}\n",
+            ),
+            ("a/I.java", "package com.a;\n\npublic interface I {\n    /** Implicitly public. */\n    void run();\n}\n"),
+            ("Main.java", "class Main {\n}\n"),
+        ],
+    );
+    let paths: Vec<_> = ["a/A.java", "a/I.java", "Main.java"].iter().map(|p| root.join(p)).collect();
+    let (packages, overall) = javadoc_coverage(&Project::load(&paths, &Config::default()));
+    let found: Vec<_> = packages.iter().map(|p| (p.package.as_str(), p.documented, p.total)).collect();
+    assert_eq!(found, [("com.a", 3, 5)]);
+    assert_eq!((overall.documented, overall.total), (3, 5));
+    assert_eq!(overall.percent(), 60.0);
+
+    // Nothing public to document counts as fully covered.
+    let (packages, overall) = javadoc_coverage(&Project::load(&paths[2..], &Config::default()));
+    assert!(packages.is_empty());
+    assert_eq!(overall.percent(), 100.0);
+}
+
+#[test]
+fn source_roots_come_from_the_build_layout() {
+    let root = tree(
+        "layout",
+        &[
+            ("pom.xml", "<project><build><sourceDirectory>${project.basedir}/gen</sourceDirectory></build></project>\n"),
+            ("build.gradle", "sourceSets { it { java { srcDirs = ['src/it/code', \"missing\"] } } }\n"),
+            ("src/main/java/com/app/App.java", "package com.app;\n\nclass App {\n}\n"),
+            ("src/main/java/com/app/web/Web.java", "package com.app;\n\nclass Web {\n}\n"),
+            ("src/integrationTest/java/com/app/AppIT.java", "package com.app;\n\nclass AppIT {\n}\n"),
+            ("src/it/code/Loose.java", "class Loose {\n}\n"),
+            ("gen/com/gen/Gen.java", "class Gen {\n}\n"),
+            ("src/main/java/.hidden/H.java", "class H {\n}\n"),
+        ],
+    );
+    let layout = SourceLayout::detect(&root).expect("layout");
+    let roots: Vec<_> =
+        layout.roots.iter().map(|r| (r.path.strip_prefix(&root).expect("under root").to_string_lossy().into_owned(), r.kind)).collect();
+    assert_eq!(
+        roots,
+        [
+            ("gen".to_owned(), SourceKind::Main),
+            ("src/integrationTest/java".to_owned(), SourceKind::Test),
+            ("src/it/code".to_owned(), SourceKind::Main),
+            ("src/main/java".to_owned(), SourceKind::Main),
+        ]
+    );
+    assert!(layout.is_test(&root.join("src/integrationTest/java/com/app/AppIT.java")));
+    assert!(!layout.is_test(&root.join("src/main/java/com/app/App.java")));
+    let files = layout.java_files().expect("files");
+    assert_eq!(files.len(), 5);
+
+    let project = Project::load(&files, &Config::default()).with_roots(layout.roots.iter().map(|r| r.path.clone()));
+    let issues = project.check(&[&PackagePathRule]);
+    let found: Vec<_> = issues.iter().map(|i| (i.path.file_name().expect("file").to_string_lossy().into_owned(), i.line, i.message.to_string())).collect();
+    assert_eq!(
+        found,
+        [
+            ("Gen.java".to_owned(), 1, "File declares the default package but its directory implies package 'com.gen'".to_owned()),
+            ("Web.java".to_owned(), 1, "File declares package 'com.app' but its directory implies package 'com.app.web'".to_owned()),
+        ]
+    );
+
+    let flat = tree("layout-flat", &[("A.java", "class A {\n}\n")]);
+    assert_eq!(SourceLayout::detect(&flat).expect("layout").roots[0].path, flat);
+}
+
+#[test]
+fn files_belong_to_their_innermost_module() {
+    let root = tree(
+        "modules",
+        &[
+            ("pom.xml", "<project/>\n"),
+            ("src/main/java/Root.java", "class Root {\n}\n"),
+            ("svc/build.gradle", "\n"),
+            ("svc/src/main/java/Svc.java", "class Svc {\n}\n"),
+            ("svc/src/test/java/SvcTest.java", "class SvcTest {\n}\n"),
+            ("svc/build/pom.xml", "<project/>\n"),
+            (".git/pom.xml", "<project/>\n"),
+        ],
+    );
+    let modules = discover_modules(&root).expect("modules");
+    let found: Vec<_> = modules
+        .iter()
+        .map(|m| {
+            let files: Vec<_> = m.files.iter().map(|f| f.file_name().expect("file").to_string_lossy().into_owned()).collect();
+            (m.root.strip_prefix(&root).expect("under root").to_string_lossy().into_owned(), files)
+        })
+        .collect();
+    assert_eq!(
+        found,
+        [("".to_owned(), vec!["Root.java".to_owned()]), ("svc".to_owned(), vec!["Svc.java".to_owned(), "SvcTest.java".to_owned()])]
+    );
+    assert!(modules[1].layout.is_test(&modules[1].files[1]));
+
+    let plain = tree("modules-plain", &[("A.java", "class A {\n}\n")]);
+    let modules = discover_modules(&plain).expect("modules");
+    assert_eq!((modules.len(), modules[0].files.len()), (1, 1));
+}
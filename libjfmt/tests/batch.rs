@@ -0,0 +1,386 @@
+//! Linting files on disk in batches.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use libjfmt::{
+    compare_reports, lint_files, lint_text, unified_diff, Applicability, CacheEntry, CacheKey, CacheStats, CacheStore, Config, DiskCache, FileError, Fix, IssueSummary, LintError, LintIssue, LintOptions,
+    LintStats, Linter, ReportedIssue, RuleSet, SkipReason, SourceText,
+};
+
+/// A fresh directory holding `files` (path, content), removed first if an
+/// earlier run left it behind.
+fn tree(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("jfmt-batch-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    for (path, content) in files {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().expect("parent")).expect("create dir");
+        fs::write(path, content).expect("write");
+    }
+    root
+}
+
+#[test]
+fn reports_are_sorted_and_independent_of_the_worker_count() {
+    let root = tree(
+        "parallel",
+        &[("c/C.java", "class C {\n\tint x;\n}\n"), ("a/A.java", "import java.util.*;\n\nclass A {\n}\n"), ("b/B.java", "class B {\n}\n")],
+    );
+    let paths = vec![root.join("c/C.java"), root.join("a/A.java"), root.join("b/B.java"), root.join("missing/D.java")];
+    let summary = |threads| {
+        let options = LintOptions { threads: Some(threads), ..Default::default() };
+        lint_files(&paths, &Config::default(), &options)
+            .expect("lint")
+            .into_iter()
+            .map(|r| (r.path, r.outcome.map(|o| o.issues.iter().map(|i| i.rule_id).collect::<Vec<_>>()).map_err(|e| e.to_string())))
+            .collect::<Vec<_>>()
+    };
+    let serial = summary(1);
+    assert_eq!(serial, summary(4));
+    let names: Vec<_> = serial.iter().map(|(p, _)| p.strip_prefix(&root).expect("under root").to_path_buf()).collect();
+    assert_eq!(names, ["a/A.java", "b/B.java", "c/C.java", "missing/D.java"].map(PathBuf::from));
+    assert_eq!(serial[0].1, Ok(vec!["no-wildcard-imports"]));
+    assert_eq!(serial[1].1, Ok(vec![]));
+    assert!(serial[3].1.is_err());
+}
+
+#[test]
+fn fixing_returns_new_content_and_remaining_issues() {
+    let root = tree("fix", &[("A.java", "import java.util.*;\n\nclass A {\n\tint x;\n}\n")]);
+    let options = LintOptions { fix: true, ..Default::default() };
+    let reports = lint_files(&[root.join("A.java")], &Config::default(), &options).expect("lint");
+    let outcome = reports[0].outcome.as_ref().expect("outcome");
+    assert_eq!(outcome.fixed.as_deref(), Some("import java.util.*;\n\nclass A {\n    int x;\n}\n"));
+    let remaining: Vec<_> = outcome.issues.iter().map(|i| i.rule_id).collect();
+    assert_eq!(remaining, ["no-wildcard-imports"]);
+    // Writing is left to the caller.
+    assert_eq!(fs::read_to_string(root.join("A.java")).expect("read"), "import java.util.*;\n\nclass A {\n\tint x;\n}\n");
+
+    let missing = lint_files(&[root.join("B.java")], &Config::default(), &options).expect("lint");
+    assert!(matches!(missing[0].outcome, Err(FileError::Io(_))));
+}
+
+#[test]
+fn cache_hits_until_content_or_config_changes() {
+    let root = tree("cache", &[("A.java", "import java.util.*;\n\nclass A {\n\tint x;\n}\n"), ("B.java", "class B {\n}\n")]);
+    let paths = vec![root.join("A.java"), root.join("B.java")];
+    let options = LintOptions { cache: Some(Arc::new(DiskCache::new(root.join(".jfmt-cache")))), ..Default::default() };
+    let run = |config: &Config| {
+        let reports = lint_files(&paths, config, &options).expect("lint");
+        let issues: Vec<Vec<_>> =
+            reports.iter().map(|r| r.outcome.as_ref().expect("outcome").issues.iter().map(|i| (i.rule_id, i.line, i.column)).collect()).collect();
+        (CacheStats::from_reports(&reports), issues)
+    };
+    let config = Config::default();
+    let (first, fresh) = run(&config);
+    assert_eq!((first.hits, first.misses), (0, 2));
+    let (second, cached) = run(&config);
+    assert_eq!((second.hits, second.misses), (2, 0));
+    assert_eq!(cached, fresh);
+
+    fs::write(root.join("B.java"), "class B {\n\tint y;\n}\n").expect("write");
+    let (edited, _) = run(&config);
+    assert_eq!((edited.hits, edited.misses), (1, 1));
+    let wider = Config { max_line_length: config.max_line_length + 1, ..config.clone() };
+    let (reconfigured, _) = run(&wider);
+    assert_eq!((reconfigured.hits, reconfigured.misses), (0, 2));
+
+    // Unreadable entries are misses, not errors.
+    for entry in fs::read_dir(root.join(".jfmt-cache")).expect("cache dir") {
+        fs::write(entry.expect("entry").path(), "not json").expect("write");
+    }
+    let (corrupt, issues) = run(&config);
+    assert_eq!((corrupt.hits, corrupt.misses), (0, 2));
+    assert_eq!(issues[0], fresh[0]);
+}
+
+#[test]
+fn files_from_the_mmap_threshold_up_are_mapped() {
+    let source = "class A {\n\tint x;\n}\n";
+    let root = tree("mmap", &[("A.java", source), ("Empty.java", "")]);
+    let path = root.join("A.java");
+    let len = source.len() as u64;
+    let mapped = SourceText::read(&path, len).expect("read");
+    assert!(matches!(mapped, SourceText::Mapped(_)));
+    assert_eq!(&*mapped, source);
+    assert!(matches!(SourceText::read(&path, len + 1).expect("read"), SourceText::Owned(_)));
+    assert!(matches!(SourceText::read(&root.join("Empty.java"), 0).expect("read"), SourceText::Owned(_)));
+
+    fs::write(root.join("Bad.java"), b"class A { \xff }").expect("write");
+    let err = SourceText::read(&root.join("Bad.java"), 1).err().expect("invalid UTF-8");
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    let lint = |mmap_threshold| {
+        let config = Config { mmap_threshold, ..Config::default() };
+        let reports = lint_files(std::slice::from_ref(&path), &config, &LintOptions::default()).expect("lint");
+        reports[0].outcome.as_ref().expect("outcome").issues.iter().map(|i| (i.rule_id, i.line)).collect::<Vec<_>>()
+    };
+    assert_eq!(lint(1), lint(u64::MAX));
+    assert_eq!(lint(1), [("indent-style", 2)]);
+}
+
+#[test]
+fn timings_are_collected_per_file_only_when_asked_for() {
+    let root = tree("timing", &[("A.java", "class A {\n\tint x;;\n}\n"), ("B.java", "class B {\n}\n")]);
+    let paths = vec![root.join("A.java"), root.join("B.java")];
+    let untimed = lint_files(&paths, &Config::default(), &LintOptions::default()).expect("lint");
+    assert!(untimed.iter().all(|r| r.outcome.as_ref().expect("outcome").stats.is_none()));
+
+    let options = LintOptions { timing: true, fix: true, ..Default::default() };
+    let reports = lint_files(&paths, &Config::default(), &options).expect("lint");
+    let mut total = LintStats::default();
+    for report in &reports {
+        let stats = report.outcome.as_ref().expect("outcome").stats.as_ref().expect("timed");
+        assert!(stats.per_rule.contains_key("max-line-length"));
+        assert!(stats.per_rule.contains_key("indent-style"));
+        total.merge(stats);
+    }
+    assert!(total.per_rule.contains_key("no-empty-statement"));
+    let phases: Vec<_> = total.phases().iter().map(|(name, _)| *name).collect();
+    assert_eq!(phases, ["io", "parse", "rules", "fix", "write"]);
+
+    // Text-only rule sets never reach the parser.
+    let text_only = LintOptions { timing: true, rules: RuleSet::only(["indent-style"]), ..Default::default() };
+    let reports = lint_files(&paths, &Config::default(), &text_only).expect("lint");
+    let stats = reports[0].outcome.as_ref().expect("outcome").stats.clone().expect("timed");
+    assert_eq!(stats.parse, std::time::Duration::ZERO);
+    assert_eq!(stats.per_rule.keys().copied().collect::<Vec<_>>(), ["indent-style"]);
+}
+
+#[test]
+fn files_over_the_size_or_parse_time_limits_are_skipped() {
+    let small = "class A {\n\tint x;\n}\n";
+    let mut big = String::from("class Big {\n");
+    for i in 0..40_000 {
+        big.push_str(&format!("    int f{i}() {{ return {i} + g(\"{i}\"); }}\n"));
+    }
+    big.push_str("}\n");
+    let root = tree("limits", &[("A.java", small), ("Big.java", &big)]);
+    let paths = vec![root.join("A.java"), root.join("Big.java")];
+    let outcomes = |config: &Config| {
+        lint_files(&paths, config, &LintOptions::default())
+            .expect("lint")
+            .into_iter()
+            .map(|r| r.outcome.expect("outcome"))
+            .collect::<Vec<_>>()
+    };
+
+    let sized = outcomes(&Config { max_file_size: small.len() as u64, ..Config::default() });
+    assert_eq!(sized[0].skipped, None);
+    assert_eq!(sized[0].issues.len(), 1);
+    assert_eq!(sized[1].skipped, Some(SkipReason::TooLarge { size: big.len() as u64, limit: small.len() as u64 }));
+    assert!(sized[1].issues.is_empty());
+
+    let timed = outcomes(&Config { max_parse_time_ms: 1, max_file_size: 0, ..Config::default() });
+    assert_eq!(timed[1].skipped, Some(SkipReason::ParseTimeout { limit_ms: 1 }));
+    assert_eq!(timed[1].skipped.as_ref().expect("skipped").to_string(), "file skipped: parsing took longer than 1ms");
+
+    // A parser that timed out starts the next file from scratch.
+    let mut linter = Linter::new(Config { max_parse_time_ms: 1, ..Config::default() }).expect("java grammar");
+    assert!(matches!(linter.lint(&big), Err(LintError::ParseTimeout { limit_ms: 1 })));
+    assert_eq!(linter.lint(small).expect("lint").len(), 1);
+}
+
+#[test]
+fn check_mode_reports_pending_changes_without_fixing() {
+    let root = tree(
+        "check",
+        &[("A.java", "class A {\n\tint x;\n}\n"), ("B.java", "import java.util.*;\n\nclass B {\n}\n"), ("C.java", "class C {\n}\n")],
+    );
+    let paths = vec![root.join("A.java"), root.join("B.java"), root.join("C.java")];
+    let options = LintOptions { check: true, fix: true, ..Default::default() };
+    let reports = lint_files(&paths, &Config::default(), &options).expect("lint");
+    let outcomes: Vec<_> = reports.iter().map(|r| r.outcome.as_ref().expect("outcome")).collect();
+    assert_eq!(outcomes.iter().map(|o| o.would_change).collect::<Vec<_>>(), [true, false, false]);
+    assert!(outcomes.iter().all(|o| o.fixed.is_none()));
+    assert_eq!(outcomes[0].issues.len(), 1);
+    assert_eq!(outcomes[1].issues[0].rule_id, "no-wildcard-imports");
+    assert_eq!(fs::read_to_string(root.join("A.java")).expect("read"), "class A {\n\tint x;\n}\n");
+    // Without `diff`, nothing is built for a diff.
+    assert!(outcomes.iter().all(|o| o.preview.is_none() && o.original.is_none()));
+
+    let options = LintOptions { check: true, diff: true, ..Default::default() };
+    let reports = lint_files(&paths, &Config::default(), &options).expect("lint");
+    let a = reports[0].outcome.as_ref().expect("outcome");
+    assert_eq!(a.preview.as_deref(), Some("class A {\n    int x;\n}\n"));
+    let diff = unified_diff(a.original.as_deref().expect("original"), a.preview.as_deref().expect("preview"), "A.java", "A.java");
+    assert_eq!(diff, "--- A.java\n+++ A.java\n@@ -1,3 +1,3 @@\n class A {\n-\tint x;\n+    int x;\n }\n");
+    assert!(reports[1].outcome.as_ref().expect("outcome").preview.is_none());
+}
+
+#[test]
+fn idempotency_is_recorded_only_when_verified() {
+    let root = tree("idempotent", &[("A.java", "class A {\n\tvoid f() {\n\t\tg();;\n\t}\n}\n")]);
+    let paths = vec![root.join("A.java")];
+    let outcome = |options: &LintOptions| lint_files(&paths, &Config::default(), options).expect("lint").remove(0).outcome.expect("outcome");
+    assert_eq!(outcome(&LintOptions::default()).idempotent, None);
+    let verified = outcome(&LintOptions { verify_idempotent: true, ..Default::default() });
+    assert_eq!(verified.idempotent, Some(true));
+    assert!(verified.fixed.is_none());
+    assert_eq!(verified.issues.len(), 4);
+}
+
+#[test]
+fn paths_naming_the_same_file_are_linted_once() {
+    let root = tree("dedup", &[("a/A.java", "class A {\n\tint x;\n}\n")]);
+    #[cfg(unix)]
+    std::os::unix::fs::symlink(root.join("a/A.java"), root.join("Link.java")).expect("symlink");
+    let paths = vec![root.join("a/A.java"), root.join("a/../a/A.java"), root.join("a/A.java"), root.join("Link.java"), root.join("Gone.java")];
+    let options = LintOptions { fix: true, ..Default::default() };
+    let reports = lint_files(&paths, &Config::default(), &options).expect("lint");
+    let names: Vec<_> = reports.iter().map(|r| r.path.strip_prefix(&root).expect("under root").to_path_buf()).collect();
+    assert_eq!(names, ["Gone.java", "a/A.java"].map(PathBuf::from));
+    assert!(reports[0].outcome.is_err());
+    assert!(reports[1].outcome.as_ref().expect("outcome").fixed.is_some());
+}
+
+/// In-memory store whose entries tests can tamper with.
+#[derive(Debug, Default)]
+struct MemoryCache(Mutex<HashMap<CacheKey, CacheEntry>>);
+
+impl CacheStore for MemoryCache {
+    fn get(&self, key: &CacheKey) -> Option<CacheEntry> {
+        self.0.lock().unwrap().get(key).cloned()
+    }
+
+    fn put(&self, key: &CacheKey, issues: &[LintIssue], syntax_errors: Option<usize>) {
+        self.0.lock().unwrap().insert(key.clone(), CacheEntry { issues: issues.to_vec(), syntax_errors });
+    }
+}
+
+#[test]
+fn fixes_that_break_the_syntax_are_refused_even_from_the_cache() {
+    let source = "class A {\n    void f() {\n        g();;\n    }\n}\n";
+    let root = tree("syntax", &[("A.java", source)]);
+    let paths = vec![root.join("A.java")];
+    let cache = Arc::new(MemoryCache::default());
+    let options = LintOptions { cache: Some(cache.clone()), ..Default::default() };
+    lint_files(&paths, &Config::default(), &options).expect("lint");
+    let key = CacheKey::new(source, Some("A.java"), &Config::default(), &RuleSet::all());
+    let mut entry = cache.get(&key).expect("cached");
+    assert_eq!(entry.syntax_errors, Some(0));
+
+    // A cached fix that deletes the closing brace of `f`.
+    let brace = source.rfind("    }").expect("brace");
+    entry.issues[0].fix = Some(Fix { start_byte: brace, end_byte: brace + 6, replacement: "".into(), applicability: Applicability::Safe });
+    cache.put(&key, &entry.issues, entry.syntax_errors);
+    let fix = LintOptions { fix: true, ..options.clone() };
+    let reports = lint_files(&paths, &Config::default(), &fix).expect("lint");
+    match &reports[0].outcome {
+        Err(FileError::Lint(LintError::FixBrokeSyntax { rules })) => assert_eq!(rules, &["no-empty-statement"]),
+        other => panic!("expected a refused fix, got {other:?}"),
+    }
+    assert_eq!(fs::read_to_string(root.join("A.java")).expect("read"), source);
+
+    // The original isn't re-parsed on a hit: its error count comes from the entry.
+    cache.put(&key, &entry.issues, Some(usize::MAX));
+    let reports = lint_files(&paths, &Config::default(), &fix).expect("lint");
+    assert!(reports[0].outcome.as_ref().expect("outcome").fixed.is_some());
+}
+
+#[test]
+fn summaries_count_issues_by_rule_directory_and_file() {
+    let root = tree(
+        "summary",
+        &[
+            ("a/A.java", "import java.util.*;\n\nclass A {\n\tvoid f() {\n\t\tg();;\n\t}\n}\n"),
+            ("a/B.java", "class B {\n\tint y;\n}\n"),
+            ("b/C.java", "class C {\n}\n"),
+        ],
+    );
+    let paths = vec![root.join("a/A.java"), root.join("a/B.java"), root.join("b/C.java"), root.join("b/Gone.java")];
+    let reports = lint_files(&paths, &Config::default(), &LintOptions::default()).expect("lint");
+    let summary = IssueSummary::from_reports(&reports);
+    let rules: Vec<_> = summary.per_rule.iter().map(|(rule, c)| (*rule, c.total, c.fixable, c.manual())).collect();
+    assert_eq!(rules, [("indent-style", 4, 4, 0), ("no-empty-statement", 1, 1, 0), ("no-wildcard-imports", 1, 0, 1)]);
+    assert_eq!((summary.total().total, summary.total().manual()), (6, 1));
+    assert_eq!(summary.per_directory.into_iter().collect::<Vec<_>>(), [(root.join("a"), 6)]);
+    assert_eq!(summary.per_file, [(root.join("a/A.java"), 5), (root.join("a/B.java"), 1)]);
+}
+
+#[test]
+fn report_diffs_ignore_issues_that_only_moved() {
+    let root = tree("compare", &[("A.java", "import java.util.*;\n\nclass A {\n\tint x;\n}\n")]);
+    let paths = vec![root.join("A.java")];
+    let report = || ReportedIssue::from_reports(&lint_files(&paths, &Config::default(), &LintOptions::default()).expect("lint"));
+    let old = report();
+    assert_eq!(old.iter().map(|i| (i.rule.as_str(), i.line, i.fixable)).collect::<Vec<_>>(), [("no-wildcard-imports", 1, false), ("indent-style", 4, true)]);
+
+    // Shifting everything down a line changes no keys; a second tab line adds one.
+    fs::write(root.join("A.java"), "import java.util.*;\n\n// A\nclass A {\n\tint x;\n\tint y;\n}\n").expect("write");
+    let new = report();
+    let diff = compare_reports(&old, &new);
+    assert_eq!(diff.introduced.iter().map(|i| (i.rule.as_str(), i.line)).collect::<Vec<_>>(), [("indent-style", 6)]);
+    assert!(diff.resolved.is_empty());
+    let back = compare_reports(&new, &old);
+    assert_eq!((back.introduced.len(), back.resolved.len()), (0, 1));
+}
+
+#[test]
+fn test_sources_get_the_test_rules_profile() {
+    let source = "import static org.junit.Assert.*;\nimport java.util.*;\n\nclass A {\n\tint x;\n}\n";
+    let root = tree(
+        "test-rules",
+        &[("src/main/java/A.java", source), ("src/test/java/A.java", source), ("src/integrationTest/java/A.java", source)],
+    );
+    let paths: Vec<_> = ["src/main/java/A.java", "src/test/java/A.java", "src/integrationTest/java/A.java"].iter().map(|p| root.join(p)).collect();
+    // The imports are out of order too, which isn't what this is about.
+    let mut config: Config = toml::from_str("[test-rules]\ndisable = [\"indent-style\"]\n\n[rules]\norganize-imports = false\n").expect("config");
+    let found = |config: &Config| {
+        lint_files(&paths, config, &LintOptions::default())
+            .expect("lint")
+            .into_iter()
+            .map(|r| r.outcome.expect("outcome").issues.iter().map(|i| (i.rule_id, i.line)).collect::<Vec<_>>())
+            .collect::<Vec<_>>()
+    };
+    let main = vec![("no-wildcard-imports", 1), ("no-wildcard-imports", 2), ("indent-style", 5)];
+    let test = vec![("no-wildcard-imports", 2)];
+    // Reports come back in path order: integrationTest, main, test.
+    assert_eq!(found(&config), [test.clone(), main, test]);
+
+    config.test_rules.allow_static_wildcard_imports = false;
+    config.allow_static_wildcard_imports = true;
+    let relaxed = vec![("no-wildcard-imports", 2), ("indent-style", 5)];
+    assert_eq!(found(&config)[1], relaxed);
+    assert_eq!(found(&config)[2], [("no-wildcard-imports", 2)]);
+}
+
+#[test]
+fn maybe_incorrect_fixes_wait_for_unsafe() {
+    let source = "class A {\n\t// recieve it\n}\n";
+    let root = tree("unsafe", &[("A.java", source)]);
+    let paths = vec![root.join("A.java")];
+    let config = Config { spellcheck: true, ..Config::default() };
+    let run = |options: LintOptions| lint_files(&paths, &config, &options).expect("lint").remove(0).outcome.expect("outcome");
+
+    let safe = run(LintOptions { fix: true, ..Default::default() });
+    assert_eq!(safe.fixed.as_deref(), Some("class A {\n    // recieve it\n}\n"));
+    let left: Vec<_> = safe.issues.iter().map(|i| (i.rule_id, i.fix.as_ref().map(|f| f.applicability))).collect();
+    assert_eq!(left, [("spellcheck", Some(Applicability::MaybeIncorrect))]);
+
+    let all = run(LintOptions { fix: true, unsafe_fixes: true, ..Default::default() });
+    assert_eq!(all.fixed.as_deref(), Some("class A {\n    // receive it\n}\n"));
+    assert!(all.issues.is_empty());
+
+    fs::write(root.join("A.java"), "class A {\n    // recieve it\n}\n").expect("write");
+    assert!(!run(LintOptions { check: true, ..Default::default() }).would_change);
+    assert!(run(LintOptions { check: true, unsafe_fixes: true, ..Default::default() }).would_change);
+}
+
+#[test]
+fn text_is_linted_like_a_file_at_its_name() {
+    let source = "import java.util.*;\n\nclass A {\n\tint x;\n}\n";
+    let config: Config = toml::from_str("[test-rules]\ndisable = [\"indent-style\"]\n").expect("config");
+    let rules = |path: Option<&str>| {
+        let outcome = lint_text(source, path.map(std::path::Path::new), &config, &LintOptions::default()).expect("lint");
+        outcome.issues.iter().map(|i| i.rule_id).collect::<Vec<_>>()
+    };
+    assert_eq!(rules(None), ["no-wildcard-imports", "indent-style"]);
+    assert_eq!(rules(Some("src/test/java/A.java")), ["no-wildcard-imports"]);
+
+    let fixed = lint_text(source, None, &config, &LintOptions { fix: true, ..Default::default() }).expect("fix");
+    assert_eq!(fixed.fixed.as_deref(), Some("import java.util.*;\n\nclass A {\n    int x;\n}\n"));
+}
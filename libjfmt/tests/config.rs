@@ -0,0 +1,126 @@
+//! Checking, migrating and resolving `jfmt.toml` files.
+
+use std::fs;
+use std::path::PathBuf;
+
+use libjfmt::{config_warnings, migrate_config, ConfigError, ConfigResolver};
+
+/// A fresh directory holding `files` (path, content), removed first if an
+/// earlier run left it behind.
+fn tree(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let root = std::env::temp_dir().join(format!("jfmt-config-{}-{name}", std::process::id()));
+    let _ = fs::remove_dir_all(&root);
+    for (path, content) in files {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().expect("parent")).expect("create dir");
+        fs::write(path, content).expect("write");
+    }
+    root
+}
+
+#[test]
+fn unknown_keys_are_warned_about_and_left_alone() {
+    let text = "# Team settings\nmax_line_lenght = 100\nindent_width = 2\n\n[test-rules]\ndisable = [\"spellcheck\"]\nallow_wildcards = true\n";
+    let warnings = config_warnings(text).expect("valid toml");
+    assert_eq!(warnings, ["unknown key `max_line_lenght` is ignored", "unknown key `test-rules.allow_wildcards` is ignored"]);
+
+    // Nothing is deprecated yet, so migration keeps the file byte for byte.
+    let (migrated, changes) = migrate_config(text).expect("valid toml");
+    assert!(changes.is_empty());
+    assert_eq!(migrated, text);
+
+    assert!(config_warnings("indent_width = 4\n").expect("valid toml").is_empty());
+    assert!(matches!(config_warnings("indent_width = "), Err(ConfigError::Edit(_))));
+    assert!(matches!(migrate_config("[test-rules"), Err(ConfigError::Edit(_))));
+}
+
+#[test]
+fn nested_files_merge_with_the_closest_winning() {
+    let root = tree(
+        "nested",
+        &[
+            ("jfmt.toml", "max_line_length = 120\nindent_width = 2\n\n[rules]\nspacing = false\n"),
+            ("sub/jfmt.toml", "max_line_length = 80\n\n[rules]\nmagic-number = false\n"),
+        ],
+    );
+    let mut resolver = ConfigResolver::new();
+    let config = resolver.config_for(&root.join("sub/A.java")).expect("config");
+    assert_eq!(config.max_line_length, 80);
+    assert_eq!(config.indent_width, 2);
+    // Tables merge key by key.
+    assert_eq!(config.rules.get("spacing"), Some(&false));
+    assert_eq!(config.rules.get("magic-number"), Some(&false));
+    assert_eq!(resolver.config_for(&root.join("A.java")).expect("config").max_line_length, 120);
+}
+
+#[test]
+fn root_stops_the_search() {
+    let root = tree("root", &[("jfmt.toml", "indent_width = 2\n"), ("sub/jfmt.toml", "root = true\nmax_line_length = 80\n")]);
+    let config = ConfigResolver::new().config_for(&root.join("sub/A.java")).expect("config");
+    assert_eq!(config.max_line_length, 80);
+    assert_eq!(config.indent_width, 4);
+}
+
+#[test]
+fn overrides_apply_to_matching_files() {
+    let root = tree(
+        "overrides",
+        &[(
+            "jfmt.toml",
+            "max_line_length = 100\n\n[[overrides]]\nfiles = [\"src/test/**\"]\nmax_line_length = 140\n\n[[overrides]]\nfiles = \"**/Generated*.java\"\nmax_line_length = 200\n",
+        )],
+    );
+    let mut resolver = ConfigResolver::new();
+    let length = |resolver: &mut ConfigResolver, path: &str| resolver.config_for(&root.join(path)).expect("config").max_line_length;
+    assert_eq!(length(&mut resolver, "src/main/java/A.java"), 100);
+    assert_eq!(length(&mut resolver, "src/test/java/ATest.java"), 140);
+    // Later entries win.
+    assert_eq!(length(&mut resolver, "src/test/java/GeneratedTest.java"), 200);
+
+    let files = vec![root.join("src/main/java/A.java"), root.join("src/test/java/ATest.java"), root.join("src/main/java/B.java")];
+    let groups = resolver.group(&files).expect("groups");
+    assert_eq!(groups.len(), 2);
+    assert_eq!(groups[0].1, vec![files[0].clone(), files[2].clone()]);
+}
+
+#[test]
+fn extends_merges_the_base_underneath() {
+    let root = tree(
+        "extends",
+        &[
+            ("style/base.toml", "max_line_length = 90\nindent_width = 2\n\n[[overrides]]\nfiles = \"src/test/**\"\nmax_line_length = 150\n"),
+            ("repo/jfmt.toml", "extends = \"../style/base.toml\"\nindent_width = 3\n"),
+        ],
+    );
+    let mut resolver = ConfigResolver::new();
+    let config = resolver.config_for(&root.join("repo/src/main/A.java")).expect("config");
+    assert_eq!((config.max_line_length, config.indent_width), (90, 3));
+    // The base's override globs are relative to the file extending it.
+    let test = resolver.config_for(&root.join("repo/src/test/ATest.java")).expect("config");
+    assert_eq!(test.max_line_length, 150);
+}
+
+#[test]
+fn extends_a_preset() {
+    let root = tree("preset", &[("jfmt.toml", "extends = \"aosp\"\nmax_line_length = 120\n")]);
+    let config = ConfigResolver::new().config_in(&root).expect("config");
+    // `aosp` extends `google` and widens its indentation.
+    assert_eq!((config.indent_width, config.continuation_indent, config.max_line_length), (4, 8, 120));
+}
+
+#[test]
+fn extends_cycles_and_unknown_presets_are_errors() {
+    let root = tree(
+        "cycle",
+        &[("a/jfmt.toml", "extends = \"../b/base.toml\"\n"), ("b/base.toml", "extends = \"../a/jfmt.toml\"\n"), ("c/jfmt.toml", "extends = \"nope\"\n")],
+    );
+    let mut resolver = ConfigResolver::new();
+    match resolver.config_in(&root.join("a")) {
+        Err(ConfigError::File { message, .. }) => assert!(message.contains("leads back"), "{message}"),
+        other => panic!("expected a cycle error, got {other:?}"),
+    }
+    match resolver.config_in(&root.join("c")) {
+        Err(ConfigError::File { message, .. }) => assert!(message.contains("unknown preset `nope`"), "{message}"),
+        other => panic!("expected an unknown preset error, got {other:?}"),
+    }
+}
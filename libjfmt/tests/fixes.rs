@@ -0,0 +1,419 @@
+//! Autofixes, one rule at a time: each test checks the fixed output, and
+//! `fixed` checks that fixing it again changes nothing.
+
+use libjfmt::{lint_java_source, Config, Linter, RuleSet};
+
+fn config(toml: &str) -> Config {
+    toml::from_str(toml).expect("valid config")
+}
+
+/// `source` fixed by `rule` alone under `config`, after checking that a
+/// second pass leaves the result alone and that nothing fixable is left.
+fn fixed_with(rule: &str, config: Config, unsafe_fixes: bool, source: &str) -> String {
+    let linter = Linter::new(config).expect("java grammar").with_rules(RuleSet::only([rule]));
+    fixed_by(&mut linter.with_unsafe_fixes(unsafe_fixes), rule, source)
+}
+
+/// `fixed_with` for a linter set up by the caller.
+fn fixed_by(linter: &mut Linter, rule: &str, source: &str) -> String {
+    let once = linter.fix(source).expect("fix");
+    assert!(once.issues.iter().any(|i| i.rule_id == rule), "{rule} reported nothing in:\n{source}");
+    let twice = linter.fix(&once.fixed).expect("second fix");
+    assert_eq!(twice.fixed, once.fixed, "fixing {rule} again changed the output");
+    assert!(
+        twice.issues.iter().all(|i| linter.applied_fix(i).is_none()),
+        "{rule} still has fixes to apply after fixing:\n{}",
+        once.fixed
+    );
+    once.fixed
+}
+
+fn fixed(rule: &str, source: &str) -> String {
+    fixed_with(rule, Config::default(), false, source)
+}
+
+fn crlf(text: &str) -> String {
+    text.replace('\n', "\r\n")
+}
+
+#[test]
+fn license_header() {
+    let header = config("license_header = \"\"\"\n// Copyright ${year} Acme\n// All rights reserved\n\"\"\"");
+    let inserted = fixed_with("license-header", header.clone(), false, "class A {\n}\n");
+    let year = &inserted["// Copyright ".len()..][..4];
+    let expected = format!("// Copyright {year} Acme\n// All rights reserved\n\nclass A {{\n}}\n");
+    assert_eq!(inserted, expected);
+    assert_eq!(fixed_with("license-header", header.clone(), false, &crlf("class A {\n}\n")), crlf(&expected));
+    // Replacing an outdated header keeps its year, but only with --unsafe.
+    let outdated = "// Copyright 2019 Old Owner\nclass A {\n}\n";
+    let mut safe = Linter::new(header.clone()).expect("java grammar").with_rules(RuleSet::only(["license-header"]));
+    assert_eq!(safe.fix(outdated).expect("fix").fixed, outdated);
+    assert_eq!(
+        fixed_with("license-header", header.clone(), true, "// Copyright 2019 Old Owner\nclass A {\n}\n"),
+        "// Copyright 2019 Acme\n// All rights reserved\nclass A {\n}\n"
+    );
+    assert_eq!(
+        fixed_with("license-header", header.clone(), true, &crlf("/* Copyright 2019-2021 Old Owner */\nclass A {\n}\n")),
+        crlf("// Copyright 2019-2021 Acme\n// All rights reserved\nclass A {\n}\n")
+    );
+
+    // Any year or range satisfies `${year}`; without a template the rule is off.
+    let mut linter = Linter::new(header).expect("java grammar").with_rules(RuleSet::only(["license-header"]));
+    assert!(linter.lint("// Copyright 2001-2002 Acme\n// All rights reserved\nclass A {\n}\n").expect("lint").is_empty());
+    let mut unset = Linter::new(Config::default()).expect("java grammar").with_rules(RuleSet::only(["license-header"]));
+    assert!(unset.lint("class A {\n}\n").expect("lint").is_empty());
+}
+
+#[test]
+fn spellcheck_corrects_comments_keeping_case() {
+    let on = config("spellcheck = true");
+    assert_eq!(
+        fixed_with("spellcheck", on.clone(), true, "class A {\n    // Recieve the data, RECIEVE it, recieve it\n    int x;\n}\n"),
+        "class A {\n    // Receive the data, RECEIVE it, receive it\n    int x;\n}\n"
+    );
+
+    // Project corrections may start with a multibyte character.
+    let dir = std::env::temp_dir().join(format!("jfmt-fixes-{}-spelling", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create dir");
+    let words = dir.join("words.txt");
+    std::fs::write(&words, "ecole->école\nrecieve\n").expect("write");
+    let project = Config { spellcheck_dictionary: Some(words), ..on };
+    assert_eq!(
+        fixed_with("spellcheck", project.clone(), true, "class A {\n    // Ecole and ECOLE\n}\n"),
+        "class A {\n    // École and ÉCOLE\n}\n"
+    );
+    let mut linter = Linter::new(project).expect("java grammar").with_rules(RuleSet::only(["spellcheck"]));
+    assert!(linter.lint("class A {\n    // recieve\n}\n").expect("lint").is_empty());
+}
+
+#[test]
+fn spellcheck_reports_declared_names_without_renaming() {
+    let source = "class Adress {\n    void recieveData(int adress) {\n        recieveData(adress);\n    }\n}\n";
+    let mut off = Linter::new(Config::default()).expect("java grammar").with_rules(RuleSet::only(["spellcheck"]));
+    assert!(off.lint(source).expect("lint").is_empty());
+    let mut linter = Linter::new(config("spellcheck = true")).expect("java grammar").with_rules(RuleSet::only(["spellcheck"]));
+    let issues = linter.lint(source).expect("lint");
+    let found: Vec<_> = issues.iter().map(|i| (i.line, i.column, i.fix.is_some())).collect();
+    assert_eq!(found, [(1, 7, false), (2, 10, false), (2, 26, false)]);
+    assert_eq!(issues[1].message, "Possible typo 'recieve' (did you mean 'receive'?)");
+
+    let missing = config("spellcheck = true\nspellcheck_dictionary = \"/nonexistent/words.txt\"");
+    let mut linter = Linter::new(missing).expect("java grammar").with_rules(RuleSet::only(["spellcheck"]));
+    let issues = linter.lint("class A {\n}\n").expect("lint");
+    assert!(issues[0].message.starts_with("cannot read spellcheck dictionary /nonexistent/words.txt"));
+}
+
+#[test]
+fn unused_suppression() {
+    let source = "class A {\n    int x; // jfmt:ignore no-wildcard-imports\n    // jfmt:ignore\n    int y;\n}\n";
+    let rules = RuleSet::only(["unused-suppression", "no-wildcard-imports"]);
+    let mut linter = Linter::new(Config::default()).expect("java grammar").with_rules(rules);
+    assert_eq!(fixed_by(&mut linter, "unused-suppression", source), "class A {\n    int x;\n    int y;\n}\n");
+
+    // Without a tree a marker inside a string looks like a comment, so
+    // removing it waits for --unsafe.
+    let text_only = RuleSet::only(["unused-suppression", "indent-style"]);
+    let mut linter = Linter::new(Config::default()).expect("java grammar").with_rules(text_only);
+    let string = "class A {\n    String s = \"x\"; // jfmt:ignore indent-style\n}\n";
+    assert_eq!(linter.fix(string).expect("fix").fixed, string);
+    let mut linter = linter.with_unsafe_fixes(true);
+    assert_eq!(fixed_by(&mut linter, "unused-suppression", string), "class A {\n    String s = \"x\";\n}\n");
+}
+
+#[test]
+fn indentation() {
+    assert_eq!(
+        fixed("indentation", "class A {\n  void f() {\n      g();\n   }\n}\n"),
+        "class A {\n    void f() {\n        g();\n    }\n}\n"
+    );
+    let switch = "class A {\n    void f(int x) {\n        switch (x) {\n        case 1:\n          g();\n      }\n        run(() -> {\n                h();\n        });\n    }\n}\n";
+    assert_eq!(
+        fixed("indentation", switch),
+        "class A {\n    void f(int x) {\n        switch (x) {\n            case 1:\n                g();\n        }\n        run(() -> {\n            h();\n        });\n    }\n}\n"
+    );
+}
+
+#[test]
+fn operator_whitespace() {
+    assert_eq!(
+        fixed("operator-whitespace", "class A {\n    int f(int a) {\n        int b=a+1;\n        b +=2;\n        i ++;\n        return - b;\n    }\n}\n"),
+        "class A {\n    int f(int a) {\n        int b = a + 1;\n        b += 2;\n        i++;\n        return -b;\n    }\n}\n"
+    );
+    // Operators that must stay apart, or that break the line, are left alone.
+    let mut linter = Linter::new(Config::default()).expect("java grammar").with_rules(RuleSet::only(["operator-whitespace"]));
+    assert!(linter.lint("class A {\n    int x = - -y + (a\n        + b);\n}\n").expect("lint").is_empty());
+}
+
+#[test]
+fn spacing() {
+    assert_eq!(
+        fixed(
+            "spacing",
+            "class A {\n    void f(int a,int b){\n        if(a > b){\n            g(a ,  \"x\") ;\n        }else {\n        }\n        try{\n        }catch(E e) {\n        }finally {\n        }\n        for (;;) {\n        }\n    }\n}\n"
+        ),
+        "class A {\n    void f(int a, int b) {\n        if (a > b) {\n            g(a, \"x\");\n        } else {\n        }\n        try {\n        } catch (E e) {\n        } finally {\n        }\n        for (;;) {\n        }\n    }\n}\n"
+    );
+    // Line breaks and comments in a gap are left alone, and checks can be turned off.
+    let source = "class A {\n    void f(int a,\n            int b) /* c */ {\n        g(a,b) ;\n    }\n}\n";
+    let mut linter = Linter::new(Config::default()).expect("java grammar").with_rules(RuleSet::only(["spacing"]));
+    let found: Vec<_> = linter.lint(source).expect("lint").iter().map(|i| (i.line, i.column)).collect();
+    assert_eq!(found, [(4, 13), (4, 15)]);
+    let relaxed = config("[spacing]\nafter_comma = false\nbefore_semicolon = false\n");
+    let mut linter = Linter::new(relaxed).expect("java grammar").with_rules(RuleSet::only(["spacing"]));
+    assert!(linter.lint(source).expect("lint").is_empty());
+}
+
+#[test]
+fn no_blank_line_at_block_boundary() {
+    assert_eq!(
+        fixed("no-blank-line-at-block-boundary", "class A {\n\n    void f() {\n\n        g();\n\n\n    }\n\n}\n"),
+        "class A {\n    void f() {\n        g();\n    }\n}\n"
+    );
+    assert_eq!(fixed("no-blank-line-at-block-boundary", &crlf("class A {\n\n}\n")), crlf("class A {\n}\n"));
+    // A brace sharing its line with code or a comment is not a boundary.
+    let mut linter = Linter::new(Config::default()).expect("java grammar").with_rules(RuleSet::only(["no-blank-line-at-block-boundary"]));
+    assert!(linter.lint("class A { // c\n\n    int x;\n\n    int y; }\n").expect("lint").is_empty());
+}
+
+#[test]
+fn switch_style_arrow() {
+    let arrow = config("switch_style = \"arrow\"");
+    let source = "class A {\n    void f(int x) {\n        switch (x) {\n            case 1:\n            case 2:\n                g();\n                break;\n            default:\n                h();\n        }\n    }\n}\n";
+    let expected = "class A {\n    void f(int x) {\n        switch (x) {\n            case 1, 2 -> g();\n            default -> h();\n        }\n    }\n}\n";
+    assert_eq!(fixed_with("switch-style", arrow.clone(), false, source), expected);
+    assert_eq!(fixed_with("switch-style", arrow, false, &crlf(source)), crlf(expected));
+}
+
+#[test]
+fn instanceof_pattern() {
+    assert_eq!(
+        fixed(
+            "instanceof-pattern",
+            "class A {\n    void f(Object o) {\n        if (o instanceof String) {\n            String s = (String) o;\n            g(s);\n        }\n    }\n}\n"
+        ),
+        "class A {\n    void f(Object o) {\n        if (o instanceof String s) {\n            g(s);\n        }\n    }\n}\n"
+    );
+}
+
+#[test]
+fn switch_expression() {
+    let source = "class A {\n    int f(int x) {\n        switch (x) {\n            case 1:\n                return 10;\n            default:\n                return 20;\n        }\n    }\n}\n";
+    let expected = "class A {\n    int f(int x) {\n        return switch (x) {\n            case 1 -> 10;\n            default -> 20;\n        };\n    }\n}\n";
+    assert_eq!(fixed("switch-expression", source), expected);
+    assert_eq!(fixed("switch-expression", &crlf(source)), crlf(expected));
+}
+
+#[test]
+fn bigdecimal_double_with_unsafe() {
+    let source = "class A {\n    Object x = new BigDecimal(-1_000.5d);\n}\n";
+    assert_eq!(fixed_with("bigdecimal-double", Config::default(), true, source), "class A {\n    Object x = new BigDecimal(\"-1000.5\");\n}\n");
+    assert_eq!(fixed_with("bigdecimal-double", Config::default(), false, source), source);
+}
+
+#[test]
+fn missing_charset() {
+    let java21 = config("java_version = 21");
+    assert_eq!(
+        fixed_with("missing-charset", java21, false, "class A {\n    String f(byte[] b) {\n        return new String(b);\n    }\n}\n"),
+        "class A {\n    String f(byte[] b) {\n        return new String(b, java.nio.charset.StandardCharsets.UTF_8);\n    }\n}\n"
+    );
+    // Below Java 18 the default charset may not be UTF-8, so the fix waits for unsafe fixes.
+    let source = "import java.nio.charset.StandardCharsets;\n\nclass A {\n    byte[] f(String s) {\n        return s.getBytes();\n    }\n}\n";
+    assert_eq!(fixed_with("missing-charset", Config::default(), false, source), source);
+    assert_eq!(fixed_with("missing-charset", Config::default(), true, source), source.replace("getBytes()", "getBytes(StandardCharsets.UTF_8)"));
+}
+
+#[test]
+fn organize_imports() {
+    let source = "import static org.junit.Assert.assertTrue;\nimport java.util.Map;\nimport com.acme.Widget;\nimport java.util.List;\nimport java.util.List;\n\nclass A {\n}\n";
+    let expected = "import java.util.List;\nimport java.util.Map;\n\nimport com.acme.Widget;\n\nimport static org.junit.Assert.assertTrue;\n\nclass A {\n}\n";
+    assert_eq!(fixed("organize-imports", source), expected);
+    assert_eq!(fixed("organize-imports", &crlf(source)), crlf(expected));
+    let acme_first = config("[imports]\ngroups = [[\"com.acme\"], [\"*\", \"static\"]]\nblank_line_between_groups = false\n");
+    assert_eq!(
+        fixed_with("organize-imports", acme_first, false, source),
+        "import com.acme.Widget;\nimport java.util.List;\nimport java.util.Map;\nimport static org.junit.Assert.assertTrue;\n\nclass A {\n}\n"
+    );
+}
+
+#[test]
+fn unused_import() {
+    assert_eq!(
+        fixed("unused-import", "import java.util.List;\nimport java.util.Map;\nimport java.io.*;\n\nclass A {\n    List<String> l;\n}\n"),
+        "import java.util.List;\nimport java.io.*;\n\nclass A {\n    List<String> l;\n}\n"
+    );
+    // Types only named in Javadoc still need their import.
+    let javadoc = "import java.util.Map;\n\n/** See {@link Map.Entry}. */\nclass A {\n}\n";
+    assert!(lint_java_source(javadoc, &Config::default()).expect("lint").iter().all(|i| i.rule_id != "unused-import"));
+    // Organizing the block leaves unused imports out, so the two fixes agree.
+    let rules = RuleSet::only(["unused-import", "organize-imports"]);
+    let mut linter = Linter::new(Config::default()).expect("java grammar").with_rules(rules);
+    let source = "import java.util.Map;\nimport java.util.List;\n\nclass A {\n    List<String> l;\n}\n";
+    assert_eq!(fixed_by(&mut linter, "unused-import", source), "import java.util.List;\n\nclass A {\n    List<String> l;\n}\n");
+}
+
+#[test]
+fn no_wildcard_imports_expands_with_unsafe() {
+    let source = "import java.util.*;\n\nclass A {\n    List<String> l;\n    Map<String, Integer> m;\n}\n";
+    let expected = "import java.util.List;\nimport java.util.Map;\n\nclass A {\n    List<String> l;\n    Map<String, Integer> m;\n}\n";
+    assert_eq!(fixed_with("no-wildcard-imports", Config::default(), false, source), source);
+    assert_eq!(fixed_with("no-wildcard-imports", Config::default(), true, source), expected);
+    assert_eq!(fixed_with("no-wildcard-imports", Config::default(), true, &crlf(source)), crlf(expected));
+}
+
+#[test]
+fn no_trailing_whitespace_can_skip_blocks() {
+    let skip = config("trailing_whitespace_ignore_blocks = true");
+    let source = "class A {  \n    /* kept  \n     */\n    String s = \"/*\";  \n}\n";
+    assert_eq!(
+        fixed_with("no-trailing-whitespace", skip, false, source),
+        "class A {\n    /* kept  \n     */\n    String s = \"/*\";\n}\n"
+    );
+}
+
+#[test]
+fn no_trailing_whitespace() {
+    assert_eq!(fixed("no-trailing-whitespace", "class A { \t\n    int x;  \n}\n"), "class A {\n    int x;\n}\n");
+    assert_eq!(fixed("no-trailing-whitespace", "class A {  \r\n}\r\n"), "class A {\r\n}\r\n");
+    let skip = config("trailing_whitespace_ignore_blocks = true");
+    let source = "class A {  \n    /* kept  \n     */\n    String s = \"/*\";  \n}\n";
+    assert_eq!(
+        fixed_with("no-trailing-whitespace", skip, false, source),
+        "class A {\n    /* kept  \n     */\n    String s = \"/*\";\n}\n"
+    );
+}
+
+#[test]
+fn eof_newline() {
+    assert_eq!(fixed("eof-newline", "class A {\n}"), "class A {\n}\n");
+    assert_eq!(fixed("eof-newline", "class A {\r\n}"), "class A {\r\n}\r\n");
+    let one = config("eof_newline_exactly_one = true");
+    assert_eq!(fixed_with("eof-newline", one, false, "class A {\n}\n\n\n"), "class A {\n}\n");
+}
+
+#[test]
+fn line_ending() {
+    let lf = config("line_ending = \"lf\"");
+    assert_eq!(fixed_with("line-ending", lf, false, "class A {\r\n}\r\n"), "class A {\n}\n");
+    let crlf_config = config("line_ending = \"crlf\"");
+    assert_eq!(fixed_with("line-ending", crlf_config.clone(), false, "class A {\n}\r\n"), "class A {\r\n}\r\n");
+    // Missing line breaks at the end of the file take the configured ending.
+    let rules = RuleSet::only(["line-ending", "eof-newline"]);
+    let mut linter = Linter::new(crlf_config).expect("java grammar").with_rules(rules);
+    assert_eq!(fixed_by(&mut linter, "line-ending", "class A {\n}"), "class A {\r\n}\r\n");
+}
+
+#[test]
+fn brace_style() {
+    let kr = config("brace_style = \"kr\"");
+    assert_eq!(
+        fixed_with("brace-style", kr, false, "class A\n{\n    void f()\n    {\n    }\n}\n"),
+        "class A {\n    void f() {\n    }\n}\n"
+    );
+    let allman = config("brace_style = \"allman\"");
+    let source = "class A {\n    void f() {\n    }\n}\n";
+    let expected = "class A\n{\n    void f()\n    {\n    }\n}\n";
+    assert_eq!(fixed_with("brace-style", allman.clone(), false, source), expected);
+    assert_eq!(fixed_with("brace-style", allman, false, &crlf(source)), crlf(expected));
+}
+
+#[test]
+fn indent_style() {
+    assert_eq!(fixed("indent-style", "class A {\n\tint x;\n}\n"), "class A {\n    int x;\n}\n");
+    let tabs = config("indent_style = \"tabs\"");
+    assert_eq!(fixed_with("indent-style", tabs, false, "class A {\n    int x;\n}\n"), "class A {\n\tint x;\n}\n");
+}
+
+#[test]
+fn continuation_indent() {
+    let source = "class A {\n    void f() {\n        int x = first\n            + second\n                      + third;\n    }\n}\n";
+    assert_eq!(
+        fixed("continuation-indent", source),
+        "class A {\n    void f() {\n        int x = first\n                + second\n                + third;\n    }\n}\n"
+    );
+    let tabs = config("indent_style = \"tabs\"\ncontinuation_indent = 6");
+    assert_eq!(
+        fixed_with("continuation-indent", tabs, false, "class A {\n\tint x = first\n\t\t+ second;\n}\n"),
+        "class A {\n\tint x = first\n\t\t  + second;\n}\n"
+    );
+}
+
+#[test]
+fn max_line_length_wraps() {
+    // Wrapping needs the tree, which a text-only rule set doesn't parse.
+    let rules = RuleSet::only(["max-line-length", "no-empty-statement"]);
+    let mut linter = Linter::new(config("max_line_length = 40")).expect("java grammar").with_rules(rules);
+    let source = "class A {\n    void f() {\n        call(firstArgument, secondArgument);\n    }\n}\n";
+    let expected = "class A {\n    void f() {\n        call(firstArgument,\n                secondArgument);\n    }\n}\n";
+    assert_eq!(fixed_by(&mut linter, "max-line-length", source), expected);
+    assert_eq!(fixed_by(&mut linter, "max-line-length", &crlf(source)), crlf(expected));
+    let chain = "class A {\n    int f() {\n        return items.stream().filter(x -> x > 0).count();\n    }\n}\n";
+    assert_eq!(
+        fixed_by(&mut linter, "max-line-length", chain),
+        "class A {\n    int f() {\n        return items.stream()\n                .filter(x -> x > 0)\n                .count();\n    }\n}\n"
+    );
+}
+
+#[test]
+fn max_blank_lines() {
+    assert_eq!(
+        fixed("max-blank-lines", "class A {\n    int x;\n\n\n\n    int y;\n    /*\n\n\n     */\n}\n"),
+        "class A {\n    int x;\n\n    int y;\n    /*\n\n\n     */\n}\n"
+    );
+}
+
+#[test]
+fn blank_line_between_methods() {
+    let source = "class A {\n    int x;\n    void f() {\n    }\n    // g\n    void g() {\n    }\n}\n";
+    let expected = "class A {\n    int x;\n\n    void f() {\n    }\n\n    // g\n    void g() {\n    }\n}\n";
+    assert_eq!(fixed("blank-line-between-methods", source), expected);
+    assert_eq!(fixed("blank-line-between-methods", &crlf(source)), crlf(expected));
+}
+
+#[test]
+fn modifier_order() {
+    assert_eq!(
+        fixed("modifier-order", "class A {\n    final static public int X = 1;\n    @Override synchronized public void run() {\n    }\n}\n"),
+        "class A {\n    public static final int X = 1;\n    @Override public synchronized void run() {\n    }\n}\n"
+    );
+}
+
+#[test]
+fn require_braces() {
+    let source = "class A {\n    void f(boolean b) {\n        if (b) g();\n        while (b)\n            g();\n        if (b) {\n        } else if (!b) g(); else h();\n    }\n}\n";
+    let expected = "class A {\n    void f(boolean b) {\n        if (b) { g(); }\n        while (b) {\n            g();\n        }\n        if (b) {\n        } else if (!b) { g(); } else { h(); }\n    }\n}\n";
+    assert_eq!(fixed("require-braces", source), expected);
+    assert_eq!(fixed("require-braces", &crlf(source)), crlf(expected));
+}
+
+#[test]
+fn string_equality_with_unsafe() {
+    let source = "class A {\n    boolean f(String s) {\n        return s == \"x\" || s != \"y\";\n    }\n}\n";
+    assert_eq!(fixed_with("string-equality", Config::default(), false, source), source);
+    assert_eq!(
+        fixed_with("string-equality", Config::default(), true, source),
+        "class A {\n    boolean f(String s) {\n        return \"x\".equals(s) || !\"y\".equals(s);\n    }\n}\n"
+    );
+}
+
+#[test]
+fn javadoc_format() {
+    let narrow = config("max_line_length = 40");
+    let source = "class A {\n    /** Returns the first value of the list when there is one.\n      * @return the value */\n    int f() {\n        return 0;\n    }\n}\n";
+    let expected = "class A {\n    /**\n     * Returns the first value of the\n     * list when there is one.\n     * @return the value\n     */\n    int f() {\n        return 0;\n    }\n}\n";
+    assert_eq!(fixed_with("javadoc-format", narrow.clone(), false, source), expected);
+    assert_eq!(fixed_with("javadoc-format", narrow, false, &crlf(source)), crlf(expected));
+}
+
+#[test]
+fn annotation_placement() {
+    let source = "class A {\n    @Deprecated void f() {\n    }\n\n    @Override public String toString() {\n        return \"\";\n    }\n\n    public @Nullable String g;\n}\n";
+    let expected = "class A {\n    @Deprecated\n    void f() {\n    }\n\n    @Override public String toString() {\n        return \"\";\n    }\n\n    public @Nullable String g;\n}\n";
+    assert_eq!(fixed("annotation-placement", source), expected);
+    assert_eq!(fixed("annotation-placement", &crlf(source)), crlf(expected));
+    let none = config("same_line_annotations = []");
+    assert_eq!(
+        fixed_with("annotation-placement", none, false, "class A {\n    @Override public String toString() {\n        return \"\";\n    }\n}\n"),
+        "class A {\n    @Override\n    public String toString() {\n        return \"\";\n    }\n}\n"
+    );
+}
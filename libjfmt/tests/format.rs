@@ -0,0 +1,26 @@
+//! The pretty-printer behind `jfmt fmt`.
+
+use libjfmt::{format_java_source, Config};
+
+fn formatted(source: &str, config: &Config) -> String {
+    let once = format_java_source(source, config);
+    assert_eq!(format_java_source(&once, config), once, "formatting again changed the output");
+    once
+}
+
+#[test]
+fn layout_is_normalized() {
+    let source = "class A\n{\n  void f(int a,int b){\n      if(a>b){g();}\n\n\n      else{h( );}\n  }\n}\n";
+    assert_eq!(
+        formatted(source, &Config::default()),
+        "class A {\n    void f(int a, int b) {\n        if (a > b) {\n            g();\n        } else {\n            h();\n        }\n    }\n}\n"
+    );
+    let tabs: Config = toml::from_str("indent_style = \"tabs\"").expect("valid config");
+    assert_eq!(formatted("class A {\n  int x;\n\n\n  int y;\n}\n", &tabs), "class A {\n\tint x;\n\n\tint y;\n}\n");
+}
+
+#[test]
+fn source_that_does_not_parse_is_left_alone() {
+    let source = "class B {\n  int x = ;\n}\n";
+    assert_eq!(format_java_source(source, &Config::default()), source);
+}
@@ -0,0 +1,688 @@
+//! Linting through the library API.
+
+use libjfmt::{
+    all_rules, apply_fixes, lint_java_source, would_change, Applicability, Config, Document, Fix, FixError, LineIndex, LineLengthUnit, Linter, RuleKind, RuleSet, TextEdit,
+};
+
+#[test]
+fn a_reused_linter_matches_one_shot_linting() {
+    let config = Config::default();
+    let sources = [
+        "import java.util.*;\n\nclass A {\n    void f() {\n        g();;\n    }\n}\n",
+        "class B {\n\tint x;\n}\n",
+        "class C {\n}\n",
+    ];
+    let mut linter = Linter::new(config.clone()).expect("java grammar");
+    for source in sources {
+        let reused: Vec<_> = linter.lint(source).expect("lint").iter().map(|i| (i.rule_id, i.line, i.column)).collect();
+        let fresh: Vec<_> = lint_java_source(source, &config).expect("lint").iter().map(|i| (i.rule_id, i.line, i.column)).collect();
+        assert_eq!(reused, fresh);
+    }
+}
+
+#[test]
+fn only_stray_semicolons_are_empty_statements() {
+    let source = "class A {\n    int x;\n\n    A() {\n        ;\n    }\n\n    void f() {\n        g();;\n        for (;;) {\n        }\n        if (x > 0);\n    }\n}\n";
+    let mut linter = Linter::new(Config::default()).expect("java grammar");
+    let lines: Vec<usize> =
+        linter.lint(source).expect("lint").iter().filter(|i| i.rule_id == "no-empty-statement").map(|i| i.line).collect();
+    assert_eq!(lines, [5, 9]);
+    let fixed = linter.fix(source).expect("fix").fixed;
+    assert_eq!(
+        fixed,
+        "class A {\n    int x;\n\n    A() {\n        \n    }\n\n    void f() {\n        g();\n        for (;;) {\n        }\n        if (x > 0);\n    }\n}\n"
+    );
+}
+
+#[test]
+fn documents_relint_after_edits_like_a_fresh_parse() {
+    let config = Config::default();
+    let mut linter = Linter::new(config.clone()).expect("java grammar");
+    let mut doc = Document::new("class A {\n    void f() {\n        g();\n    }\n}\n");
+    assert!(linter.lint_document(&mut doc).expect("lint").is_empty());
+
+    let at = doc.source().find("g();").expect("call") + "g();".len();
+    doc.edit(&TextEdit { start_byte: at, end_byte: at, new_text: ";\n\tg(1);".to_string() }).expect("edit");
+    let edited: Vec<_> = linter.lint_document(&mut doc).expect("lint").iter().map(|i| (i.rule_id, i.line, i.column)).collect();
+    let fresh: Vec<_> = lint_java_source(doc.source(), &config).expect("lint").iter().map(|i| (i.rule_id, i.line, i.column)).collect();
+    assert_eq!(edited, fresh);
+    assert_eq!(edited, [("no-empty-statement", 3, 13), ("indent-style", 4, 1), ("indentation", 4, 5)]);
+
+    let len = doc.source().len();
+    assert!(doc.edit(&TextEdit { start_byte: len, end_byte: len + 1, new_text: String::new() }).is_err());
+}
+
+#[test]
+fn nested_nodes_are_each_checked_once() {
+    let source = "import java.io.*;\nimport java.util.*;\n\nclass A {\n    void f() {\n        run(() -> {\n            g();;\n        });\n        new Object() {\n            void h() {\n                ;\n            }\n        };\n    }\n\n    class B {\n        B() {\n            ;\n        }\n    }\n}\n";
+    let issues = lint_java_source(source, &Config::default()).expect("lint");
+    let found: Vec<_> = issues.iter().map(|i| (i.rule_id, i.line)).collect();
+    assert_eq!(
+        found,
+        [("no-wildcard-imports", 1), ("no-wildcard-imports", 2), ("no-empty-statement", 7), ("no-empty-statement", 11), ("no-empty-statement", 18)]
+    );
+}
+
+#[test]
+fn issues_left_after_fixing_match_linting_the_fixed_text() {
+    let config = Config::default();
+    let source = "import java.util.*;\n\nclass A {\n\tvoid f() {\n\t\tg();;\n\t}\n}\n";
+    let result = Linter::new(config.clone()).expect("java grammar").fix(source).expect("fix");
+    assert_eq!(result.fixed, "import java.util.*;\n\nclass A {\n    void f() {\n        g();\n    }\n}\n");
+    assert_eq!(result.issues.len(), 5);
+    let remaining: Vec<_> = result.remaining.iter().map(|i| (i.rule_id, i.line, i.column)).collect();
+    let fresh: Vec<_> = lint_java_source(&result.fixed, &config).expect("lint").iter().map(|i| (i.rule_id, i.line, i.column)).collect();
+    assert_eq!(remaining, fresh);
+    assert_eq!(remaining, [("no-wildcard-imports", 1, 1)]);
+}
+
+#[test]
+fn rules_are_classified_and_text_only_sets_need_no_tree() {
+    let kinds: Vec<_> = all_rules().collect();
+    assert!(kinds.contains(&("max-line-length", RuleKind::Text)));
+    assert!(kinds.contains(&("indent-style", RuleKind::Text)));
+    assert!(kinds.contains(&("no-empty-statement", RuleKind::Ast)));
+    assert!(!RuleSet::only(["max-line-length", "indent-style"]).needs_tree());
+    assert!(RuleSet::only(["indent-style", "no-empty-statement"]).needs_tree());
+    assert_eq!(RuleSet::only(["indent-style", "no-such-rule"]).ids().collect::<Vec<_>>(), ["indent-style"]);
+
+    let source = "import java.util.*;\n\nclass A {\n\tvoid f() {\n\t\tg();;\n\t}\n}\n";
+    let mut linter = Linter::new(Config::default()).expect("java grammar").with_rules(RuleSet::only(["indent-style"]));
+    let issues = linter.lint(source).expect("lint");
+    assert!(issues.iter().all(|i| i.rule_id == "indent-style"));
+    assert_eq!(issues.len(), 3);
+    let mut doc = Document::new(source);
+    assert_eq!(linter.lint_document(&mut doc).expect("lint").len(), 3);
+    assert_eq!(linter.fix(source).expect("fix").remaining.len(), 0);
+}
+
+#[test]
+fn the_registry_lists_every_rule_once_with_a_description() {
+    let registry = libjfmt::registry();
+    let ids: std::collections::BTreeSet<_> = registry.iter().map(|r| r.id()).collect();
+    assert_eq!(ids.len(), registry.len());
+    assert!(registry.iter().all(|r| !r.description().is_empty()));
+    assert_eq!(all_rules().collect::<Vec<_>>(), registry.iter().map(|r| (r.id(), r.kind())).collect::<Vec<_>>());
+    let rule = registry.iter().find(|r| r.id() == "no-wildcard-imports").expect("registered");
+    assert_eq!(rule.node_kinds(), ["import_declaration"]);
+    assert_eq!(rule.kind(), RuleKind::Ast);
+    assert!(RuleSet::all().ids().eq(ids.iter().copied()));
+}
+
+#[test]
+fn pooled_parsers_lint_like_new_ones_on_any_thread() {
+    let source = "import java.util.*;\n\nclass A {\n    void f() {\n        g();;\n    }\n}\n";
+    let expected: Vec<_> = lint_java_source(source, &Config::default()).expect("lint").iter().map(|i| (i.rule_id, i.line)).collect();
+    for _ in 0..3 {
+        // Each linter takes the parser its predecessor handed back on drop.
+        let mut linter = Linter::new(Config::default()).expect("java grammar");
+        let issues: Vec<_> = linter.lint(source).expect("lint").iter().map(|i| (i.rule_id, i.line)).collect();
+        assert_eq!(issues, expected);
+    }
+    let workers: Vec<_> = (0..4)
+        .map(|_| std::thread::spawn(move || lint_java_source(source, &Config::default()).expect("lint").iter().map(|i| (i.rule_id, i.line)).collect::<Vec<_>>()))
+        .collect();
+    for worker in workers {
+        assert_eq!(worker.join().expect("worker"), expected);
+    }
+}
+
+#[test]
+fn indentation_fixes_cover_runs_longer_than_the_borrowed_whitespace() {
+    let deep = format!("class A {{\n{}int x;\n\t \tint y;\n}}\n", "\t".repeat(20));
+    let mut linter = Linter::new(Config::default()).expect("java grammar").with_rules(RuleSet::only(["indent-style"]));
+    let fixed = linter.fix(&deep).expect("fix").fixed;
+    assert_eq!(fixed, format!("class A {{\n{}int x;\n        int y;\n}}\n", " ".repeat(80)));
+    let issues = linter.lint(&deep).expect("lint");
+    assert_eq!(issues[0].message, "Use spaces for indentation");
+}
+
+#[test]
+fn timing_is_reset_each_time_it_is_taken() {
+    let source = "class A {\n\tint x;;\n}\n";
+    let mut untimed = Linter::new(Config::default()).expect("java grammar");
+    untimed.lint(source).expect("lint");
+    assert!(untimed.take_stats().is_none());
+
+    let mut linter = Linter::new(Config::default()).expect("java grammar").with_timing();
+    linter.fix(source).expect("fix");
+    let stats = linter.take_stats().expect("timed");
+    assert!(stats.per_rule.contains_key("no-empty-statement"));
+    assert!(stats.parse > std::time::Duration::ZERO);
+    let empty = linter.take_stats().expect("timed");
+    assert!(empty.per_rule.is_empty());
+    assert_eq!(empty.parse, std::time::Duration::ZERO);
+}
+
+#[test]
+fn fixes_that_keep_the_text_are_not_changes() {
+    let source = "class A {\n    int x;\n}\n";
+    let same = Fix { start_byte: 10, end_byte: 14, replacement: "    ".into(), applicability: Applicability::Safe };
+    let other = Fix { start_byte: 10, end_byte: 14, replacement: "\t".into(), applicability: Applicability::Safe };
+    let out_of_range = Fix { start_byte: 10, end_byte: 100, replacement: "".into(), applicability: Applicability::Safe };
+    assert!(!would_change(source, [&same]));
+    assert!(would_change(source, [&same, &other]));
+    assert!(would_change(source, [&out_of_range]));
+    assert!(!would_change(source, []));
+}
+
+#[test]
+fn line_length_is_measured_in_the_configured_unit() {
+    let source = "class A {\n    // 日本語日本語\n}\n";
+    let lengths = |unit| {
+        let config = Config { max_line_length: 12, line_length_unit: unit, ..Config::default() };
+        lint_java_source(source, &config)
+            .expect("lint")
+            .into_iter()
+            .filter(|i| i.rule_id == "max-line-length")
+            .map(|i| (i.column, i.message.into_owned()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(lengths(LineLengthUnit::Chars), [(13, "Line exceeds 12 characters (was 13)".to_string())]);
+    assert_eq!(lengths(LineLengthUnit::Width), [(10, "Line exceeds 12 columns (was 19)".to_string())]);
+    assert_eq!(lengths(LineLengthUnit::Bytes), [(9, "Line exceeds 12 bytes (was 25)".to_string())]);
+
+    let config: Config = toml::from_str("line_length_unit = \"width\"").expect("config");
+    assert_eq!(config.line_length_unit, LineLengthUnit::Width);
+    assert_eq!(LineLengthUnit::Width.measure("e\u{301}日", 4), 3);
+}
+
+#[test]
+fn tabs_advance_columns_and_line_length_to_the_next_stop() {
+    let source = "class A {\n\tvoid f() {\n\t\tg();;\n\t}\n}\n";
+    let issues = lint_java_source(source, &Config { indent_style: libjfmt::IndentStyle::Tabs, ..Config::default() }).expect("lint");
+    let found: Vec<_> = issues.iter().map(|i| (i.rule_id, i.line, i.column, i.offset)).collect();
+    assert_eq!(found, [("no-empty-statement", 3, 13, source.find(";;").unwrap() + 1)]);
+
+    let config = Config { indent_style: libjfmt::IndentStyle::Tabs, tab_width: 8, max_line_length: 20, ..Config::default() };
+    let long = lint_java_source(source, &config).expect("lint");
+    let long: Vec<_> = long.iter().map(|i| (i.rule_id, i.column)).collect();
+    assert_eq!(long, [("max-line-length", 21), ("no-empty-statement", 21)]);
+
+    let index = LineIndex::new("a\n\tb\r\n");
+    assert_eq!((index.line(0), index.line(2), index.line(3), index.line(6)), (0, 1, 1, 2));
+    assert_eq!(index.display_column("a\n\tb\r\n", 3, 4), 4);
+    assert_eq!(index.line_start(2), 6);
+}
+
+#[test]
+fn unbreakable_long_lines_can_be_exempted() {
+    let source = "import com.example.very.long.pkg.Name;\n\nclass A {\n    String s = \"a string that is long\";\n    // see https://example.com/a/long/path\n    /** {@link com.example.Thing#method} */\n    int abcdefghijklmnopqrstuvwxyz0123456789;\n}\n";
+    let long_lines = |config: &Config| {
+        lint_java_source(source, config).expect("lint").iter().filter(|i| i.rule_id == "max-line-length").map(|i| i.line).collect::<Vec<_>>()
+    };
+    let base = Config { max_line_length: 30, ..Config::default() };
+    assert_eq!(long_lines(&base), [1, 4, 5, 6, 7]);
+    assert_eq!(long_lines(&Config { max_line_length_ignore_imports: true, ..base.clone() }), [4, 5, 6, 7]);
+    assert_eq!(long_lines(&Config { max_line_length_ignore_strings: true, ..base.clone() }), [1, 6, 7]);
+    assert_eq!(long_lines(&Config { max_line_length_ignore_links: true, ..base.clone() }), [1, 4, 5, 7]);
+}
+
+#[test]
+fn fixes_with_bad_ranges_are_errors_not_panics() {
+    let source = "class É {\n}\n";
+    let fix = |start_byte, end_byte| Fix { start_byte, end_byte, replacement: "E".into(), applicability: Applicability::Safe };
+    assert_eq!(apply_fixes(source, &[fix(6, 8)]).expect("valid"), "class E {\n}\n");
+    assert_eq!(apply_fixes(source, &[fix(6, 7)]), Err(FixError::NotCharBoundary { start: 6, end: 7 }));
+    assert_eq!(apply_fixes(source, &[fix(8, 6)]), Err(FixError::OutOfBounds { start: 8, end: 6, len: source.len() }));
+    assert_eq!(apply_fixes(source, &[fix(0, 99)]), Err(FixError::OutOfBounds { start: 0, end: 99, len: source.len() }));
+    assert_eq!(apply_fixes(source, &[]).expect("no fixes"), source);
+}
+
+#[test]
+fn fixing_twice_changes_nothing_more() {
+    let mut linter = Linter::new(Config::default()).expect("java grammar");
+    for source in ["import java.util.*;\n\nclass A {\n\tvoid f() {\n\t\tg();;;\n\t}\n}\n", "class B {\n  \tint x;\n}\n", "class C {\n\t\tint x;\n}\n"] {
+        assert_eq!(linter.verify_idempotent(source).expect("fix"), None);
+    }
+    // `indentation` and `indent-style` both rewrite the tabs; the depth fix wins.
+    assert_eq!(linter.fix("class C {\n\t\tint x;\n}\n").expect("fix").fixed, "class C {\n    int x;\n}\n");
+}
+
+#[test]
+fn issues_come_out_by_line_then_column_then_rule() {
+    let source = "import java.util.*;\nclass A {\n\tvoid f() {\n\t\tg();;\n\t}\n}\n";
+    let config = Config { max_line_length: 5, ..Config::default() };
+    let issues = lint_java_source(source, &config).expect("lint");
+    let keys: Vec<_> = issues.iter().map(|i| i.sort_key()).collect();
+    let mut sorted = keys.clone();
+    sorted.sort();
+    assert_eq!(keys, sorted);
+    let line4: Vec<_> = issues.iter().filter(|i| i.line == 4).map(|i| (i.column, i.rule_id)).collect();
+    assert_eq!(line4, [(1, "indent-style"), (5, "max-line-length"), (13, "no-empty-statement")]);
+}
+
+#[test]
+fn wrapped_lines_need_a_continuation_indent_or_paren_alignment() {
+    let source = "class A {\n    @Override\n    public String toString() {\n        int total = first\n                + second;\n        int bad = first\n            + second;\n        call(alpha,\n             beta);\n        run(() -> {\n            int inner = a\n              + b;\n        });\n        String s = \"\"\"\n  text\n            \"\"\";\n        return \"\";\n    }\n}\n";
+    let config = Config::default();
+    let mut linter = Linter::new(config).expect("java grammar").with_rules(RuleSet::only(["continuation-indent"]));
+    let lines: Vec<_> = linter.lint(source).expect("lint").iter().map(|i| (i.line, i.column)).collect();
+    assert_eq!(lines, [(7, 13), (12, 15)]);
+
+    let off = Config { continuation_indent: 0, ..Config::default() };
+    let mut linter = Linter::new(off).expect("java grammar").with_rules(RuleSet::only(["continuation-indent"]));
+    assert!(linter.lint(source).expect("lint").is_empty());
+}
+
+#[test]
+fn suppressions_silence_the_rules_they_name() {
+    let source = "\
+class A {
+    void f() {
+        g();; // jfmt:ignore no-empty-statement
+        // jfmt:ignore
+        g();;
+        g();; // jfmt:ignore indent-style
+\tint x;
+        String s = \"// jfmt:ignore no-empty-statement\"; g();;
+        /* // jfmt:ignore */ g();;
+    }
+
+    @SuppressWarnings({\"unchecked\", \"jfmt:no-empty-statement\"})
+    void h() {
+        g();;
+    }
+}
+";
+    let mut linter = Linter::new(Config::default()).expect("java grammar").with_rules(RuleSet::only(["no-empty-statement", "indent-style"]));
+    let found: Vec<_> = linter.lint(source).expect("lint").iter().map(|i| (i.line, i.rule_id)).collect();
+    assert_eq!(found, [(6, "no-empty-statement"), (7, "indent-style"), (8, "no-empty-statement"), (9, "no-empty-statement")]);
+
+    let whole = format!("// jfmt:ignore-file no-empty-statement, indent-style\n{source}");
+    assert!(linter.lint(&whole).expect("lint").is_empty());
+}
+
+#[test]
+fn suppressions_that_suppress_nothing_are_reported() {
+    let source = "\
+class A {
+    void f() {
+        g();; // jfmt:ignore no-empty-statement
+        g(); // jfmt:ignore no-empty-statement, max-line-length
+        g(); // jfmt:ignore no-such-rule
+        // jfmt:ignore
+        g();
+        g(); // jfmt:ignore indent-style
+    }
+
+    @SuppressWarnings(\"jfmt:no-empty-statement\")
+    void h() {
+    }
+}
+";
+    let rules = RuleSet::only(["unused-suppression", "no-empty-statement", "max-line-length"]);
+    let mut linter = Linter::new(Config::default()).expect("java grammar").with_rules(rules);
+    let issues = linter.lint(source).expect("lint");
+    let found: Vec<_> = issues.iter().map(|i| (i.line, i.message.as_ref(), i.fix.is_some())).collect();
+    assert_eq!(
+        found,
+        [
+            (4, "Suppression of 'no-empty-statement', 'max-line-length' is unused", true),
+            (5, "Suppression names unknown rule 'no-such-rule'", false),
+            (6, "Suppression does not suppress anything", true),
+            (11, "Suppression of 'no-empty-statement' is unused", false),
+        ]
+    );
+    // `indent-style` didn't run, so its suppression on line 8 can't be judged.
+    assert!(issues.iter().all(|i| i.rule_id == "unused-suppression"));
+}
+
+#[test]
+fn anonymous_classes_with_several_methods_or_many_lines_are_reported() {
+    let source = "class A {\n    Runnable r = new Runnable() {\n        public void run() {\n        }\n    };\n    Object o = new Object() {\n        void a() {\n        }\n\n        void b() {\n        }\n    };\n}\n";
+    let found = |config: Config| {
+        let mut linter = Linter::new(config).expect("java grammar").with_rules(RuleSet::only(["anonymous-class-size"]));
+        linter.lint(source).expect("lint").iter().map(|i| (i.line, i.message.to_string())).collect::<Vec<_>>()
+    };
+    assert_eq!(found(Config::default()), [(6, "Anonymous class declares 2 methods; extract it to a named nested or local class".to_string())]);
+    let short = Config { max_anonymous_class_lines: 3, ..Config::default() };
+    assert_eq!(
+        found(short).iter().map(|(line, m)| (*line, &m[..m.find(';').unwrap_or(m.len())])).collect::<Vec<_>>(),
+        [(2, "Anonymous class spans 4 lines (max 3)"), (6, "Anonymous class spans 7 lines (max 3)")]
+    );
+}
+
+#[test]
+fn machine_specific_paths_are_reported_unless_allowed() {
+    let source = r#"class A {
+    File a = new File("C:\\data\\in.txt");
+    File b = new File("/home/me/in.txt");
+    File c = new File(base + "\\in.txt");
+    String d = "a\\b";
+    String e = "/etc/myapp/app.conf";
+    String f = "/api/users";
+}
+"#;
+    let lines = |config: Config| {
+        let mut linter = Linter::new(config).expect("java grammar").with_rules(RuleSet::only(["hardcoded-path"]));
+        linter.lint(source).expect("lint").iter().map(|i| i.line).collect::<Vec<_>>()
+    };
+    assert_eq!(lines(Config::default()), [2, 3, 4, 6]);
+    assert_eq!(lines(Config { hardcoded_path_allow: vec!["/etc/myapp/*".into()], ..Config::default() }), [2, 3, 4]);
+}
+
+#[test]
+fn calls_relying_on_the_default_locale_are_reported() {
+    let source = "class A {\n    String f(String s) {\n        s.toUpperCase();\n        s.toLowerCase(Locale.ROOT);\n        String.format(\"%d\", 1);\n        String.format(Locale.ROOT, \"%d\", 1);\n        return s.toLowerCase();\n    }\n}\n";
+    let lines = |config: Config| {
+        let mut linter = Linter::new(config).expect("java grammar").with_rules(RuleSet::only(["locale-sensitive"]));
+        linter.lint(source).expect("lint").iter().map(|i| i.line).collect::<Vec<_>>()
+    };
+    assert_eq!(lines(Config::default()), [3, 5, 7]);
+    assert!(lines(Config { allow_default_locale: true, ..Config::default() }).is_empty());
+}
+
+#[test]
+fn null_returned_for_collections_suggests_an_empty_value() {
+    let source = "class A {\n    List<String> a() {\n        return null;\n    }\n\n    int[] b() {\n        return null;\n    }\n\n    String c() {\n        return null;\n    }\n\n    Optional<String> d() {\n        Supplier<String> s = () -> {\n            return null;\n        };\n        return null;\n    }\n}\n";
+    let mut linter = Linter::new(Config::default()).expect("java grammar").with_rules(RuleSet::only(["null-collection-return"]));
+    let found: Vec<_> = linter.lint(source).expect("lint").iter().map(|i| (i.line, i.message.to_string())).collect();
+    let expected = [(3, "List.of()"), (7, "new int[0]"), (18, "Optional.empty()")]
+        .map(|(line, empty)| (line, format!("Return an empty value such as '{empty}' instead of null")));
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn floating_point_equality_is_reported() {
+    let source = "class A {\n    boolean f(double a, int b, Double c) {\n        float d = 1;\n        return a == 0.1 || b == 1 || c != null || d == b || b == -(2.5) || c == a;\n    }\n}\n";
+    let mut linter = Linter::new(Config::default()).expect("java grammar").with_rules(RuleSet::only(["float-equality"]));
+    let columns: Vec<_> = linter.lint(source).expect("lint").iter().map(|i| i.column).collect();
+    assert_eq!(columns, [16, 51, 61, 76]);
+}
+
+#[test]
+fn arrays_printed_or_concatenated_are_reported() {
+    let source = "class A {\n    int[] counts;\n\n    void f(String[] args, char[] name) {\n        System.out.println(args);\n        System.out.println(name);\n        String s = \"counts: \" + counts;\n        String t = \"name: \" + name;\n        System.out.println(args.length);\n    }\n}\n";
+    let mut linter = Linter::new(Config::default()).expect("java grammar").with_rules(RuleSet::only(["array-to-string"]));
+    let found: Vec<_> = linter.lint(source).expect("lint").iter().map(|i| (i.line, i.message.to_string())).collect();
+    let expected = [(5, "args"), (7, "counts"), (8, "name")]
+        .map(|(line, name)| (line, format!("Array '{name}' prints as a type and hash code; use Arrays.toString({name})")));
+    assert_eq!(found, expected);
+}
+
+#[test]
+fn legacy_date_time_classes_are_reported_from_java_8() {
+    let source = "import java.util.Date;\nimport java.util.List;\nimport java.text.SimpleDateFormat;\n\nclass A {\n    Object a = new Date();\n    Object b = new java.util.GregorianCalendar();\n    Object c = new com.example.Date();\n}\n";
+    let lines = |config: Config| {
+        let mut linter = Linter::new(config).expect("java grammar").with_rules(RuleSet::only(["legacy-date-time"]));
+        linter.lint(source).expect("lint").iter().map(|i| i.line).collect::<Vec<_>>()
+    };
+    assert_eq!(lines(Config::default()), [1, 3, 6, 7]);
+    assert!(lines(Config { java_version: 7, ..Config::default() }).is_empty());
+}
+
+#[test]
+fn predictable_random_is_reported_in_security_sensitive_packages() {
+    let body = "class Login {\n    double a = Math.random();\n    Object b = new Random();\n    Object c = new SecureRandom();\n}\n";
+    let lines = |source: &str, config: Config| {
+        let mut linter = Linter::new(config).expect("java grammar").with_rules(RuleSet::only(["insecure-random"]));
+        linter.lint(source).expect("lint").iter().map(|i| i.line).collect::<Vec<_>>()
+    };
+    let auth = format!("package com.acme.auth;\n\n{body}");
+    assert_eq!(lines(&auth, Config::default()), [4, 5]);
+    assert!(lines(&format!("package com.acme.billing;\n\n{body}"), Config::default()).is_empty());
+    let only_login = Config { security_sensitive_paths: vec!["com/*/Login.java".into()], ..Config::default() };
+    assert!(lines(&auth, only_login.clone()).is_empty());
+    assert_eq!(lines(&format!("package com.acme;\n\n{body}"), only_login), [4, 5]);
+}
+
+#[test]
+fn resources_never_closed_or_handed_on_are_reported() {
+    let source = "\
+class A {
+    Scanner f(String path) throws Exception {
+        BufferedReader leaked = new BufferedReader(new FileReader(path));
+        FileInputStream closed = new FileInputStream(path);
+        closed.close();
+        Scanner returned = new Scanner(path);
+        try (Connection c = DriverManager.getConnection(path)) {
+            Statement stmt = c.createStatement();
+            use(stmt);
+        }
+        leaked.readLine();
+        return returned;
+    }
+}
+";
+    let mut linter = Linter::new(Config::default()).expect("java grammar").with_rules(RuleSet::only(["unclosed-resource"]));
+    let found: Vec<_> = linter.lint(source).expect("lint").iter().map(|i| (i.line, i.message.to_string())).collect();
+    assert_eq!(found, [(3, "'leaked' is never closed; declare it in a try-with-resources statement".to_string())]);
+}
+
+#[test]
+fn swallowed_exceptions_are_reported_unless_named_ignored() {
+    let source = "\
+class A {
+    Object f() {
+        try { return g(); } catch (IOException e) { return null; }
+        try { return g(); } catch (IOException e) { log.warn(\"failed\"); }
+        try { return g(); } catch (IOException e) { throw new IllegalStateException(); }
+        try { return g(); } catch (IOException e) { return e; }
+        try { return g(); } catch (IOException ignored) { return null; }
+        return null;
+    }
+}
+";
+    let mut linter = Linter::new(Config::default()).expect("java grammar").with_rules(RuleSet::only(["swallowed-exception"]));
+    let lines: Vec<_> = linter.lint(source).expect("lint").iter().map(|i| i.line).collect();
+    assert_eq!(lines, [3]);
+}
+
+#[test]
+fn rules_switched_off_in_the_rules_table_are_not_run() {
+    let config: Config = toml::from_str("[rules]\nno-empty-statement = false\nindent-style = true\n").expect("valid config");
+    let source = "class A {\n\tvoid f() {\n\t\tg();;\n\t}\n}\n";
+    let rules: Vec<_> = lint_java_source(source, &config).expect("lint").iter().map(|i| i.rule_id).collect();
+    assert!(rules.contains(&"indent-style"), "{rules:?}");
+    assert!(!rules.contains(&"no-empty-statement"), "{rules:?}");
+    let linter = Linter::new(config).expect("java grammar").with_rules(RuleSet::only(["no-empty-statement", "indent-style"]));
+    assert_eq!(linter.rules().ids().collect::<Vec<_>>(), ["indent-style"]);
+    assert_eq!(
+        libjfmt::config_warnings("[rules]\nno-such-rule = false\n").expect("parses"),
+        ["unknown rule `no-such-rule` in `[rules]` is ignored"]
+    );
+}
+
+#[test]
+fn line_rules_run_without_parsing() {
+    for id in ["no-trailing-whitespace", "max-blank-lines", "max-line-length", "line-ending", "eof-newline"] {
+        let rule = libjfmt::registry().iter().find(|r| r.id() == id).expect("registered");
+        assert_eq!(rule.kind(), RuleKind::Text, "{id}");
+    }
+    assert!(!RuleSet::only(["no-trailing-whitespace", "max-blank-lines"]).needs_tree());
+}
+
+#[test]
+fn names_are_held_to_the_naming_patterns() {
+    let source = "package com.Acme;\n\ninterface bad_name {\n    int maxSize = 1;\n    static final long serialVersionUID = 1L;\n    void Run();\n}\n\nclass Good {\n    @Override\n    public String ToString() {\n        return \"\";\n    }\n}\n";
+    let found = |config: &Config| -> Vec<_> {
+        lint_java_source(source, config).expect("lint").iter().filter(|i| i.rule_id.ends_with("-naming")).map(|i| (i.rule_id, i.line)).collect()
+    };
+    assert_eq!(found(&Config::default()), [("package-naming", 1), ("class-naming", 3), ("constant-naming", 4), ("method-naming", 6)]);
+    let loose: Config = toml::from_str("[naming]\nclass = \"[a-z_]+|[A-Z][a-zA-Z]*\"\nmethod = \"[A-Za-z]+\"\n").expect("valid config");
+    assert_eq!(found(&loose), [("package-naming", 1), ("constant-naming", 4)]);
+    let warnings = libjfmt::config_warnings("[naming]\nmethod = \"[a-z\"\n").expect("parses");
+    assert!(warnings.len() == 1 && warnings[0].starts_with("`naming.method` is not a valid regex"), "{warnings:?}");
+}
+
+#[test]
+fn locals_and_fixed_parameters_never_read_are_reported() {
+    let source = r#"class A {
+    private int f(int used, int unused2) {
+        int a = 1;
+        int b;
+        b = 2;
+        int c = used;
+        int ignored = 0;
+        Runnable r = () -> g(c);
+        new Object() {
+            void h(int p) {
+                int a = 2;
+            }
+        };
+        return 0;
+    }
+
+    public void open(int overridable) {
+    }
+
+    {
+        int d = 0;
+    }
+
+    static Runnable field = () -> {
+        int e = 0;
+    };
+}
+"#;
+    let issues = lint_java_source(source, &Config::default()).expect("lint");
+    let found: Vec<_> = issues.iter().filter(|i| i.rule_id == "unused-variable").map(|i| (i.line, i.message.to_string())).collect();
+    assert_eq!(
+        found,
+        [
+            (2, "Parameter 'unused2' is never read".to_string()),
+            (3, "Local variable 'a' is never read".to_string()),
+            (4, "Local variable 'b' is never read".to_string()),
+            (8, "Local variable 'r' is never read".to_string()),
+            (11, "Local variable 'a' is never read".to_string()),
+            (21, "Local variable 'd' is never read".to_string()),
+            (25, "Local variable 'e' is never read".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn empty_catches_are_reported_once() {
+    let catch = |body: &str, name: &str| format!("class A {{\n    void f() {{\n        try {{\n            g();\n        }} catch (IllegalStateException {name}) {body}\n    }}\n}}\n");
+    let found = |source: &str, config: &Config| -> Vec<_> {
+        lint_java_source(source, config).expect("lint").iter().map(|i| i.rule_id).filter(|id| matches!(*id, "empty-catch" | "swallowed-exception")).collect()
+    };
+    let config = Config::default();
+    assert_eq!(found(&catch("{}", "e"), &config), ["empty-catch"]);
+    assert!(found(&catch("{ /* retried by the caller */ }", "e"), &config).is_empty());
+    assert!(found(&catch("{}", "ignored"), &config).is_empty());
+    assert_eq!(found(&catch("{ return; }", "e"), &config), ["swallowed-exception"]);
+    let strict: Config = toml::from_str("empty_catch_allow_comments = false\nempty_catch_allow_ignored = false\n").expect("valid config");
+    assert_eq!(found(&catch("{ /* retried by the caller */ }", "e"), &strict), ["empty-catch"]);
+    assert_eq!(found(&catch("{}", "ignored"), &strict), ["empty-catch"]);
+}
+
+#[test]
+fn equals_without_hash_code_is_reported_and_the_reverse() {
+    let source = "class A {\n    public boolean equals(Object o) {\n        return o == this;\n    }\n}\n\nenum E {\n    X;\n\n    public int hashCode() {\n        return 1;\n    }\n}\n\nclass B {\n    public boolean equals(B other) {\n        return true;\n    }\n\n    public boolean equals(java.lang.Object o) {\n        return false;\n    }\n\n    public int hashCode() {\n        return 0;\n    }\n}\n";
+    let issues = lint_java_source(source, &Config::default()).expect("lint");
+    let found: Vec<_> = issues.iter().filter(|i| i.rule_id == "equals-hashcode").map(|i| (i.line, i.column, i.message.to_string())).collect();
+    assert_eq!(
+        found,
+        [
+            (2, 20, "Class overrides equals(Object) but not hashCode()".to_string()),
+            (10, 16, "Class overrides hashCode() but not equals(Object)".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn switches_falling_through_or_without_default_are_reported() {
+    let source = "class A {\n    void f(int x, Color c) {\n        switch (x) {\n            case 1:\n                g();\n            case 2:\n            case 3:\n                g();\n                // falls through\n            case 4:\n                if (x > 0) {\n                    return;\n                } else {\n                    break;\n                }\n            default:\n                g();\n        }\n        switch (c) {\n            case RED:\n                break;\n            case GREEN:\n                break;\n        }\n        switch (c) {\n            case RED, GREEN, BLUE -> g();\n        }\n        int y = switch (x) {\n            case 1 -> 1;\n            default -> 2;\n        };\n    }\n}\n\nenum Color {\n    RED, GREEN, BLUE\n}\n";
+    let issues = lint_java_source(source, &Config::default()).expect("lint");
+    let found: Vec<_> = issues.iter().filter(|i| matches!(i.rule_id, "switch-fallthrough" | "missing-default")).map(|i| (i.rule_id, i.line)).collect();
+    assert_eq!(found, [("switch-fallthrough", 6), ("missing-default", 19)]);
+}
+
+#[test]
+fn public_api_needs_javadoc_with_matching_tags() {
+    let source = "/** Docs. */\npublic class A {\n    public A() {\n    }\n\n    /**\n     * Adds.\n     * @param a first\n     * @param c not a parameter\n     * @return nothing\n     */\n    public void add(int a, int b) {\n    }\n\n    @Override\n    public String toString() {\n        return \"\";\n    }\n\n    void hidden() {\n    }\n\n    class Inner {\n        public void f() {\n        }\n    }\n}\n";
+    let issues = lint_java_source(source, &Config::default()).expect("lint");
+    let found: Vec<_> = issues.iter().filter(|i| matches!(i.rule_id, "missing-javadoc" | "javadoc-tags")).map(|i| (i.rule_id, i.line)).collect();
+    // The constructor; `@param c`, `@return` on a void method and the missing `@param b`.
+    assert_eq!(found, [("missing-javadoc", 3), ("javadoc-tags", 9), ("javadoc-tags", 10), ("javadoc-tags", 12)]);
+}
+
+#[test]
+fn console_output_is_reported_unless_allowed() {
+    let source = "class A {\n    void f(Exception e) {\n        System.out.println(\"x\");\n        e.printStackTrace();\n        e.printStackTrace(writer);\n    }\n\n    public static void main(String[] args) {\n        System.err.print(\"usage\");\n    }\n}\n";
+    let lines = |config: &Config| -> Vec<_> {
+        lint_java_source(source, config)
+            .expect("lint")
+            .iter()
+            .filter(|i| i.rule_id == "no-console-output")
+            .map(|i| i.line)
+            .collect()
+    };
+    assert_eq!(lines(&Config::default()), [3, 4, 9]);
+    let main: Config = toml::from_str("console_output_allow_main = true").expect("valid config");
+    assert_eq!(lines(&main), [3, 4]);
+    let allowed: Config = toml::from_str("allow_console_output = true").expect("valid config");
+    assert!(lines(&allowed).is_empty());
+}
+
+#[test]
+fn magic_numbers_outside_constants_are_reported() {
+    let source = "class A {\n    static final int LIMIT = 42;\n    int retries = 3;\n\n    @Size(max = 64)\n    void f() {\n        int a = -1 + 0x1 + 1L;\n        int b = 7;\n        byte[] buf = new byte[4096];\n    }\n}\n";
+    let lines = |config: &Config| -> Vec<_> {
+        lint_java_source(source, config)
+            .expect("lint")
+            .iter()
+            .filter(|i| i.rule_id == "magic-number")
+            .map(|i| (i.line, i.message.clone()))
+            .collect()
+    };
+    let found = lines(&Config::default());
+    assert_eq!(found.iter().map(|(line, _)| *line).collect::<Vec<_>>(), [3, 8, 9]);
+    assert!(found[0].1.contains("Magic number 3"), "{}", found[0].1);
+    let config: Config = toml::from_str("[magic-numbers]\nallow = [-1, 1, 3, 7]\nignore_array_sizes = true\nignore_annotations = false").expect("valid config");
+    assert_eq!(lines(&config).iter().map(|(line, _)| *line).collect::<Vec<_>>(), [5]);
+}
+
+#[test]
+fn long_methods_and_parameter_lists_are_reported() {
+    let source = "class A {\n    A(int a, int b, int c) {\n    }\n\n    void f() {\n        g();\n        if (x) {\n            h();\n        }\n    }\n\n    @Override\n    public void g(int a, int b, int c) {\n    }\n}\n";
+    let lint = |toml_source: &str| -> Vec<_> {
+        let config: Config = toml::from_str(toml_source).expect("valid config");
+        lint_java_source(source, &config)
+            .expect("lint")
+            .into_iter()
+            .filter(|i| matches!(i.rule_id, "max-method-length" | "max-parameters"))
+            .map(|i| (i.rule_id, i.line, i.message))
+            .collect()
+    };
+    assert!(lint("").is_empty());
+    let found = lint("max_method_length = 5\nmax_parameters = 2");
+    assert_eq!(found.iter().map(|(id, line, _)| (*id, *line)).collect::<Vec<_>>(), [("max-parameters", 2), ("max-method-length", 5)]);
+    assert_eq!(found[1].2, "Method 'f' has 6 lines (max 5)");
+    let found = lint("max_method_length = 2\nmethod_length_unit = \"statements\"");
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].2, "Method 'f' has 3 statements (max 2)");
+}
+
+#[test]
+fn complex_methods_are_reported() {
+    let source = "class A {\n    int f(int x) {\n        if (x > 0 && x < 9) {\n            for (int i = 0; i < x; i++) {\n                x += i % 2 == 0 ? 1 : 2;\n            }\n        }\n        Runnable r = new Runnable() {\n            public void run() {\n                if (ready) {\n                    go();\n                }\n            }\n        };\n        return x;\n    }\n}\n";
+    let lint = |toml_source: &str| -> Vec<_> {
+        let config: Config = toml::from_str(toml_source).expect("valid config");
+        lint_java_source(source, &config)
+            .expect("lint")
+            .into_iter()
+            .filter(|i| i.rule_id == "max-complexity")
+            .map(|i| (i.line, i.message))
+            .collect()
+    };
+    assert!(lint("").is_empty());
+    // 1, plus the `if`, `&&`, `for` and `?:`; the anonymous class's `if` is its own method's.
+    assert!(lint("max_complexity = 5").is_empty());
+    assert_eq!(lint("max_complexity = 4"), [(2, "Method 'f' has cyclomatic complexity 5 (max 4)".into())]);
+}
+
+#[test]
+fn top_level_types_are_one_per_file_and_named_after_it() {
+    let source = "public class Foo {\n    class Inner {\n    }\n}\n\ninterface Helper {\n}\n\nenum Mode {\n    ON\n}\n";
+    let mut linter = Linter::new(Config::default()).expect("java grammar").with_rules(RuleSet::only(["one-top-level-class", "class-file-name"]));
+    let found = |linter: &mut Linter| -> Vec<_> { linter.lint(source).expect("lint").into_iter().map(|i| (i.rule_id, i.line)).collect() };
+    // Without a file name only the count is checked.
+    assert_eq!(found(&mut linter), [("one-top-level-class", 6), ("one-top-level-class", 9)]);
+    linter.set_file_name(Some(std::path::Path::new("src/Bar.java")));
+    assert_eq!(found(&mut linter), [("class-file-name", 1), ("one-top-level-class", 6), ("one-top-level-class", 9)]);
+    linter.set_file_name(Some(std::path::Path::new("src/Foo.java")));
+    assert_eq!(found(&mut linter), [("one-top-level-class", 6), ("one-top-level-class", 9)]);
+}
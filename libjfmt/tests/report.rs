@@ -0,0 +1,122 @@
+//! Machine-readable report formats.
+
+use std::path::PathBuf;
+
+use libjfmt::{checkstyle_report, lint_java_source, sarif_report, sonar_report, ColumnUnit, Config, ReportedIssue, Severity};
+use serde_json::json;
+
+fn issue(rule: &str, line: usize, column: usize, fixable: bool) -> ReportedIssue {
+    ReportedIssue {
+        path: PathBuf::from("src/A.java"),
+        line,
+        column,
+        end_line: line,
+        end_column: column,
+        rule: rule.to_string(),
+        severity: Severity::Error,
+        message: format!("{rule} message"),
+        fixable,
+    }
+}
+
+#[test]
+fn sonar_reports_list_each_fired_rule_once() {
+    let issues = [issue("max-line-length", 3, 121, false), issue("indent-style", 2, 1, true), issue("indent-style", 4, 1, true), issue("made-up", 1, 1, false)];
+    let report = sonar_report(&issues);
+    let rules: Vec<_> = report["rules"].as_array().expect("rules").iter().map(|r| (r["id"].clone(), r["name"].clone(), r["severity"].clone())).collect();
+    assert_eq!(
+        rules,
+        [
+            (json!("indent-style"), json!("Indentation style"), json!("MINOR")),
+            (json!("made-up"), json!("made-up"), json!("MINOR")),
+            (json!("max-line-length"), json!("Line too long"), json!("MINOR")),
+        ]
+    );
+    assert_eq!(report["rules"][0]["impacts"], json!([{ "softwareQuality": "MAINTAINABILITY", "severity": "LOW" }]));
+    assert_eq!(
+        report["issues"][0],
+        json!({
+            "ruleId": "max-line-length",
+            "effortMinutes": 2,
+            "primaryLocation": {
+                "message": "max-line-length message",
+                "filePath": "src/A.java",
+                "textRange": { "startLine": 3, "startColumn": 120 },
+            },
+        })
+    );
+    let efforts: Vec<_> = report["issues"].as_array().expect("issues").iter().map(|i| i["effortMinutes"].as_u64().expect("effort")).collect();
+    assert_eq!(efforts, [2, 1, 1, 5]);
+    assert_eq!(sonar_report(&[]), json!({ "rules": [], "issues": [] }));
+}
+
+#[test]
+fn issues_about_code_span_it_and_sonar_ranges_include_the_end() {
+    let source = "import java.util.*;\n\nclass A {\n    void f() {\n        g();;\n    }\n}\n";
+    let issues = lint_java_source(source, &Config::default()).expect("lint");
+    let spans: Vec<_> = issues.iter().map(|i| (i.rule_id, i.line, i.column, i.end_line, i.end_column)).collect();
+    // The wildcard import spans its declaration; the stray `;` is replaced by its fix.
+    assert_eq!(spans, [("no-wildcard-imports", 1, 1, 1, 20), ("no-empty-statement", 5, 13, 5, 14)]);
+
+    let mut spanning = issue("no-wildcard-imports", 1, 1, false);
+    spanning.end_column = 20;
+    let report = sonar_report(&[spanning, issue("max-line-length", 3, 121, false)]);
+    assert_eq!(report["issues"][0]["primaryLocation"]["textRange"], json!({ "startLine": 1, "startColumn": 0, "endLine": 1, "endColumn": 19 }));
+    assert_eq!(report["issues"][1]["primaryLocation"]["textRange"], json!({ "startLine": 3, "startColumn": 120 }));
+}
+
+#[test]
+fn sarif_logs_index_rules_and_keep_paths_relative() {
+    let mut fixable = issue("indent-style", 2, 1, true);
+    fixable.path = PathBuf::from("./src/my dir/A.java");
+    fixable.severity = Severity::Info;
+    let issues = [issue("max-line-length", 3, 121, false), fixable];
+    let log = sarif_report(&issues, ColumnUnit::Utf16);
+    let run = &log["runs"][0];
+    assert_eq!(log["version"], "2.1.0");
+    assert_eq!(run["columnKind"], "utf16CodeUnits");
+    let rules: Vec<_> = run["tool"]["driver"]["rules"].as_array().expect("rules").iter().map(|r| r["id"].clone()).collect();
+    assert_eq!(rules, [json!("indent-style"), json!("max-line-length")]);
+    assert_eq!(
+        run["results"][1],
+        json!({
+            "ruleId": "indent-style",
+            "ruleIndex": 0,
+            "level": "note",
+            "message": { "text": "indent-style message" },
+            "locations": [{
+                "physicalLocation": {
+                    "artifactLocation": { "uri": "src/my%20dir/A.java", "uriBaseId": "%SRCROOT%" },
+                    "region": { "startLine": 2, "startColumn": 1 },
+                },
+            }],
+            "properties": { "fixable": true },
+        })
+    );
+    assert_eq!(run["results"][0]["ruleIndex"], 1);
+    assert!(run["results"][0].get("properties").is_none());
+    assert_eq!(sarif_report(&[], ColumnUnit::Chars)["runs"][0]["columnKind"], "unicodeCodePoints");
+}
+
+#[test]
+fn checkstyle_groups_issues_by_file_with_their_severity() {
+    let mut other = issue("bigdecimal-double", 5, 9, false);
+    other.path = PathBuf::from("src/B.java");
+    other.message = "use \"0.1\" & not <0.1>".to_string();
+    let (mut info, mut warning) = (issue("indent-style", 2, 1, true), issue("float-equality", 3, 7, false));
+    info.severity = Severity::Info;
+    warning.severity = Severity::Warning;
+    let issues = [info, warning, other];
+    assert_eq!(
+        checkstyle_report(&issues),
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<checkstyle version=\"8.0\">\n  \
+         <file name=\"src/A.java\">\n    \
+         <error line=\"2\" column=\"1\" severity=\"info\" message=\"indent-style message\" source=\"jfmt.indent-style\"/>\n    \
+         <error line=\"3\" column=\"7\" severity=\"warning\" message=\"float-equality message\" source=\"jfmt.float-equality\"/>\n  \
+         </file>\n  \
+         <file name=\"src/B.java\">\n    \
+         <error line=\"5\" column=\"9\" severity=\"error\" message=\"use &quot;0.1&quot; &amp; not &lt;0.1&gt;\" source=\"jfmt.bigdecimal-double\"/>\n  \
+         </file>\n</checkstyle>\n"
+    );
+    assert_eq!(checkstyle_report(&[]), "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<checkstyle version=\"8.0\">\n</checkstyle>\n");
+}